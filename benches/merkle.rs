@@ -0,0 +1,88 @@
+use ark_crypto_primitives::{
+    crh::{CRHScheme, TwoToOneCRHScheme},
+    merkle_tree::{Config, MerkleTree},
+};
+use ark_ff::Field;
+use ark_serialize::CanonicalSerialize;
+use ark_std::UniformRand;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use whir::crypto::{
+    fields::{Field256, Field64_2},
+    merkle_tree::{blake3, keccak},
+};
+
+const SIZES: [usize; 3] = [10, 12, 14];
+const LEAF_SIZE: usize = 16;
+
+fn bench_tree<F, MerkleConfig>(
+    c: &mut Criterion,
+    name: &str,
+    leaf_hash_params: &<MerkleConfig::LeafHash as CRHScheme>::Parameters,
+    two_to_one_params: &<MerkleConfig::TwoToOneHash as TwoToOneCRHScheme>::Parameters,
+) where
+    F: Field + CanonicalSerialize + Send,
+    MerkleConfig: Config<Leaf = [F]>,
+{
+    let mut rng = ark_std::test_rng();
+    let mut group = c.benchmark_group(format!("merkle/{}", name));
+    for log_leaves in SIZES {
+        let num_leaves = 1 << log_leaves;
+        let leaves: Vec<F> = (0..num_leaves * LEAF_SIZE)
+            .map(|_| F::rand(&mut rng))
+            .collect();
+        group.throughput(Throughput::Elements(num_leaves as u64));
+        group.bench_with_input(
+            BenchmarkId::new("commit", log_leaves),
+            &leaves,
+            |b, leaves| {
+                b.iter(|| {
+                    #[cfg(not(feature = "parallel"))]
+                    let leafs_iter = leaves.chunks_exact(LEAF_SIZE);
+                    #[cfg(feature = "parallel")]
+                    let leafs_iter = leaves.par_chunks_exact(LEAF_SIZE);
+                    MerkleTree::<MerkleConfig>::new(leaf_hash_params, two_to_one_params, leafs_iter)
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_merkle(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+
+    let (leaf, two_to_one) = blake3::default_config::<Field64_2>(&mut rng);
+    bench_tree::<Field64_2, blake3::MerkleTreeParams<Field64_2>>(
+        c,
+        "Goldilocks2/Blake3",
+        &leaf,
+        &two_to_one,
+    );
+    let (leaf, two_to_one) = keccak::default_config::<Field64_2>(&mut rng);
+    bench_tree::<Field64_2, keccak::MerkleTreeParams<Field64_2>>(
+        c,
+        "Goldilocks2/Keccak",
+        &leaf,
+        &two_to_one,
+    );
+    let (leaf, two_to_one) = blake3::default_config::<Field256>(&mut rng);
+    bench_tree::<Field256, blake3::MerkleTreeParams<Field256>>(
+        c,
+        "Field256/Blake3",
+        &leaf,
+        &two_to_one,
+    );
+    let (leaf, two_to_one) = keccak::default_config::<Field256>(&mut rng);
+    bench_tree::<Field256, keccak::MerkleTreeParams<Field256>>(
+        c,
+        "Field256/Keccak",
+        &leaf,
+        &two_to_one,
+    );
+}
+
+criterion_group!(benches, bench_merkle);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+use ark_ff::FftField;
+use ark_std::UniformRand;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use whir::{
+    crypto::fields::{Field256, Field64, Field64_2},
+    ntt::{expand_from_coeff, ntt, ArkPolyBackend, FftBackend, NttBackend},
+};
+
+const SIZES: [usize; 3] = [14, 16, 18];
+const EXPANSION: usize = 4;
+// Sizes at which the transforms become memory-bound.
+const LARGE_SIZES: [usize; 2] = [22, 24];
+
+fn bench_field<F: FftField>(c: &mut Criterion, name: &str) {
+    let mut rng = ark_std::test_rng();
+    let mut group = c.benchmark_group(format!("ntt/{}", name));
+    for log_size in SIZES {
+        let size = 1 << log_size;
+        let coeffs: Vec<F> = (0..size).map(|_| F::rand(&mut rng)).collect();
+        group.throughput(Throughput::Elements(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("ntt", log_size), &coeffs, |b, coeffs| {
+            b.iter_batched_ref(
+                || coeffs.clone(),
+                |values| ntt(values),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(
+            BenchmarkId::new("expand_from_coeff", log_size),
+            &coeffs,
+            |b, coeffs| b.iter(|| expand_from_coeff(coeffs, EXPANSION)),
+        );
+    }
+    group.finish();
+}
+
+// The six-step NTT of this crate against the radix-2 FFT of ark-poly, on codewords that do not fit
+// in the caches.
+fn bench_backends<F: FftField>(c: &mut Criterion, name: &str) {
+    let mut rng = ark_std::test_rng();
+    let mut group = c.benchmark_group(format!("rs_encode/{}", name));
+    group.sample_size(10);
+    for log_size in LARGE_SIZES {
+        let size = 1 << (log_size - EXPANSION.ilog2() as usize);
+        let coeffs: Vec<F> = (0..size).map(|_| F::rand(&mut rng)).collect();
+        group.throughput(Throughput::Elements((size * EXPANSION) as u64));
+
+        group.bench_with_input(BenchmarkId::new("ntt", log_size), &coeffs, |b, coeffs| {
+            b.iter(|| NttBackend.expand_from_coeff(coeffs, EXPANSION))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("ark_poly", log_size),
+            &coeffs,
+            |b, coeffs| b.iter(|| ArkPolyBackend.expand_from_coeff(coeffs, EXPANSION)),
+        );
+    }
+    group.finish();
+}
+
+fn bench_ntt(c: &mut Criterion) {
+    bench_field::<Field64>(c, "Goldilocks");
+    bench_field::<Field64_2>(c, "Goldilocks2");
+    bench_field::<Field256>(c, "Field256");
+    bench_backends::<Field64>(c, "Goldilocks");
+}
+
+criterion_group!(benches, bench_ntt);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+use ark_ff::Field;
+use ark_std::UniformRand;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use whir::{
+    crypto::fields::{Field256, Field64_2},
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    sumcheck::prover_single::SumcheckSingle,
+};
+
+const SIZES: [usize; 3] = [14, 16, 18];
+const NUM_POINTS: usize = 2;
+
+fn bench_field<F: Field>(c: &mut Criterion, name: &str) {
+    let mut rng = ark_std::test_rng();
+    let mut group = c.benchmark_group(format!("sumcheck/{}", name));
+    for num_variables in SIZES {
+        let polynomial =
+            CoefficientList::new((0..1 << num_variables).map(|_| F::rand(&mut rng)).collect());
+        let points: Vec<_> = (0..NUM_POINTS)
+            .map(|_| MultilinearPoint::rand(&mut rng, num_variables))
+            .collect();
+        let evaluations: Vec<_> = points.iter().map(|p| polynomial.evaluate(p)).collect();
+        let combination_randomness: Vec<_> = (0..NUM_POINTS).map(|_| F::rand(&mut rng)).collect();
+        let new_sumcheck = || {
+            SumcheckSingle::new(
+                polynomial.clone(),
+                &points,
+                &combination_randomness,
+                &evaluations,
+            )
+        };
+
+        // Filling the table of the equality polynomial, i.e. `eval_eq`
+        group.bench_function(BenchmarkId::new("eval_eq", num_variables), |b| {
+            b.iter_batched_ref(
+                new_sumcheck,
+                |prover| prover.add_new_equality(&points, &combination_randomness, &evaluations),
+                BatchSize::LargeInput,
+            )
+        });
+
+        // A single round: computing the sumcheck polynomial and folding the tables
+        let folding_randomness = MultilinearPoint(vec![F::rand(&mut rng)]);
+        group.bench_function(BenchmarkId::new("round", num_variables), |b| {
+            b.iter_batched_ref(
+                new_sumcheck,
+                |prover| {
+                    let sumcheck_poly = prover.compute_sumcheck_polynomial();
+                    prover.compress(F::ONE, &folding_randomness, &sumcheck_poly);
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_sumcheck(c: &mut Criterion) {
+    bench_field::<Field64_2>(c, "Goldilocks2");
+    bench_field::<Field256>(c, "Field256");
+}
+
+criterion_group!(benches, bench_sumcheck);
+criterion_main!(benches);
@@ -0,0 +1,158 @@
+use ark_crypto_primitives::{
+    crh::{CRHScheme, TwoToOneCRHScheme},
+    merkle_tree::Config,
+};
+use ark_ff::{FftField, Field};
+use ark_serialize::CanonicalSerialize;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use nimue::{DefaultHash, IOPattern};
+use nimue_pow::blake3::Blake3PoW;
+use whir::{
+    crypto::{
+        fields::{Field256, Field64_2},
+        merkle_tree::{blake3, keccak},
+    },
+    parameters::{
+        default_max_pow, FoldType, MultivariateParameters, SoundnessType, WhirParameters,
+    },
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    whir::{
+        committer::Committer, iopattern::WhirIOPattern, parameters::WhirConfig, prover::Prover,
+        verifier::Verifier, Statement,
+    },
+};
+
+const SIZES: [usize; 3] = [16, 18, 20];
+const FOLDING_FACTOR: usize = 4;
+const RATE: usize = 1;
+const SECURITY_LEVEL: usize = 100;
+
+fn bench_whir<F, MerkleConfig>(
+    c: &mut Criterion,
+    name: &str,
+    fold_optimisation: FoldType,
+    leaf_hash_params: <MerkleConfig::LeafHash as CRHScheme>::Parameters,
+    two_to_one_params: <MerkleConfig::TwoToOneHash as TwoToOneCRHScheme>::Parameters,
+) where
+    F: FftField + CanonicalSerialize,
+    MerkleConfig: Config<Leaf = [F]> + Clone,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+{
+    let mut rng = ark_std::test_rng();
+    let mut group = c.benchmark_group(format!("whir/{}", name));
+    group.sample_size(10);
+    for num_variables in SIZES {
+        let whir_params = WhirParameters::<MerkleConfig, Blake3PoW> {
+            security_level: SECURITY_LEVEL,
+            pow_bits: default_max_pow(num_variables, RATE),
+            folding_factor: FOLDING_FACTOR,
+            leaf_hash_params: leaf_hash_params.clone(),
+            two_to_one_params: two_to_one_params.clone(),
+            soundness_type: SoundnessType::ConjectureList,
+            fold_optimisation,
+            zk: false,
+            final_form: Default::default(),
+            max_proof_size: None,
+            round_merkle_params: vec![],
+            _pow_parameters: Default::default(),
+            starting_log_inv_rate: RATE,
+        };
+        let params = WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+        let io = IOPattern::<DefaultHash>::new("🌪️")
+            .commit_statement(&params)
+            .add_whir_proof(&params);
+
+        let polynomial = CoefficientList::new(
+            (0..1 << num_variables)
+                .map(<F as Field>::BasePrimeField::from)
+                .collect(),
+        );
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let evaluation = polynomial.evaluate_at_extension(&point);
+        let statement = Statement::new(vec![point], vec![evaluation]);
+
+        let committer = Committer::new(params.clone());
+        let prover = Prover(params.clone());
+        group.bench_function(BenchmarkId::new("prove", num_variables), |b| {
+            b.iter_batched(
+                || (polynomial.clone(), statement.clone()),
+                |(polynomial, statement)| {
+                    let mut merlin = io.to_merlin();
+                    let witness = committer.commit(&mut merlin, polynomial).unwrap();
+                    prover.prove(&mut merlin, statement, witness).unwrap()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+
+        let mut merlin = io.to_merlin();
+        let witness = committer.commit(&mut merlin, polynomial).unwrap();
+        let proof = prover
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+        let verifier = Verifier::new(params);
+        group.bench_function(BenchmarkId::new("verify", num_variables), |b| {
+            b.iter(|| {
+                let mut arthur = io.to_arthur(merlin.transcript());
+                verifier.verify(&mut arthur, &statement, &proof).unwrap()
+            })
+        });
+
+        // The same proof with hints, see `whir::hints`, if the fold takes any
+        let hinted = verifier
+            .hint_proof(&mut io.to_arthur(merlin.transcript()), &statement, &proof)
+            .unwrap();
+        if !hinted.hints().is_empty() {
+            group.bench_function(BenchmarkId::new("verify_hinted", num_variables), |b| {
+                b.iter(|| {
+                    let mut arthur = io.to_arthur(merlin.transcript());
+                    verifier.verify(&mut arthur, &statement, &hinted).unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+
+    let (leaf, two_to_one) = blake3::default_config::<Field64_2>(&mut rng);
+    bench_whir::<Field64_2, blake3::MerkleTreeParams<Field64_2>>(
+        c,
+        "Goldilocks2/Blake3",
+        FoldType::ProverHelps,
+        leaf.clone(),
+        two_to_one.clone(),
+    );
+    bench_whir::<Field64_2, blake3::MerkleTreeParams<Field64_2>>(
+        c,
+        "Goldilocks2/Blake3/Naive",
+        FoldType::Naive,
+        leaf,
+        two_to_one,
+    );
+    let (leaf, two_to_one) = keccak::default_config::<Field64_2>(&mut rng);
+    bench_whir::<Field64_2, keccak::MerkleTreeParams<Field64_2>>(
+        c,
+        "Goldilocks2/Keccak",
+        FoldType::ProverHelps,
+        leaf,
+        two_to_one,
+    );
+    let (leaf, two_to_one) = blake3::default_config::<Field256>(&mut rng);
+    bench_whir::<Field256, blake3::MerkleTreeParams<Field256>>(
+        c,
+        "Field256/Blake3",
+        FoldType::ProverHelps,
+        leaf,
+        two_to_one,
+    );
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,211 @@
+//! A batched opening of several polynomials with a single commitment and a single WHIR proof.
+//!
+//! The `2^batch_variables` polynomials `p_i` in `n` variables are stacked into the polynomial
+//! `P(S, X) = sum_i eq(i, S) * p_i(X)` in `batch_variables + n` variables, whose evaluations over
+//! the hypercube are those of the `p_i` one after the other. A claim `p_i(z) = v` is then the
+//! claim `P(i, z) = v`, so that one proof of a statement with all the claims opens every
+//! polynomial at its points.
+//!
+//! Run with `cargo run --release --example batch`.
+
+use ark_ff::{AdditiveGroup, Field};
+use nimue::{DefaultHash, IOPattern, ProofResult};
+use nimue_pow::blake3::Blake3PoW;
+use whir::{
+    crypto::{
+        fields::{Field64, Field64_2},
+        merkle_tree::blake3 as merkle_tree,
+    },
+    parameters::{FoldType, MultivariateParameters, SoundnessType, WhirParameters},
+    poly_utils::{coeffs::CoefficientList, evals::EvaluationsList, MultilinearPoint},
+    whir::{
+        committer::Committer, iopattern::WhirIOPattern, parameters::WhirConfig, prover::Prover,
+        verifier::Verifier, Statement, WhirProof,
+    },
+};
+
+type BaseField = Field64;
+type F = Field64_2;
+type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+type PowStrategy = Blake3PoW;
+type Config = WhirConfig<F, MerkleConfig, PowStrategy>;
+
+/// The polynomial whose evaluations over the hypercube are those of `polynomials`, one after
+/// the other.
+fn stack(polynomials: &[CoefficientList<BaseField>]) -> CoefficientList<BaseField> {
+    assert!(polynomials.len().is_power_of_two());
+    let evals = polynomials
+        .iter()
+        .flat_map(|polynomial| EvaluationsList::from(polynomial.clone()).evals().to_vec())
+        .collect();
+    EvaluationsList::new(evals).into()
+}
+
+/// The point of the stacked polynomial at which it evaluates to `p_index(point)`.
+fn stacked_point(
+    batch_variables: usize,
+    index: usize,
+    point: &MultilinearPoint<F>,
+) -> MultilinearPoint<F> {
+    MultilinearPoint(
+        (0..batch_variables)
+            .rev()
+            .map(|bit| {
+                if (index >> bit) & 1 == 1 {
+                    F::ONE
+                } else {
+                    F::ZERO
+                }
+            })
+            .chain(point.0.iter().copied())
+            .collect(),
+    )
+}
+
+/// Claims `p_i(z) = v` for each polynomial and each of its points.
+struct Claims {
+    batch_variables: usize,
+    claims: Vec<(usize, MultilinearPoint<F>, F)>,
+}
+
+impl Claims {
+    fn statement(&self) -> Statement<F> {
+        let (points, evaluations) = self
+            .claims
+            .iter()
+            .map(|(index, point, evaluation)| {
+                (
+                    stacked_point(self.batch_variables, *index, point),
+                    *evaluation,
+                )
+            })
+            .unzip();
+        Statement::new(points, evaluations)
+    }
+}
+
+fn setup(num_variables: usize) -> (Config, IOPattern) {
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+    let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+        security_level: 100,
+        pow_bits: 20,
+        folding_factor: 4,
+        leaf_hash_params,
+        two_to_one_params,
+        soundness_type: SoundnessType::ConjectureList,
+        fold_optimisation: FoldType::ProverHelps,
+        zk: false,
+        final_form: Default::default(),
+        max_proof_size: None,
+        round_merkle_params: vec![],
+        _pow_parameters: Default::default(),
+        starting_log_inv_rate: 1,
+    };
+    let params = Config::try_new(MultivariateParameters::new(num_variables), whir_params).unwrap();
+    let io = IOPattern::<DefaultHash>::new("🌪️")
+        .commit_statement(&params)
+        .add_whir_proof(&params);
+    (params, io)
+}
+
+fn prove(
+    params: &Config,
+    io: &IOPattern,
+    polynomials: &[CoefficientList<BaseField>],
+    claims: &Claims,
+) -> ProofResult<(Vec<u8>, WhirProof<MerkleConfig, F>)> {
+    let mut merlin = io.to_merlin();
+    let witness = Committer::new(params.clone()).commit(&mut merlin, stack(polynomials))?;
+    let whir_proof = Prover(params.clone()).prove(&mut merlin, claims.statement(), witness)?;
+    Ok((merlin.transcript().to_vec(), whir_proof))
+}
+
+fn verify(
+    params: &Config,
+    io: &IOPattern,
+    claims: &Claims,
+    transcript: &[u8],
+    whir_proof: &WhirProof<MerkleConfig, F>,
+) -> ProofResult<()> {
+    let mut arthur = io.to_arthur(transcript);
+    Verifier::new(params.clone()).verify(&mut arthur, &claims.statement(), whir_proof)
+}
+
+/// `2^batch_variables` polynomials in `num_variables` variables, each claimed at two points.
+fn instance(
+    batch_variables: usize,
+    num_variables: usize,
+) -> (Vec<CoefficientList<BaseField>>, Claims) {
+    let mut rng = ark_std::test_rng();
+    let polynomials: Vec<_> = (0..1 << batch_variables)
+        .map(|i| {
+            CoefficientList::new(
+                (0..1 << num_variables)
+                    .map(|j| BaseField::from(((i << num_variables) + j + 1) as u64))
+                    .collect(),
+            )
+        })
+        .collect();
+    let claims = polynomials
+        .iter()
+        .enumerate()
+        .flat_map(|(index, polynomial)| {
+            (0..2)
+                .map(|_| {
+                    let point = MultilinearPoint::rand(&mut rng, num_variables);
+                    let evaluation = polynomial.evaluate_at_extension(&point);
+                    (index, point, evaluation)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let claims = Claims {
+        batch_variables,
+        claims,
+    };
+    (polynomials, claims)
+}
+
+fn main() {
+    let (batch_variables, num_variables) = (3, 16);
+    let (params, io) = setup(batch_variables + num_variables);
+    println!("{}", params);
+
+    let (polynomials, claims) = instance(batch_variables, num_variables);
+    let (transcript, whir_proof) = prove(&params, &io, &polynomials, &claims).unwrap();
+    verify(&params, &io, &claims, &transcript, &whir_proof).unwrap();
+    println!(
+        "Opened {} polynomials at {} points with one proof",
+        polynomials.len(),
+        claims.claims.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack() {
+        let (polynomials, claims) = instance(2, 4);
+        let stacked = stack(&polynomials);
+        let statement = claims.statement();
+        for (point, evaluation) in statement.points.iter().zip(&statement.evaluations) {
+            assert_eq!(stacked.evaluate_at_extension(point), *evaluation);
+        }
+    }
+
+    #[test]
+    fn test_batch() {
+        let (batch_variables, num_variables) = (2, 6);
+        let (params, io) = setup(batch_variables + num_variables);
+        let (polynomials, mut claims) = instance(batch_variables, num_variables);
+        let (transcript, whir_proof) = prove(&params, &io, &polynomials, &claims).unwrap();
+        assert!(verify(&params, &io, &claims, &transcript, &whir_proof).is_ok());
+
+        // A claim moved to another polynomial
+        claims.claims[0].0 = 1;
+        assert!(verify(&params, &io, &claims, &transcript, &whir_proof).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+//! WHIR over BN254 with Keccak Merkle trees, the configuration for proofs checked on Ethereum.
+//!
+//! Commits to a polynomial, proves its evaluation at a point at 100 bits of security, and
+//! verifies the proof.
+//!
+//! Run with `cargo run --release --example ethereum`.
+
+use nimue::{DefaultHash, IOPattern, ProofResult};
+use nimue_pow::blake3::Blake3PoW;
+use whir::{
+    crypto::{fields::Field256, merkle_tree::keccak as merkle_tree},
+    parameters::{FoldType, MultivariateParameters, SoundnessType, WhirParameters},
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    whir::{
+        committer::Committer, iopattern::WhirIOPattern, parameters::WhirConfig, prover::Prover,
+        verifier::Verifier, whir_proof_size, Statement, WhirProof,
+    },
+};
+
+type F = Field256;
+type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+type PowStrategy = Blake3PoW;
+type Config = WhirConfig<F, MerkleConfig, PowStrategy>;
+
+fn setup(num_variables: usize, security_level: usize) -> (Config, IOPattern) {
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+    let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+        security_level,
+        pow_bits: 16,
+        folding_factor: 4,
+        leaf_hash_params,
+        two_to_one_params,
+        soundness_type: SoundnessType::ConjectureList,
+        fold_optimisation: FoldType::ProverHelps,
+        zk: false,
+        final_form: Default::default(),
+        max_proof_size: None,
+        round_merkle_params: vec![],
+        _pow_parameters: Default::default(),
+        starting_log_inv_rate: 2,
+    };
+    let params = Config::try_new(MultivariateParameters::new(num_variables), whir_params).unwrap();
+    let io = IOPattern::<DefaultHash>::new("🌪️")
+        .commit_statement(&params)
+        .add_whir_proof(&params);
+    (params, io)
+}
+
+fn prove(
+    params: &Config,
+    io: &IOPattern,
+    polynomial: CoefficientList<F>,
+    statement: &Statement<F>,
+) -> ProofResult<(Vec<u8>, WhirProof<MerkleConfig, F>)> {
+    let mut merlin = io.to_merlin();
+    let witness = Committer::new(params.clone()).commit(&mut merlin, polynomial)?;
+    let whir_proof = Prover(params.clone()).prove(&mut merlin, statement.clone(), witness)?;
+    Ok((merlin.transcript().to_vec(), whir_proof))
+}
+
+fn verify(
+    params: &Config,
+    io: &IOPattern,
+    statement: &Statement<F>,
+    transcript: &[u8],
+    whir_proof: &WhirProof<MerkleConfig, F>,
+) -> ProofResult<()> {
+    let mut arthur = io.to_arthur(transcript);
+    Verifier::new(params.clone()).verify(&mut arthur, statement, whir_proof)
+}
+
+fn instance(num_variables: usize) -> (CoefficientList<F>, Statement<F>) {
+    let mut rng = ark_std::test_rng();
+    let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+    let point = MultilinearPoint::rand(&mut rng, num_variables);
+    let evaluation = polynomial.evaluate(&point);
+    (polynomial, Statement::new(vec![point], vec![evaluation]))
+}
+
+fn main() {
+    let num_variables = 16;
+    let (params, io) = setup(num_variables, 100);
+    println!("{}", params);
+
+    let (polynomial, statement) = instance(num_variables);
+    let (transcript, whir_proof) = prove(&params, &io, polynomial, &statement).unwrap();
+    verify(&params, &io, &statement, &transcript, &whir_proof).unwrap();
+
+    println!(
+        "Proof: {} bytes (bound {})",
+        whir_proof_size(&transcript, &whir_proof),
+        params.proof_size_bound(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ethereum() {
+        let num_variables = 8;
+        let (params, io) = setup(num_variables, 32);
+        let (polynomial, statement) = instance(num_variables);
+        let (transcript, whir_proof) = prove(&params, &io, polynomial, &statement).unwrap();
+        assert!(verify(&params, &io, &statement, &transcript, &whir_proof).is_ok());
+        assert!(whir_proof_size(&transcript, &whir_proof) <= params.proof_size_bound());
+    }
+}
@@ -0,0 +1,385 @@
+//! A small Spartan-style SNARK for R1CS, using WHIR as the polynomial commitment scheme.
+//!
+//! The prover commits to the assignment `z`, and runs the two sumchecks of Spartan to reduce
+//! `Az ∘ Bz = Cz` to an evaluation of `z` at a random point. That evaluation is then opened
+//! together with the constant `1` and the public output, in a single batched WHIR proof.
+//!
+//! Run with `cargo run --release --example spartan`.
+
+use ark_ff::{AdditiveGroup, Field};
+use nimue::{
+    plugins::ark::{FieldChallenges, FieldIOPattern, FieldReader, FieldWriter},
+    Arthur, DefaultHash, IOPattern, Merlin, ProofError, ProofResult,
+};
+use nimue_pow::blake3::Blake3PoW;
+use whir::{
+    crypto::{
+        fields::{Field64, Field64_2},
+        merkle_tree::blake3 as merkle_tree,
+    },
+    parameters::{FoldType, MultivariateParameters, SoundnessType, WhirParameters},
+    poly_utils::{
+        coeffs::CoefficientList, eq_poly_outside, evals::EvaluationsList,
+        hypercube::BinaryHypercubePoint, MultilinearPoint,
+    },
+    whir::{
+        committer::Committer, iopattern::WhirIOPattern, parameters::WhirConfig, prover::Prover,
+        verifier::Verifier, Statement, WhirProof,
+    },
+};
+
+type BaseField = Field64;
+type F = Field64_2;
+type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+type PowStrategy = Blake3PoW;
+type Config = WhirConfig<F, MerkleConfig, PowStrategy>;
+
+/// A sparse matrix, as `(row, column, value)` entries.
+type Matrix = Vec<(usize, usize, BaseField)>;
+
+/// `Az ∘ Bz = Cz`, with `2^row_variables` constraints and `2^column_variables` variables.
+/// `z[0]` is the constant `1` and `z[1]` the public output.
+struct R1CS {
+    row_variables: usize,
+    column_variables: usize,
+    a: Matrix,
+    b: Matrix,
+    c: Matrix,
+}
+
+/// The R1CS of `x^3 + x + 5 = out`, with `z = (1, out, x, x^2, x^3)` padded to 8 variables.
+fn cubic() -> R1CS {
+    let one = BaseField::ONE;
+    R1CS {
+        row_variables: 2,
+        column_variables: 3,
+        // x * x = x^2, x^2 * x = x^3, (x^3 + x + 5) * 1 = out
+        a: vec![
+            (0, 2, one),
+            (1, 3, one),
+            (2, 4, one),
+            (2, 2, one),
+            (2, 0, 5u64.into()),
+        ],
+        b: vec![(0, 2, one), (1, 2, one), (2, 0, one)],
+        c: vec![(0, 3, one), (1, 4, one), (2, 1, one)],
+    }
+}
+
+fn cubic_assignment(x: u64) -> Vec<BaseField> {
+    let x = BaseField::from(x);
+    let out = x * x * x + x + BaseField::from(5u64);
+    vec![
+        BaseField::ONE,
+        out,
+        x,
+        x * x,
+        x * x * x,
+        BaseField::ZERO,
+        BaseField::ZERO,
+        BaseField::ZERO,
+    ]
+}
+
+impl R1CS {
+    fn multiply(&self, matrix: &Matrix, z: &[BaseField]) -> Vec<F> {
+        let mut result = vec![F::ZERO; 1 << self.row_variables];
+        for &(row, column, value) in matrix {
+            result[row] += F::from_base_prime_field(value * z[column]);
+        }
+        result
+    }
+
+    /// The multilinear extension of `matrix` at `(row_point, column_point)`.
+    fn evaluate(&self, matrix: &Matrix, row_point: &[F], column_point: &[F]) -> F {
+        let rows = eq_table(row_point);
+        let columns = eq_table(column_point);
+        matrix
+            .iter()
+            .map(|&(row, column, value)| {
+                rows[row] * columns[column] * F::from_base_prime_field(value)
+            })
+            .sum()
+    }
+}
+
+/// The evaluations of `eq(point, ·)` over the hypercube, in lexicographic order.
+fn eq_table(point: &[F]) -> Vec<F> {
+    let mut table = vec![F::ONE];
+    for &coordinate in point {
+        table = table
+            .iter()
+            .flat_map(|&value| [value * (F::ONE - coordinate), value * coordinate])
+            .collect();
+    }
+    table
+}
+
+/// Evaluates the polynomial of degree `evaluations.len() - 1` taking `evaluations` on
+/// `0, 1, ...` at `point`.
+fn interpolate(evaluations: &[F], point: F) -> F {
+    let nodes: Vec<_> = (0..evaluations.len() as u64).map(F::from).collect();
+    evaluations
+        .iter()
+        .zip(&nodes)
+        .map(|(&evaluation, &node)| {
+            let (num, den) = nodes
+                .iter()
+                .filter(|&&other| other != node)
+                .fold((F::ONE, F::ONE), |(num, den), &other| {
+                    (num * (point - other), den * (node - other))
+                });
+            evaluation * num * den.inverse().unwrap()
+        })
+        .sum()
+}
+
+fn sumcheck_io(mut io: IOPattern, num_rounds: usize, degree: usize) -> IOPattern {
+    for _ in 0..num_rounds {
+        io = FieldIOPattern::<F>::add_scalars(io, degree + 1, "sumcheck_poly");
+        io = FieldIOPattern::<F>::challenge_scalars(io, 1, "sumcheck_randomness");
+    }
+    io
+}
+
+fn io_pattern(r1cs: &R1CS, params: &Config) -> IOPattern {
+    let io = IOPattern::<DefaultHash>::new("🌪️ spartan").commit_statement(params);
+    let io = FieldIOPattern::<F>::challenge_scalars(io, r1cs.row_variables, "tau");
+    let io = sumcheck_io(io, r1cs.row_variables, 3);
+    let io = FieldIOPattern::<F>::add_scalars(io, 3, "matrix_evaluations");
+    let io = FieldIOPattern::<F>::challenge_scalars(io, 3, "matrix_combination");
+    let io = sumcheck_io(io, r1cs.column_variables, 2);
+    let io = FieldIOPattern::<F>::add_scalars(io, 1, "z_evaluation");
+    io.add_whir_proof(params)
+}
+
+/// Proves `sum_x combine(tables(x)) = claimed sum`, where `combine` has degree `degree`, by
+/// binding the variables in order. Returns the sumcheck randomness.
+fn prove_sumcheck(
+    merlin: &mut Merlin,
+    tables: &mut [Vec<F>],
+    degree: usize,
+    combine: impl Fn(&[F]) -> F,
+) -> ProofResult<Vec<F>> {
+    let mut randomness = vec![];
+    while tables[0].len() > 1 {
+        let half = tables[0].len() / 2;
+        let evaluations: Vec<F> = (0..=degree as u64)
+            .map(|x| {
+                let x = F::from(x);
+                (0..half)
+                    .map(|i| {
+                        let values: Vec<_> = tables
+                            .iter()
+                            .map(|table| table[i] + x * (table[i + half] - table[i]))
+                            .collect();
+                        combine(&values)
+                    })
+                    .sum()
+            })
+            .collect();
+        merlin.add_scalars(&evaluations)?;
+        let [r]: [F; 1] = merlin.challenge_scalars()?;
+        for table in tables.iter_mut() {
+            let (low, high) = table.split_at(half);
+            *table = low
+                .iter()
+                .zip(high)
+                .map(|(&low, &high)| low + r * (high - low))
+                .collect();
+        }
+        randomness.push(r);
+    }
+    Ok(randomness)
+}
+
+/// Checks the sumcheck of `claimed_sum`, returning the randomness and the claim it reduces to.
+fn verify_sumcheck(
+    arthur: &mut Arthur,
+    num_rounds: usize,
+    degree: usize,
+    mut claimed_sum: F,
+) -> ProofResult<(Vec<F>, F)> {
+    let mut randomness = vec![];
+    for _ in 0..num_rounds {
+        let mut evaluations = vec![F::ZERO; degree + 1];
+        arthur.fill_next_scalars(&mut evaluations)?;
+        if evaluations[0] + evaluations[1] != claimed_sum {
+            return Err(ProofError::InvalidProof);
+        }
+        let [r]: [F; 1] = arthur.challenge_scalars()?;
+        claimed_sum = interpolate(&evaluations, r);
+        randomness.push(r);
+    }
+    Ok((randomness, claimed_sum))
+}
+
+/// The point of the hypercube selecting `z[index]`.
+fn position(r1cs: &R1CS, index: usize) -> MultilinearPoint<F> {
+    MultilinearPoint::from_binary_hypercube_point(
+        BinaryHypercubePoint(index),
+        r1cs.column_variables,
+    )
+}
+
+fn prove(
+    r1cs: &R1CS,
+    params: &Config,
+    io: &IOPattern,
+    z: Vec<BaseField>,
+) -> ProofResult<(Vec<u8>, WhirProof<MerkleConfig, F>)> {
+    let mut merlin = io.to_merlin();
+    let (az, bz, cz) = (
+        r1cs.multiply(&r1cs.a, &z),
+        r1cs.multiply(&r1cs.b, &z),
+        r1cs.multiply(&r1cs.c, &z),
+    );
+    let lifted_z: Vec<F> = z.iter().copied().map(F::from_base_prime_field).collect();
+    let polynomial = CoefficientList::from(EvaluationsList::new(z));
+    let witness = Committer::new(params.clone()).commit(&mut merlin, polynomial)?;
+
+    // First sumcheck: sum_x eq(tau, x) (Az(x) Bz(x) - Cz(x)) = 0
+    let mut tau = vec![F::ZERO; r1cs.row_variables];
+    merlin.fill_challenge_scalars(&mut tau)?;
+    let mut tables = [eq_table(&tau), az, bz, cz];
+    let row_point = prove_sumcheck(&mut merlin, &mut tables, 3, |v| v[0] * (v[1] * v[2] - v[3]))?;
+    let matrix_evaluations = [tables[1][0], tables[2][0], tables[3][0]];
+    merlin.add_scalars(&matrix_evaluations)?;
+
+    // Second sumcheck: sum_y (r_A A(r_x, y) + r_B B(r_x, y) + r_C C(r_x, y)) z(y)
+    let combination: [F; 3] = merlin.challenge_scalars()?;
+    let rows = eq_table(&row_point);
+    let mut combined = vec![F::ZERO; 1 << r1cs.column_variables];
+    for (matrix, rand) in [&r1cs.a, &r1cs.b, &r1cs.c].into_iter().zip(combination) {
+        for &(row, column, value) in matrix {
+            combined[column] += rand * rows[row] * F::from_base_prime_field(value);
+        }
+    }
+    let mut tables = [combined, lifted_z.clone()];
+    let column_point = prove_sumcheck(&mut merlin, &mut tables, 2, |v| v[0] * v[1])?;
+    let z_evaluation = tables[1][0];
+    merlin.add_scalars(&[z_evaluation])?;
+
+    // Open z at the end of the sumcheck, at the constant and at the output in one proof
+    let statement = Statement::new(
+        vec![
+            MultilinearPoint(column_point),
+            position(r1cs, 0),
+            position(r1cs, 1),
+        ],
+        vec![z_evaluation, lifted_z[0], lifted_z[1]],
+    );
+    let whir_proof = Prover(params.clone()).prove(&mut merlin, statement, witness)?;
+    Ok((merlin.transcript().to_vec(), whir_proof))
+}
+
+fn verify(
+    r1cs: &R1CS,
+    params: &Config,
+    io: &IOPattern,
+    out: BaseField,
+    transcript: &[u8],
+    whir_proof: &WhirProof<MerkleConfig, F>,
+) -> ProofResult<()> {
+    let mut arthur = io.to_arthur(transcript);
+    let verifier = Verifier::new(params.clone());
+    let commitment = verifier.parse_commitment(&mut arthur)?;
+
+    let mut tau = vec![F::ZERO; r1cs.row_variables];
+    arthur.fill_challenge_scalars(&mut tau)?;
+    let (row_point, claim) = verify_sumcheck(&mut arthur, r1cs.row_variables, 3, F::ZERO)?;
+    let [va, vb, vc]: [F; 3] = arthur.next_scalars()?;
+    let eq = eq_poly_outside(&MultilinearPoint(tau), &MultilinearPoint(row_point.clone()));
+    if eq * (va * vb - vc) != claim {
+        return Err(ProofError::InvalidProof);
+    }
+
+    let [ra, rb, rc]: [F; 3] = arthur.challenge_scalars()?;
+    let (column_point, claim) = verify_sumcheck(
+        &mut arthur,
+        r1cs.column_variables,
+        2,
+        ra * va + rb * vb + rc * vc,
+    )?;
+    let [z_evaluation]: [F; 1] = arthur.next_scalars()?;
+    let combined = ra * r1cs.evaluate(&r1cs.a, &row_point, &column_point)
+        + rb * r1cs.evaluate(&r1cs.b, &row_point, &column_point)
+        + rc * r1cs.evaluate(&r1cs.c, &row_point, &column_point);
+    if combined * z_evaluation != claim {
+        return Err(ProofError::InvalidProof);
+    }
+
+    let statement = Statement::new(
+        vec![
+            MultilinearPoint(column_point),
+            position(r1cs, 0),
+            position(r1cs, 1),
+        ],
+        vec![z_evaluation, F::ONE, F::from_base_prime_field(out)],
+    );
+    verifier.verify_with_commitment(&mut arthur, &commitment, &statement, whir_proof)
+}
+
+fn setup(r1cs: &R1CS) -> (Config, IOPattern) {
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+    let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+        security_level: 32,
+        pow_bits: 0,
+        folding_factor: 1,
+        leaf_hash_params,
+        two_to_one_params,
+        soundness_type: SoundnessType::ConjectureList,
+        fold_optimisation: FoldType::ProverHelps,
+        zk: false,
+        final_form: Default::default(),
+        max_proof_size: None,
+        round_merkle_params: vec![],
+        _pow_parameters: Default::default(),
+        starting_log_inv_rate: 1,
+    };
+    let params = Config::try_new(
+        MultivariateParameters::new(r1cs.column_variables),
+        whir_params,
+    )
+    .unwrap();
+    let io = io_pattern(r1cs, &params);
+    (params, io)
+}
+
+fn main() {
+    let r1cs = cubic();
+    let (params, io) = setup(&r1cs);
+    println!("{}", params);
+
+    let z = cubic_assignment(3);
+    let out = z[1];
+    let (transcript, whir_proof) = prove(&r1cs, &params, &io, z).unwrap();
+    verify(&r1cs, &params, &io, out, &transcript, &whir_proof).unwrap();
+    println!("Proved x^3 + x + 5 = {}", out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spartan() {
+        let r1cs = cubic();
+        let (params, io) = setup(&r1cs);
+        let z = cubic_assignment(3);
+        let out = z[1];
+        let (transcript, whir_proof) = prove(&r1cs, &params, &io, z.clone()).unwrap();
+        assert!(verify(&r1cs, &params, &io, out, &transcript, &whir_proof).is_ok());
+
+        // Wrong public output
+        let wrong_out = out + BaseField::ONE;
+        assert!(verify(&r1cs, &params, &io, wrong_out, &transcript, &whir_proof).is_err());
+
+        // Unsatisfying assignment
+        let mut bad_z = z;
+        bad_z[3] += BaseField::ONE;
+        let (transcript, whir_proof) = prove(&r1cs, &params, &io, bad_z).unwrap();
+        assert!(verify(&r1cs, &params, &io, out, &transcript, &whir_proof).is_err());
+    }
+}
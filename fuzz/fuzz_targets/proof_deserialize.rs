@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use whir::whir::proof_from_bytes;
+use whir_fuzz::{MerkleConfig, F};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = proof_from_bytes::<MerkleConfig, F>(data);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use whir_fuzz::instance;
+
+fuzz_target!(|data: &[u8]| {
+    let instance = instance();
+    let _ = instance
+        .verifier
+        .verify_bytes(&instance.io, &instance.statement, data);
+});
@@ -0,0 +1,67 @@
+#![no_main]
+
+//! Corrupts a valid proof in a structured way, so that the fuzzer explores the verifier beyond
+//! the parsing of the proof.
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use whir_fuzz::instance;
+
+#[derive(Debug, Arbitrary)]
+enum Mutation {
+    /// Xor a byte of the proof
+    Flip { index: u16, mask: u8 },
+    /// Overwrite a run of bytes
+    Overwrite { index: u16, bytes: Vec<u8> },
+    /// Insert bytes
+    Insert { index: u16, bytes: Vec<u8> },
+    /// Remove a run of bytes
+    Remove { index: u16, len: u8 },
+    /// Drop the end of the proof
+    Truncate { len: u16 },
+}
+
+fn apply(proof: &mut Vec<u8>, mutation: &Mutation) {
+    let at = |index: u16, len: usize| index as usize % (len + 1);
+    match mutation {
+        Mutation::Flip { index, mask } => {
+            if !proof.is_empty() {
+                let i = *index as usize % proof.len();
+                proof[i] ^= mask;
+            }
+        }
+        Mutation::Overwrite { index, bytes } => {
+            let start = at(*index, proof.len());
+            for (dst, src) in proof[start..].iter_mut().zip(bytes) {
+                *dst = *src;
+            }
+        }
+        Mutation::Insert { index, bytes } => {
+            let start = at(*index, proof.len());
+            proof.splice(start..start, bytes.iter().copied());
+        }
+        Mutation::Remove { index, len } => {
+            let start = at(*index, proof.len());
+            let end = (start + *len as usize).min(proof.len());
+            proof.drain(start..end);
+        }
+        Mutation::Truncate { len } => {
+            let len = at(*len, proof.len());
+            proof.truncate(len);
+        }
+    }
+}
+
+fuzz_target!(|mutations: Vec<Mutation>| {
+    let instance = instance();
+    let mut proof = instance.proof_bytes.clone();
+    for mutation in &mutations {
+        apply(&mut proof, mutation);
+    }
+    let result = instance
+        .verifier
+        .verify_bytes(&instance.io, &instance.statement, &proof);
+    if proof == instance.proof_bytes {
+        assert!(result.is_ok());
+    }
+});
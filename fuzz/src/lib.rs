@@ -0,0 +1,86 @@
+//! Shared setup of the fuzz targets: a small WHIR instance and a valid proof for it.
+//!
+//! Every target expects the verifier to return an error on malformed input, and never to panic.
+
+use std::sync::OnceLock;
+
+use ark_ff::Field;
+use nimue::{DefaultHash, IOPattern};
+use nimue_pow::blake3::Blake3PoW;
+use whir::{
+    crypto::{
+        fields::{Field64, Field64_2},
+        merkle_tree::blake3::{self as merkle_tree, MerkleTreeParams},
+    },
+    parameters::{FoldType, MultivariateParameters, SoundnessType, WhirParameters},
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    whir::{
+        committer::Committer, iopattern::WhirIOPattern, parameters::WhirConfig, proof_to_bytes,
+        prover::Prover, verifier::Verifier, Statement,
+    },
+};
+
+pub type F = Field64_2;
+pub type MerkleConfig = MerkleTreeParams<F>;
+pub type Config = WhirConfig<F, MerkleConfig, Blake3PoW>;
+
+pub const NUM_VARIABLES: usize = 8;
+
+pub struct Instance {
+    pub io: IOPattern,
+    pub verifier: Verifier<F, MerkleConfig, Blake3PoW>,
+    pub statement: Statement<F>,
+    /// `proof_to_bytes` of a valid proof of `statement`.
+    pub proof_bytes: Vec<u8>,
+}
+
+fn setup() -> Instance {
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+    let whir_params = WhirParameters::<MerkleConfig, Blake3PoW> {
+        security_level: 32,
+        pow_bits: 0,
+        folding_factor: 2,
+        leaf_hash_params,
+        two_to_one_params,
+        soundness_type: SoundnessType::ConjectureList,
+        fold_optimisation: FoldType::ProverHelps,
+        zk: false,
+        final_form: Default::default(),
+        max_proof_size: None,
+        round_merkle_params: vec![],
+        _pow_parameters: Default::default(),
+        starting_log_inv_rate: 1,
+    };
+    let params = Config::try_new(MultivariateParameters::new(NUM_VARIABLES), whir_params).unwrap();
+    let io = IOPattern::<DefaultHash>::new("🌪️")
+        .commit_statement(&params)
+        .add_whir_proof(&params);
+
+    let polynomial = CoefficientList::new((0..1 << NUM_VARIABLES).map(Field64::from).collect());
+    let point = MultilinearPoint(vec![F::ONE + F::ONE; NUM_VARIABLES]);
+    let evaluation = polynomial.evaluate_at_extension(&point);
+    let statement = Statement::new(vec![point], vec![evaluation]);
+
+    let mut merlin = io.to_merlin();
+    let witness = Committer::new(params.clone())
+        .commit(&mut merlin, polynomial)
+        .unwrap();
+    let proof = Prover(params.clone())
+        .prove(&mut merlin, statement.clone(), witness)
+        .unwrap();
+    let proof_bytes = proof_to_bytes(merlin.transcript(), &proof).unwrap();
+
+    Instance {
+        io,
+        verifier: Verifier::new(params),
+        statement,
+        proof_bytes,
+    }
+}
+
+/// The instance is only built once per fuzzing process.
+pub fn instance() -> &'static Instance {
+    static INSTANCE: OnceLock<Instance> = OnceLock::new();
+    INSTANCE.get_or_init(setup)
+}
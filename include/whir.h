@@ -0,0 +1,51 @@
+/* C bindings for WHIR, built with `--features ffi`, e.g.
+ *   cargo rustc --release --lib --features ffi --crate-type cdylib
+ * (or `--crate-type staticlib`).
+ *
+ * Instantiated over the quadratic extension of Goldilocks with Blake3 Merkle trees.
+ * Base field elements are passed as one canonical u64, extension elements as two (c0, c1).
+ */
+#ifndef WHIR_H
+#define WHIR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define WHIR_OK 0
+#define WHIR_ERR_NULL_POINTER -1
+#define WHIR_ERR_INVALID_ARGUMENT -2
+#define WHIR_ERR_PROVING_FAILED -3
+#define WHIR_ERR_INVALID_PROOF -4
+#define WHIR_ERR_PANIC -5
+
+typedef struct WhirFfiConfig WhirFfiConfig;
+typedef struct WhirFfiWitness WhirFfiWitness;
+
+typedef struct {
+    uint8_t *ptr;
+    size_t len;
+} WhirBuffer;
+
+/* soundness_type: 0 = UniqueDecoding, 1 = ProvableList, 2 = ConjectureList. Returns NULL on error. */
+WhirFfiConfig *whir_config_new(size_t num_variables, size_t folding_factor,
+                               size_t starting_log_inv_rate, size_t security_level,
+                               size_t pow_bits, uint32_t soundness_type);
+void whir_config_free(WhirFfiConfig *config);
+
+/* coeffs: num_coeffs == 2^num_variables base field elements. */
+int32_t whir_commit(const WhirFfiConfig *config, const uint64_t *coeffs, size_t num_coeffs,
+                    WhirFfiWitness **out_witness);
+void whir_witness_free(WhirFfiWitness *witness);
+
+/* Consumes the witness. points: num_points * num_variables extension elements.
+ * out_evaluations (optional): receives num_points extension elements. */
+int32_t whir_prove(const WhirFfiConfig *config, WhirFfiWitness *witness, const uint64_t *points,
+                   size_t num_points, uint64_t *out_evaluations, WhirBuffer *out_proof);
+
+int32_t whir_verify(const WhirFfiConfig *config, const uint64_t *points,
+                    const uint64_t *evaluations, size_t num_points, const uint8_t *proof,
+                    size_t proof_len);
+
+void whir_buffer_free(WhirBuffer buffer);
+
+#endif /* WHIR_H */
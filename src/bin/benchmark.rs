@@ -235,6 +235,10 @@ fn run_whir<F, MerkleConfig>(
         two_to_one_params,
         soundness_type,
         fold_optimisation,
+        zk: false,
+        final_form: Default::default(),
+        max_proof_size: None,
+        round_merkle_params: vec![],
         _pow_parameters: Default::default(),
         starting_log_inv_rate: starting_rate,
     };
@@ -259,7 +263,7 @@ fn run_whir<F, MerkleConfig>(
         };
 
         let params =
-            WhirConfig::<F, MerkleConfig, PowStrategy>::new(mv_params, whir_params.clone());
+            WhirConfig::<F, MerkleConfig, PowStrategy>::new(mv_params, whir_params.clone().into());
         if !params.check_pow_bits() {
             println!("WARN: more PoW bits required than what specified.");
         }
@@ -322,7 +326,8 @@ fn run_whir<F, MerkleConfig>(
             verifier::Verifier, whir_proof_size,
         };
 
-        let params = WhirConfig::<F, MerkleConfig, PowStrategy>::new(mv_params, whir_params);
+        let params =
+            WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(mv_params, whir_params).unwrap();
         if !params.check_pow_bits() {
             println!("WARN: more PoW bits required than what specified.");
         }
@@ -341,10 +346,7 @@ fn run_whir<F, MerkleConfig>(
             .iter()
             .map(|point| polynomial.evaluate_at_extension(&point))
             .collect();
-        let statement = Statement {
-            points,
-            evaluations,
-        };
+        let statement = Statement::new(points, evaluations);
 
         HashCounter::reset();
         let whir_prover_time = Instant::now();
@@ -0,0 +1,480 @@
+//! Runs the schemes of the crate on the same polynomials and prints a table of their prover
+//! times, verifier times and proof sizes, at the same security level, rate and folding factor.
+//!
+//! The schemes are WHIR as a polynomial commitment (`whir`, with one evaluation claim), WHIR as a
+//! low-degree test (`whir_ldt`) and the FRI low-degree test as a baseline (`fri`), over
+//! Goldilocks^2 with Blake3 Merkle trees. A baseline is a function from the setting and the
+//! polynomial to a `Row`, usually `run` of its `Protocol`; adding one is adding it to `SCHEMES`.
+
+use std::time::{Duration, Instant};
+
+use ark_crypto_primitives::merkle_tree::{Config, LeafParam, MerkleTree, MultiPath, TwoToOneParam};
+use ark_ff::{AdditiveGroup, Field};
+use ark_serialize::CanonicalSerialize;
+use clap::Parser;
+use nimue::{
+    plugins::ark::*, Arthur, ByteChallenges, ByteReader, ByteWriter, DefaultHash, IOPattern,
+    Merlin, ProofError, ProofResult,
+};
+use nimue_pow::{blake3::Blake3PoW, PoWChallenge};
+use rand::SeedableRng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use whir::{
+    crypto::{fields::Field64_2, merkle_tree::blake3 as merkle_tree},
+    domain::Domain,
+    errors::WhirResult,
+    fs_utils::WhirPoWIOPattern,
+    ntt::expand_from_coeff,
+    parameters::{
+        default_max_pow, FoldType, MultivariateParameters, SoundnessType, WhirParameters,
+    },
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    protocol::{Protocol, Whir, WhirLdt},
+    soundness, utils,
+    whir::fold::{compute_fold, FoldDomain},
+};
+
+type F = Field64_2;
+type BaseField = <F as ark_ff::Field>::BasePrimeField;
+type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+type PowStrategy = Blake3PoW;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The numbers of variables of the polynomials.
+    #[arg(short = 'd', long, value_delimiter = ',', default_value = "16,18,20")]
+    num_variables: Vec<usize>,
+
+    #[arg(short = 'l', long, default_value = "100")]
+    security_level: usize,
+
+    #[arg(short = 'r', long, default_value = "1")]
+    rate: usize,
+
+    #[arg(short = 'k', long = "fold", default_value = "4")]
+    folding_factor: usize,
+
+    #[arg(long = "sec", default_value = "ConjectureList")]
+    soundness_type: SoundnessType,
+
+    #[arg(long = "reps", default_value = "100")]
+    verifier_repetitions: usize,
+
+    /// Print CSV instead of a table.
+    #[arg(long)]
+    csv: bool,
+}
+
+struct Row {
+    scheme: &'static str,
+    num_variables: usize,
+    prover_time: Duration,
+    verifier_time: Duration,
+    proof_bytes: usize,
+}
+
+type Scheme =
+    fn(&Args, WhirParameters<MerkleConfig, PowStrategy>, &CoefficientList<BaseField>) -> Row;
+
+const SCHEMES: [Scheme; 3] = [run_whir, run_whir_ldt, run_fri];
+
+// The row of the protocol `P` proving `statement` on `polynomial`.
+fn run<P>(
+    args: &Args,
+    config: P::Config,
+    statement: P::Statement,
+    polynomial: &CoefficientList<BaseField>,
+) -> Row
+where
+    P: Protocol<F>,
+    P::Statement: Clone,
+{
+    let io = P::iopattern(&config, IOPattern::<DefaultHash>::new("🌪️"));
+
+    let prover_time = Instant::now();
+    let mut merlin = io.to_merlin();
+    let witness = P::commit(&config, &mut merlin, polynomial.clone()).unwrap();
+    let proof = P::prove(&config, &mut merlin, statement.clone(), witness).unwrap();
+    let prover_time = prover_time.elapsed();
+
+    let verifier_time = Instant::now();
+    for _ in 0..args.verifier_repetitions {
+        let mut arthur = io.to_arthur(merlin.transcript());
+        P::verify(&config, &mut arthur, &statement, &proof).unwrap();
+    }
+    let verifier_time = verifier_time.elapsed() / args.verifier_repetitions as u32;
+
+    Row {
+        scheme: P::NAME,
+        num_variables: polynomial.num_variables(),
+        prover_time,
+        verifier_time,
+        proof_bytes: P::proof_size(merlin.transcript(), &proof),
+    }
+}
+
+fn run_whir(
+    args: &Args,
+    whir_params: WhirParameters<MerkleConfig, PowStrategy>,
+    polynomial: &CoefficientList<BaseField>,
+) -> Row {
+    use whir::whir::{parameters::WhirConfig, Statement};
+
+    let num_variables = polynomial.num_variables();
+    let config = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+        MultivariateParameters::new(num_variables),
+        whir_params,
+    )
+    .unwrap();
+    let point = MultilinearPoint::rand(&mut ark_std::test_rng(), num_variables);
+    let evaluation = polynomial.evaluate_at_extension(&point);
+    let statement = Statement::new(vec![point], vec![evaluation]);
+    run::<Whir<MerkleConfig, PowStrategy>>(args, config, statement, polynomial)
+}
+
+fn run_whir_ldt(
+    args: &Args,
+    whir_params: WhirParameters<MerkleConfig, PowStrategy>,
+    polynomial: &CoefficientList<BaseField>,
+) -> Row {
+    use whir::whir_ldt::parameters::WhirConfig;
+
+    let config = WhirConfig::<F, MerkleConfig, PowStrategy>::new(
+        MultivariateParameters::new(polynomial.num_variables()),
+        whir_params.into(),
+    );
+    run::<WhirLdt<MerkleConfig, PowStrategy>>(args, config, (), polynomial)
+}
+
+fn run_fri(
+    args: &Args,
+    whir_params: WhirParameters<MerkleConfig, PowStrategy>,
+    polynomial: &CoefficientList<BaseField>,
+) -> Row {
+    let config = FriConfig::new(&whir_params, polynomial.num_variables());
+    run::<Fri>(args, config, (), polynomial)
+}
+
+// FRI: each round folds the codeword by `2^k` at the same rate and commits to the fold, down to
+// a polynomial sent in the clear, and each query is checked through all the rounds. It takes its
+// queries from the soundness of `whir` after grinding `pow_bits`, and has no other PoW.
+struct Fri;
+
+#[derive(Clone)]
+struct FriConfig {
+    num_variables: usize,
+    log_inv_rate: usize,
+    folding_factor: usize,
+    // The rounds committing to a codeword, the first of which is the commitment.
+    num_rounds: usize,
+    num_queries: usize,
+    pow_bits: f64,
+    leaf_hash_params: LeafParam<MerkleConfig>,
+    two_to_one_params: TwoToOneParam<MerkleConfig>,
+}
+
+struct FriWitness {
+    polynomial: CoefficientList<F>,
+    merkle_tree: MerkleTree<MerkleConfig>,
+    leaves: Vec<F>,
+}
+
+// The openings of the queries in each round.
+type FriProof = Vec<(MultiPath<MerkleConfig>, Vec<Vec<F>>)>;
+
+impl FriConfig {
+    fn new(whir_params: &WhirParameters<MerkleConfig, PowStrategy>, num_variables: usize) -> Self {
+        assert!(
+            num_variables >= whir_params.folding_factor,
+            "FRI folds at least once"
+        );
+        let pow_bits = whir_params.pow_bits.min(whir_params.security_level);
+        Self {
+            num_variables,
+            log_inv_rate: whir_params.starting_log_inv_rate,
+            folding_factor: whir_params.folding_factor,
+            num_rounds: num_variables / whir_params.folding_factor,
+            num_queries: soundness::queries(
+                whir_params.soundness_type,
+                whir_params.security_level - pow_bits,
+                whir_params.starting_log_inv_rate,
+            ),
+            pow_bits: pow_bits as f64,
+            leaf_hash_params: whir_params.leaf_hash_params.clone(),
+            two_to_one_params: whir_params.two_to_one_params.clone(),
+        }
+    }
+
+    // The domain of the codeword of `round`, the last being that of the final polynomial.
+    fn domain(&self, round: usize) -> Domain<F> {
+        Domain::new(1 << self.num_variables, self.log_inv_rate)
+            .unwrap()
+            .scale(1 << (round * self.folding_factor))
+    }
+
+    fn final_variables(&self) -> usize {
+        self.num_variables - self.num_rounds * self.folding_factor
+    }
+
+    // The Merkle tree of a codeword, with each coset of `2^k` elements as a leaf, and its leaves.
+    fn commit(&self, codeword: Vec<F>) -> (MerkleTree<MerkleConfig>, Vec<F>) {
+        let leaves = utils::stack_evaluations(codeword, self.folding_factor);
+        #[cfg(not(feature = "parallel"))]
+        let leaf_iter = leaves.chunks_exact(1 << self.folding_factor);
+        #[cfg(feature = "parallel")]
+        let leaf_iter = leaves.par_chunks_exact(1 << self.folding_factor);
+        let merkle_tree =
+            MerkleTree::new(&self.leaf_hash_params, &self.two_to_one_params, leaf_iter).unwrap();
+        (merkle_tree, leaves)
+    }
+
+    // The leaves of the first codeword that are queried.
+    fn queries(&self, transcript: &mut impl ByteChallenges) -> ProofResult<Vec<usize>> {
+        let mut seed = [0u8; 32];
+        transcript.fill_challenge_bytes(&mut seed)?;
+        let mut rng = rand_chacha::ChaCha20Rng::from_seed(seed);
+        let num_leaves = self.domain(0).folded_size(self.folding_factor);
+        Ok(utils::dedup(
+            (0..self.num_queries).map(|_| utils::sample_index(&mut rng, num_leaves)),
+        ))
+    }
+}
+
+impl Protocol<F> for Fri {
+    const NAME: &'static str = "fri";
+    type Config = FriConfig;
+    type Statement = ();
+    type Witness = FriWitness;
+    type Proof = FriProof;
+
+    fn iopattern(config: &FriConfig, mut io: IOPattern) -> IOPattern {
+        for _ in 0..config.num_rounds {
+            io = io
+                .add_bytes(32, "merkle_digest")
+                .challenge_scalars(config.folding_factor, "folding_randomness");
+        }
+        io.add_scalars(1 << config.final_variables(), "final_coeffs")
+            .pow(config.pow_bits)
+            .challenge_bytes(32, "queries_seed")
+    }
+
+    fn commit(
+        config: &FriConfig,
+        merlin: &mut Merlin,
+        polynomial: CoefficientList<BaseField>,
+    ) -> WhirResult<FriWitness> {
+        let expansion = config.domain(0).size() / polynomial.num_coeffs();
+        let codeword = expand_from_coeff(polynomial.coeffs(), expansion)
+            .into_iter()
+            .map(F::from_base_prime_field)
+            .collect();
+        let (merkle_tree, leaves) = config.commit(codeword);
+        merlin.add_bytes(merkle_tree.root().as_ref())?;
+        Ok(FriWitness {
+            polynomial: polynomial.to_extension(),
+            merkle_tree,
+            leaves,
+        })
+    }
+
+    fn prove(
+        config: &FriConfig,
+        merlin: &mut Merlin,
+        _statement: (),
+        witness: FriWitness,
+    ) -> WhirResult<FriProof> {
+        let mut polynomial = witness.polynomial;
+        let mut trees = vec![(witness.merkle_tree, witness.leaves)];
+        for round in 1..=config.num_rounds {
+            let mut folding_randomness = vec![F::ZERO; config.folding_factor];
+            merlin.fill_challenge_scalars(&mut folding_randomness)?;
+            polynomial = polynomial.fold(&MultilinearPoint(folding_randomness));
+            if round < config.num_rounds {
+                let expansion = config.domain(round).size() / polynomial.num_coeffs();
+                let (merkle_tree, leaves) =
+                    config.commit(expand_from_coeff(polynomial.coeffs(), expansion));
+                merlin.add_bytes(merkle_tree.root().as_ref())?;
+                trees.push((merkle_tree, leaves));
+            }
+        }
+        merlin.add_scalars(polynomial.coeffs())?;
+        if config.pow_bits > 0. {
+            merlin.challenge_pow::<PowStrategy>(config.pow_bits)?;
+        }
+
+        // The positions in the codeword of a round are the leaves queried in the previous one.
+        let mut indexes = config.queries(merlin)?;
+        let fold_size = 1 << config.folding_factor;
+        let mut proof = Vec::with_capacity(config.num_rounds);
+        for (round, (merkle_tree, leaves)) in trees.iter().enumerate() {
+            let num_leaves = config.domain(round).folded_size(config.folding_factor);
+            indexes = utils::dedup(indexes.iter().map(|index| index % num_leaves));
+            let answers = indexes
+                .iter()
+                .map(|i| leaves[i * fold_size..(i + 1) * fold_size].to_vec())
+                .collect();
+            proof.push((
+                merkle_tree.generate_multi_proof(indexes.clone()).unwrap(),
+                answers,
+            ));
+        }
+        Ok(proof)
+    }
+
+    fn verify(
+        config: &FriConfig,
+        arthur: &mut Arthur,
+        _statement: &(),
+        proof: &FriProof,
+    ) -> ProofResult<()> {
+        let mut rounds = Vec::with_capacity(config.num_rounds);
+        for _ in 0..config.num_rounds {
+            let root: [u8; 32] = arthur.next_bytes()?;
+            let mut folding_randomness = vec![F::ZERO; config.folding_factor];
+            arthur.fill_challenge_scalars(&mut folding_randomness)?;
+            rounds.push((
+                <MerkleConfig as Config>::InnerDigest::from(root),
+                MultilinearPoint(folding_randomness),
+            ));
+        }
+        let mut final_coefficients = vec![F::ZERO; 1 << config.final_variables()];
+        arthur.fill_next_scalars(&mut final_coefficients)?;
+        let final_polynomial = CoefficientList::new(final_coefficients);
+        if config.pow_bits > 0. {
+            arthur.challenge_pow::<PowStrategy>(config.pow_bits)?;
+        }
+        if proof.len() != config.num_rounds {
+            return Err(ProofError::InvalidProof);
+        }
+
+        // The folds at the queried positions of the codeword of the next round
+        let mut folds: Vec<(usize, F)> = config
+            .queries(arthur)?
+            .into_iter()
+            .map(|index| (index, F::ZERO))
+            .collect();
+        for (round, ((root, folding_randomness), (merkle_proof, answers))) in
+            rounds.iter().zip(proof).enumerate()
+        {
+            let domain = config.domain(round);
+            let num_leaves = domain.folded_size(config.folding_factor);
+            let indexes = utils::dedup(folds.iter().map(|(index, _)| index % num_leaves));
+            if merkle_proof.leaf_indexes != indexes
+                || answers.len() != indexes.len()
+                || answers
+                    .iter()
+                    .any(|answer| answer.len() != 1 << config.folding_factor)
+                || !merkle_proof
+                    .verify(
+                        &config.leaf_hash_params,
+                        &config.two_to_one_params,
+                        root,
+                        answers.iter().map(|a| a.as_ref()),
+                    )
+                    .unwrap()
+            {
+                return Err(ProofError::InvalidProof);
+            }
+            // The first round has no folds to check.
+            if round > 0 {
+                for &(index, fold) in &folds {
+                    let leaf = indexes.binary_search(&(index % num_leaves)).unwrap();
+                    if answers[leaf][index / num_leaves] != fold {
+                        return Err(ProofError::InvalidProof);
+                    }
+                }
+            }
+            let fold_domain = FoldDomain::new(&domain, config.folding_factor, FoldType::Naive);
+            folds = indexes
+                .into_iter()
+                .zip(answers)
+                .map(|(index, answer)| {
+                    let fold = compute_fold(&fold_domain, folding_randomness, index, answer);
+                    (index, fold)
+                })
+                .collect();
+        }
+
+        let final_domain = config.domain(config.num_rounds);
+        for (index, fold) in folds {
+            let point = MultilinearPoint::expand_from_univariate(
+                final_domain.element(index),
+                config.final_variables(),
+            );
+            if final_polynomial.evaluate(&point) != fold {
+                return Err(ProofError::InvalidProof);
+            }
+        }
+        Ok(())
+    }
+
+    fn proof_size(transcript: &[u8], proof: &FriProof) -> usize {
+        transcript.len() + proof.compressed_size()
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    assert!(args.verifier_repetitions > 0, "--reps must be positive");
+
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+
+    let mut rows = Vec::new();
+    for &num_variables in &args.num_variables {
+        let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+            security_level: args.security_level,
+            pow_bits: default_max_pow(num_variables, args.rate),
+            folding_factor: args.folding_factor,
+            leaf_hash_params: leaf_hash_params.clone(),
+            two_to_one_params: two_to_one_params.clone(),
+            soundness_type: args.soundness_type,
+            fold_optimisation: FoldType::ProverHelps,
+            zk: false,
+            final_form: Default::default(),
+            max_proof_size: None,
+            round_merkle_params: vec![],
+            _pow_parameters: Default::default(),
+            starting_log_inv_rate: args.rate,
+        };
+        let polynomial = CoefficientList::new(
+            (0..1 << num_variables)
+                .map(|i| BaseField::from(i as u64))
+                .collect(),
+        );
+        for scheme in SCHEMES {
+            rows.push(scheme(&args, whir_params.clone(), &polynomial));
+        }
+    }
+
+    if args.csv {
+        println!("scheme,num_variables,prover_time_us,verifier_time_us,proof_bytes");
+        for row in &rows {
+            println!(
+                "{},{},{},{},{}",
+                row.scheme,
+                row.num_variables,
+                row.prover_time.as_micros(),
+                row.verifier_time.as_micros(),
+                row.proof_bytes
+            );
+        }
+    } else {
+        println!(
+            "{:<10} {:>9} {:>14} {:>14} {:>12}",
+            "scheme", "variables", "prover", "verifier", "proof bytes"
+        );
+        for row in &rows {
+            println!(
+                "{:<10} {:>9} {:>14} {:>14} {:>12}",
+                row.scheme,
+                row.num_variables,
+                format!("{:.2?}", row.prover_time),
+                format!("{:.2?}", row.verifier_time),
+                row.proof_bytes
+            );
+        }
+    }
+}
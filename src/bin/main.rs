@@ -54,6 +54,10 @@ struct Args {
     #[arg(long = "fold_type", default_value = "ProverHelps")]
     fold_optimisation: FoldType,
 
+    /// The form of the polynomial of the last round, for the PCS.
+    #[arg(long = "final_form", default_value = "Coefficients")]
+    final_form: FinalForm,
+
     #[arg(short = 'f', long = "field", default_value = "Goldilocks2")]
     field: AvailableFields,
 
@@ -200,8 +204,12 @@ fn run_whir_as_ldt<F, MerkleConfig>(
     MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
 {
     use whir::whir_ldt::{
-        committer::Committer, iopattern::WhirIOPattern, parameters::WhirConfig, prover::Prover,
-        verifier::Verifier, whir_proof_size,
+        committer::Committer,
+        iopattern::WhirIOPattern,
+        parameters::{LdtParameters, WhirConfig},
+        prover::Prover,
+        verifier::Verifier,
+        whir_proof_size,
     };
 
     // Runs as a LDT
@@ -222,7 +230,7 @@ fn run_whir_as_ldt<F, MerkleConfig>(
 
     let mv_params = MultivariateParameters::<F>::new(num_variables);
 
-    let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+    let ldt_params = LdtParameters::<MerkleConfig, PowStrategy> {
         security_level,
         pow_bits,
         folding_factor,
@@ -234,7 +242,7 @@ fn run_whir_as_ldt<F, MerkleConfig>(
         starting_log_inv_rate: starting_rate,
     };
 
-    let params = WhirConfig::<F, MerkleConfig, PowStrategy>::new(mv_params, whir_params);
+    let params = WhirConfig::<F, MerkleConfig, PowStrategy>::new(mv_params, ldt_params);
 
     let io = IOPattern::<DefaultHash>::new("🌪️")
         .commit_statement(&params)
@@ -324,11 +332,16 @@ fn run_whir_pcs<F, MerkleConfig>(
         two_to_one_params,
         soundness_type,
         fold_optimisation,
+        zk: false,
+        final_form: args.final_form,
+        max_proof_size: None,
+        round_merkle_params: vec![],
         _pow_parameters: Default::default(),
         starting_log_inv_rate: starting_rate,
     };
 
-    let params = WhirConfig::<F, MerkleConfig, PowStrategy>::new(mv_params, whir_params);
+    let params =
+        WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(mv_params, whir_params).unwrap();
 
     let io = IOPattern::<DefaultHash>::new("🌪️")
         .commit_statement(&params)
@@ -359,10 +372,7 @@ fn run_whir_pcs<F, MerkleConfig>(
         .map(|point| polynomial.evaluate_at_extension(&point))
         .collect();
 
-    let statement = Statement {
-        points,
-        evaluations,
-    };
+    let statement = Statement::new(points, evaluations);
 
     let whir_prover_time = Instant::now();
 
@@ -0,0 +1,155 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand};
+use whir::test_vectors::{default_vectors, ProofCase, TestVector};
+
+#[derive(Parser, Debug)]
+#[command(name = "whir-vectors", author, version, about = "Generate or check WHIR test vectors and proofs", long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write the default vectors as JSON
+    Generate {
+        #[arg(short = 'o', long, default_value = "whir_vectors.json")]
+        output: String,
+    },
+    /// Check vectors produced by another implementation
+    Check { input: String },
+    /// Verify proofs produced by another implementation, each given by a case file (JSON)
+    Verify {
+        cases: Vec<PathBuf>,
+
+        /// Print the steps of the cases that pass too
+        #[arg(short = 'v', long)]
+        verbose: bool,
+    },
+    /// Write the proofs of the default vectors as case files, with their proof files
+    Export {
+        #[arg(short = 'o', long, default_value = "whir_cases")]
+        output: PathBuf,
+    },
+}
+
+// Checks the case in `path`, printing its steps if it fails or if `verbose`.
+fn verify_case(path: &Path, verbose: bool) -> bool {
+    let case = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<ProofCase>(&json).map_err(|e| e.to_string()));
+    let case = match case {
+        Ok(case) => case,
+        Err(err) => {
+            println!("FAIL {}: cannot read the case: {}", path.display(), err);
+            return false;
+        }
+    };
+    let proof_path = path.parent().unwrap_or(path).join(&case.proof);
+    let proof_bytes = match fs::read(&proof_path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            println!(
+                "FAIL {}: cannot read {}: {}",
+                path.display(),
+                proof_path.display(),
+                err
+            );
+            return false;
+        }
+    };
+
+    let diagnostics = case.diagnose(&proof_bytes);
+    let passed = diagnostics.accepted() == case.valid;
+    println!(
+        "{} {}: {} ({})",
+        if passed { "PASS" } else { "FAIL" },
+        path.display(),
+        if diagnostics.accepted() {
+            "accepted"
+        } else {
+            "rejected"
+        },
+        if case.valid {
+            "valid case"
+        } else {
+            "invalid case"
+        },
+    );
+    if !passed || verbose {
+        for (step, result) in &diagnostics.steps {
+            match result {
+                Ok(found) => println!("  {:<12} ok: {}", step, found),
+                Err(err) => println!("  {:<12} failed: {}", step, err),
+            }
+        }
+    }
+    passed
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    match args.command {
+        Command::Generate { output } => {
+            let vectors = default_vectors();
+            fs::write(&output, serde_json::to_string_pretty(&vectors).unwrap()).unwrap();
+            println!("Wrote {} vectors to {}", vectors.len(), output);
+            ExitCode::SUCCESS
+        }
+        Command::Check { input } => {
+            let vectors: Vec<TestVector> =
+                serde_json::from_str(&fs::read_to_string(&input).unwrap()).unwrap();
+            let mut failures = 0;
+            for (i, vector) in vectors.iter().enumerate() {
+                if let Err(err) = vector.check() {
+                    println!("Vector {}: {}", i, err);
+                    failures += 1;
+                }
+            }
+            println!(
+                "{}/{} vectors passed",
+                vectors.len() - failures,
+                vectors.len()
+            );
+            if failures == 0 {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Command::Verify { cases, verbose } => {
+            let failures = cases
+                .iter()
+                .filter(|path| !verify_case(path, verbose))
+                .count();
+            println!("{}/{} cases passed", cases.len() - failures, cases.len());
+            if failures == 0 {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Command::Export { output } => {
+            fs::create_dir_all(&output).unwrap();
+            let vectors = default_vectors();
+            for (i, vector) in vectors.iter().enumerate() {
+                let proof = format!("case_{}.bin", i);
+                let (case, proof_bytes) = ProofCase::from_vector(vector, &proof).unwrap();
+                fs::write(output.join(&proof), proof_bytes).unwrap();
+                fs::write(
+                    output.join(format!("case_{}.json", i)),
+                    serde_json::to_string_pretty(&case).unwrap(),
+                )
+                .unwrap();
+            }
+            println!("Wrote {} cases to {}", vectors.len(), output.display());
+            ExitCode::SUCCESS
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use whir::cli::{run, Cli};
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
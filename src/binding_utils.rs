@@ -0,0 +1,139 @@
+//! Shared plumbing for the language bindings.
+//!
+//! All bindings use the same instantiation (quadratic extension of Goldilocks, Blake3 Merkle trees
+//! and Blake3 PoW) and the same encoding of field elements as `u64` limbs in canonical form:
+//! base field elements take one limb, extension elements two (`c0` first). In text formats,
+//! elements are canonical decimal strings and extension elements `[c0, c1]` pairs.
+
+use ark_ff::{FftField, PrimeField};
+use nimue::{DefaultHash, IOPattern};
+use nimue_pow::blake3::Blake3PoW;
+
+use crate::{
+    crypto::{
+        fields::{Field64, Field64_2},
+        merkle_tree::blake3 as merkle_tree,
+    },
+    parameters::{FoldType, MultivariateParameters, SoundnessType, WhirParameters},
+    poly_utils::MultilinearPoint,
+    whir::{iopattern::WhirIOPattern, parameters::WhirConfig},
+};
+
+pub(crate) type F = Field64_2;
+pub(crate) type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+pub(crate) type PowStrategy = Blake3PoW;
+pub(crate) type Config = WhirConfig<F, MerkleConfig, PowStrategy>;
+
+pub(crate) fn soundness_type_from_code(code: u32) -> Option<SoundnessType> {
+    match code {
+        0 => Some(SoundnessType::UniqueDecoding),
+        1 => Some(SoundnessType::ProvableList),
+        2 => Some(SoundnessType::ConjectureList),
+        _ => None,
+    }
+}
+
+/// Builds a configuration with `ProverHelps` folding, or `None` if the parameters are rejected.
+pub(crate) fn new_config(
+    num_variables: usize,
+    folding_factor: usize,
+    starting_log_inv_rate: usize,
+    security_level: usize,
+    pow_bits: usize,
+    soundness_type: SoundnessType,
+) -> Option<Config> {
+    if folding_factor == 0
+        || num_variables < folding_factor
+        || num_variables
+            .checked_add(starting_log_inv_rate)
+            .map_or(true, |log_size| {
+                log_size > <F as FftField>::TWO_ADICITY as usize
+            })
+    {
+        return None;
+    }
+
+    WhirConfig::try_new(
+        MultivariateParameters::try_new(num_variables).ok()?,
+        whir_parameters(
+            folding_factor,
+            starting_log_inv_rate,
+            security_level,
+            pow_bits,
+            soundness_type,
+        ),
+    )
+    .ok()
+}
+
+/// The parameters of `new_config`, with the Merkle tree parameters of all the bindings.
+pub(crate) fn whir_parameters(
+    folding_factor: usize,
+    starting_log_inv_rate: usize,
+    security_level: usize,
+    pow_bits: usize,
+    soundness_type: SoundnessType,
+) -> WhirParameters<MerkleConfig, PowStrategy> {
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+    WhirParameters {
+        starting_log_inv_rate,
+        folding_factor,
+        soundness_type,
+        security_level,
+        pow_bits,
+        fold_optimisation: FoldType::ProverHelps,
+        zk: false,
+        final_form: Default::default(),
+        max_proof_size: None,
+        round_merkle_params: vec![],
+        _pow_parameters: Default::default(),
+        leaf_hash_params,
+        two_to_one_params,
+    }
+}
+
+pub(crate) fn io_pattern(params: &Config) -> IOPattern {
+    IOPattern::<DefaultHash>::new("🌪️")
+        .commit_statement(params)
+        .add_whir_proof(params)
+}
+
+pub(crate) fn read_extension_elements(limbs: &[u64]) -> Vec<F> {
+    limbs
+        .chunks_exact(2)
+        .map(|c| F::new(Field64::from(c[0]), Field64::from(c[1])))
+        .collect()
+}
+
+pub(crate) fn write_extension_element(value: F, out: &mut [u64]) {
+    out[0] = value.c0.into_bigint().0[0];
+    out[1] = value.c1.into_bigint().0[0];
+}
+
+/// Reads points of `num_variables` extension elements each, laid out point after point.
+pub(crate) fn read_points(limbs: &[u64], num_variables: usize) -> Vec<MultilinearPoint<F>> {
+    read_extension_elements(limbs)
+        .chunks_exact(num_variables)
+        .map(|point| MultilinearPoint(point.to_vec()))
+        .collect()
+}
+
+pub(crate) fn encode_extension(value: &F) -> [String; 2] {
+    [value.c0.to_string(), value.c1.to_string()]
+}
+
+pub(crate) fn decode_base(s: &str) -> Result<Field64, String> {
+    let value: Field64 = s
+        .parse()
+        .map_err(|_| format!("Invalid field element: {}", s))?;
+    // `from_str` reduces modulo p, canonical encodings must not rely on it.
+    if value.into_bigint().to_string() != s {
+        return Err(format!("Non-canonical field element: {}", s));
+    }
+    Ok(value)
+}
+
+pub(crate) fn decode_extension([c0, c1]: &[String; 2]) -> Result<F, String> {
+    Ok(F::new(decode_base(c0)?, decode_base(c1)?))
+}
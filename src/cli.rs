@@ -0,0 +1,396 @@
+//! The `whir` command-line tool, proving and verifying evaluations of polynomials stored in files.
+//!
+//! Uses the instantiation of the language bindings. Polynomials are read either from JSON (an
+//! array of `2^num_variables` base field elements) or, with `--binary`, as little-endian `u64`
+//! coefficients. Points are a JSON array of points, each an array of `[c0, c1]` extension
+//! elements, and evaluations a JSON array of `[c0, c1]`. Proof files hold `proof_to_bytes`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ark_ff::PrimeField;
+use clap::{Args, Parser, Subcommand};
+
+use crate::{
+    binding_utils::{
+        decode_base, decode_extension, encode_extension, io_pattern, new_config, whir_parameters,
+        Config, F,
+    },
+    crypto::fields::Field64,
+    parameters::{default_max_pow, SoundnessType},
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    whir::{
+        self,
+        committer::Committer,
+        proof_from_bytes_for_config, proof_to_bytes,
+        prover::Prover,
+        sweep::{sweep, to_csv},
+        verifier::Verifier,
+        Statement,
+    },
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "whir", author, version, about = "Prove and verify WHIR evaluation claims", long_about = None)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Commit to a polynomial and prove its evaluations at the given points
+    Prove {
+        #[command(flatten)]
+        params: ParamArgs,
+
+        #[arg(long)]
+        polynomial: PathBuf,
+
+        /// Read the polynomial as little-endian u64 coefficients instead of JSON
+        #[arg(long)]
+        binary: bool,
+
+        #[arg(long)]
+        points: PathBuf,
+
+        #[arg(short = 'o', long)]
+        proof: PathBuf,
+
+        /// Where to write the evaluations, printed to stdout if omitted
+        #[arg(long)]
+        evaluations: Option<PathBuf>,
+    },
+    /// Verify a proof file against the given points and evaluations
+    Verify {
+        #[command(flatten)]
+        params: ParamArgs,
+
+        #[arg(long)]
+        points: PathBuf,
+
+        #[arg(long)]
+        evaluations: PathBuf,
+
+        #[arg(long)]
+        proof: PathBuf,
+    },
+    /// Print the costs of the configurations for each folding factor, rate and PoW budget as CSV
+    Sweep {
+        #[arg(short = 'd', long)]
+        num_variables: usize,
+
+        #[arg(short = 'l', long, default_value = "100")]
+        security_level: usize,
+
+        #[arg(long = "sec", default_value = "ConjectureList")]
+        soundness_type: SoundnessType,
+
+        #[arg(long, value_delimiter = ',', default_value = "1,2,3,4,5,6")]
+        folds: Vec<usize>,
+
+        #[arg(long, value_delimiter = ',', default_value = "1,2,3,4")]
+        rates: Vec<usize>,
+
+        #[arg(long, value_delimiter = ',', default_value = "0,10,20,30")]
+        pow_bits: Vec<usize>,
+    },
+    /// Print the contents of a proof file, round by round
+    Inspect {
+        #[command(flatten)]
+        params: ParamArgs,
+
+        #[arg(long)]
+        proof: PathBuf,
+    },
+}
+
+#[derive(Args, Debug)]
+struct ParamArgs {
+    #[arg(short = 'd', long)]
+    num_variables: usize,
+
+    #[arg(short = 'k', long = "fold", default_value = "4")]
+    folding_factor: usize,
+
+    #[arg(short = 'r', long, default_value = "1")]
+    rate: usize,
+
+    #[arg(short = 'l', long, default_value = "100")]
+    security_level: usize,
+
+    #[arg(short = 'p', long)]
+    pow_bits: Option<usize>,
+
+    #[arg(long = "sec", default_value = "ConjectureList")]
+    soundness_type: SoundnessType,
+}
+
+impl ParamArgs {
+    fn config(&self) -> Result<Config, String> {
+        new_config(
+            self.num_variables,
+            self.folding_factor,
+            self.rate,
+            self.security_level,
+            self.pow_bits
+                .unwrap_or_else(|| default_max_pow(self.num_variables, self.rate)),
+            self.soundness_type,
+        )
+        .ok_or_else(|| "Invalid parameters".to_string())
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), String> {
+    fs::write(path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, String> {
+    serde_json::from_slice(&read(path)?).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn read_polynomial(path: &Path, binary: bool) -> Result<CoefficientList<Field64>, String> {
+    let coeffs: Vec<Field64> = if binary {
+        let bytes = read(path)?;
+        if bytes.len() % 8 != 0 {
+            return Err(format!("{}: truncated coefficient", path.display()));
+        }
+        bytes
+            .chunks_exact(8)
+            .map(|c| {
+                let value = u64::from_le_bytes(c.try_into().unwrap());
+                if value >= Field64::MODULUS.0[0] {
+                    return Err(format!("Non-canonical field element: {}", value));
+                }
+                Ok(Field64::from(value))
+            })
+            .collect::<Result<_, _>>()?
+    } else {
+        read_json::<Vec<String>>(path)?
+            .iter()
+            .map(|c| decode_base(c))
+            .collect::<Result<_, _>>()?
+    };
+    if !coeffs.len().is_power_of_two() {
+        return Err("The number of coefficients must be a power of two".to_string());
+    }
+    Ok(CoefficientList::new(coeffs))
+}
+
+fn read_points(path: &Path, num_variables: usize) -> Result<Vec<MultilinearPoint<F>>, String> {
+    read_json::<Vec<Vec<[String; 2]>>>(path)?
+        .iter()
+        .map(|point| {
+            if point.len() != num_variables {
+                return Err(format!("Points must have {} coordinates", num_variables));
+            }
+            point
+                .iter()
+                .map(decode_extension)
+                .collect::<Result<_, _>>()
+                .map(MultilinearPoint)
+        })
+        .collect()
+}
+
+fn prove(
+    params: &ParamArgs,
+    polynomial: &Path,
+    binary: bool,
+    points: &Path,
+    proof: &Path,
+    evaluations: Option<&Path>,
+) -> Result<(), String> {
+    let config = params.config()?;
+    let polynomial = read_polynomial(polynomial, binary)?;
+    if polynomial.num_variables() != params.num_variables {
+        return Err(format!(
+            "Expected a polynomial in {} variables",
+            params.num_variables
+        ));
+    }
+    let points = read_points(points, params.num_variables)?;
+    let evaluations = points
+        .iter()
+        .map(|point| polynomial.evaluate_at_extension(point))
+        .collect();
+    let statement = Statement::new(points, evaluations);
+    let encoded_evaluations = serde_json::to_string(
+        &statement
+            .evaluations
+            .iter()
+            .map(encode_extension)
+            .collect::<Vec<_>>(),
+    )
+    .unwrap();
+
+    let mut merlin = io_pattern(&config).to_merlin();
+    let witness = Committer::new(config.clone())
+        .commit(&mut merlin, polynomial)
+        .map_err(|e| format!("Commitment failed: {}", e))?;
+    let whir_proof = Prover(config)
+        .prove(&mut merlin, statement, witness)
+        .map_err(|e| format!("Proving failed: {}", e))?;
+    let bytes = proof_to_bytes(merlin.transcript(), &whir_proof).map_err(|e| e.to_string())?;
+    write(proof, bytes)?;
+
+    match evaluations {
+        Some(path) => write(path, encoded_evaluations),
+        None => {
+            println!("{}", encoded_evaluations);
+            Ok(())
+        }
+    }
+}
+
+fn verify(
+    params: &ParamArgs,
+    points: &Path,
+    evaluations: &Path,
+    proof: &Path,
+) -> Result<(), String> {
+    let config = params.config()?;
+    let statement = Statement {
+        points: read_points(points, params.num_variables)?,
+        evaluations: read_json::<Vec<[String; 2]>>(evaluations)?
+            .iter()
+            .map(decode_extension)
+            .collect::<Result<_, _>>()?,
+    };
+    if statement.points.len() != statement.evaluations.len() {
+        return Err("There must be as many evaluations as points".to_string());
+    }
+    let io = io_pattern(&config);
+    Verifier::new(config)
+        .verify_bytes(&io, &statement, &read(proof)?)
+        .map_err(|_| "Invalid proof".to_string())
+}
+
+fn inspect(params: &ParamArgs, proof: &Path) -> Result<String, String> {
+    let config = params.config()?;
+    let (transcript, whir_proof) = proof_from_bytes_for_config(&read(proof)?, &config)
+        .map_err(|e| format!("{}: {}", proof.display(), e))?;
+    whir::inspect::inspect(&config, &io_pattern(&config), &transcript, &whir_proof)
+        .map(|report| report.to_string())
+        .map_err(|_| "The proof does not match the parameters".to_string())
+}
+
+/// Runs the command, returning a message describing the failure, if any.
+pub fn run(cli: Cli) -> Result<(), String> {
+    match &cli.command {
+        Command::Prove {
+            params,
+            polynomial,
+            binary,
+            points,
+            proof,
+            evaluations,
+        } => prove(
+            params,
+            polynomial,
+            *binary,
+            points,
+            proof,
+            evaluations.as_deref(),
+        ),
+        Command::Verify {
+            params,
+            points,
+            evaluations,
+            proof,
+        } => {
+            verify(params, points, evaluations, proof)?;
+            println!("Proof is valid");
+            Ok(())
+        }
+        Command::Sweep {
+            num_variables,
+            security_level,
+            soundness_type,
+            folds,
+            rates,
+            pow_bits,
+        } => {
+            // The sweep sets the folding factor, the rate and the PoW bits
+            let whir_parameters = whir_parameters(0, 0, *security_level, 0, *soundness_type);
+            let rows = sweep::<F, _, _>(*num_variables, &whir_parameters, folds, rates, pow_bits);
+            print!("{}", to_csv(&rows));
+            Ok(())
+        }
+        Command::Inspect { params, proof } => {
+            print!("{}", inspect(params, proof)?);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("whir-cli-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+        let (poly, points, proof, evals, wrong_evals) = (
+            path("poly.bin"),
+            path("points.json"),
+            path("proof.bin"),
+            path("evals.json"),
+            path("wrong.json"),
+        );
+
+        let coeffs: Vec<u8> = (0..1u64 << 6).flat_map(|c| c.to_le_bytes()).collect();
+        fs::write(&poly, coeffs).unwrap();
+        let point = r#"[[["1","2"],["3","0"],["5","6"],["7","8"],["9","10"],["11","12"]]]"#;
+        fs::write(&points, point).unwrap();
+        fs::write(&wrong_evals, r#"[["1","1"]]"#).unwrap();
+
+        let params = ["-d", "6", "-k", "2", "-l", "32", "-p", "0"];
+        let prove = [
+            "prove",
+            "--binary",
+            "--polynomial",
+            &poly,
+            "--points",
+            &points,
+        ];
+        let prove = prove
+            .into_iter()
+            .chain(["--proof", &proof, "--evaluations", &evals]);
+        run(Cli::parse_from(
+            ["whir"].into_iter().chain(params).chain(prove),
+        ))
+        .unwrap();
+
+        let verify = |evaluations: &str| {
+            let args = ["verify", "--points", &points, "--proof", &proof];
+            let args = args.into_iter().chain(["--evaluations", evaluations]);
+            run(Cli::parse_from(
+                ["whir"].into_iter().chain(params).chain(args),
+            ))
+        };
+        assert!(verify(&evals).is_ok());
+        assert!(verify(&wrong_evals).is_err());
+
+        let sweep = [
+            "sweep", "-d", "10", "-l", "32", "--folds", "2,3", "--rates", "1",
+        ];
+        assert!(run(Cli::parse_from(["whir"].into_iter().chain(sweep))).is_ok());
+
+        let inspect = ["inspect", "--proof", &proof];
+        assert!(run(Cli::parse_from(
+            ["whir"].into_iter().chain(params).chain(inspect),
+        ))
+        .is_ok());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use std::{borrow::Borrow, marker::PhantomData};
+use ark_std::{borrow::Borrow, marker::PhantomData, vec::Vec};
 
 use super::{HashCounter, IdentityDigestConverter};
 use ark_crypto_primitives::{
@@ -6,7 +6,7 @@ use ark_crypto_primitives::{
     merkle_tree::Config,
     sponge::Absorb,
 };
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Write};
 use rand::RngCore;
 
 #[derive(
@@ -38,6 +38,20 @@ impl Absorb for Blake3Digest {
     }
 }
 
+// Feeds the bytes written to it to the hasher.
+pub(super) struct HasherWriter<'a>(pub(super) &'a mut blake3::Hasher);
+
+impl Write for HasherWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> ark_std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> ark_std::io::Result<()> {
+        Ok(())
+    }
+}
+
 pub struct Blake3LeafHash<F>(PhantomData<F>);
 pub struct Blake3TwoToOneCRHScheme;
 
@@ -54,11 +68,11 @@ impl<F: CanonicalSerialize + Send> CRHScheme for Blake3LeafHash<F> {
         _: &Self::Parameters,
         input: T,
     ) -> Result<Self::Output, ark_crypto_primitives::Error> {
-        let mut buf = vec![];
-        CanonicalSerialize::serialize_compressed(input.borrow(), &mut buf)?;
-
+        // Serialized straight into the hasher: an allocation per leaf is a large part of the
+        // cost of hashing small leaves. The blake3 crate has no public API to hash many inputs at
+        // once, its SIMD only spans the chunks of one input.
         let mut h = blake3::Hasher::new();
-        h.update(&buf);
+        CanonicalSerialize::serialize_compressed(input.borrow(), HasherWriter(&mut h))?;
 
         let mut output = [0; 32];
         output.copy_from_slice(h.finalize().as_bytes());
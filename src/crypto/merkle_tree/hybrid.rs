@@ -0,0 +1,158 @@
+//! Merkle trees hashed with Blake3 or with an algebraic hash (e.g. Poseidon2), as chosen by the
+//! hash parameters of each tree. With `WhirParameters::round_merkle_params`, a configuration
+//! hashes the large tree of the commitment with Blake3, and only the smaller trees of the later
+//! rounds, whose paths dominate the cost of a recursive verifier, with the algebraic hash.
+//!
+//! The crate has no algebraic hash of its own: it is plugged in as an `AlgebraicHash`, whose
+//! digests have 32 bytes like those of Blake3, e.g. the little-endian encoding of a field
+//! element. The Blake3 trees are those of `blake3::MerkleTreeParams`, with the same roots.
+
+use ark_std::{borrow::Borrow, marker::PhantomData, sync::Arc};
+
+use super::{
+    blake3::{Blake3Digest, Blake3LeafHash, Blake3TwoToOneCRHScheme},
+    HashCounter, IdentityDigestConverter,
+};
+use ark_crypto_primitives::{
+    crh::{CRHScheme, TwoToOneCRHScheme},
+    merkle_tree::Config,
+};
+use ark_serialize::CanonicalSerialize;
+use rand::RngCore;
+
+/// An algebraic hash of the leaves and of the inner nodes.
+pub trait AlgebraicHash<F>: Send + Sync {
+    /// The digest of a leaf.
+    fn hash_leaf(&self, leaf: &[F]) -> [u8; 32];
+    /// The digest of an inner node, from those of its children.
+    fn compress(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32];
+}
+
+/// The hash of a tree, the parameters of both its leaf and its two-to-one hashes.
+pub enum HybridHash<F> {
+    Blake3,
+    Algebraic(Arc<dyn AlgebraicHash<F>>),
+}
+
+// Not derived, which would require `F: Clone`.
+impl<F> Clone for HybridHash<F> {
+    fn clone(&self) -> Self {
+        match self {
+            HybridHash::Blake3 => HybridHash::Blake3,
+            HybridHash::Algebraic(hash) => HybridHash::Algebraic(hash.clone()),
+        }
+    }
+}
+
+pub struct HybridLeafHash<F>(PhantomData<F>);
+pub struct HybridTwoToOneCRHScheme<F>(PhantomData<F>);
+
+impl<F: CanonicalSerialize + Send> CRHScheme for HybridLeafHash<F> {
+    type Input = [F];
+    type Output = Blake3Digest;
+    type Parameters = HybridHash<F>;
+
+    fn setup<R: RngCore>(_: &mut R) -> Result<Self::Parameters, ark_crypto_primitives::Error> {
+        Ok(HybridHash::Blake3)
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        parameters: &Self::Parameters,
+        input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        match parameters {
+            HybridHash::Blake3 => Blake3LeafHash::<F>::evaluate(&(), input),
+            HybridHash::Algebraic(hash) => {
+                HashCounter::add();
+                Ok(hash.hash_leaf(input.borrow()).into())
+            }
+        }
+    }
+}
+
+impl<F: Send> TwoToOneCRHScheme for HybridTwoToOneCRHScheme<F> {
+    type Input = Blake3Digest;
+    type Output = Blake3Digest;
+    type Parameters = HybridHash<F>;
+
+    fn setup<R: RngCore>(_: &mut R) -> Result<Self::Parameters, ark_crypto_primitives::Error> {
+        Ok(HybridHash::Blake3)
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        parameters: &Self::Parameters,
+        left_input: T,
+        right_input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        match parameters {
+            HybridHash::Blake3 => {
+                Blake3TwoToOneCRHScheme::evaluate(&(), left_input.borrow(), right_input.borrow())
+            }
+            HybridHash::Algebraic(hash) => {
+                let digest = |input: &Blake3Digest| {
+                    let mut digest = [0; 32];
+                    digest.copy_from_slice(input.as_ref());
+                    digest
+                };
+                HashCounter::add();
+                Ok(hash
+                    .compress(&digest(left_input.borrow()), &digest(right_input.borrow()))
+                    .into())
+            }
+        }
+    }
+
+    fn compress<T: Borrow<Self::Output>>(
+        parameters: &Self::Parameters,
+        left_input: T,
+        right_input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        <Self as TwoToOneCRHScheme>::evaluate(parameters, left_input, right_input)
+    }
+}
+
+pub type LeafH<F> = HybridLeafHash<F>;
+pub type CompressH<F> = HybridTwoToOneCRHScheme<F>;
+
+#[derive(Debug, Default, Clone)]
+pub struct MerkleTreeParams<F>(PhantomData<F>);
+
+impl<F: CanonicalSerialize + Send> Config for MerkleTreeParams<F> {
+    type Leaf = [F];
+
+    type LeafDigest = <LeafH<F> as CRHScheme>::Output;
+    type LeafInnerDigestConverter = IdentityDigestConverter<Blake3Digest>;
+    type InnerDigest = <CompressH<F> as TwoToOneCRHScheme>::Output;
+
+    type LeafHash = LeafH<F>;
+    type TwoToOneHash = CompressH<F>;
+}
+
+/// The parameters of a tree hashed with `hash`, e.g. for the later rounds.
+pub fn algebraic_config<F>(
+    hash: Arc<dyn AlgebraicHash<F>>,
+) -> (
+    <LeafH<F> as CRHScheme>::Parameters,
+    <CompressH<F> as TwoToOneCRHScheme>::Parameters,
+)
+where
+    F: CanonicalSerialize + Send,
+{
+    (
+        HybridHash::Algebraic(hash.clone()),
+        HybridHash::Algebraic(hash),
+    )
+}
+
+/// The parameters of a tree hashed with Blake3, e.g. for the commitment.
+pub fn default_config<F: CanonicalSerialize + Send>(
+    rng: &mut impl RngCore,
+) -> (
+    <LeafH<F> as CRHScheme>::Parameters,
+    <CompressH<F> as TwoToOneCRHScheme>::Parameters,
+) {
+    let leaf_hash_params = <LeafH<F> as CRHScheme>::setup(rng).unwrap();
+    let two_to_one_params = <CompressH<F> as TwoToOneCRHScheme>::setup(rng).unwrap();
+
+    (leaf_hash_params, two_to_one_params)
+}
@@ -1,4 +1,4 @@
-use std::{borrow::Borrow, marker::PhantomData};
+use ark_std::{borrow::Borrow, marker::PhantomData, vec::Vec};
 
 use super::{HashCounter, IdentityDigestConverter};
 use ark_crypto_primitives::{
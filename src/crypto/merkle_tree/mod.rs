@@ -1,40 +1,54 @@
+//! Merkle tree configurations. The trees themselves are built by `MerkleTree` of
+//! `ark-crypto-primitives`, which with the `parallel` feature (enabled by ours) hashes the leaves
+//! and then each layer of inner nodes in parallel, so the committer needs no builder of its own.
+
 pub mod blake3;
+pub mod hybrid;
 pub mod keccak;
 pub mod mock;
+pub mod packed;
 
-use std::{borrow::Borrow, marker::PhantomData, sync::atomic::AtomicUsize};
+use ark_std::{
+    borrow::Borrow, collections::BTreeSet, marker::PhantomData, sync::atomic::AtomicUsize, vec::Vec,
+};
 
-use ark_crypto_primitives::{crh::CRHScheme, merkle_tree::DigestConverter, Error};
+use ark_crypto_primitives::{
+    crh::CRHScheme,
+    merkle_tree::{Config, DigestConverter, MerkleTree, MultiPath},
+    Error,
+};
 use ark_serialize::CanonicalSerialize;
-use lazy_static::lazy_static;
 use rand::RngCore;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 #[derive(Debug, Default)]
 pub struct HashCounter {
     counter: AtomicUsize,
 }
 
-lazy_static! {
-    static ref HASH_COUNTER: HashCounter = HashCounter::default();
-}
+static HASH_COUNTER: HashCounter = HashCounter {
+    counter: AtomicUsize::new(0),
+};
 
 impl HashCounter {
+    // Relaxed: the hashes of a tree are counted from all the threads, and only the total is read.
     pub(crate) fn add() -> usize {
         HASH_COUNTER
             .counter
-            .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+            .fetch_add(1, ark_std::sync::atomic::Ordering::Relaxed)
     }
 
     pub fn reset() {
         HASH_COUNTER
             .counter
-            .store(0, std::sync::atomic::Ordering::SeqCst)
+            .store(0, ark_std::sync::atomic::Ordering::SeqCst)
     }
 
     pub fn get() -> usize {
         HASH_COUNTER
             .counter
-            .load(std::sync::atomic::Ordering::SeqCst)
+            .load(ark_std::sync::atomic::Ordering::SeqCst)
     }
 }
 
@@ -71,3 +85,86 @@ impl<T> DigestConverter<T, T> for IdentityDigestConverter<T> {
         Ok(item)
     }
 }
+
+/// The same proof as `MerkleTree::generate_multi_proof`, with the path of each leaf generated in
+/// parallel, then prefix-encoded in order. Returns `None` if an index is out of range.
+pub fn generate_multi_proof<P: Config>(
+    tree: &MerkleTree<P>,
+    indexes: impl IntoIterator<Item = usize>,
+) -> Option<MultiPath<P>> {
+    let leaf_indexes = Vec::from_iter(BTreeSet::from_iter(indexes));
+    #[cfg(not(feature = "parallel"))]
+    let paths = leaf_indexes
+        .iter()
+        .map(|&index| tree.generate_proof(index).ok());
+    #[cfg(feature = "parallel")]
+    let paths = leaf_indexes
+        .par_iter()
+        .map(|&index| tree.generate_proof(index).ok())
+        .collect::<Vec<_>>();
+    let paths = paths.into_iter().collect::<Option<Vec<_>>>()?;
+
+    let mut multi_path = MultiPath {
+        leaf_siblings_hashes: Vec::with_capacity(paths.len()),
+        auth_paths_prefix_lenghts: Vec::with_capacity(paths.len()),
+        auth_paths_suffixes: Vec::with_capacity(paths.len()),
+        leaf_indexes,
+    };
+    let mut previous: &[P::InnerDigest] = &[];
+    for path in &paths {
+        // The length of the prefix shared with the previous path, and the rest of the path
+        let prefix_length = previous
+            .iter()
+            .zip(&path.auth_path)
+            .take_while(|(a, b)| a == b)
+            .count();
+        multi_path
+            .leaf_siblings_hashes
+            .push(path.leaf_sibling_hash.clone());
+        multi_path.auth_paths_prefix_lenghts.push(prefix_length);
+        multi_path
+            .auth_paths_suffixes
+            .push(path.auth_path[prefix_length..].to_vec());
+        previous = &path.auth_path;
+    }
+    Some(multi_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::fields::Field64;
+
+    #[test]
+    fn test_generate_multi_proof() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = blake3::default_config::<Field64>(&mut rng);
+        let leaves: Vec<_> = (0..256u64).map(Field64::from).collect();
+        let tree = MerkleTree::<blake3::MerkleTreeParams<Field64>>::new(
+            &leaf_hash_params,
+            &two_to_one_params,
+            leaves.chunks_exact(2),
+        )
+        .unwrap();
+        for indexes in [
+            vec![],
+            vec![5],
+            vec![0, 1, 2, 127],
+            vec![90, 3, 3, 64, 17, 18],
+        ] {
+            let expected = tree.generate_multi_proof(indexes.clone()).unwrap();
+            let multi_path = generate_multi_proof(&tree, indexes).unwrap();
+            assert_eq!(multi_path.leaf_indexes, expected.leaf_indexes);
+            assert_eq!(
+                multi_path.leaf_siblings_hashes,
+                expected.leaf_siblings_hashes
+            );
+            assert_eq!(
+                multi_path.auth_paths_prefix_lenghts,
+                expected.auth_paths_prefix_lenghts
+            );
+            assert_eq!(multi_path.auth_paths_suffixes, expected.auth_paths_suffixes);
+        }
+        assert!(generate_multi_proof(&tree, [128]).is_none());
+    }
+}
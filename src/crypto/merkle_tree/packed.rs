@@ -0,0 +1,167 @@
+//! Blake3 Merkle trees whose leaves are hashed as the packed base-field coordinates of their
+//! elements, instead of their compressed arkworks serialization, which prefixes the leaf with its
+//! 8-byte length and leaves the layout of an extension-field element to arkworks.
+//!
+//! The packed leaf `[x_0, ..., x_{n - 1}]` is the concatenation, for each element `x_i` in
+//! order, of its `d` coordinates over the base prime field (`x_i = c_0 + c_1 * X + ...`, as
+//! `Field::to_base_prime_field_elements`), `c_0` first. Each coordinate is the little-endian
+//! encoding of its canonical representative on `ceil(log2(p) / 8)` bytes, with no flags. A leaf
+//! of `n` elements is `n * d * ceil(log2(p) / 8)` bytes, e.g. 16 bytes per element of
+//! Goldilocks^2, and its hash is the Blake3 hash of these bytes.
+//!
+//! A verifier with the packed bytes of a leaf, e.g. from another proof format, reconstructs its
+//! elements with `unpack_leaf`.
+
+use ark_std::{borrow::Borrow, marker::PhantomData, vec::Vec};
+
+use super::{
+    blake3::{Blake3Digest, Blake3TwoToOneCRHScheme, HasherWriter},
+    HashCounter, IdentityDigestConverter,
+};
+use ark_crypto_primitives::{
+    crh::{CRHScheme, TwoToOneCRHScheme},
+    merkle_tree::Config,
+};
+use ark_ff::{Field, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Write};
+use rand::RngCore;
+
+// The size in bytes of a coordinate, that of the compressed serialization of a prime field
+// element, which has no flags.
+fn coordinate_size<F: Field>() -> usize {
+    (F::BasePrimeField::MODULUS_BIT_SIZE as usize).div_ceil(8)
+}
+
+/// The size in bytes of the packed leaf of `num_elements` elements.
+pub fn packed_leaf_size<F: Field>(num_elements: usize) -> usize {
+    num_elements * F::extension_degree() as usize * coordinate_size::<F>()
+}
+
+/// Writes the packed leaf to `writer`, see the module documentation.
+pub fn write_packed_leaf<F: Field, W: Write>(
+    leaf: &[F],
+    mut writer: W,
+) -> Result<(), ark_serialize::SerializationError> {
+    for coordinate in leaf.iter().flat_map(Field::to_base_prime_field_elements) {
+        coordinate.serialize_compressed(&mut writer)?;
+    }
+    Ok(())
+}
+
+/// The packed leaf, see the module documentation.
+pub fn pack_leaf<F: Field>(leaf: &[F]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(packed_leaf_size::<F>(leaf.len()));
+    write_packed_leaf(leaf, &mut bytes).expect("writing to a vector cannot fail");
+    bytes
+}
+
+/// The elements of the packed leaf `bytes`. Returns `None` if `bytes` is not a whole number of
+/// elements or has a non-canonical coordinate.
+pub fn unpack_leaf<F: Field>(bytes: &[u8]) -> Option<Vec<F>> {
+    let element_size = packed_leaf_size::<F>(1);
+    if bytes.len() % element_size != 0 {
+        return None;
+    }
+    bytes
+        .chunks_exact(element_size)
+        .map(|element| {
+            let coordinates = element
+                .chunks_exact(coordinate_size::<F>())
+                .map(|mut coordinate| F::BasePrimeField::deserialize_compressed(&mut coordinate))
+                .collect::<Result<Vec<_>, _>>()
+                .ok()?;
+            F::from_base_prime_field_elems(coordinates)
+        })
+        .collect()
+}
+
+pub struct PackedLeafHash<F>(PhantomData<F>);
+
+impl<F: Field> CRHScheme for PackedLeafHash<F> {
+    type Input = [F];
+    type Output = Blake3Digest;
+    type Parameters = ();
+
+    fn setup<R: RngCore>(_: &mut R) -> Result<Self::Parameters, ark_crypto_primitives::Error> {
+        Ok(())
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        _: &Self::Parameters,
+        input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        let mut h = blake3::Hasher::new();
+        write_packed_leaf(input.borrow(), HasherWriter(&mut h))?;
+
+        let mut output = [0; 32];
+        output.copy_from_slice(h.finalize().as_bytes());
+        HashCounter::add();
+        Ok(output.into())
+    }
+}
+
+pub type LeafH<F> = PackedLeafHash<F>;
+pub type CompressH = Blake3TwoToOneCRHScheme;
+
+#[derive(Debug, Default, Clone)]
+pub struct MerkleTreeParams<F>(PhantomData<F>);
+
+impl<F: Field> Config for MerkleTreeParams<F> {
+    type Leaf = [F];
+
+    type LeafDigest = <LeafH<F> as CRHScheme>::Output;
+    type LeafInnerDigestConverter = IdentityDigestConverter<Blake3Digest>;
+    type InnerDigest = <CompressH as TwoToOneCRHScheme>::Output;
+
+    type LeafHash = LeafH<F>;
+    type TwoToOneHash = CompressH;
+}
+
+pub fn default_config<F: Field>(
+    rng: &mut impl RngCore,
+) -> (
+    <LeafH<F> as CRHScheme>::Parameters,
+    <CompressH as TwoToOneCRHScheme>::Parameters,
+) {
+    let leaf_hash_params = <LeafH<F> as CRHScheme>::setup(rng).unwrap();
+    let two_to_one_params = <CompressH as TwoToOneCRHScheme>::setup(rng).unwrap();
+
+    (leaf_hash_params, two_to_one_params)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::{AdditiveGroup, UniformRand};
+
+    use super::*;
+    use crate::crypto::fields::{Field64, Field64_2};
+
+    #[test]
+    fn test_packed_leaf() {
+        let mut rng = ark_std::test_rng();
+        let leaf: Vec<_> = (0..4).map(|_| Field64_2::rand(&mut rng)).collect();
+        let bytes = pack_leaf(&leaf);
+        assert_eq!(bytes.len(), packed_leaf_size::<Field64_2>(4));
+        assert_eq!(bytes.len(), 4 * 16);
+        // The layout, coordinate after coordinate
+        let c1 = leaf[0].c1.into_bigint().0[0];
+        assert_eq!(bytes[8..16], c1.to_le_bytes());
+        assert_eq!(unpack_leaf::<Field64_2>(&bytes).unwrap(), leaf);
+        assert_eq!(
+            PackedLeafHash::<Field64_2>::evaluate(&(), leaf.as_slice()).unwrap(),
+            Blake3Digest::from(<[u8; 32]>::from(blake3::hash(&bytes)))
+        );
+
+        // Base field leaves pack to their coordinates alone
+        let base = [Field64::from(3), Field64::ZERO];
+        assert_eq!(
+            pack_leaf(&base),
+            [[3, 0, 0, 0, 0, 0, 0, 0], [0; 8]].concat()
+        );
+
+        assert!(unpack_leaf::<Field64_2>(&bytes[1..]).is_none());
+        let mut non_canonical = bytes.clone();
+        non_canonical[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(unpack_leaf::<Field64_2>(&non_canonical).is_none());
+    }
+}
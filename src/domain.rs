@@ -1,30 +1,65 @@
+//! Evaluation domains of the Reed-Solomon codewords: a coset `g * <w>` of a multiplicative
+//! subgroup, whose `i`-th element `g * w^i` is the `i`-th position of the codeword. Sizes are
+//! powers of two, or `2^a * 3^b` (mixed radix) over fields with a small subgroup of order 3.
+
 use ark_ff::FftField;
 use ark_poly::{
     EvaluationDomain, GeneralEvaluationDomain, MixedRadixEvaluationDomain, Radix2EvaluationDomain,
 };
+use ark_serialize::{CanonicalSerialize, Compress, SerializationError, Write};
+use ark_std::{sync::Arc, vec, vec::Vec};
+
+use crate::utils::{batch_inverse, checked_pow2, is_power_of_two};
 
 #[derive(Debug, Clone)]
 pub struct Domain<F>
 where
     F: FftField,
 {
-    pub base_domain: Option<GeneralEvaluationDomain<F::BasePrimeField>>, // The domain (in the base
-    // field) for the initial FFT
+    /// The domain in the base field, for the initial FFT. Only set for the starting domain.
+    pub base_domain: Option<GeneralEvaluationDomain<F::BasePrimeField>>,
+    /// The domain in the extension field.
     pub backing_domain: GeneralEvaluationDomain<F>,
+    // Precomputed powers of the generator, see `with_precomputed_powers`.
+    table: Option<PowersTable<F>>,
+}
+
+// The powers `W^0, ..., W^(N - 1)` of the generator `W` of the domain from which the table was
+// precomputed, shared with all the domains derived from it. The generator of this domain is
+// `W^stride`.
+#[derive(Debug, Clone)]
+struct PowersTable<F> {
+    powers: Arc<[F]>,
+    stride: usize,
+}
+
+impl<F> PowersTable<F> {
+    fn with_stride(&self, factor: usize) -> Self {
+        Self {
+            powers: self.powers.clone(),
+            stride: self.stride * factor,
+        }
+    }
 }
 
 impl<F> Domain<F>
 where
     F: FftField,
 {
+    /// The domain of size exactly `degree * 2^log_rho_inv`: radix-2 for a power of two, mixed
+    /// radix for sizes `2^a * 3^b` over fields with a small subgroup of order 3. Returns `None` if
+    /// the field has no such domain, including when the size does not fit in a usize.
     pub fn new(degree: usize, log_rho_inv: usize) -> Option<Self> {
-        let size = degree * (1 << log_rho_inv);
-        let base_domain = GeneralEvaluationDomain::new(size)?;
+        let size = degree.checked_mul(checked_pow2(log_rho_inv)?)?;
+        // `GeneralEvaluationDomain` rounds up to the next size it supports, which would not match
+        // the code.
+        let base_domain = GeneralEvaluationDomain::new(size).filter(|d| d.size() == size)?;
         let backing_domain = Self::to_extension_domain(&base_domain);
 
         Some(Self {
             backing_domain,
             base_domain: Some(base_domain),
+            table: None,
         })
     }
 
@@ -40,10 +75,146 @@ where
         self.backing_domain.size()
     }
 
+    /// The generator `w` of the domain.
+    pub fn generator(&self) -> F {
+        self.backing_domain.group_gen()
+    }
+
+    /// The offset `g` of the domain, which is `g * <w>`.
+    pub fn offset(&self) -> F {
+        self.backing_domain.coset_offset()
+    }
+
+    /// A digest of the size, generator and offset of the domain (its serialization), for a prover
+    /// and a verifier built separately to check that they agree on the domain.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut bytes = Vec::with_capacity(self.compressed_size());
+        self.serialize_compressed(&mut bytes).unwrap();
+        blake3::hash(&bytes).into()
+    }
+
+    /// Precomputes the powers of the generator, so that `element` is a lookup instead of an
+    /// exponentiation. The table is shared (not copied) by the domains derived from this one,
+    /// e.g. the domains of the later rounds given by `scale`, and by the clones of the domain.
+    pub fn with_precomputed_powers(mut self) -> Self {
+        let generator = self.generator();
+        let mut power = F::ONE;
+        let powers = (0..self.size())
+            .map(|_| {
+                let value = power;
+                power *= generator;
+                value
+            })
+            .collect();
+        self.table = Some(PowersTable { powers, stride: 1 });
+        self
+    }
+
+    /// Whether the powers of the generator are precomputed, see `with_precomputed_powers`.
+    pub fn has_precomputed_powers(&self) -> bool {
+        self.table.is_some()
+    }
+
+    /// The element at position `index` of the codeword, `g * w^index`.
+    pub fn element(&self, index: usize) -> F {
+        match &self.table {
+            Some(table) => {
+                self.offset() * table.powers[(index * table.stride) % table.powers.len()]
+            }
+            None => self.backing_domain.element(index),
+        }
+    }
+
+    /// The elements in their order in the codeword.
+    pub fn elements(&self) -> impl Iterator<Item = F> {
+        self.backing_domain.elements()
+    }
+
+    /// The elements in bit-reversed order: the `i`-th element is at position `rev(i)`, with
+    /// `rev` reversing the `log2(size)` bits of `i`. The size must be a power of two.
+    pub fn elements_bit_reversed(&self) -> impl Iterator<Item = F> + '_ {
+        assert!(
+            is_power_of_two(self.size()),
+            "bit reversal needs a power-of-two domain"
+        );
+        let shift = usize::BITS - self.size().trailing_zeros();
+        (0..self.size())
+            .map(move |i| self.element(i.reverse_bits().checked_shr(shift).unwrap_or(0)))
+    }
+
+    /// The evaluations over the domain, in codeword order, of the univariate polynomial with
+    /// coefficients `coeffs`, which may be more than the size of the domain: the polynomial is
+    /// first reduced modulo `X^size - g^size`, which vanishes on the domain. For a small domain,
+    /// e.g. a `sub_domain`, this costs little more than reading the coefficients.
+    pub fn evaluate(&self, coeffs: &[F]) -> Vec<F> {
+        let size = self.size();
+        let offset_pow_size = self.backing_domain.coset_offset_pow_size();
+        let mut reduced = vec![F::ZERO; size];
+        let mut scale = F::ONE;
+        for chunk in coeffs.chunks(size) {
+            for (value, coeff) in reduced.iter_mut().zip(chunk) {
+                *value += scale * coeff;
+            }
+            scale *= offset_pow_size;
+        }
+        self.backing_domain.fft(&reduced)
+    }
+
+    /// The domain moved to the coset `offset * <w>` of its subgroup `<w>`, for an `offset` of the
+    /// base field, also moving the base field domain. Returns `None` if `offset` is zero.
+    pub fn with_offset(&self, offset: F::BasePrimeField) -> Option<Self> {
+        if offset.is_zero() {
+            return None;
+        }
+        let base_domain = match &self.base_domain {
+            Some(base_domain) => Some(base_domain.get_coset(offset)?),
+            None => None,
+        };
+        let offset = F::from_base_prime_field(offset);
+        Some(Self {
+            backing_domain: self.with_parts(self.size(), self.generator(), offset),
+            base_domain,
+            table: self.table.clone(),
+        })
+    }
+
+    /// The domain shifted by `shift`, `shift * g * <w>`, or `None` if `shift` is zero.
+    pub fn coset(&self, shift: F) -> Option<Self> {
+        if shift.is_zero() {
+            return None;
+        }
+        Some(Self {
+            backing_domain: self.with_parts(self.size(), self.generator(), self.offset() * shift),
+            base_domain: None,
+            table: self.table.clone(),
+        })
+    }
+
+    /// The coset of `coset_size` elements at the positions `index + j * size / coset_size` of the
+    /// codeword, `g * w^index * <w^(size / coset_size)>`. With `coset_size = 2^folding_factor`,
+    /// these are the positions opened by the leaf `index` of the Merkle tree. Returns `None` if
+    /// `coset_size` does not divide the size or if `index` is not below `size / coset_size`.
+    pub fn sub_domain(&self, coset_size: usize, index: usize) -> Option<Self> {
+        if coset_size == 0 || self.size() % coset_size != 0 || index >= self.size() / coset_size {
+            return None;
+        }
+        let stride = self.size() / coset_size;
+        Some(Self {
+            backing_domain: self.with_parts(
+                coset_size,
+                self.generator().pow([stride as u64]),
+                self.element(index),
+            ),
+            base_domain: None,
+            table: self.table.as_ref().map(|table| table.with_stride(stride)),
+        })
+    }
+
     pub fn scale(&self, power: usize) -> Self {
         Self {
             backing_domain: self.scale_generator_by(power),
             base_domain: None, // Set to zero because we only care for the initial
+            table: self.table.as_ref().map(|table| table.with_stride(power)),
         }
     }
 
@@ -95,23 +266,30 @@ where
         let starting_size = self.size();
         assert_eq!(starting_size % power, 0);
         let new_size = starting_size / power;
-        let log_size_of_group = new_size.trailing_zeros();
-        let size_as_field_element = F::from(new_size as u64);
-
-        match self.backing_domain {
-            GeneralEvaluationDomain::Radix2(r2) => {
-                let group_gen = r2.group_gen.pow([power as u64]);
-                let group_gen_inv = group_gen.inverse().unwrap();
+        self.with_parts(
+            new_size,
+            self.generator().pow([power as u64]),
+            self.offset().pow([power as u64]),
+        )
+    }
 
-                let offset = r2.offset.pow([power as u64]);
-                let offset_inv = r2.offset_inv.pow([power as u64]);
-                let offset_pow_size = offset.pow([new_size as u64]);
+    // A domain of the same kind (radix-2 or mixed radix) as the backing domain, from its size,
+    // generator and offset.
+    fn with_parts(&self, size: usize, group_gen: F, offset: F) -> GeneralEvaluationDomain<F> {
+        let log_size_of_group = size.trailing_zeros();
+        let size_as_field_element = F::from(size as u64);
+        let inverses = batch_inverse(&[size_as_field_element, group_gen, offset]);
+        let (size_inv, group_gen_inv, offset_inv) = (inverses[0], inverses[1], inverses[2]);
+        let offset_pow_size = offset.pow([size as u64]);
+        let size = size as u64;
 
+        match self.backing_domain {
+            GeneralEvaluationDomain::Radix2(_) => {
                 GeneralEvaluationDomain::Radix2(Radix2EvaluationDomain {
-                    size: new_size as u64,
+                    size,
                     log_size_of_group,
                     size_as_field_element,
-                    size_inv: size_as_field_element.inverse().unwrap(),
+                    size_inv,
                     group_gen,
                     group_gen_inv,
                     offset,
@@ -119,19 +297,12 @@ where
                     offset_pow_size,
                 })
             }
-            GeneralEvaluationDomain::MixedRadix(mr) => {
-                let group_gen = mr.group_gen.pow([power as u64]);
-                let group_gen_inv = mr.group_gen_inv.pow([power as u64]);
-
-                let offset = mr.offset.pow([power as u64]);
-                let offset_inv = mr.offset_inv.pow([power as u64]);
-                let offset_pow_size = offset.pow([new_size as u64]);
-
+            GeneralEvaluationDomain::MixedRadix(_) => {
                 GeneralEvaluationDomain::MixedRadix(MixedRadixEvaluationDomain {
-                    size: new_size as u64,
+                    size,
                     log_size_of_group,
                     size_as_field_element,
-                    size_inv: size_as_field_element.inverse().unwrap(),
+                    size_inv,
                     group_gen,
                     group_gen_inv,
                     offset,
@@ -142,3 +313,128 @@ where
         }
     }
 }
+
+// The size, generator and offset of the domain, which determine its elements. The base field
+// domain and the table of powers are left out: they are the same elements.
+impl<F> CanonicalSerialize for Domain<F>
+where
+    F: FftField,
+{
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        (self.size() as u64).serialize_with_mode(&mut writer, compress)?;
+        self.generator()
+            .serialize_with_mode(&mut writer, compress)?;
+        self.offset().serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        0u64.serialized_size(compress)
+            + self.generator().serialized_size(compress)
+            + self.offset().serialized_size(compress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::Field;
+    use ark_serialize::CanonicalSerialize;
+
+    use super::Domain;
+    use crate::{crypto::fields::Field64 as F, poly_utils::coeffs::CoefficientList};
+
+    #[test]
+    fn test_domain_api() {
+        let domain = Domain::<F>::new(8, 1).unwrap();
+        assert_eq!(domain.size(), 16);
+        assert_eq!(domain.offset(), F::ONE);
+        let elements: Vec<F> = domain.elements().collect();
+        for (i, element) in elements.iter().enumerate() {
+            assert_eq!(*element, domain.generator().pow([i as u64]));
+            assert_eq!(domain.element(i), *element);
+        }
+
+        let bit_reversed: Vec<F> = domain.elements_bit_reversed().collect();
+        assert_eq!(bit_reversed[1], elements[8]);
+        assert_eq!(bit_reversed[2], elements[4]);
+        assert_eq!(bit_reversed[3], elements[12]);
+
+        let shift = F::from(7);
+        let coset = domain.coset(shift).unwrap();
+        assert!(coset.elements().zip(&elements).all(|(x, y)| x == shift * y));
+        assert!(domain.coset(F::ZERO).is_none());
+
+        // The leaf 3 of a codeword folded 4 times holds the positions 3, 7, 11 and 15.
+        let sub_domain = domain.sub_domain(4, 3).unwrap();
+        let positions: Vec<F> = sub_domain.elements().collect();
+        assert_eq!(positions, [3, 7, 11, 15].map(|i| elements[i]));
+        assert!(domain.sub_domain(4, 4).is_none());
+        assert!(domain.sub_domain(3, 0).is_none());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let coeffs: Vec<F> = (0..32).map(|i| F::from(i * 7 + 1)).collect();
+        let polynomial = CoefficientList::new(coeffs.clone());
+        let domain = Domain::<F>::new(16, 2)
+            .unwrap()
+            .with_offset(F::from(3))
+            .unwrap();
+        for domain in [domain.clone(), domain.sub_domain(4, 5).unwrap()] {
+            let elements: Vec<F> = domain.elements().collect();
+            assert_eq!(
+                domain.evaluate(&coeffs),
+                polynomial.evaluate_at_univariate(&elements)
+            );
+        }
+    }
+
+    #[test]
+    fn test_precomputed_powers() {
+        let domain = Domain::<F>::new(16, 2)
+            .unwrap()
+            .with_offset(F::from(3))
+            .unwrap();
+        let tabled = domain.clone().with_precomputed_powers();
+        let derived = |domain: &Domain<F>| {
+            [
+                domain.clone(),
+                domain.scale(2),
+                domain.scale(2).scale(4),
+                domain.coset(F::from(5)).unwrap(),
+                domain.sub_domain(8, 5).unwrap(),
+                domain.scale(2).sub_domain(4, 1).unwrap(),
+            ]
+        };
+        for (plain, tabled) in derived(&domain).iter().zip(&derived(&tabled)) {
+            assert!(tabled.table.is_some());
+            for i in 0..plain.size() {
+                assert_eq!(plain.element(i), tabled.element(i));
+            }
+        }
+    }
+
+    #[test]
+    fn test_digest() {
+        let domain = Domain::<F>::new(16, 2).unwrap();
+        assert_eq!(domain.digest(), Domain::<F>::new(16, 2).unwrap().digest());
+        assert_eq!(
+            domain.digest(),
+            domain.clone().with_precomputed_powers().digest()
+        );
+        assert_eq!(
+            domain.uncompressed_size(),
+            8 + 2 * F::ONE.uncompressed_size()
+        );
+
+        assert_ne!(domain.digest(), Domain::<F>::new(32, 1).unwrap().digest());
+        assert_ne!(domain.digest(), domain.scale(2).digest());
+        assert_ne!(
+            domain.digest(),
+            domain.with_offset(F::from(3)).unwrap().digest()
+        );
+    }
+}
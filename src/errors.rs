@@ -0,0 +1,124 @@
+//! Errors of the public entry points on malformed inputs.
+
+use ark_std::fmt::{self, Display};
+use nimue::ProofError;
+
+#[derive(Debug)]
+pub enum WhirError {
+    /// A list of coefficients or evaluations whose length is not a power of two.
+    NotPowerOfTwo(usize),
+    /// A polynomial, a point or the weights of a claim with the wrong number of variables.
+    NumVariables { expected: usize, found: usize },
+    /// A statement without one claim type (or one evaluation) per point.
+    StatementLength { points: usize, found: usize },
+    /// A claim repeating an earlier claim of the statement, at this index.
+    DuplicateClaim(usize),
+    /// A claim at the point of the earlier claim at this index, with another evaluation.
+    ConflictingClaim(usize),
+    /// More variables than the number of coefficients (`2^num_variables`) fits in a usize.
+    TooManyVariables(usize),
+    /// An input longer than the configuration allows.
+    TooLong { max: usize, found: usize },
+    /// A statement not starting with the claims of the OOD samples it stands for, see
+    /// `WhirConfig::with_statement_ood_samples`: `found` of the `expected` claims match.
+    StatementOodSamples { expected: usize, found: usize },
+    /// A claim that the configuration does not support, e.g. a rotated claim in ZK mode.
+    UnsupportedClaim,
+    /// Parameters for which there is no configuration.
+    InvalidParameters(&'static str),
+    /// A message of another machine that does not fit the protocol, see `whir::distributed`.
+    InvalidMessage(&'static str),
+    /// No configuration within the proof size budget: the smallest proof allowed by its bounds on
+    /// the PoW bits and the folding factor, and the parameters it was found with.
+    ProofTooLarge {
+        max: usize,
+        smallest: usize,
+        pow_bits: usize,
+        folding_factor: usize,
+    },
+    /// Proving was cancelled, in this round (`n_rounds` for the final round), see
+    /// `whir::cancel`.
+    ProvingAborted { round: usize },
+    /// The PoW service returned no nonce, or one that does not solve the PoW, in this round
+    /// (`n_rounds` for the final round), see `whir::pow`.
+    PowFailed { round: usize },
+    /// Writing to or reading from the transcript failed.
+    Proof(ProofError),
+}
+
+pub type WhirResult<T> = Result<T, WhirError>;
+
+impl Display for WhirError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WhirError::NotPowerOfTwo(len) => write!(f, "length {len} is not a power of two"),
+            WhirError::NumVariables { expected, found } => {
+                write!(f, "expected {expected} variables, found {found}")
+            }
+            WhirError::StatementLength { points, found } => {
+                write!(f, "expected one entry per point ({points}), found {found}")
+            }
+            WhirError::DuplicateClaim(index) => {
+                write!(
+                    f,
+                    "claim {index} repeats an earlier claim at the same point"
+                )
+            }
+            WhirError::ConflictingClaim(index) => {
+                write!(
+                    f,
+                    "a claim at the point of claim {index} has another evaluation"
+                )
+            }
+            WhirError::TooManyVariables(num_variables) => {
+                write!(f, "{num_variables} variables do not fit in a usize")
+            }
+            WhirError::TooLong { max, found } => {
+                write!(f, "expected at most {max} elements, found {found}")
+            }
+            WhirError::StatementOodSamples { expected, found } => write!(
+                f,
+                "expected {expected} evaluation claims as OOD samples, found {found}"
+            ),
+            WhirError::UnsupportedClaim => write!(f, "claim not supported by the configuration"),
+            WhirError::InvalidParameters(reason) => write!(f, "invalid parameters: {reason}"),
+            WhirError::InvalidMessage(reason) => write!(f, "invalid message: {reason}"),
+            WhirError::ProofTooLarge {
+                max,
+                smallest,
+                pow_bits,
+                folding_factor,
+            } => write!(
+                f,
+                "proofs of at least {smallest} bytes (with {pow_bits} PoW bits and folding factor \
+                 {folding_factor}), above the budget of {max}: allow more PoW bits or a larger \
+                 folding factor"
+            ),
+            WhirError::ProvingAborted { round } => write!(f, "proving aborted in round {round}"),
+            WhirError::PowFailed { round } => {
+                write!(f, "the PoW service found no nonce in round {round}")
+            }
+            WhirError::Proof(err) => write!(f, "transcript error: {err:?}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WhirError {}
+
+impl From<ProofError> for WhirError {
+    fn from(err: ProofError) -> Self {
+        WhirError::Proof(err)
+    }
+}
+
+// So that callers in `ProofResult` functions can keep using `?`, malformed inputs are then
+// reported as an invalid use of the transcript.
+impl From<WhirError> for ProofError {
+    fn from(err: WhirError) -> Self {
+        match err {
+            WhirError::Proof(err) => err,
+            _ => ProofError::InvalidIO,
+        }
+    }
+}
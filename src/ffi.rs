@@ -0,0 +1,359 @@
+//! C ABI for committing, proving and verifying with WHIR.
+//!
+//! The bindings are instantiated with the quadratic extension of Goldilocks, Blake3 Merkle trees
+//! and Blake3 PoW. Configurations and witnesses are passed around as opaque handles, proofs as
+//! byte buffers (see `whir::proof_to_bytes`).
+//!
+//! Field elements cross the boundary as `u64` limbs in canonical (non-Montgomery) form:
+//! coefficients of the committed polynomial are elements of the base field (one limb each),
+//! points and evaluations are elements of the extension (two limbs each, `c0` first).
+//!
+//! The crate is only built as a Rust library, so that its dependents do not link C artifacts;
+//! the shared and static libraries are built on demand:
+//! ```text
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! cargo rustc --release --lib --features ffi --crate-type staticlib
+//! ```
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use nimue::Merlin;
+
+use crate::{
+    binding_utils::{
+        io_pattern, new_config, read_extension_elements, read_points, soundness_type_from_code,
+        write_extension_element, Config, MerkleConfig, F,
+    },
+    crypto::fields::Field64,
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    whir::{
+        committer::{Committer, Witness},
+        proof_to_bytes,
+        prover::Prover,
+        verifier::Verifier,
+        Statement,
+    },
+};
+
+pub const WHIR_OK: i32 = 0;
+pub const WHIR_ERR_NULL_POINTER: i32 = -1;
+pub const WHIR_ERR_INVALID_ARGUMENT: i32 = -2;
+pub const WHIR_ERR_PROVING_FAILED: i32 = -3;
+pub const WHIR_ERR_INVALID_PROOF: i32 = -4;
+pub const WHIR_ERR_PANIC: i32 = -5;
+
+/// Opaque handle to a `WhirConfig`.
+pub struct WhirFfiConfig(Config);
+
+/// Opaque handle to a committed polynomial, together with the transcript of its commitment.
+pub struct WhirFfiWitness {
+    merlin: Merlin,
+    witness: Witness<F, MerkleConfig>,
+}
+
+/// Byte buffer owned by Rust. Must be released with `whir_buffer_free`.
+#[repr(C)]
+pub struct WhirBuffer {
+    pub ptr: *mut u8,
+    pub len: usize,
+}
+
+impl WhirBuffer {
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        let ptr = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        WhirBuffer { ptr, len }
+    }
+}
+
+/// # Safety
+/// `points` must point to `2 * num_points * num_variables` readable `u64`s (or `num_points == 0`).
+unsafe fn read_raw_points(
+    num_variables: usize,
+    points: *const u64,
+    num_points: usize,
+) -> Vec<MultilinearPoint<F>> {
+    if num_points == 0 {
+        return vec![];
+    }
+    read_points(
+        slice::from_raw_parts(points, 2 * num_points * num_variables),
+        num_variables,
+    )
+}
+
+fn guard(f: impl FnOnce() -> i32) -> i32 {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(WHIR_ERR_PANIC)
+}
+
+/// Creates a new configuration, using `ProverHelps` folding.
+///
+/// `soundness_type` is 0 for `UniqueDecoding`, 1 for `ProvableList` and 2 for `ConjectureList`.
+/// Returns a null pointer if the parameters are invalid.
+#[no_mangle]
+pub extern "C" fn whir_config_new(
+    num_variables: usize,
+    folding_factor: usize,
+    starting_log_inv_rate: usize,
+    security_level: usize,
+    pow_bits: usize,
+    soundness_type: u32,
+) -> *mut WhirFfiConfig {
+    catch_unwind(|| {
+        soundness_type_from_code(soundness_type)
+            .and_then(|soundness_type| {
+                new_config(
+                    num_variables,
+                    folding_factor,
+                    starting_log_inv_rate,
+                    security_level,
+                    pow_bits,
+                    soundness_type,
+                )
+            })
+            .map_or(ptr::null_mut(), |params| {
+                Box::into_raw(Box::new(WhirFfiConfig(params)))
+            })
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Releases a configuration created by `whir_config_new`.
+///
+/// # Safety
+/// `config` must be null or a pointer returned by `whir_config_new` that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn whir_config_free(config: *mut WhirFfiConfig) {
+    if !config.is_null() {
+        drop(Box::from_raw(config));
+    }
+}
+
+/// Commits to the multilinear polynomial with the `num_coeffs` base field coefficients `coeffs`.
+/// On success, `*out_witness` is set to a new witness handle.
+///
+/// # Safety
+/// `config` must be a valid configuration, `coeffs` must point to `num_coeffs` readable `u64`s,
+/// and `out_witness` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn whir_commit(
+    config: *const WhirFfiConfig,
+    coeffs: *const u64,
+    num_coeffs: usize,
+    out_witness: *mut *mut WhirFfiWitness,
+) -> i32 {
+    if config.is_null() || coeffs.is_null() || out_witness.is_null() {
+        return WHIR_ERR_NULL_POINTER;
+    }
+    let params = &(*config).0;
+    if num_coeffs != 1 << params.mv_parameters.num_variables {
+        return WHIR_ERR_INVALID_ARGUMENT;
+    }
+    let coeffs = slice::from_raw_parts(coeffs, num_coeffs);
+
+    guard(|| {
+        let polynomial = CoefficientList::new(coeffs.iter().copied().map(Field64::from).collect());
+        let mut merlin = io_pattern(params).to_merlin();
+        let committer = Committer::new(params.clone());
+        match committer.commit(&mut merlin, polynomial) {
+            Ok(witness) => {
+                *out_witness = Box::into_raw(Box::new(WhirFfiWitness { merlin, witness }));
+                WHIR_OK
+            }
+            Err(_) => WHIR_ERR_PROVING_FAILED,
+        }
+    })
+}
+
+/// Releases a witness that was not consumed by `whir_prove`.
+///
+/// # Safety
+/// `witness` must be null or a pointer returned by `whir_commit` that was not freed or consumed yet.
+#[no_mangle]
+pub unsafe extern "C" fn whir_witness_free(witness: *mut WhirFfiWitness) {
+    if !witness.is_null() {
+        drop(Box::from_raw(witness));
+    }
+}
+
+/// Proves the evaluations of the committed polynomial at the `num_points` points `points`
+/// (`num_points * num_variables` extension elements, point after point).
+///
+/// The witness is consumed, even on failure. If `out_evaluations` is not null, the
+/// `num_points` evaluations are written to it. On success `*out_proof` holds the proof bytes.
+///
+/// # Safety
+/// `config` and `witness` must be valid handles, `points` must point to
+/// `2 * num_points * num_variables` readable `u64`s, `out_evaluations` must be null or point to
+/// `2 * num_points` writable `u64`s and `out_proof` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn whir_prove(
+    config: *const WhirFfiConfig,
+    witness: *mut WhirFfiWitness,
+    points: *const u64,
+    num_points: usize,
+    out_evaluations: *mut u64,
+    out_proof: *mut WhirBuffer,
+) -> i32 {
+    if config.is_null() || witness.is_null() || out_proof.is_null() {
+        return WHIR_ERR_NULL_POINTER;
+    }
+    let WhirFfiWitness {
+        mut merlin,
+        witness,
+    } = *Box::from_raw(witness);
+    if num_points > 0 && points.is_null() {
+        return WHIR_ERR_NULL_POINTER;
+    }
+    let params = &(*config).0;
+    let num_variables = params.mv_parameters.num_variables;
+
+    guard(|| {
+        let points = read_raw_points(num_variables, points, num_points);
+        let evaluations: Vec<F> = points
+            .iter()
+            .map(|point| witness.polynomial.evaluate(point))
+            .collect();
+        if !out_evaluations.is_null() {
+            let out = slice::from_raw_parts_mut(out_evaluations, 2 * num_points);
+            for (value, out) in evaluations.iter().zip(out.chunks_exact_mut(2)) {
+                write_extension_element(*value, out);
+            }
+        }
+
+        let statement = Statement::new(points, evaluations);
+        let prover = Prover(params.clone());
+        let Ok(proof) = prover.prove(&mut merlin, statement, witness) else {
+            return WHIR_ERR_PROVING_FAILED;
+        };
+        let Ok(bytes) = proof_to_bytes(merlin.transcript(), &proof) else {
+            return WHIR_ERR_PROVING_FAILED;
+        };
+        *out_proof = WhirBuffer::from_vec(bytes);
+        WHIR_OK
+    })
+}
+
+/// Verifies a proof produced by `whir_prove` against the given points and evaluations.
+/// Returns `WHIR_OK` if the proof is valid and `WHIR_ERR_INVALID_PROOF` otherwise.
+///
+/// # Safety
+/// `config` must be a valid handle, `points` must point to `2 * num_points * num_variables`
+/// readable `u64`s, `evaluations` to `2 * num_points` readable `u64`s and `proof` to
+/// `proof_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn whir_verify(
+    config: *const WhirFfiConfig,
+    points: *const u64,
+    evaluations: *const u64,
+    num_points: usize,
+    proof: *const u8,
+    proof_len: usize,
+) -> i32 {
+    if config.is_null() || proof.is_null() {
+        return WHIR_ERR_NULL_POINTER;
+    }
+    if num_points > 0 && (points.is_null() || evaluations.is_null()) {
+        return WHIR_ERR_NULL_POINTER;
+    }
+    let params = &(*config).0;
+    let proof = slice::from_raw_parts(proof, proof_len);
+
+    guard(|| {
+        let statement = Statement::new(
+            read_raw_points(params.mv_parameters.num_variables, points, num_points),
+            read_extension_elements(slice::from_raw_parts(evaluations, 2 * num_points)),
+        );
+        let verifier = Verifier::new(params.clone());
+        match verifier.verify_bytes(&io_pattern(params), &statement, proof) {
+            Ok(()) => WHIR_OK,
+            Err(_) => WHIR_ERR_INVALID_PROOF,
+        }
+    })
+}
+
+/// Releases a buffer returned by this library.
+///
+/// # Safety
+/// `buffer` must have been returned by this library and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn whir_buffer_free(buffer: WhirBuffer) {
+    if !buffer.ptr.is_null() {
+        drop(Box::from_raw(slice::from_raw_parts_mut(
+            buffer.ptr, buffer.len,
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn test_ffi_roundtrip() {
+        let num_variables = 6;
+        let config = whir_config_new(num_variables, 2, 1, 32, 0, 2);
+        assert!(!config.is_null());
+
+        let coeffs: Vec<u64> = (0..1 << num_variables).collect();
+        let points: Vec<u64> = (0..2 * num_variables as u64).collect();
+        let mut evaluations = vec![0u64; 2];
+
+        unsafe {
+            let mut witness = ptr::null_mut();
+            assert_eq!(
+                whir_commit(config, coeffs.as_ptr(), coeffs.len(), &mut witness),
+                WHIR_OK
+            );
+
+            let mut proof = WhirBuffer {
+                ptr: ptr::null_mut(),
+                len: 0,
+            };
+            assert_eq!(
+                whir_prove(
+                    config,
+                    witness,
+                    points.as_ptr(),
+                    1,
+                    evaluations.as_mut_ptr(),
+                    &mut proof
+                ),
+                WHIR_OK
+            );
+
+            assert_eq!(
+                whir_verify(
+                    config,
+                    points.as_ptr(),
+                    evaluations.as_ptr(),
+                    1,
+                    proof.ptr,
+                    proof.len
+                ),
+                WHIR_OK
+            );
+
+            evaluations[0] += 1;
+            assert_eq!(
+                whir_verify(
+                    config,
+                    points.as_ptr(),
+                    evaluations.as_ptr(),
+                    1,
+                    proof.ptr,
+                    proof.len
+                ),
+                WHIR_ERR_INVALID_PROOF
+            );
+
+            whir_buffer_free(proof);
+            whir_config_free(config);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Adapter for halo2-like frontends, so that WHIR can be used as the backend PCS of a circuit
+//! given by its column assignments.
+//!
+//! The columns are stacked into a single multilinear polynomial, with the column index in the
+//! most significant variables: the stacked polynomial restricted to the column index `j` is
+//! the multilinear extension of column `j` over the rows. A claim about the evaluation of a
+//! column thus becomes a claim about the stacked polynomial, which is committed to and opened
+//! as any other polynomial.
+
+use ark_ff::Field;
+use ark_std::vec::Vec;
+
+use crate::{
+    poly_utils::{coeffs::CoefficientList, evals::EvaluationsList, MultilinearPoint},
+    whir::Statement,
+};
+
+/// The shape of the assignments: the number of rows, a power of two, and of columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    num_rows: usize,
+    num_columns: usize,
+}
+
+/// The multilinear extension of a column evaluates to `evaluation` at `point`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnClaim<E> {
+    pub column: usize,
+    pub point: MultilinearPoint<E>,
+    pub evaluation: E,
+}
+
+impl Layout {
+    pub fn new(num_rows: usize, num_columns: usize) -> Option<Self> {
+        if !num_rows.is_power_of_two() || num_columns == 0 {
+            return None;
+        }
+        Some(Layout {
+            num_rows,
+            num_columns,
+        })
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.num_columns
+    }
+
+    pub fn row_variables(&self) -> usize {
+        self.num_rows.ilog2() as usize
+    }
+
+    /// The number of columns is padded to a power of two with zero columns.
+    pub fn column_variables(&self) -> usize {
+        self.num_columns.next_power_of_two().ilog2() as usize
+    }
+
+    /// The number of variables of the stacked polynomial.
+    pub fn num_variables(&self) -> usize {
+        self.column_variables() + self.row_variables()
+    }
+
+    /// The point at which the stacked polynomial equals the multilinear extension of `column`
+    /// at `row_point`.
+    pub fn column_point<E: Field>(
+        &self,
+        column: usize,
+        row_point: &MultilinearPoint<E>,
+    ) -> MultilinearPoint<E> {
+        assert!(column < self.num_columns);
+        assert_eq!(row_point.n_variables(), self.row_variables());
+        let column_variables = self.column_variables();
+        MultilinearPoint(
+            (0..column_variables)
+                .rev()
+                .map(|i| {
+                    if (column >> i) & 1 == 1 {
+                        E::ONE
+                    } else {
+                        E::ZERO
+                    }
+                })
+                .chain(row_point.0.iter().copied())
+                .collect(),
+        )
+    }
+
+    /// The statement about the stacked polynomial that proves all of `claims`.
+    pub fn statement<E: Field>(&self, claims: &[ColumnClaim<E>]) -> Statement<E> {
+        Statement::new(
+            claims
+                .iter()
+                .map(|claim| self.column_point(claim.column, &claim.point))
+                .collect(),
+            claims.iter().map(|claim| claim.evaluation).collect(),
+        )
+    }
+}
+
+/// Column assignments: `columns[j][i]` is the value of column `j` at row `i`.
+#[derive(Debug, Clone)]
+pub struct Assignment<F> {
+    layout: Layout,
+    columns: Vec<Vec<F>>,
+}
+
+impl<F: Field> Assignment<F> {
+    /// Returns `None` if there are no columns, if they have different lengths, or if the
+    /// number of rows is not a power of two.
+    pub fn new(columns: Vec<Vec<F>>) -> Option<Self> {
+        let num_rows = columns.first()?.len();
+        if columns.iter().any(|column| column.len() != num_rows) {
+            return None;
+        }
+        Some(Assignment {
+            layout: Layout::new(num_rows, columns.len())?,
+            columns,
+        })
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn columns(&self) -> &[Vec<F>] {
+        &self.columns
+    }
+
+    /// The polynomial to commit to.
+    pub fn stacked_polynomial(&self) -> CoefficientList<F> {
+        let mut evals = Vec::with_capacity(1 << self.layout.num_variables());
+        for column in &self.columns {
+            evals.extend_from_slice(column);
+        }
+        evals.resize(1 << self.layout.num_variables(), F::ZERO);
+        EvaluationsList::new(evals).into()
+    }
+
+    /// Evaluates (the multilinear extension of) `column` at `point`.
+    pub fn claim<E: Field<BasePrimeField = F>>(
+        &self,
+        column: usize,
+        point: MultilinearPoint<E>,
+    ) -> ColumnClaim<E> {
+        let polynomial = CoefficientList::from(EvaluationsList::new(self.columns[column].clone()));
+        ColumnClaim {
+            column,
+            evaluation: polynomial.evaluate_at_extension(&point),
+            point,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::AdditiveGroup;
+
+    use super::*;
+    use crate::crypto::fields::{Field64, Field64_2};
+
+    #[test]
+    fn test_stacking() {
+        let columns: Vec<Vec<Field64>> = (0..3u64)
+            .map(|j| (0..8).map(|i| Field64::from(10 * j + i)).collect())
+            .collect();
+        let assignment = Assignment::new(columns).unwrap();
+        let layout = assignment.layout();
+        assert_eq!(layout.num_variables(), 5);
+
+        let stacked = assignment.stacked_polynomial();
+        let mut rng = ark_std::test_rng();
+        for column in 0..3 {
+            let claim = assignment.claim(column, MultilinearPoint::<Field64_2>::rand(&mut rng, 3));
+            let point = layout.column_point(column, &claim.point);
+            assert_eq!(stacked.evaluate_at_extension(&point), claim.evaluation);
+
+            // At a row, the claim is the assigned value
+            let row = MultilinearPoint(vec![Field64::ONE, Field64::ZERO, Field64::ONE]);
+            let point = layout.column_point(column, &row);
+            assert_eq!(stacked.evaluate(&point), assignment.columns()[column][5]);
+        }
+
+        let statement = layout
+            .statement(&[assignment.claim(2, MultilinearPoint::<Field64_2>::rand(&mut rng, 3))]);
+        assert_eq!(
+            stacked.evaluate_at_extension(&statement.points[0]),
+            statement.evaluations[0]
+        );
+
+        assert!(Assignment::<Field64>::new(vec![vec![Field64::ONE; 3]]).is_none());
+        assert!(Assignment::new(vec![vec![Field64::ONE; 4], vec![Field64::ONE; 2]]).is_none());
+    }
+}
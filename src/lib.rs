@@ -1,11 +1,77 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+
+// A `tracing` event of the protocol (under the `whir` target), compiled out without the
+// `tracing` feature.
+macro_rules! event {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        {
+            tracing::debug!(target: "whir", $($arg)*);
+        }
+    }};
+}
+
+// A phase of the protocol. With the `profiling` feature, it runs in a function of its own named
+// `name` and never inlined, so that profiles (e.g. of `cargo flamegraph`) break the time down by
+// phase instead of by field operation. `body` cannot return early, with `?` or otherwise.
+macro_rules! phase {
+    ($name:ident, $body:expr) => {{
+        #[cfg(feature = "profiling")]
+        {
+            #[inline(never)]
+            fn $name<R>(f: impl FnOnce() -> R) -> R {
+                core::hint::black_box(f())
+            }
+            $name(|| $body)
+        }
+        #[cfg(not(feature = "profiling"))]
+        {
+            $body
+        }
+    }};
+}
+
+#[cfg(any(
+    feature = "cli",
+    feature = "ffi",
+    feature = "python",
+    feature = "test-vectors",
+    feature = "wasm"
+))]
+#[allow(dead_code)] // Each binding only uses some of the helpers
+mod binding_utils;
+#[cfg(feature = "cli")]
+pub mod cli; // The `whir` command-line tool
+#[cfg(feature = "std")]
 pub mod cmdline_utils;
 pub mod crypto; // Crypto utils
 pub mod domain; // Domain that we are evaluating over
+pub mod errors;
+#[cfg(feature = "ffi")]
+pub mod ffi; // C bindings
 pub mod fs_utils;
+pub mod halo2; // Adapter for halo2-like frontends
 pub mod ntt;
 pub mod parameters;
 pub mod poly_utils; // Utils for polynomials
+pub mod protocol; // A common interface to the protocols
+#[cfg(feature = "python")]
+pub mod python; // Python bindings
+pub mod soundness; // Round-by-round soundness of the parameters
 pub mod sumcheck; // Sumcheck specialised
+#[cfg(feature = "test-utils")]
+pub mod test_utils; // Proptest strategies for downstream crates
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors; // Vectors for other implementations
+#[cfg(feature = "parallel")]
+pub mod tuning; // Thresholds of the parallel recursions
 pub mod utils; // Utils in general
+#[cfg(feature = "wasm")]
+pub mod wasm; // JS bindings for the verifier
 pub mod whir; // The real prover
+#[cfg(feature = "std")]
 pub mod whir_ldt; // Whir as a LDT // Shared parameters
@@ -0,0 +1,63 @@
+//! Pluggable Reed-Solomon encoders, so that the committer and the prover can run a
+//! hand-optimized or a GPU NTT instead of the one of this crate.
+
+use ark_ff::FftField;
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+
+use super::expand_from_coeff;
+
+/// Reed-Solomon encoding of polynomials over `F`.
+pub trait FftBackend<F: FftField>: Send + Sync {
+    /// Evaluates the polynomial with coefficients `coeffs` over the subgroup of order
+    /// `coeffs.len() * expansion`, in the natural order `w^0, w^1, ...` of the subgroup, for `w`
+    /// the root of unity of that order chosen by the `FftField` implementation of `F` (as in
+    /// `F::get_root_of_unity`). Any other order or generator breaks the proofs.
+    fn expand_from_coeff(&self, coeffs: &[F], expansion: usize) -> Vec<F>;
+}
+
+/// The NTT of this crate (see `ntt`), the default backend.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NttBackend;
+
+impl<F: FftField> FftBackend<F> for NttBackend {
+    fn expand_from_coeff(&self, coeffs: &[F], expansion: usize) -> Vec<F> {
+        expand_from_coeff(coeffs, expansion)
+    }
+}
+
+/// The radix-2 FFT of ark-poly.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArkPolyBackend;
+
+impl<F: FftField> FftBackend<F> for ArkPolyBackend {
+    fn expand_from_coeff(&self, coeffs: &[F], expansion: usize) -> Vec<F> {
+        let domain = Radix2EvaluationDomain::<F>::new(coeffs.len() * expansion)
+            .expect("the field should have a subgroup of the size of the codeword");
+        domain.fft(coeffs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+
+    use super::{ArkPolyBackend, FftBackend, NttBackend};
+    use crate::crypto::fields::{Field64, Field64_2};
+
+    #[test]
+    fn test_backends_agree() {
+        let mut rng = ark_std::test_rng();
+        let coeffs: Vec<Field64> = (0..64).map(|_| Field64::rand(&mut rng)).collect();
+        for expansion in [1, 2, 4] {
+            assert_eq!(
+                FftBackend::expand_from_coeff(&NttBackend, &coeffs, expansion),
+                FftBackend::expand_from_coeff(&ArkPolyBackend, &coeffs, expansion),
+            );
+        }
+        let coeffs: Vec<Field64_2> = (0..16).map(|_| Field64_2::rand(&mut rng)).collect();
+        assert_eq!(
+            FftBackend::expand_from_coeff(&NttBackend, &coeffs, 4),
+            FftBackend::expand_from_coeff(&ArkPolyBackend, &coeffs, 4),
+        );
+    }
+}
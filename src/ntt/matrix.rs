@@ -3,7 +3,7 @@
 
 #![allow(unsafe_code)]
 
-use std::{
+use ark_std::{
     marker::PhantomData,
     ops::{Index, IndexMut},
     ptr, slice,
@@ -1,24 +1,31 @@
 //! NTT and related algorithms.
 
+#[cfg(feature = "std")]
+mod backend;
 mod matrix;
+#[cfg(feature = "std")]
 mod ntt;
 mod transpose;
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
 mod utils;
 mod wavelet;
 
 use self::matrix::MatrixMut;
+#[cfg(feature = "std")]
 use ark_ff::FftField;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+#[cfg(feature = "std")]
 pub use self::{
+    backend::{ArkPolyBackend, FftBackend, NttBackend},
     ntt::{intt, intt_batch, ntt, ntt_batch},
-    transpose::transpose,
-    wavelet::wavelet_transform,
 };
+pub use self::{transpose::transpose, wavelet::wavelet_transform};
 
 /// RS encode at a rate 1/`expansion`.
+#[cfg(feature = "std")]
 pub fn expand_from_coeff<F: FftField>(coeffs: &[F], expansion: usize) -> Vec<F> {
     let engine = ntt::NttEngine::<F>::new_from_cache();
     let expanded_size = coeffs.len() * expansion;
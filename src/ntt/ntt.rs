@@ -1,4 +1,5 @@
-//! Number-theoretic transforms (NTTs) over fields with high two-adicity.
+//! Number-theoretic transforms (NTTs) of sizes 2^a * 3^b (mixed radix), so fields with a small
+//! subgroup of order 3 can make up for a limited two-adicity.
 //!
 //! Implements the √N Cooley-Tukey six-step algorithm to achieve parallelism with good locality.
 //! A global cache is used for twiddle factors.
@@ -7,6 +8,7 @@ use super::{
     transpose,
     utils::{lcm, sqrt_factor, workload_size},
 };
+use crate::utils::checked_pow2;
 use ark_ff::{FftField, Field};
 use std::{
     any::{Any, TypeId},
@@ -24,7 +26,7 @@ static ENGINE_CACHE: LazyLock<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
 /// Enginge for computing NTTs over arbitrary fields.
-/// Assumes the field has large two-adicity.
+/// Supports sizes of the form 2^a * 3^b dividing the order of the engine.
 pub struct NttEngine<F: Field> {
     order: usize,   // order of omega_orger
     omega_order: F, // primitive order'th root.
@@ -77,17 +79,32 @@ impl<F: FftField> NttEngine<F> {
         }
     }
 
-    /// Construct a new engine from the field's `FftField` trait.
+    /// Construct a new engine from the field's `FftField` trait, over the largest subgroup of
+    /// order `2^a * 3^b` that the field has and whose order fits in a usize.
     fn new_from_fftfield() -> Self {
-        // TODO: Support SMALL_SUBGROUP
-        if F::TWO_ADICITY <= 63 {
-            Self::new(1 << F::TWO_ADICITY, F::TWO_ADIC_ROOT_OF_UNITY)
-        } else {
-            let mut generator = F::TWO_ADIC_ROOT_OF_UNITY;
-            for _ in 0..(63 - F::TWO_ADICITY) {
-                generator = generator.square();
+        let (mut root, mut twos, mut threes) = match (
+            F::SMALL_SUBGROUP_BASE,
+            F::SMALL_SUBGROUP_BASE_ADICITY,
+            F::LARGE_SUBGROUP_ROOT_OF_UNITY,
+        ) {
+            (Some(3), Some(threes), Some(root)) => (root, F::TWO_ADICITY, threes),
+            _ => (F::TWO_ADIC_ROOT_OF_UNITY, F::TWO_ADICITY, 0),
+        };
+        loop {
+            let order = checked_pow2(twos as usize)
+                .and_then(|order| order.checked_mul(3_usize.checked_pow(threes)?));
+            match order {
+                Some(order) => return Self::new(order, root),
+                // Drop the factors of three first, then the factors of two.
+                _ if threes > 0 => {
+                    root = root.pow([3]);
+                    threes -= 1;
+                }
+                _ => {
+                    root = root.square();
+                    twos -= 1;
+                }
             }
-            Self::new(1 << 63, generator)
         }
     }
 }
@@ -96,7 +113,11 @@ impl<F: FftField> NttEngine<F> {
 impl<F: Field> NttEngine<F> {
     pub fn new(order: usize, omega_order: F) -> Self {
         assert!(order.trailing_zeros() > 0, "Order must be a multiple of 2.");
-        // TODO: Assert that omega factors into 2s and 3s.
+        let odd = order >> order.trailing_zeros();
+        assert!(
+            3_usize.pow(odd.ilog(3)) == odd,
+            "Order must be of the form 2^a * 3^b."
+        );
         assert_eq!(omega_order.pow([order as u64]), F::ONE);
         assert_ne!(omega_order.pow([order as u64 / 2]), F::ONE);
         let mut res = NttEngine {
@@ -406,3 +427,41 @@ impl<F: Field> NttEngine<F> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::{Field, One, Zero};
+
+    use super::NttEngine;
+    use crate::crypto::fields::Field64 as F;
+
+    #[test]
+    fn test_mixed_radix_ntt() {
+        // The Goldilocks multiplicative group has order 2^32 * 3 * 5 * 17 * 257 * 65537.
+        let order = 3 << 32;
+        let omega = F::from(7).pow([((1u64 << 32) - 1) / 3]);
+        let engine = NttEngine::new(order, omega);
+        for size in [3, 6, 12, 24, 48, 96] {
+            let values: Vec<F> = (0..size as u64).map(|i| F::from(i * i + 1)).collect();
+            let root = engine.root(size);
+            let expected: Vec<F> = (0..size)
+                .map(|k| {
+                    values
+                        .iter()
+                        .enumerate()
+                        .map(|(j, v)| *v * root.pow([(j * k) as u64]))
+                        .fold(F::zero(), |acc, x| acc + x)
+                })
+                .collect();
+            let mut result = values.clone();
+            engine.ntt(&mut result);
+            assert_eq!(result, expected, "size {size}");
+
+            // Inverse, up to the 1/n factor.
+            engine.intt(&mut result);
+            let n = F::from(size as u64);
+            assert!(result.iter().zip(&values).all(|(r, v)| *r == n * v));
+            assert!(root.pow([size as u64]).is_one());
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use super::super::utils::is_power_of_two;
 use super::{utils::workload_size, MatrixMut};
-use std::mem::swap;
+use ark_std::mem::swap;
 
 #[cfg(feature = "parallel")]
 use rayon::join;
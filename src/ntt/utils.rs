@@ -20,18 +20,21 @@ pub fn as_chunks_exact_mut<T, const N: usize>(slice: &mut [T]) -> &mut [[T; N]]
     let new_len = slice.len() / N;
     // SAFETY: We cast a slice of `new_len * N` elements into
     // a slice of `new_len` many `N` elements chunks.
-    unsafe { std::slice::from_raw_parts_mut(slice.as_mut_ptr().cast(), new_len) }
+    unsafe { ark_std::slice::from_raw_parts_mut(slice.as_mut_ptr().cast(), new_len) }
 }
 
-/// Compute the largest factor of n that is <= sqrt(n).
-/// Assumes n is of the form 2^k * {1,3,9}.
+/// Compute a factor of n close to sqrt(n), the largest factor <= sqrt(n) when n is a power of two.
+/// Assumes n is of the form 2^a * 3^b.
 pub fn sqrt_factor(n: usize) -> usize {
     let twos = n.trailing_zeros();
-    match n >> twos {
-        1 => 1 << (twos / 2),
-        3 | 9 => 3 << (twos / 2),
-        _ => panic!(),
+    let mut odd = n >> twos;
+    let mut threes = 0;
+    while odd % 3 == 0 {
+        odd /= 3;
+        threes += 1;
     }
+    assert_eq!(odd, 1, "size should be of the form 2^a * 3^b");
+    3_usize.pow(threes.div_ceil(2)) << (twos / 2)
 }
 
 /// Least common multiple.
@@ -110,6 +113,18 @@ mod tests {
         for i in 0..10 {
             assert_eq!(sqrt_factor(1 << i), get_largest_divisor_up_to_sqrt(1 << i));
         }
+
+        // Mixed radix sizes split into two non-trivial factors.
+        for twos in 0..6 {
+            for threes in 0..6 {
+                let n = 3_usize.pow(threes) << twos;
+                let factor = sqrt_factor(n);
+                assert_eq!(n % factor, 0);
+                if n > 3 {
+                    assert!(factor > 1 && factor < n);
+                }
+            }
+        }
     }
 
     #[test]
@@ -140,7 +155,7 @@ mod tests {
                 [12]
             ]
         );
-        let should_not_work = std::panic::catch_unwind(|| {
+        let should_not_work = ark_std::panic::catch_unwind(|| {
             as_chunks_exact_mut::<_, 2>(&mut [1, 2, 3]);
             return;
         });
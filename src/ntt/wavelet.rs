@@ -1,6 +1,6 @@
 use super::{transpose, utils::workload_size};
 use ark_ff::Field;
-use std::cmp::max;
+use ark_std::cmp::max;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
@@ -1,8 +1,13 @@
-use std::{fmt::Display, marker::PhantomData, str::FromStr};
+use ark_std::{fmt::Display, marker::PhantomData, str::FromStr, string::String, vec::Vec};
 
 use ark_crypto_primitives::merkle_tree::{Config, LeafParam, TwoToOneParam};
 use serde::Serialize;
 
+use crate::{
+    errors::{WhirError, WhirResult},
+    utils::checked_pow2,
+};
+
 pub fn default_max_pow(num_variables: usize, log_inv_rate: usize) -> usize {
     num_variables + log_inv_rate - 3
 }
@@ -15,7 +20,7 @@ pub enum SoundnessType {
 }
 
 impl Display for SoundnessType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
         write!(
             f,
             "{}",
@@ -56,10 +61,17 @@ impl<F> MultivariateParameters<F> {
             _field: PhantomData,
         }
     }
+
+    /// Same as `new`, but fails if a polynomial in `num_variables` variables has more
+    /// coefficients than fit in a usize.
+    pub fn try_new(num_variables: usize) -> WhirResult<Self> {
+        checked_pow2(num_variables).ok_or(WhirError::TooManyVariables(num_variables))?;
+        Ok(Self::new(num_variables))
+    }
 }
 
 impl<F> Display for MultivariateParameters<F> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
         write!(f, "Number of variables: {}", self.num_variables)
     }
 }
@@ -84,7 +96,7 @@ impl FromStr for FoldType {
 }
 
 impl Display for FoldType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
         write!(
             f,
             "{}",
@@ -96,7 +108,56 @@ impl Display for FoldType {
     }
 }
 
-#[derive(Clone)]
+/// How the prover sends the polynomial of the last round. Both forms have `2^final_sumcheck_rounds`
+/// elements: the evaluation form saves the verifier from evaluating the coefficients in the
+/// Lagrange basis of the hypercube, and the prover from converting its last fold back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FinalForm {
+    /// The coefficients of the multilinear polynomial.
+    #[default]
+    Coefficients,
+    /// Its evaluations over the hypercube, in the order of `EvaluationsList`.
+    Evaluations,
+}
+
+impl FromStr for FinalForm {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "Coefficients" {
+            Ok(FinalForm::Coefficients)
+        } else if s == "Evaluations" {
+            Ok(FinalForm::Evaluations)
+        } else {
+            Err(format!("Invalid final form specification: {}", s))
+        }
+    }
+}
+
+impl Display for FinalForm {
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                FinalForm::Coefficients => "Coefficients",
+                FinalForm::Evaluations => "Evaluations",
+            }
+        )
+    }
+}
+
+/// A bound on the size of the proofs of a configuration, which `WhirConfig::try_new` meets by
+/// raising the PoW bits and the folding factor, up to the given maxima.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofSizeBudget {
+    /// The bound on `WhirConfig::proof_size_bound`, in bytes.
+    pub max_bytes: usize,
+    /// The largest `WhirParameters::pow_bits` to try.
+    pub max_pow_bits: usize,
+    /// The largest `WhirParameters::folding_factor` to try.
+    pub max_folding_factor: usize,
+}
+
 pub struct WhirParameters<MerkleConfig, PowStrategy>
 where
     MerkleConfig: Config,
@@ -109,19 +170,58 @@ where
 
     pub fold_optimisation: FoldType,
 
+    // Mask the committed polynomial, so that the proof hides it
+    pub zk: bool,
+
+    // The form of the polynomial of the last round, only used by `whir`
+    pub final_form: FinalForm,
+
+    // A bound on the size of the proofs, only used by `whir`, see `WhirConfig::try_new`
+    pub max_proof_size: Option<ProofSizeBudget>,
+
     // PoW parameters
     pub _pow_parameters: PhantomData<PowStrategy>,
 
     // Merkle tree parameters
     pub leaf_hash_params: LeafParam<MerkleConfig>,
     pub two_to_one_params: TwoToOneParam<MerkleConfig>,
+
+    // The Merkle hash parameters of the trees committed in the rounds, from the first round on,
+    // whose last entry also hashes the trees of the later rounds, e.g. an algebraic hash after a
+    // Blake3 commitment (see `crypto::merkle_tree::hybrid`). Empty to hash every tree as the
+    // commitment. Only used by `whir`.
+    pub round_merkle_params: Vec<(LeafParam<MerkleConfig>, TwoToOneParam<MerkleConfig>)>,
+}
+
+// Not derived, which would require the markers `MerkleConfig` and `PowStrategy` to be `Clone`.
+impl<MerkleConfig, PowStrategy> Clone for WhirParameters<MerkleConfig, PowStrategy>
+where
+    MerkleConfig: Config,
+{
+    fn clone(&self) -> Self {
+        WhirParameters {
+            starting_log_inv_rate: self.starting_log_inv_rate,
+            folding_factor: self.folding_factor,
+            soundness_type: self.soundness_type,
+            security_level: self.security_level,
+            pow_bits: self.pow_bits,
+            fold_optimisation: self.fold_optimisation,
+            zk: self.zk,
+            final_form: self.final_form,
+            max_proof_size: self.max_proof_size,
+            _pow_parameters: PhantomData,
+            leaf_hash_params: self.leaf_hash_params.clone(),
+            two_to_one_params: self.two_to_one_params.clone(),
+            round_merkle_params: self.round_merkle_params.clone(),
+        }
+    }
 }
 
 impl<MerkleConfig, PowStrategy> Display for WhirParameters<MerkleConfig, PowStrategy>
 where
     MerkleConfig: Config,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
         writeln!(
             f,
             "Targeting {}-bits of security with {}-bits of PoW - soundness: {:?}",
@@ -129,8 +229,27 @@ where
         )?;
         writeln!(
             f,
-            "Starting rate: 2^-{}, folding_factor: {}, fold_opt_type: {}",
-            self.starting_log_inv_rate, self.folding_factor, self.fold_optimisation,
-        )
+            "Starting rate: 2^-{}, folding_factor: {}, fold_opt_type: {}, zk: {}, final_form: {}",
+            self.starting_log_inv_rate,
+            self.folding_factor,
+            self.fold_optimisation,
+            self.zk,
+            self.final_form,
+        )?;
+        if !self.round_merkle_params.is_empty() {
+            writeln!(
+                f,
+                "Merkle parameters for the trees of {} rounds",
+                self.round_merkle_params.len()
+            )?;
+        }
+        if let Some(budget) = &self.max_proof_size {
+            writeln!(
+                f,
+                "Proof size budget: {} bytes, up to {}-bits of PoW and folding_factor {}",
+                budget.max_bytes, budget.max_pow_bits, budget.max_folding_factor
+            )?;
+        }
+        Ok(())
     }
 }
@@ -1,11 +1,19 @@
-use super::{evals::EvaluationsList, hypercube::BinaryHypercubePoint, MultilinearPoint};
-use crate::ntt::wavelet_transform;
+use super::{
+    evals::EvaluationsList, hypercube::BinaryHypercubePoint, MultilinearPoint, VariableOrder,
+};
+use crate::{
+    errors::{WhirError, WhirResult},
+    ntt::wavelet_transform,
+};
 use ark_ff::Field;
 use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+use ark_std::vec::Vec;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 #[cfg(feature = "parallel")]
 use {
+    ark_std::mem::size_of,
     rayon::{join, prelude::*},
-    std::mem::size_of,
 };
 
 /// A CoefficientList models a (multilinear) polynomial in `num_variable` variables in coefficient form.
@@ -43,16 +51,35 @@ where
     /// Evaluate the given polynomial at `point` from F^n.
     pub fn evaluate(&self, point: &MultilinearPoint<F>) -> F {
         assert_eq!(self.num_variables, point.n_variables());
+        let subcube = point.subcube();
+        if subcube.iter().any(Option::is_some) {
+            // Additions only, then a smaller evaluation
+            return eval_multivariate(
+                &restrict_coeffs(&self.coeffs, &subcube),
+                &point.free_coordinates().0,
+            );
+        }
         eval_multivariate(&self.coeffs, &point.0)
     }
 
+    /// The polynomial in the free coordinates obtained by fixing the others as in `subcube`.
+    pub fn restrict(&self, subcube: &[Option<bool>]) -> Self {
+        assert_eq!(subcube.len(), self.num_variables);
+        CoefficientList::new(restrict_coeffs(&self.coeffs, subcube))
+    }
+
     #[inline]
     fn eval_extension<E: Field<BasePrimeField = F>>(coeff: &[F], eval: &[E], scalar: E) -> E {
         // explicit "return" just to simplify static code-analyzers' tasks (that can't figure out the cfg's are disjoint)
         #[cfg(not(feature = "parallel"))]
         return Self::eval_extension_nonparallel(coeff, eval, scalar);
         #[cfg(feature = "parallel")]
-        return Self::eval_extension_parallel(coeff, eval, scalar);
+        return Self::eval_extension_parallel(
+            coeff,
+            eval,
+            scalar,
+            crate::tuning::thresholds().eval_extension,
+        );
     }
 
     // NOTE (Gotti): This algorithm uses 2^{n+1}-1 multiplications for a polynomial in n variables.
@@ -79,20 +106,21 @@ where
         }
     }
 
+    // Forks while more than `threshold` variables are left, see `tuning`.
     #[cfg(feature = "parallel")]
-    fn eval_extension_parallel<E: Field<BasePrimeField = F>>(
+    pub(crate) fn eval_extension_parallel<E: Field<BasePrimeField = F>>(
         coeff: &[F],
         eval: &[E],
         scalar: E,
+        threshold: usize,
     ) -> E {
-        const PARALLEL_THRESHOLD: usize = 10;
         debug_assert_eq!(coeff.len(), 1 << eval.len());
         if let Some((&x, tail)) = eval.split_first() {
             let (low, high) = coeff.split_at(coeff.len() / 2);
-            if tail.len() > PARALLEL_THRESHOLD {
+            if tail.len() > threshold {
                 let (a, b) = rayon::join(
-                    || Self::eval_extension_parallel(low, tail, scalar),
-                    || Self::eval_extension_parallel(high, tail, scalar * x),
+                    || Self::eval_extension_parallel(low, tail, scalar, threshold),
+                    || Self::eval_extension_parallel(high, tail, scalar * x, threshold),
                 );
                 a + b
             } else {
@@ -133,6 +161,7 @@ where
 }
 
 impl<F> CoefficientList<F> {
+    /// Panics if the number of coefficients is not a power of two, see `try_new`.
     pub fn new(coeffs: Vec<F>) -> Self {
         let len = coeffs.len();
         assert!(len.is_power_of_two());
@@ -144,6 +173,32 @@ impl<F> CoefficientList<F> {
         }
     }
 
+    pub fn try_new(coeffs: Vec<F>) -> WhirResult<Self> {
+        if !coeffs.len().is_power_of_two() {
+            return Err(WhirError::NotPowerOfTwo(coeffs.len()));
+        }
+        Ok(Self::new(coeffs))
+    }
+
+    /// The order of the monomials, see `VariableOrder`.
+    pub const ORDER: VariableOrder = VariableOrder::BigEndian;
+
+    /// The polynomial with the coefficients `coeffs`, given in `order`.
+    pub fn from_order(mut coeffs: Vec<F>, order: VariableOrder) -> Self {
+        order.convert_list(Self::ORDER, &mut coeffs);
+        Self::new(coeffs)
+    }
+
+    /// The coefficients in `order`.
+    pub fn to_order(&self, order: VariableOrder) -> Vec<F>
+    where
+        F: Clone,
+    {
+        let mut coeffs = self.coeffs.clone();
+        Self::ORDER.convert_list(order, &mut coeffs);
+        coeffs
+    }
+
     pub fn coeffs(&self) -> &[F] {
         &self.coeffs
     }
@@ -170,6 +225,27 @@ impl<F> CoefficientList<F> {
 }
 
 /// Multivariate evaluation in coefficient form.
+// Fixing X_0 = 0 keeps the coefficients of the monomials without X_0, and X_0 = 1 adds those
+// with X_0 to them.
+fn restrict_coeffs<F: Field>(coeffs: &[F], subcube: &[Option<bool>]) -> Vec<F> {
+    debug_assert_eq!(coeffs.len(), 1 << subcube.len());
+    let Some((fixed, tail)) = subcube.split_first() else {
+        return coeffs.to_vec();
+    };
+    let (low, high) = coeffs.split_at(coeffs.len() / 2);
+    let mut result = restrict_coeffs(low, tail);
+    match fixed {
+        Some(false) => {}
+        Some(true) => {
+            for (coeff, high) in result.iter_mut().zip(restrict_coeffs(high, tail)) {
+                *coeff += high;
+            }
+        }
+        None => result.extend(restrict_coeffs(high, tail)),
+    }
+    result
+}
+
 fn eval_multivariate<F: Field>(coeffs: &[F], point: &[F]) -> F {
     debug_assert_eq!(coeffs.len(), 1 << point.len());
     match point {
@@ -258,6 +334,15 @@ where
     }
 }
 
+// Leaves an empty list behind.
+#[cfg(feature = "zeroize")]
+impl<F: Zeroize> Zeroize for CoefficientList<F> {
+    fn zeroize(&mut self) {
+        self.coeffs.zeroize();
+        self.num_variables.zeroize();
+    }
+}
+
 impl<F> From<CoefficientList<F>> for DensePolynomial<F>
 where
     F: Field,
@@ -287,6 +372,29 @@ where
     }
 }
 
+impl<F> From<EvaluationsList<F>> for CoefficientList<F>
+where
+    F: Field,
+{
+    // Inverse of the wavelet transform, i.e. recursively applies the kernel
+    //   [ 1 0]
+    //   [-1 1]
+    fn from(value: EvaluationsList<F>) -> Self {
+        let mut coeffs = value.evals().to_vec();
+        let mut size = 1;
+        while size < coeffs.len() {
+            for chunk in coeffs.chunks_exact_mut(2 * size) {
+                let (low, high) = chunk.split_at_mut(size);
+                for (high, low) in high.iter_mut().zip(low.iter()) {
+                    *high -= *low;
+                }
+            }
+            size *= 2;
+        }
+        CoefficientList::new(coeffs)
+    }
+}
+
 /* Previous recursive version
 impl<F> From<CoefficientList<F>> for EvaluationsList<F>
 where
@@ -364,6 +472,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_interpolation() {
+        let coeffs: Vec<_> = (0..16).map(|i| F::from(i * i + 3)).collect();
+        let evaluations = EvaluationsList::from(CoefficientList::new(coeffs.clone()));
+        let coeffs_list = CoefficientList::from(evaluations);
+
+        assert_eq!(coeffs_list.coeffs(), &coeffs[..]);
+    }
+
     #[test]
     fn test_folding() {
         let coeffs = vec![F::from(22), F::from(05), F::from(00), F::from(00)];
@@ -402,6 +519,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_subcube_evaluation() {
+        let coeffs_list = CoefficientList::new((0..1 << 6).map(|i| F::from(i * i + 1)).collect());
+        let subcube = [Some(true), None, Some(false), None, Some(true), None];
+        let free = [F::from(3), F::from(5), F::from(8)];
+        let point = MultilinearPoint::from_subcube(&subcube, &free);
+
+        let expected = super::eval_multivariate(coeffs_list.coeffs(), &point.0);
+        assert_eq!(coeffs_list.evaluate(&point), expected);
+        assert_eq!(
+            coeffs_list
+                .restrict(&subcube)
+                .evaluate(&MultilinearPoint(free.to_vec())),
+            expected
+        );
+    }
+
     #[test]
     fn test_evaluation_mv() {
         let polynomial = vec![
@@ -436,4 +570,15 @@ mod tests {
             mv_poly.evaluate(&MultilinearPoint::expand_from_univariate(eval_point, 4))
         )
     }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_zeroize() {
+        use zeroize::Zeroize;
+
+        let mut poly = CoefficientList::new(vec![F::from(1); 8]);
+        poly.zeroize();
+        assert_eq!(poly.num_coeffs(), 0);
+        assert_eq!(poly.num_variables(), 0);
+    }
 }
@@ -1,14 +1,17 @@
-use std::ops::Index;
+use ark_std::{ops::Index, vec::Vec};
 
 use ark_ff::Field;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
-use super::{sequential_lag_poly::LagrangePolynomialIterator, MultilinearPoint};
+use super::{sequential_lag_poly::LagrangePolynomialIterator, MultilinearPoint, VariableOrder};
+use crate::errors::{WhirError, WhirResult};
 
 /// An EvaluationsList models a multi-linear polynomial f in `num_variables`
 /// unknowns, stored via their evaluations at {0,1}^{num_variables}
 ///
 /// `evals` stores the evaluation in lexicographic order.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EvaluationsList<F> {
     evals: Vec<F>,
     num_variables: usize,
@@ -21,7 +24,7 @@ where
     /// Constructs a EvaluationList from the given vector `eval` of evaluations.
     ///
     /// The provided `evals` is supposed to be the list of evaluations, where the ordering of evaluation points in {0,1}^n
-    /// is lexicographic.
+    /// is lexicographic. Panics if their number is not a power of two, see `try_new`.
     pub fn new(evals: Vec<F>) -> Self {
         let len = evals.len();
         assert!(len.is_power_of_two());
@@ -33,11 +36,38 @@ where
         }
     }
 
+    pub fn try_new(evals: Vec<F>) -> WhirResult<Self> {
+        if !evals.len().is_power_of_two() {
+            return Err(WhirError::NotPowerOfTwo(evals.len()));
+        }
+        Ok(Self::new(evals))
+    }
+
+    /// The order of the vertices, see `VariableOrder`.
+    pub const ORDER: VariableOrder = VariableOrder::BigEndian;
+
+    /// The polynomial with the evaluations `evals`, given in `order`.
+    pub fn from_order(mut evals: Vec<F>, order: VariableOrder) -> Self {
+        order.convert_list(Self::ORDER, &mut evals);
+        Self::new(evals)
+    }
+
+    /// The evaluations in `order`.
+    pub fn to_order(&self, order: VariableOrder) -> Vec<F> {
+        let mut evals = self.evals.clone();
+        Self::ORDER.convert_list(order, &mut evals);
+        evals
+    }
+
     /// evaluate the polynomial at `point`
     pub fn evaluate(&self, point: &MultilinearPoint<F>) -> F {
         if let Some(point) = point.to_hypercube() {
             return self.evals[point.0];
         }
+        let subcube = point.subcube();
+        if subcube.iter().any(Option::is_some) {
+            return self.restrict(&subcube).evaluate(&point.free_coordinates());
+        }
 
         let mut sum = F::ZERO;
         for (b, lag) in LagrangePolynomialIterator::new(point) {
@@ -47,6 +77,29 @@ where
         sum
     }
 
+    /// The evaluations over the subcube where the coordinates are fixed as in `subcube`, of the
+    /// multilinear in the free coordinates. This only selects entries.
+    pub fn restrict(&self, subcube: &[Option<bool>]) -> Self {
+        assert_eq!(subcube.len(), self.num_variables);
+        let mut evals = self.evals.clone();
+        // From the least significant coordinate, counting the free ones already passed
+        let mut num_free_below = 0;
+        for fixed in subcube.iter().rev() {
+            match fixed {
+                Some(bit) => {
+                    let stride = 1 << num_free_below;
+                    evals = evals
+                        .chunks_exact(2 * stride)
+                        .flat_map(|chunk| &chunk[*bit as usize * stride..][..stride])
+                        .copied()
+                        .collect();
+                }
+                None => num_free_below += 1,
+            }
+        }
+        EvaluationsList::new(evals)
+    }
+
     pub fn evals(&self) -> &[F] {
         &self.evals
     }
@@ -64,6 +117,15 @@ where
     }
 }
 
+// Leaves an empty list behind.
+#[cfg(feature = "zeroize")]
+impl<F: Zeroize> Zeroize for EvaluationsList<F> {
+    fn zeroize(&mut self) {
+        self.evals.zeroize();
+        self.num_variables.zeroize();
+    }
+}
+
 impl<F> Index<usize> for EvaluationsList<F> {
     type Output = F;
     fn index(&self, index: usize) -> &Self::Output {
@@ -92,4 +154,27 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_subcube_evaluation() {
+        let num_variables = 5;
+        let evals = EvaluationsList::new((0..1 << num_variables).map(F::from).collect());
+        let subcube = [None, Some(true), None, Some(false), Some(true)];
+        let free = [F::from(7), F::from(11)];
+        let point = MultilinearPoint::from_subcube(&subcube, &free);
+        assert_eq!(point.subcube(), subcube);
+        assert_eq!(point.free_coordinates().0, free);
+
+        let restricted = evals.restrict(&subcube);
+        assert_eq!(
+            restricted.evals(),
+            &[F::from(9), F::from(13), F::from(25), F::from(29)]
+        );
+
+        let mut expected = F::ZERO;
+        for (b, lag) in LagrangePolynomialIterator::new(&point) {
+            expected += lag * evals[b.0];
+        }
+        assert_eq!(evals.evaluate(&point), expected);
+    }
 }
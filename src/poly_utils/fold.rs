@@ -1,6 +1,11 @@
-use crate::ntt::intt_batch;
-use crate::parameters::FoldType;
+use crate::{domain::Domain, parameters::FoldType};
+#[cfg(feature = "std")]
+use crate::{
+    ntt::{intt_batch, transpose, FftBackend},
+    utils::coset_shift,
+};
 use ark_ff::{FftField, Field};
+use ark_std::{ops::Range, vec::Vec};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
@@ -44,10 +49,15 @@ pub fn compute_fold<F: Field>(
     answers[0]
 }
 
+/// Given the evaluations of f over the domain `offset * <w>`, stacked by cosets (see
+/// `stack_evaluations`), with `ProverHelps` replaces the evaluations over each coset by the
+/// coefficients of the folded polynomial on that coset. Takes the inverses of the offset and of
+/// the generator `w`.
+#[cfg(feature = "std")]
 pub fn restructure_evaluations<F: FftField>(
     mut stacked_evaluations: Vec<F>,
     fold_type: FoldType,
-    _domain_gen: F,
+    domain_offset_inv: F,
     domain_gen_inv: F,
     folding_factor: usize,
 ) -> Vec<F> {
@@ -63,11 +73,11 @@ pub fn restructure_evaluations<F: FftField>(
             intt_batch(&mut stacked_evaluations, folding_size as usize);
 
             // Apply coset and size correction.
-            // Stacked evaluation at i is f(B_l) where B_l = w^i * <w^n/k>
+            // Stacked evaluation at i is f(B_l) where B_l = offset * w^i * <w^n/k>
             let size_inv = F::from(folding_size).inverse().unwrap();
             #[cfg(not(feature = "parallel"))]
             {
-                let mut coset_offset_inv = F::ONE;
+                let mut coset_offset_inv = domain_offset_inv;
                 for answers in stacked_evaluations.chunks_exact_mut(folding_size as usize) {
                     let mut scale = size_inv;
                     for v in answers.iter_mut() {
@@ -83,7 +93,7 @@ pub fn restructure_evaluations<F: FftField>(
                 .enumerate()
                 .for_each_with(F::ZERO, |offset, (i, answers)| {
                     if *offset == F::ZERO {
-                        *offset = domain_gen_inv.pow([i as u64]);
+                        *offset = domain_offset_inv * domain_gen_inv.pow([i as u64]);
                     } else {
                         *offset *= domain_gen_inv;
                     }
@@ -99,17 +109,107 @@ pub fn restructure_evaluations<F: FftField>(
     }
 }
 
+/// The leaves of the encoding of `coeffs` at rate 1/`expansion` over `offset * <w>`, as
+/// `restructure_evaluations` leaves them with `ProverHelps`, without encoding and then undoing it
+/// coset by coset.
+///
+/// Writing f(X) = sum_r X^r h_r(X^(2^k)), the coefficients of the fold on the coset with offset x
+/// are the h_r(x^(2^k)), so leaf i holds the evaluations of the h_r at the i-th element of the
+/// folded domain `offset^(2^k) * <w^(2^k)>`. This takes one transpose of the coefficients and one
+/// of the evaluations, against the transposes of `expand_from_coeff` and `stack_evaluations` and
+/// the inverse NTTs and scaling of `restructure_evaluations`.
+#[cfg(feature = "std")]
+pub fn encode_folded<F: FftField>(
+    backend: &dyn FftBackend<F>,
+    coeffs: &[F],
+    expansion: usize,
+    offset: F,
+    folding_factor: usize,
+) -> Vec<F> {
+    let mut evaluations = encode_folded_rows(backend, coeffs, expansion, offset, folding_factor);
+    let folded_len = evaluations.len() >> folding_factor;
+    transpose(&mut evaluations, 1 << folding_factor, folded_len);
+    evaluations
+}
+
+/// `encode_folded` before its last transpose: the evaluations of h_0, then of h_1, ... over the
+/// folded domain, which `utils::stack_evaluations` (or `stack_evaluations_into`) turns into the
+/// leaves.
+#[cfg(feature = "std")]
+pub fn encode_folded_rows<F: FftField>(
+    backend: &dyn FftBackend<F>,
+    coeffs: &[F],
+    expansion: usize,
+    offset: F,
+    folding_factor: usize,
+) -> Vec<F> {
+    let folding_size = 1 << folding_factor;
+    assert_eq!(coeffs.len() % folding_size, 0);
+    let folded_len = coeffs.len() / folding_size;
+
+    // One row per h_r, with the coefficients c_r, c_(r + 2^k), ...
+    let mut coeffs = coeffs.to_vec();
+    transpose(&mut coeffs, folded_len, folding_size);
+
+    let folded_offset = offset.pow([folding_size as u64]);
+    let mut evaluations = Vec::with_capacity(coeffs.len() * expansion);
+    for h in coeffs.chunks_exact(folded_len) {
+        evaluations.extend(backend.expand_from_coeff(&coset_shift(h, folded_offset), expansion));
+    }
+    evaluations
+}
+
+/// The leaves in `leaves` of the codeword of `coeffs` over `domain`, as the committer lays them
+/// out with `fold_type`, computed from the coefficients without encoding the rest of the codeword.
+/// Each leaf costs a pass over the coefficients, so this is for answering a few queries, not for
+/// the whole codeword.
+pub fn encode_leaves<F: FftField>(
+    coeffs: &[F],
+    domain: &Domain<F>,
+    folding_factor: usize,
+    fold_type: FoldType,
+    leaves: Range<usize>,
+) -> Vec<F> {
+    let folding_size = 1 << folding_factor;
+    leaves
+        .flat_map(|index| match fold_type {
+            // The evaluations over the coset of the leaf.
+            FoldType::Naive => domain
+                .sub_domain(folding_size, index)
+                .expect("leaf index out of range")
+                .evaluate(coeffs),
+            // The h_r(x^(2^k)) for the offset x of the coset, see `encode_folded`.
+            FoldType::ProverHelps => {
+                let point = domain.element(index).pow([folding_size as u64]);
+                (0..folding_size)
+                    .map(|r| {
+                        coeffs
+                            .iter()
+                            .skip(r)
+                            .step_by(folding_size)
+                            .rev()
+                            .fold(F::ZERO, |acc, coeff| acc * point + coeff)
+                    })
+                    .collect()
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use ark_ff::{FftField, Field};
 
     use crate::{
         crypto::fields::Field64,
+        domain::Domain,
+        ntt::{expand_from_coeff, NttBackend},
+        parameters::FoldType,
         poly_utils::{coeffs::CoefficientList, MultilinearPoint},
-        utils::stack_evaluations,
+        utils::{coset_shift, stack_evaluations},
     };
 
-    use super::{compute_fold, restructure_evaluations};
+    use super::{compute_fold, encode_folded, encode_leaves, restructure_evaluations};
 
     type F = Field64;
 
@@ -193,7 +293,7 @@ mod tests {
         let processed = restructure_evaluations(
             unprocessed.clone(),
             crate::parameters::FoldType::ProverHelps,
-            root_of_unity,
+            F::ONE,
             root_of_unity_inv,
             folding_factor,
         );
@@ -221,4 +321,43 @@ mod tests {
             assert_eq!(answer_processed, answer_unprocessed);
         }
     }
+
+    #[test]
+    fn test_encode_folded() {
+        let coeffs: Vec<_> = (0..64).map(|i| F::from(i * i + 3)).collect();
+        let (expansion, folding_factor) = (4, 3);
+        for offset in [F::ONE, F::GENERATOR] {
+            let domain_gen_inv = F::get_root_of_unity(256).unwrap().inverse().unwrap();
+            let evaluations = expand_from_coeff(&coset_shift(&coeffs, offset), expansion);
+            let expected = restructure_evaluations(
+                stack_evaluations(evaluations, folding_factor),
+                FoldType::ProverHelps,
+                offset.inverse().unwrap(),
+                domain_gen_inv,
+                folding_factor,
+            );
+            let folded = encode_folded(&NttBackend, &coeffs, expansion, offset, folding_factor);
+            assert_eq!(folded, expected);
+        }
+    }
+
+    #[test]
+    fn test_encode_leaves() {
+        let coeffs: Vec<_> = (0..16).map(|i| F::from(3 * i + 2)).collect();
+        let folding_factor = 2;
+        let offset = F::GENERATOR;
+        let domain = Domain::<F>::new(16, 2)
+            .unwrap()
+            .with_offset(offset)
+            .unwrap();
+        let naive = stack_evaluations(
+            expand_from_coeff(&coset_shift(&coeffs, offset), 4),
+            folding_factor,
+        );
+        let helps = encode_folded(&NttBackend, &coeffs, 4, offset, folding_factor);
+        for (fold_type, expected) in [(FoldType::Naive, naive), (FoldType::ProverHelps, helps)] {
+            let leaves = encode_leaves(&coeffs, &domain, folding_factor, fold_type, 5..9);
+            assert_eq!(leaves, expected[5 * 4..9 * 4]);
+        }
+    }
 }
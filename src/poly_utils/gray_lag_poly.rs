@@ -1,6 +1,7 @@
 // NOTE: This is the one from Ron's
 
 use ark_ff::{batch_inversion, Field};
+use ark_std::vec::Vec;
 
 use super::{hypercube::BinaryHypercubePoint, MultilinearPoint};
 
@@ -84,7 +85,7 @@ impl<F: Field> Iterator for LagrangePolynomialGray<F> {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeSet;
+    use ark_std::collections::BTreeSet;
 
     use crate::{
         crypto::fields::Field64,
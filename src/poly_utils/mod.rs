@@ -1,4 +1,5 @@
 use ark_ff::Field;
+use ark_std::vec::Vec;
 use rand::{
     distributions::{Distribution, Standard},
     Rng, RngCore,
@@ -15,6 +16,54 @@ pub mod gray_lag_poly;
 pub mod hypercube;
 pub mod sequential_lag_poly;
 pub mod streaming_evaluation_helper;
+pub mod virtual_poly;
+
+/// The position of the variables in the bits of the indexes of the hypercube {0,1}^n, in
+/// `CoefficientList` (the monomials), `EvaluationsList` (the vertices) and `MultilinearPoint`
+/// (which index bit each coordinate binds).
+///
+/// The crate is `BigEndian` throughout: the first coordinate X_0 of a point binds the most
+/// significant of the n bits. Libraries in `LittleEndian`, where it binds the least significant
+/// bit (e.g. arkworks' `DenseMultilinearExtension`), are converted at the boundary with the
+/// `from_order` and `to_order` of each type.
+///
+/// A polynomial and the points it is evaluated at share one order, and converting either of
+/// them converts the pair: evaluations `e` in `LittleEndian` evaluated at `r` are
+/// `EvaluationsList::new(e)` at `MultilinearPoint::from_order(r, LittleEndian)`, or
+/// `EvaluationsList::from_order(e, LittleEndian)` at `MultilinearPoint(r)`. Converting both
+/// reverses the variables twice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VariableOrder {
+    #[default]
+    BigEndian,
+    LittleEndian,
+}
+
+impl VariableOrder {
+    /// The index in `to` of the vertex (or monomial) `index` of `self`, on `num_variables` bits.
+    pub fn convert_index(self, to: Self, index: usize, num_variables: usize) -> usize {
+        if self == to || num_variables == 0 {
+            index
+        } else {
+            index.reverse_bits() >> (usize::BITS as usize - num_variables)
+        }
+    }
+
+    /// Permutes `values`, indexed by the hypercube in `self`, to their indexes in `to`.
+    pub fn convert_list<T>(self, to: Self, values: &mut [T]) {
+        assert!(values.len().is_power_of_two());
+        if self == to {
+            return;
+        }
+        let num_variables = values.len().ilog2() as usize;
+        for index in 0..values.len() {
+            let converted = self.convert_index(to, index, num_variables);
+            if index < converted {
+                values.swap(index, converted);
+            }
+        }
+    }
+}
 
 /// Point (x_1,..., x_n) in F^n for some n. Often, the x_i are binary.
 /// For the latter case, we also have BinaryHypercubePoint.
@@ -25,11 +74,31 @@ impl<F> MultilinearPoint<F>
 where
     F: Field,
 {
+    /// The order of the coordinates, see `VariableOrder`.
+    pub const ORDER: VariableOrder = VariableOrder::BigEndian;
+
     /// returns the number of variables.
     pub fn n_variables(&self) -> usize {
         self.0.len()
     }
 
+    /// The point with the coordinates `coords`, given in `order`.
+    pub fn from_order(mut coords: Vec<F>, order: VariableOrder) -> Self {
+        if order != Self::ORDER {
+            coords.reverse();
+        }
+        MultilinearPoint(coords)
+    }
+
+    /// The coordinates of the point in `order`.
+    pub fn to_order(&self, order: VariableOrder) -> Vec<F> {
+        let mut coords = self.0.clone();
+        if order != Self::ORDER {
+            coords.reverse();
+        }
+        coords
+    }
+
     // NOTE: Conversion BinaryHypercube <-> MultilinearPoint converts a
     // multilinear point (x1,x2,...,x_n) into the number with bit-pattern 0...0 x_1 x_2 ... x_n, provided all x_i are in {0,1}.
     // That means we pad zero bits in BinaryHypercube from the msb end and use big-endian for the actual conversion.
@@ -60,6 +129,51 @@ where
         Some(BinaryHypercubePoint(counter))
     }
 
+    /// The boolean coordinates of the point, `None` for the others. A point with many boolean
+    /// coordinates lies on a small subcube, where evaluations reduce to those of a multilinear in
+    /// the other coordinates, see `EvaluationsList::restrict` and `CoefficientList::restrict`.
+    pub fn subcube(&self) -> Vec<Option<bool>> {
+        self.0
+            .iter()
+            .map(|&coord| {
+                if coord == F::ZERO {
+                    Some(false)
+                } else if coord == F::ONE {
+                    Some(true)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// The coordinates of the point which are not boolean, as a point of the subcube.
+    pub fn free_coordinates(&self) -> Self {
+        MultilinearPoint(
+            self.0
+                .iter()
+                .copied()
+                .filter(|&coord| coord != F::ZERO && coord != F::ONE)
+                .collect(),
+        )
+    }
+
+    /// Inverse of `subcube` and `free_coordinates`: the point of the subcube `subcube` with
+    /// coordinates `free`.
+    pub fn from_subcube(subcube: &[Option<bool>], free: &[F]) -> Self {
+        let mut free = free.iter();
+        let point = subcube
+            .iter()
+            .map(|fixed| match fixed {
+                Some(false) => F::ZERO,
+                Some(true) => F::ONE,
+                None => *free.next().expect("one free coordinate per `None`"),
+            })
+            .collect();
+        assert!(free.next().is_none());
+        MultilinearPoint(point)
+    }
+
     /// converts a univariate evaluation point into a multilinear one.
     ///
     /// Notably, consider the usual bijection
@@ -144,6 +258,51 @@ where
     acc
 }
 
+/// Compute eq(coords, expand_from_univariate(point)) without expanding the point.
+///
+/// Writing eq(c, x) == \prod_i (1 - c_i) + x_i * (2 * c_i - 1), each variable costs a squaring and two multiplications,
+/// one multiplication fewer than expanding the point and calling `eq_poly_outside`, and nothing is allocated.
+pub fn eq_poly_univariate<F>(coords: &MultilinearPoint<F>, point: F) -> F
+where
+    F: Field,
+{
+    let mut acc = F::ONE;
+    let mut power = point;
+
+    // The last variable is the point itself, and each one before is the square of the next.
+    for &c in coords.0.iter().rev() {
+        acc *= F::ONE - c + power * (c.double() - F::ONE);
+        power.square_in_place();
+    }
+
+    acc
+}
+
+/// Compute the rotated equality polynomial eq_rot(coords, point) == \sum_b eq(coords, b) * eq(b + 1, point), where b ranges over {0,1}^n
+/// and b + 1 is taken modulo 2^n on the big-endian index of b.
+///
+/// This is the weight of a claim on a polynomial whose evaluations over the hypercube are rotated by one, see `ClaimType::Rotated`.
+pub fn eq_poly_rotated<F>(coords: &MultilinearPoint<F>, point: &MultilinearPoint<F>) -> F
+where
+    F: Field,
+{
+    assert_eq!(coords.n_variables(), point.n_variables());
+
+    // Adding one flips the trailing ones of b and the zero before them. From the least significant
+    // variable, `acc` sums over the b whose increment does not carry out of the variables seen so
+    // far, and `carry` is the weight of the all-ones b, whose increment does.
+    let mut acc = F::ZERO;
+    let mut carry = F::ONE;
+
+    for (&l, &r) in coords.0.iter().zip(&point.0).rev() {
+        acc = acc * (l * r + (F::ONE - l) * (F::ONE - r)) + carry * (F::ONE - l) * r;
+        carry *= l * (F::ONE - r);
+    }
+
+    // The increment of 1...1 wraps around to 0...0
+    acc + carry
+}
+
 // TODO: Precompute two_inv?
 // Alternatively, compute it directly without the general (and slow) .inverse() map.
 
@@ -183,12 +342,15 @@ where
 #[cfg(test)]
 mod tests {
     use crate::poly_utils::eq_poly3;
+    use crate::poly_utils::eq_poly_rotated;
     use crate::poly_utils::hypercube::BinaryHypercube;
+    use crate::poly_utils::{eq_poly_outside, eq_poly_univariate};
     use crate::{crypto::fields::Field64, poly_utils::eq_poly};
 
     use super::coeffs::CoefficientList;
+    use super::evals::EvaluationsList;
     use super::BinaryHypercubePoint;
-    use super::MultilinearPoint;
+    use super::{MultilinearPoint, VariableOrder};
 
     type F = Field64;
 
@@ -284,6 +446,46 @@ mod tests {
         let _x = eq_poly(&coords, BinaryHypercubePoint(0b100));
     }
 
+    #[test]
+    fn test_equality_rotated() {
+        let mut rng = ark_std::test_rng();
+        let num_variables = 4;
+        let coords = MultilinearPoint::rand(&mut rng, num_variables);
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+
+        let expected: F = (0..1 << num_variables)
+            .map(|b| {
+                let next = (b + 1) % (1 << num_variables);
+                eq_poly(&coords, BinaryHypercubePoint(b))
+                    * eq_poly(&point, BinaryHypercubePoint(next))
+            })
+            .sum();
+        assert_eq!(eq_poly_rotated(&coords, &point), expected);
+
+        // On the hypercube, it is one exactly when point is the successor of coords
+        let binary = |b| MultilinearPoint::from_binary_hypercube_point(BinaryHypercubePoint(b), 3);
+        assert_eq!(eq_poly_rotated(&binary(0b011), &binary(0b100)), F::from(1));
+        assert_eq!(eq_poly_rotated(&binary(0b111), &binary(0b000)), F::from(1));
+        assert_eq!(eq_poly_rotated(&binary(0b011), &binary(0b011)), F::from(0));
+    }
+
+    #[test]
+    fn test_equality_univariate() {
+        let mut rng = ark_std::test_rng();
+        for num_variables in 0..6 {
+            let coords = MultilinearPoint::rand(&mut rng, num_variables);
+            for point in [F::from(0), F::from(1), F::from(-1), F::from(42)] {
+                assert_eq!(
+                    eq_poly_univariate(&coords, point),
+                    eq_poly_outside(
+                        &coords,
+                        &MultilinearPoint::expand_from_univariate(point, num_variables)
+                    )
+                );
+            }
+        }
+    }
+
     #[test]
     fn expand_from_univariate() {
         let num_variables = 4;
@@ -323,4 +525,56 @@ mod tests {
             MultilinearPoint::<F>::from_binary_hypercube_point(hypercube_point, 5).to_hypercube()
         );
     }
+
+    #[test]
+    fn test_variable_order() {
+        use ark_ff::UniformRand;
+
+        let mut rng = ark_std::test_rng();
+        let num_variables = 4;
+        let evals: Vec<F> = (0..1 << num_variables).map(|_| F::rand(&mut rng)).collect();
+        let coords: Vec<F> = (0..num_variables).map(|_| F::rand(&mut rng)).collect();
+        // Evaluated in little endian, the first coordinate on the least significant bit
+        let little_endian: F = (0..1 << num_variables)
+            .map(|index: usize| {
+                let weight: F = coords
+                    .iter()
+                    .enumerate()
+                    .map(|(bit, &x)| if index >> bit & 1 == 1 { x } else { F::ONE - x })
+                    .product();
+                weight * evals[index]
+            })
+            .sum();
+
+        let order = VariableOrder::LittleEndian;
+        let point = MultilinearPoint::from_order(coords.clone(), order);
+        assert_eq!(point.to_order(order), coords);
+        assert_eq!(
+            EvaluationsList::new(evals.clone()).evaluate(&point),
+            little_endian
+        );
+        let converted = EvaluationsList::from_order(evals.clone(), order);
+        assert_eq!(converted.to_order(order), evals);
+        assert_eq!(
+            converted.evaluate(&MultilinearPoint(coords.clone())),
+            little_endian
+        );
+        // Converting both reverses the variables twice
+        assert_ne!(converted.evaluate(&point), little_endian);
+
+        // The monomials are ordered as the vertices
+        let coeffs = CoefficientList::from(EvaluationsList::new(evals.clone()));
+        let converted_coeffs = CoefficientList::from_order(coeffs.coeffs().to_vec(), order);
+        assert_eq!(converted_coeffs.to_order(order), coeffs.coeffs());
+        assert_eq!(
+            converted_coeffs.evaluate(&MultilinearPoint(coords)),
+            little_endian
+        );
+
+        assert_eq!(
+            VariableOrder::BigEndian.convert_index(order, 0b0011, num_variables),
+            0b1100
+        );
+        assert_eq!(order.convert_index(order, 0b0011, num_variables), 0b0011);
+    }
 }
@@ -1,6 +1,7 @@
 // NOTE: This is the one from Blendy
 
 use ark_ff::Field;
+use ark_std::vec::Vec;
 
 use super::{hypercube::BinaryHypercubePoint, MultilinearPoint};
 
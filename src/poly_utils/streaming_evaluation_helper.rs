@@ -1,6 +1,7 @@
 // NOTE: This is the one from Blendy adapted for streaming evals
 
 use ark_ff::Field;
+use ark_std::vec::Vec;
 
 use super::{hypercube::BinaryHypercubePoint, MultilinearPoint};
 
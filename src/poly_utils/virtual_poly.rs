@@ -0,0 +1,124 @@
+use ark_std::{boxed::Box, vec::Vec};
+
+use ark_ff::Field;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::coeffs::CoefficientList;
+
+/// A multilinear polynomial in `num_variables` unknowns given by a closure evaluating it on
+/// {0,1}^{num_variables}, e.g. a product or a sum of committed columns, so that it can be
+/// encoded one row of coefficients at a time, without materializing its evaluations (see
+/// `Committer::commit_virtual`, whose witness still holds the coefficients).
+///
+/// The vertices are indexed in the order of `EvaluationsList`.
+pub struct VirtualPolynomial<'a, F> {
+    num_variables: usize,
+    evaluate: Box<dyn Fn(usize) -> F + Sync + 'a>,
+}
+
+impl<'a, F> VirtualPolynomial<'a, F>
+where
+    F: Field,
+{
+    pub fn new(num_variables: usize, evaluate: impl Fn(usize) -> F + Sync + 'a) -> Self {
+        VirtualPolynomial {
+            num_variables,
+            evaluate: Box::new(evaluate),
+        }
+    }
+
+    pub fn num_variables(&self) -> usize {
+        self.num_variables
+    }
+
+    pub fn num_coeffs(&self) -> usize {
+        1 << self.num_variables
+    }
+
+    /// The evaluation at the vertex `index` of the hypercube.
+    pub fn evaluate_hypercube(&self, index: usize) -> F {
+        (self.evaluate)(index)
+    }
+
+    /// The coefficients `c_(r + j * 2^k)` for `j` in `0..2^(num_variables - k)`, those of `h_r`
+    /// in `f(X) = sum_r X^r h_r(X^(2^k))` (see `fold::encode_folded`), with `r < 2^k`. Each
+    /// costs `2^popcount(r)` evaluations, and only the row is allocated.
+    pub fn coefficient_row(&self, r: usize, k: usize) -> Vec<F> {
+        assert!(k <= self.num_variables && r < 1 << k);
+        // Inverse of the wavelet transform over the low `k` bits, at the pattern `r`
+        let entry = |j: usize| {
+            let mut sum = F::ZERO;
+            let mut s = r;
+            loop {
+                let evaluation = (self.evaluate)(s | (j << k));
+                if (r ^ s).count_ones() % 2 == 0 {
+                    sum += evaluation;
+                } else {
+                    sum -= evaluation;
+                }
+                if s == 0 {
+                    break sum;
+                }
+                s = (s - 1) & r;
+            }
+        };
+        let len = 1 << (self.num_variables - k);
+        #[cfg(not(feature = "parallel"))]
+        let mut row: Vec<F> = (0..len).map(entry).collect();
+        #[cfg(feature = "parallel")]
+        let mut row: Vec<F> = (0..len).into_par_iter().map(entry).collect();
+
+        // Then over the high bits, as `From<EvaluationsList>`
+        let mut size = 1;
+        while size < row.len() {
+            for chunk in row.chunks_exact_mut(2 * size) {
+                let (low, high) = chunk.split_at_mut(size);
+                for (high, low) in high.iter_mut().zip(low.iter()) {
+                    *high -= *low;
+                }
+            }
+            size *= 2;
+        }
+        row
+    }
+
+    /// All the coefficients, materialized.
+    pub fn to_coefficients(&self) -> CoefficientList<F> {
+        CoefficientList::new(self.coefficient_row(0, 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+
+    use super::*;
+    use crate::{crypto::fields::Field64 as F, poly_utils::evals::EvaluationsList};
+
+    #[test]
+    fn test_coefficient_rows() {
+        let mut rng = ark_std::test_rng();
+        let num_variables = 6;
+        let a: Vec<F> = (0..1 << num_variables).map(|_| F::rand(&mut rng)).collect();
+        let b: Vec<F> = (0..1 << num_variables).map(|_| F::rand(&mut rng)).collect();
+        // The product of two columns, pointwise on the hypercube
+        let product = VirtualPolynomial::new(num_variables, |index| a[index] * b[index]);
+        let evals: Vec<F> = a.iter().zip(&b).map(|(a, b)| *a * b).collect();
+        let expected = CoefficientList::from(EvaluationsList::new(evals));
+
+        assert_eq!(product.to_coefficients().coeffs(), expected.coeffs());
+        for k in 0..=3 {
+            for r in 0..1 << k {
+                let row: Vec<F> = expected
+                    .coeffs()
+                    .iter()
+                    .copied()
+                    .skip(r)
+                    .step_by(1 << k)
+                    .collect();
+                assert_eq!(product.coefficient_row(r, k), row);
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! A common interface to the protocols of the crate, for code generic over them such as
+//! benchmarks and integration tests.
+//!
+//! A protocol is a marker type, e.g. `Whir<MerkleConfig, PowStrategy>`, whose configuration is
+//! passed to each step. The steps run over the nimue transcript laid out by `iopattern`: the
+//! prover commits and proves with the same `Merlin`, and the verifier reads both from the
+//! `Arthur` of its transcript.
+
+use ark_crypto_primitives::merkle_tree::Config;
+use ark_ff::FftField;
+use ark_std::marker::PhantomData;
+use nimue::{Arthur, IOPattern, Merlin, ProofResult};
+
+use crate::{errors::WhirResult, poly_utils::coeffs::CoefficientList, whir};
+
+pub trait Protocol<F: FftField> {
+    /// The name of the protocol, e.g. in benchmark reports.
+    const NAME: &'static str;
+    type Config: Clone;
+    /// The claims on the committed polynomial, `()` for a low-degree test.
+    type Statement;
+    type Witness;
+    type Proof;
+
+    /// Appends the commitment and the proof to `io`.
+    fn iopattern(config: &Self::Config, io: IOPattern) -> IOPattern;
+
+    fn commit(
+        config: &Self::Config,
+        merlin: &mut Merlin,
+        polynomial: CoefficientList<F::BasePrimeField>,
+    ) -> WhirResult<Self::Witness>;
+
+    fn prove(
+        config: &Self::Config,
+        merlin: &mut Merlin,
+        statement: Self::Statement,
+        witness: Self::Witness,
+    ) -> WhirResult<Self::Proof>;
+
+    fn verify(
+        config: &Self::Config,
+        arthur: &mut Arthur,
+        statement: &Self::Statement,
+        proof: &Self::Proof,
+    ) -> ProofResult<()>;
+
+    /// The size in bytes of the proof and of its transcript.
+    fn proof_size(transcript: &[u8], proof: &Self::Proof) -> usize;
+}
+
+/// WHIR as a polynomial commitment, see `whir`.
+pub struct Whir<MerkleConfig, PowStrategy>(PhantomData<(MerkleConfig, PowStrategy)>);
+
+impl<F, MerkleConfig, PowStrategy> Protocol<F> for Whir<MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    const NAME: &'static str = "whir";
+    type Config = whir::parameters::WhirConfig<F, MerkleConfig, PowStrategy>;
+    type Statement = whir::Statement<F>;
+    type Witness = whir::committer::Witness<F, MerkleConfig>;
+    type Proof = whir::WhirProof<MerkleConfig, F>;
+
+    fn iopattern(config: &Self::Config, io: IOPattern) -> IOPattern {
+        use whir::iopattern::WhirIOPattern;
+        io.commit_statement(config).add_whir_proof(config)
+    }
+
+    fn commit(
+        config: &Self::Config,
+        merlin: &mut Merlin,
+        polynomial: CoefficientList<F::BasePrimeField>,
+    ) -> WhirResult<Self::Witness> {
+        whir::committer::Committer::new(config.clone()).commit(merlin, polynomial)
+    }
+
+    fn prove(
+        config: &Self::Config,
+        merlin: &mut Merlin,
+        statement: Self::Statement,
+        witness: Self::Witness,
+    ) -> WhirResult<Self::Proof> {
+        whir::prover::Prover(config.clone()).prove(merlin, statement, witness)
+    }
+
+    fn verify(
+        config: &Self::Config,
+        arthur: &mut Arthur,
+        statement: &Self::Statement,
+        proof: &Self::Proof,
+    ) -> ProofResult<()> {
+        whir::verifier::Verifier::new(config.clone()).verify(arthur, statement, proof)
+    }
+
+    fn proof_size(transcript: &[u8], proof: &Self::Proof) -> usize {
+        whir::whir_proof_size(transcript, proof)
+    }
+}
+
+/// WHIR as a low-degree test, see `whir_ldt`.
+#[cfg(feature = "std")]
+pub struct WhirLdt<MerkleConfig, PowStrategy>(PhantomData<(MerkleConfig, PowStrategy)>);
+
+#[cfg(feature = "std")]
+impl<F, MerkleConfig, PowStrategy> Protocol<F> for WhirLdt<MerkleConfig, PowStrategy>
+where
+    F: FftField + crate::crypto::fields::FieldWithSize,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    const NAME: &'static str = "whir_ldt";
+    type Config = crate::whir_ldt::parameters::WhirConfig<F, MerkleConfig, PowStrategy>;
+    type Statement = ();
+    type Witness = crate::whir_ldt::committer::Witness<F, MerkleConfig>;
+    type Proof = crate::whir_ldt::WhirProof<MerkleConfig, F>;
+
+    fn iopattern(config: &Self::Config, io: IOPattern) -> IOPattern {
+        use crate::whir_ldt::iopattern::WhirIOPattern;
+        io.commit_statement(config).add_whir_proof(config)
+    }
+
+    fn commit(
+        config: &Self::Config,
+        merlin: &mut Merlin,
+        polynomial: CoefficientList<F::BasePrimeField>,
+    ) -> WhirResult<Self::Witness> {
+        Ok(
+            crate::whir_ldt::committer::Committer::new(config.clone())
+                .commit(merlin, polynomial)?,
+        )
+    }
+
+    fn prove(
+        config: &Self::Config,
+        merlin: &mut Merlin,
+        _statement: (),
+        witness: Self::Witness,
+    ) -> WhirResult<Self::Proof> {
+        Ok(crate::whir_ldt::prover::Prover(config.clone()).prove(merlin, witness)?)
+    }
+
+    fn verify(
+        config: &Self::Config,
+        arthur: &mut Arthur,
+        _statement: &(),
+        proof: &Self::Proof,
+    ) -> ProofResult<()> {
+        crate::whir_ldt::verifier::Verifier::new(config.clone()).verify(arthur, proof)
+    }
+
+    fn proof_size(transcript: &[u8], proof: &Self::Proof) -> usize {
+        crate::whir_ldt::whir_proof_size(transcript, proof)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use nimue::DefaultHash;
+
+    use super::*;
+    use crate::{
+        crypto::merkle_tree::blake3 as merkle_tree,
+        parameters::MultivariateParameters,
+        poly_utils::MultilinearPoint,
+        whir::fixtures::{whir_parameters, MerkleConfig, PowStrategy, F},
+    };
+
+    // The plumbing of a benchmark, once for all the protocols
+    fn run<P>(
+        config: &P::Config,
+        statement: P::Statement,
+        polynomial: CoefficientList<F>,
+    ) -> ProofResult<usize>
+    where
+        P: Protocol<F>,
+        P::Statement: Clone,
+    {
+        let io = P::iopattern(config, IOPattern::<DefaultHash>::new("🌪️"));
+        let mut merlin = io.to_merlin();
+        let witness = P::commit(config, &mut merlin, polynomial).unwrap();
+        let proof = P::prove(config, &mut merlin, statement.clone(), witness).unwrap();
+        let mut arthur = io.to_arthur(merlin.transcript());
+        P::verify(config, &mut arthur, &statement, &proof)?;
+        Ok(P::proof_size(merlin.transcript(), &proof))
+    }
+
+    #[test]
+    fn test_protocols() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params =
+            whir_parameters::<MerkleConfig, PowStrategy>((leaf_hash_params, two_to_one_params));
+        let mv_params = MultivariateParameters::new(num_variables);
+        let polynomial = CoefficientList::new((0..1u64 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement =
+            whir::Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+
+        let whir_config =
+            whir::parameters::WhirConfig::try_new(mv_params, whir_params.clone()).unwrap();
+        let whir_size =
+            run::<Whir<MerkleConfig, PowStrategy>>(&whir_config, statement, polynomial.clone())
+                .unwrap();
+        let ldt_config =
+            crate::whir_ldt::parameters::WhirConfig::new(mv_params, whir_params.into());
+        let ldt_size =
+            run::<WhirLdt<MerkleConfig, PowStrategy>>(&ldt_config, (), polynomial).unwrap();
+        assert!(whir_size > 0 && ldt_size > 0);
+        assert_eq!(
+            <Whir<MerkleConfig, PowStrategy> as Protocol<F>>::NAME,
+            "whir"
+        );
+    }
+}
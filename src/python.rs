@@ -0,0 +1,196 @@
+//! Python bindings, built with pyo3 (e.g. `maturin develop --release`, which builds the crate as
+//! a `cdylib` itself).
+//!
+//! Uses the same instantiation as the C bindings. Coefficients of the committed polynomial are
+//! lists of base field elements, points and evaluations are lists of `(c0, c1)` pairs of
+//! extension field elements, all in canonical form. Proofs are `bytes` as produced by
+//! `whir::proof_to_bytes`.
+
+use nimue::Merlin;
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+
+use crate::{
+    binding_utils::{
+        io_pattern, new_config, read_extension_elements, read_points, write_extension_element,
+        Config, MerkleConfig, F,
+    },
+    crypto::fields::Field64,
+    parameters::{default_max_pow, SoundnessType},
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    whir::{
+        committer::{Committer, Witness},
+        proof_to_bytes,
+        prover::Prover,
+        verifier::Verifier,
+        Statement,
+    },
+};
+
+#[pyclass(name = "WhirConfig", frozen)]
+pub struct PyWhirConfig(Config);
+
+/// A committed polynomial, together with the transcript of its commitment.
+/// Consumed by `WhirConfig.prove`.
+#[pyclass(name = "Witness", unsendable)]
+pub struct PyWitness(Option<(Merlin, Witness<F, MerkleConfig>)>);
+
+impl PyWhirConfig {
+    fn read_points(&self, points: Vec<Vec<(u64, u64)>>) -> PyResult<Vec<MultilinearPoint<F>>> {
+        let num_variables = self.0.mv_parameters.num_variables;
+        if points.iter().any(|point| point.len() != num_variables) {
+            return Err(PyValueError::new_err(format!(
+                "points must have {num_variables} coordinates"
+            )));
+        }
+        let limbs: Vec<u64> = points
+            .into_iter()
+            .flatten()
+            .flat_map(|(c0, c1)| [c0, c1])
+            .collect();
+        Ok(read_points(&limbs, num_variables))
+    }
+}
+
+#[pymethods]
+impl PyWhirConfig {
+    /// `pow_bits` defaults to the maximum number of PoW bits for the given size and rate,
+    /// `soundness_type` is one of `UniqueDecoding`, `ProvableList` and `ConjectureList`.
+    #[new]
+    #[pyo3(signature = (
+        num_variables,
+        folding_factor = 4,
+        starting_log_inv_rate = 1,
+        security_level = 100,
+        pow_bits = None,
+        soundness_type = "ConjectureList",
+    ))]
+    fn new(
+        num_variables: usize,
+        folding_factor: usize,
+        starting_log_inv_rate: usize,
+        security_level: usize,
+        pow_bits: Option<usize>,
+        soundness_type: &str,
+    ) -> PyResult<Self> {
+        let soundness_type: SoundnessType =
+            soundness_type.parse().map_err(PyValueError::new_err)?;
+        let pow_bits =
+            pow_bits.unwrap_or_else(|| default_max_pow(num_variables, starting_log_inv_rate));
+        new_config(
+            num_variables,
+            folding_factor,
+            starting_log_inv_rate,
+            security_level,
+            pow_bits,
+            soundness_type,
+        )
+        .map(PyWhirConfig)
+        .ok_or_else(|| PyValueError::new_err("invalid WHIR parameters"))
+    }
+
+    #[getter]
+    fn num_variables(&self) -> usize {
+        self.0.mv_parameters.num_variables
+    }
+
+    #[getter]
+    fn n_rounds(&self) -> usize {
+        self.0.n_rounds()
+    }
+
+    /// Whether the PoW bits are enough to reach the requested security level.
+    fn check_pow_bits(&self) -> bool {
+        self.0.check_pow_bits()
+    }
+
+    fn __repr__(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// Commits to the multilinear polynomial with the `2^num_variables` coefficients `coeffs`.
+    fn commit(&self, coeffs: Vec<u64>) -> PyResult<PyWitness> {
+        if coeffs.len() != 1 << self.0.mv_parameters.num_variables {
+            return Err(PyValueError::new_err(
+                "the number of coefficients must be 2^num_variables",
+            ));
+        }
+        let polynomial = CoefficientList::new(coeffs.into_iter().map(Field64::from).collect());
+        let mut merlin = io_pattern(&self.0).to_merlin();
+        let witness = Committer::new(self.0.clone())
+            .commit(&mut merlin, polynomial)
+            .map_err(|err| PyRuntimeError::new_err(format!("commitment failed: {err}")))?;
+        Ok(PyWitness(Some((merlin, witness))))
+    }
+
+    /// Proves the evaluations of the committed polynomial at `points`.
+    /// Returns the evaluations together with the proof.
+    fn prove(
+        &self,
+        py: Python<'_>,
+        mut witness: PyRefMut<'_, PyWitness>,
+        points: Vec<Vec<(u64, u64)>>,
+    ) -> PyResult<(Vec<(u64, u64)>, Py<PyBytes>)> {
+        let points = self.read_points(points)?;
+        let (mut merlin, witness) = witness
+            .0
+            .take()
+            .ok_or_else(|| PyValueError::new_err("witness was already used"))?;
+
+        let evaluations: Vec<F> = points
+            .iter()
+            .map(|point| witness.polynomial.evaluate(point))
+            .collect();
+        let encoded_evaluations = evaluations
+            .iter()
+            .map(|&value| {
+                let mut limbs = [0; 2];
+                write_extension_element(value, &mut limbs);
+                (limbs[0], limbs[1])
+            })
+            .collect();
+
+        let statement = Statement::new(points, evaluations);
+        let proof = Prover(self.0.clone())
+            .prove(&mut merlin, statement, witness)
+            .map_err(|err| PyRuntimeError::new_err(format!("proving failed: {err}")))?;
+        let bytes = proof_to_bytes(merlin.transcript(), &proof)
+            .map_err(|err| PyRuntimeError::new_err(err.to_string()))?;
+        Ok((encoded_evaluations, PyBytes::new_bound(py, &bytes).unbind()))
+    }
+
+    /// Returns whether `proof` proves that the committed polynomial evaluates to `evaluations`
+    /// at `points`.
+    fn verify(
+        &self,
+        points: Vec<Vec<(u64, u64)>>,
+        evaluations: Vec<(u64, u64)>,
+        proof: &[u8],
+    ) -> PyResult<bool> {
+        if points.len() != evaluations.len() {
+            return Err(PyValueError::new_err(
+                "there must be as many evaluations as points",
+            ));
+        }
+        let limbs: Vec<u64> = evaluations
+            .into_iter()
+            .flat_map(|(c0, c1)| [c0, c1])
+            .collect();
+        let statement = Statement::new(self.read_points(points)?, read_extension_elements(&limbs));
+        let verifier = Verifier::new(self.0.clone());
+        Ok(verifier
+            .verify_bytes(&io_pattern(&self.0), &statement, proof)
+            .is_ok())
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "whir")]
+fn whir_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyWhirConfig>()?;
+    m.add_class::<PyWitness>()?;
+    Ok(())
+}
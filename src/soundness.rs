@@ -0,0 +1,270 @@
+//! The round-by-round soundness of WHIR, in bits of security, as a function of the parameters of
+//! each step. These are the formulas the configurations are derived from, exposed so that audit
+//! scripts can evaluate them without re-implementing the paper.
+//!
+//! All functions are pure. Bits are `-log2` of an error probability: a step with `b` bits is
+//! broken with probability at most `2^-b`. The parameters shared by most of them are:
+//! - `soundness_type`, the list-decoding regime (see `SoundnessType`),
+//! - `num_variables`, the number of variables of the polynomial of the step,
+//! - `log_inv_rate`, `-log2` of the rate of the code of the step,
+//! - `log_eta`, `log2` of the slack from the decoding radius, see `log_eta`,
+//! - `field_size_bits`, `log2` of the size of the field the challenges are drawn from.
+
+use ark_std::f64::consts::LOG2_10;
+
+use crate::{
+    parameters::SoundnessType,
+    utils::{ceil_f64, exp2_f64, log2_f64},
+};
+
+/// `log2` of the slack from the decoding radius used by the configurations of WHIR.
+pub fn log_eta(soundness_type: SoundnessType, log_inv_rate: usize) -> f64 {
+    // Ask me how I did this? At the time, only God and I knew. Now only God knows
+    match soundness_type {
+        SoundnessType::ProvableList => -(0.5 * log_inv_rate as f64 + LOG2_10 + 1.),
+        SoundnessType::UniqueDecoding => 0.,
+        SoundnessType::ConjectureList => -(log_inv_rate as f64 + 1.),
+    }
+}
+
+/// `log2` of the size of the list of codewords close to a word.
+pub fn list_size_bits(
+    soundness_type: SoundnessType,
+    num_variables: usize,
+    log_inv_rate: usize,
+    log_eta: f64,
+) -> f64 {
+    match soundness_type {
+        SoundnessType::ConjectureList => (num_variables + log_inv_rate) as f64 - log_eta,
+        SoundnessType::ProvableList => {
+            let log_inv_sqrt_rate: f64 = log_inv_rate as f64 / 2.;
+            log_inv_sqrt_rate - (1. + log_eta)
+        }
+        SoundnessType::UniqueDecoding => 0.0,
+    }
+}
+
+/// Bits of security of `ood_samples` out-of-domain samples, which leave a single codeword of the
+/// list consistent with the answers.
+pub fn rbr_ood_sample(
+    soundness_type: SoundnessType,
+    num_variables: usize,
+    log_inv_rate: usize,
+    log_eta: f64,
+    field_size_bits: usize,
+    ood_samples: usize,
+) -> f64 {
+    let list_size_bits = list_size_bits(soundness_type, num_variables, log_inv_rate, log_eta);
+
+    let error = 2. * list_size_bits + (num_variables * ood_samples) as f64;
+    (ood_samples * field_size_bits) as f64 + 1. - error
+}
+
+/// The smallest number of out-of-domain samples reaching `security_level` bits, none with unique
+/// decoding. There is no PoW for this step.
+///
+/// # Panics
+///
+/// If 63 samples are not enough.
+pub fn ood_samples(
+    security_level: usize,
+    soundness_type: SoundnessType,
+    num_variables: usize,
+    log_inv_rate: usize,
+    log_eta: f64,
+    field_size_bits: usize,
+) -> usize {
+    if matches!(soundness_type, SoundnessType::UniqueDecoding) {
+        return 0;
+    }
+    (1..64)
+        .find(|&ood_samples| {
+            rbr_ood_sample(
+                soundness_type,
+                num_variables,
+                log_inv_rate,
+                log_eta,
+                field_size_bits,
+                ood_samples,
+            ) >= security_level as f64
+        })
+        .expect("Could not find an appropriate number of OOD samples")
+}
+
+/// Bits of security of the proximity gaps of a fold. Each sumcheck round folds by two, so this
+/// holds for any folding factor.
+pub fn rbr_soundness_fold_prox_gaps(
+    soundness_type: SoundnessType,
+    field_size_bits: usize,
+    num_variables: usize,
+    log_inv_rate: usize,
+    log_eta: f64,
+) -> f64 {
+    let error = match soundness_type {
+        SoundnessType::ConjectureList => (num_variables + log_inv_rate) as f64 - log_eta,
+        SoundnessType::ProvableList => {
+            LOG2_10 + 3.5 * log_inv_rate as f64 + 2. * num_variables as f64
+        }
+        SoundnessType::UniqueDecoding => (num_variables + log_inv_rate) as f64,
+    };
+
+    field_size_bits as f64 - error
+}
+
+/// Bits of security of a sumcheck round of a fold.
+pub fn rbr_soundness_fold_sumcheck(
+    soundness_type: SoundnessType,
+    field_size_bits: usize,
+    num_variables: usize,
+    log_inv_rate: usize,
+    log_eta: f64,
+) -> f64 {
+    let list_size = list_size_bits(soundness_type, num_variables, log_inv_rate, log_eta);
+
+    field_size_bits as f64 - (list_size + 1.)
+}
+
+/// The PoW bits that bring a fold, the weaker of its proximity gaps and sumcheck terms, to
+/// `security_level` bits.
+pub fn folding_pow_bits(
+    security_level: usize,
+    soundness_type: SoundnessType,
+    field_size_bits: usize,
+    num_variables: usize,
+    log_inv_rate: usize,
+    log_eta: f64,
+) -> f64 {
+    let prox_gaps_error = rbr_soundness_fold_prox_gaps(
+        soundness_type,
+        field_size_bits,
+        num_variables,
+        log_inv_rate,
+        log_eta,
+    );
+    let sumcheck_error = rbr_soundness_fold_sumcheck(
+        soundness_type,
+        field_size_bits,
+        num_variables,
+        log_inv_rate,
+        log_eta,
+    );
+
+    let error = prox_gaps_error.min(sumcheck_error);
+
+    0_f64.max(security_level as f64 - error)
+}
+
+/// The number of queries reaching `protocol_security_level` bits, the security level without
+/// the PoW bits.
+pub fn queries(
+    soundness_type: SoundnessType,
+    protocol_security_level: usize,
+    log_inv_rate: usize,
+) -> usize {
+    let num_queries_f = match soundness_type {
+        SoundnessType::UniqueDecoding => {
+            let rate = exp2_f64(-(log_inv_rate as f64));
+            let denom = log2_f64(0.5 * (1. + rate));
+
+            -(protocol_security_level as f64) / denom
+        }
+        SoundnessType::ProvableList => (2 * protocol_security_level) as f64 / log_inv_rate as f64,
+        SoundnessType::ConjectureList => protocol_security_level as f64 / log_inv_rate as f64,
+    };
+    ceil_f64(num_queries_f) as usize
+}
+
+/// Bits of security of `num_queries` queries to a codeword.
+pub fn rbr_queries(soundness_type: SoundnessType, log_inv_rate: usize, num_queries: usize) -> f64 {
+    let num_queries = num_queries as f64;
+    match soundness_type {
+        SoundnessType::UniqueDecoding => {
+            let rate = exp2_f64(-(log_inv_rate as f64));
+            let denom = -log2_f64(0.5 * (1. + rate));
+
+            num_queries * denom
+        }
+        SoundnessType::ProvableList => num_queries * 0.5 * log_inv_rate as f64,
+        SoundnessType::ConjectureList => num_queries * log_inv_rate as f64,
+    }
+}
+
+/// Bits of security of the random linear combination of the `ood_samples + num_queries` claims
+/// of a round.
+pub fn rbr_soundness_queries_combination(
+    soundness_type: SoundnessType,
+    field_size_bits: usize,
+    num_variables: usize,
+    log_inv_rate: usize,
+    log_eta: f64,
+    ood_samples: usize,
+    num_queries: usize,
+) -> f64 {
+    let list_size = list_size_bits(soundness_type, num_variables, log_inv_rate, log_eta);
+
+    let log_combination = log2_f64((ood_samples + num_queries) as f64);
+
+    field_size_bits as f64 - (log_combination + list_size + 1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOUNDNESS_TYPES: [SoundnessType; 3] = [
+        SoundnessType::UniqueDecoding,
+        SoundnessType::ProvableList,
+        SoundnessType::ConjectureList,
+    ];
+
+    #[test]
+    fn test_queries_reach_security_level() {
+        for soundness_type in SOUNDNESS_TYPES {
+            for log_inv_rate in 1..6 {
+                for security_level in [32, 80, 100, 128] {
+                    let num_queries = queries(soundness_type, security_level, log_inv_rate);
+                    assert!(
+                        rbr_queries(soundness_type, log_inv_rate, num_queries)
+                            >= security_level as f64 - 1e-9
+                    );
+                    if num_queries > 1 {
+                        assert!(
+                            rbr_queries(soundness_type, log_inv_rate, num_queries - 1)
+                                < security_level as f64
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ood_samples_reach_security_level() {
+        for soundness_type in SOUNDNESS_TYPES {
+            let log_eta = log_eta(soundness_type, 2);
+            let samples = ood_samples(100, soundness_type, 20, 2, log_eta, 128);
+            if matches!(soundness_type, SoundnessType::UniqueDecoding) {
+                assert_eq!(samples, 0);
+            } else {
+                assert!(rbr_ood_sample(soundness_type, 20, 2, log_eta, 128, samples) >= 100.);
+                assert!(rbr_ood_sample(soundness_type, 20, 2, log_eta, 128, samples - 1) < 100.);
+            }
+        }
+    }
+
+    #[test]
+    fn test_folding_pow_bits() {
+        let log_eta = log_eta(SoundnessType::ConjectureList, 1);
+        // A large field needs no PoW, a small one makes up for the difference
+        assert_eq!(
+            folding_pow_bits(100, SoundnessType::ConjectureList, 192, 20, 1, log_eta),
+            0.
+        );
+        let pow_bits = folding_pow_bits(100, SoundnessType::ConjectureList, 64, 20, 1, log_eta);
+        let error =
+            rbr_soundness_fold_prox_gaps(SoundnessType::ConjectureList, 64, 20, 1, log_eta).min(
+                rbr_soundness_fold_sumcheck(SoundnessType::ConjectureList, 64, 20, 1, log_eta),
+            );
+        assert_eq!(pow_bits, 100. - error);
+    }
+}
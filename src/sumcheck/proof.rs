@@ -1,4 +1,5 @@
 use ark_ff::Field;
+use ark_std::vec::Vec;
 
 use crate::{
     poly_utils::{eq_poly3, MultilinearPoint},
@@ -1,4 +1,7 @@
 use ark_ff::Field;
+use ark_std::vec::Vec;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 use crate::{
     poly_utils::{
@@ -145,7 +148,18 @@ where
 
         // Update
         self.num_variables -= folding_factor;
+        #[cfg(feature = "zeroize")]
+        self.evaluation_of_p.zeroize();
         self.evaluation_of_p = EvaluationsList::new(evaluations_of_p);
         self.evaluation_of_equality = EvaluationsList::new(evaluations_of_eq);
     }
 }
+
+#[cfg(feature = "zeroize")]
+impl<F: Field> Zeroize for SumcheckCore<F> {
+    fn zeroize(&mut self) {
+        self.evaluation_of_p.zeroize();
+        self.evaluation_of_equality.zeroize();
+        self.num_variables.zeroize();
+    }
+}
@@ -1,16 +1,19 @@
 use ark_ff::Field;
+use ark_std::vec::Vec;
 use nimue::{
     plugins::ark::{FieldChallenges, FieldIOPattern, FieldWriter},
     IOPattern, Merlin, ProofResult,
 };
 use nimue_pow::{PoWChallenge, PowStrategy};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 use crate::{
     fs_utils::WhirPoWIOPattern,
-    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    poly_utils::{coeffs::CoefficientList, evals::EvaluationsList, MultilinearPoint},
 };
 
-use super::prover_single::SumcheckSingle;
+use super::{proof::SumcheckPolynomial, prover_single::SumcheckSingle};
 
 pub trait SumcheckNotSkippingIOPattern<F: Field> {
     fn add_sumcheck(self, folding_factor: usize, pow_bits: f64) -> Self;
@@ -71,24 +74,52 @@ where
         let mut res = Vec::with_capacity(folding_factor);
 
         for _ in 0..folding_factor {
-            let sumcheck_poly = self.sumcheck_prover.compute_sumcheck_polynomial();
+            let sumcheck_poly = phase!(
+                sumcheck_polynomial,
+                self.sumcheck_prover.compute_sumcheck_polynomial()
+            );
             merlin.add_scalars(sumcheck_poly.evaluations())?;
             let [folding_randomness]: [F; 1] = merlin.challenge_scalars()?;
             res.push(folding_randomness);
 
             // Do PoW if needed
             if pow_bits > 0. {
-                merlin.challenge_pow::<S>(pow_bits)?;
+                phase!(pow, merlin.challenge_pow::<S>(pow_bits))?;
+                event!(
+                    bits = pow_bits,
+                    nonce = %crate::utils::pow_nonce(merlin.transcript()),
+                    "sumcheck proof of work"
+                );
             }
 
-            self.sumcheck_prover
-                .compress(F::ONE, &folding_randomness.into(), &sumcheck_poly);
+            phase!(
+                sumcheck_compress,
+                self.sumcheck_prover
+                    .compress(F::ONE, &folding_randomness.into(), &sumcheck_poly)
+            );
         }
 
         res.reverse();
         Ok(MultilinearPoint(res))
     }
 
+    /// The coefficients of the polynomial folded in the randomness of the rounds so far, see
+    /// `SumcheckSingle::coefficients`.
+    pub fn coefficients(&self) -> CoefficientList<F> {
+        self.sumcheck_prover.coefficients()
+    }
+
+    /// The polynomial of the next round, for a caller driving the rounds one at a time.
+    pub fn compute_sumcheck_polynomial(&self) -> SumcheckPolynomial<F> {
+        self.sumcheck_prover.compute_sumcheck_polynomial()
+    }
+
+    /// Binds the variable of `sumcheck_poly` to `folding_randomness`.
+    pub fn compress(&mut self, folding_randomness: F, sumcheck_poly: &SumcheckPolynomial<F>) {
+        self.sumcheck_prover
+            .compress(F::ONE, &folding_randomness.into(), sumcheck_poly);
+    }
+
     pub fn add_new_equality(
         &mut self,
         points: &[MultilinearPoint<F>],
@@ -98,6 +129,33 @@ where
         self.sumcheck_prover
             .add_new_equality(points, combination_randomness, evaluations)
     }
+
+    pub fn add_new_linear_claim(
+        &mut self,
+        weights: &EvaluationsList<F>,
+        combination_randomness: F,
+        sum: F,
+    ) {
+        self.sumcheck_prover
+            .add_new_linear_claim(weights, combination_randomness, sum)
+    }
+
+    pub fn add_new_rotated_equality(
+        &mut self,
+        points: &[MultilinearPoint<F>],
+        combination_randomness: &[F],
+        evaluations: &[F],
+    ) {
+        self.sumcheck_prover
+            .add_new_rotated_equality(points, combination_randomness, evaluations)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<F: Field> Zeroize for SumcheckProverNotSkipping<F> {
+    fn zeroize(&mut self) {
+        self.sumcheck_prover.zeroize();
+    }
 }
 
 #[cfg(test)]
@@ -161,6 +219,8 @@ mod tests {
 
         // Compute the answers
         let folded_poly_1 = polynomial.fold(&folding_randomness_1);
+        // The sumcheck holds the folded polynomial
+        assert_eq!(prover.coefficients().coeffs(), folded_poly_1.coeffs());
 
         let ood_answer = polynomial.evaluate(&ood_point);
         let statement_answer = polynomial.evaluate(&statement_point);
@@ -1,8 +1,12 @@
 use super::proof::SumcheckPolynomial;
 use crate::poly_utils::{coeffs::CoefficientList, evals::EvaluationsList, MultilinearPoint};
+#[cfg(feature = "parallel")]
+use crate::tuning;
 use ark_ff::Field;
 #[cfg(feature = "parallel")]
 use rayon::{join, prelude::*};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 pub struct SumcheckSingle<F> {
     // The evaluation of p
@@ -40,6 +44,13 @@ where
         prover
     }
 
+    /// The coefficients of the polynomial of the table, the initial one folded in the randomness
+    /// of the rounds so far (see `CoefficientList::fold`), so that a caller moving its polynomial
+    /// in needs no copy of it to fold.
+    pub fn coefficients(&self) -> CoefficientList<F> {
+        self.evaluation_of_p.clone().into()
+    }
+
     #[cfg(not(feature = "parallel"))]
     pub fn compute_sumcheck_polynomial(&self) -> SumcheckPolynomial<F> {
         assert!(self.num_variables >= 1);
@@ -107,14 +118,20 @@ where
     // Evaluate the eq function on for a given point on the hypercube, and add
     // the result multiplied by the scalar to the output.
     #[cfg(not(feature = "parallel"))]
-    fn eval_eq(eval: &[F], out: &mut [F], scalar: F) {
+    pub(crate) fn eval_eq(eval: &[F], out: &mut [F], scalar: F) {
         debug_assert_eq!(out.len(), 1 << eval.len());
         if let Some((&x, tail)) = eval.split_first() {
             let (low, high) = out.split_at_mut(out.len() / 2);
             let s1 = scalar * x;
             let s0 = scalar - s1;
-            Self::eval_eq(tail, low, s0);
-            Self::eval_eq(tail, high, s1);
+            // Boolean coordinates zero out half of the table, so that points on subcubes cost
+            // the size of the subcube
+            if !s0.is_zero() {
+                Self::eval_eq(tail, low, s0);
+            }
+            if !s1.is_zero() {
+                Self::eval_eq(tail, high, s1);
+            }
         } else {
             out[0] += scalar;
         }
@@ -123,8 +140,13 @@ where
     // Evaluate the eq function on a given point on the hypercube, and add
     // the result multiplied by the scalar to the output.
     #[cfg(feature = "parallel")]
-    fn eval_eq(eval: &[F], out: &mut [F], scalar: F) {
-        const PARALLEL_THRESHOLD: usize = 10;
+    pub(crate) fn eval_eq(eval: &[F], out: &mut [F], scalar: F) {
+        Self::eval_eq_with(eval, out, scalar, tuning::thresholds().eval_eq);
+    }
+
+    // `eval_eq`, forking while more than `threshold` variables are left, see `tuning`.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn eval_eq_with(eval: &[F], out: &mut [F], scalar: F, threshold: usize) {
         debug_assert_eq!(out.len(), 1 << eval.len());
         if let Some((&x, tail)) = eval.split_first() {
             let (low, high) = out.split_at_mut(out.len() / 2);
@@ -133,14 +155,18 @@ where
             // This data dependency should go away once we implement parallel point evaluation.
             let s1 = scalar * x;
             let s0 = scalar - s1;
-            if tail.len() > PARALLEL_THRESHOLD {
+            if s0.is_zero() {
+                Self::eval_eq_with(tail, high, s1, threshold);
+            } else if s1.is_zero() {
+                Self::eval_eq_with(tail, low, s0, threshold);
+            } else if tail.len() > threshold {
                 join(
-                    || Self::eval_eq(tail, low, s0),
-                    || Self::eval_eq(tail, high, s1),
+                    || Self::eval_eq_with(tail, low, s0, threshold),
+                    || Self::eval_eq_with(tail, high, s1, threshold),
                 );
             } else {
-                Self::eval_eq(tail, low, s0);
-                Self::eval_eq(tail, high, s1);
+                Self::eval_eq_with(tail, low, s0, threshold);
+                Self::eval_eq_with(tail, high, s1, threshold);
             }
         } else {
             out[0] += scalar;
@@ -167,6 +193,55 @@ where
         }
     }
 
+    // Same as `add_new_equality`, for claims on the polynomial with evaluations rotated by one.
+    // As p(b + 1) is weighted by eq(z, b), the weights are the evaluations of eq_z rotated by one.
+    pub fn add_new_rotated_equality(
+        &mut self,
+        points: &[MultilinearPoint<F>],
+        combination_randomness: &[F],
+        evaluations: &[F],
+    ) {
+        assert_eq!(combination_randomness.len(), points.len());
+        assert_eq!(combination_randomness.len(), evaluations.len());
+        let mut rotated_equality = vec![F::ZERO; 1 << self.num_variables];
+        for (point, rand) in points.iter().zip(combination_randomness) {
+            Self::eval_eq(&point.0, &mut rotated_equality, *rand);
+        }
+        rotated_equality.rotate_right(1);
+        for (acc, eq) in self
+            .evaluation_of_equality
+            .evals_mut()
+            .iter_mut()
+            .zip(rotated_equality)
+        {
+            *acc += eq;
+        }
+
+        // Update the sum
+        for (rand, eval) in combination_randomness.iter().zip(evaluations.iter()) {
+            self.sum += *rand * eval;
+        }
+    }
+
+    // Adds the claim that the sum of weights(b) * p(b) over the hypercube is sum.
+    pub fn add_new_linear_claim(
+        &mut self,
+        weights: &EvaluationsList<F>,
+        combination_randomness: F,
+        sum: F,
+    ) {
+        assert_eq!(weights.num_variables(), self.num_variables);
+        for (acc, weight) in self
+            .evaluation_of_equality
+            .evals_mut()
+            .iter_mut()
+            .zip(weights.evals())
+        {
+            *acc += combination_randomness * weight;
+        }
+        self.sum += combination_randomness * sum;
+    }
+
     // When the folding randomness arrives, compress the table accordingly (adding the new points)
     #[cfg(not(feature = "parallel"))]
     pub fn compress(
@@ -194,6 +269,8 @@ where
 
         // Update
         self.num_variables -= 1;
+        #[cfg(feature = "zeroize")]
+        self.evaluation_of_p.zeroize();
         self.evaluation_of_p = EvaluationsList::new(evaluations_of_p);
         self.evaluation_of_equality = EvaluationsList::new(evaluations_of_eq);
         self.sum = combination_randomness * sumcheck_poly.evaluate_at_point(folding_randomness);
@@ -229,12 +306,25 @@ where
 
         // Update
         self.num_variables -= 1;
+        #[cfg(feature = "zeroize")]
+        self.evaluation_of_p.zeroize();
         self.evaluation_of_p = EvaluationsList::new(evaluations_of_p);
         self.evaluation_of_equality = EvaluationsList::new(evaluations_of_eq);
         self.sum = combination_randomness * sumcheck_poly.evaluate_at_point(folding_randomness);
     }
 }
 
+// The tables of the prover are evaluations of the witness.
+#[cfg(feature = "zeroize")]
+impl<F: Field> Zeroize for SumcheckSingle<F> {
+    fn zeroize(&mut self) {
+        self.evaluation_of_p.zeroize();
+        self.evaluation_of_equality.zeroize();
+        self.num_variables.zeroize();
+        self.sum.zeroize();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -296,3 +386,20 @@ fn test_eval_eq() {
 
     assert_eq!(&out, &expected);
 }
+
+#[test]
+fn test_eval_eq_subcube() {
+    use crate::crypto::fields::Field64 as F;
+    use crate::poly_utils::sequential_lag_poly::LagrangePolynomialIterator;
+    use ark_ff::AdditiveGroup;
+
+    let point = MultilinearPoint(vec![F::ONE, F::from(3), F::ZERO, F::from(5)]);
+    let mut out = vec![F::ZERO; 16];
+    SumcheckSingle::eval_eq(&point.0, &mut out, F::from(2));
+
+    let mut expected = vec![F::ZERO; 16];
+    for (prefix, lag) in LagrangePolynomialIterator::new(&point) {
+        expected[prefix.0] = F::from(2) * lag;
+    }
+    assert_eq!(out, expected);
+}
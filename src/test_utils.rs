@@ -0,0 +1,97 @@
+//! [proptest](https://github.com/proptest-rs/proptest) strategies for random but valid WHIR
+//! inputs, so that crates embedding WHIR can property-test their integration.
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn prove_and_verify((polynomial, statement) in instance::<F>(8, 1..4)) {
+//!         // commit to `polynomial`, prove and verify `statement`
+//!     }
+//! }
+//! ```
+
+use std::ops::Range;
+
+use ark_ff::Field;
+use proptest::{collection::vec, prelude::*};
+use rand::SeedableRng;
+
+use crate::{
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    whir::Statement,
+};
+
+/// A field element: either a small integer, so that 0, 1 and friends come up, or a uniformly
+/// random element.
+pub fn field_element<F: Field>() -> impl Strategy<Value = F> {
+    prop_oneof![
+        1 => (0u64..16).prop_map(F::from),
+        3 => any::<[u8; 32]>()
+            .prop_map(|seed| F::rand(&mut rand_chacha::ChaCha20Rng::from_seed(seed))),
+    ]
+}
+
+/// A polynomial in `num_variables` variables.
+pub fn coefficient_list<F: Field>(
+    num_variables: usize,
+) -> impl Strategy<Value = CoefficientList<F>> {
+    vec(field_element::<F>(), 1 << num_variables).prop_map(CoefficientList::new)
+}
+
+/// A point in `num_variables` variables.
+pub fn multilinear_point<F: Field>(
+    num_variables: usize,
+) -> impl Strategy<Value = MultilinearPoint<F>> {
+    vec(field_element::<F>(), num_variables).prop_map(MultilinearPoint)
+}
+
+/// A statement of evaluation claims at distinct points, `num_points` of them, that `polynomial`
+/// satisfies.
+pub fn statement<F: Field>(
+    polynomial: CoefficientList<F::BasePrimeField>,
+    num_points: Range<usize>,
+) -> impl Strategy<Value = Statement<F>> {
+    let num_variables = polynomial.num_variables();
+    vec(multilinear_point::<F>(num_variables), num_points).prop_map(move |mut points| {
+        points.sort_by(|a, b| a.0.cmp(&b.0));
+        points.dedup();
+        let evaluations = points
+            .iter()
+            .map(|point| polynomial.evaluate_at_extension(point))
+            .collect();
+        Statement::new(points, evaluations)
+    })
+}
+
+/// A polynomial in `num_variables` variables, with a statement about it of `num_points` claims.
+pub fn instance<F: Field>(
+    num_variables: usize,
+    num_points: Range<usize>,
+) -> impl Strategy<Value = (CoefficientList<F::BasePrimeField>, Statement<F>)> {
+    coefficient_list::<F::BasePrimeField>(num_variables).prop_flat_map(move |polynomial| {
+        (
+            Just(polynomial.clone()),
+            statement::<F>(polynomial, num_points.clone()),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::instance;
+    use crate::crypto::fields::Field64_2 as F;
+
+    proptest! {
+        #[test]
+        fn test_instance((polynomial, statement) in instance::<F>(4, 1..4)) {
+            prop_assert_eq!(polynomial.num_variables(), 4);
+            prop_assert_eq!(statement.points.len(), statement.evaluations.len());
+            for (point, evaluation) in statement.points.iter().zip(&statement.evaluations) {
+                prop_assert_eq!(point.0.len(), 4);
+                prop_assert_eq!(polynomial.evaluate_at_extension(point), *evaluation);
+            }
+        }
+    }
+}
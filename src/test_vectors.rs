@@ -0,0 +1,536 @@
+//! Canonical test vectors, to check other implementations of WHIR against this one.
+//!
+//! Vectors use the instantiation of the language bindings (quadratic extension of Goldilocks,
+//! Blake3 Merkle trees and Blake3 PoW, `ProverHelps` folding). Field elements are decimal strings
+//! in canonical form, extension elements `[c0, c1]` pairs, and byte strings are hex encoded.
+//! The transcript holds the prover messages of the commitment and of the proof, and the proof is
+//! the compressed serialization of the `WhirProof`.
+
+use ark_serialize::CanonicalSerialize;
+use ark_std::UniformRand;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    binding_utils::{
+        decode_base, decode_extension, encode_extension, io_pattern, new_config, Config,
+        MerkleConfig, F,
+    },
+    crypto::fields::Field64,
+    parameters::SoundnessType,
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    whir::{
+        committer::Committer, inspect::inspect, proof_from_bytes_for_config, proof_to_bytes,
+        prover::Prover, verifier::Verifier, whir_proof_size, Statement, WhirProof,
+    },
+};
+
+pub const DOMAIN_SEPARATOR: &str = "🌪️";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    pub num_variables: usize,
+    pub folding_factor: usize,
+    pub starting_log_inv_rate: usize,
+    pub security_level: usize,
+    pub pow_bits: usize,
+    pub soundness_type: String,
+    pub domain_separator: String,
+    pub polynomial: Vec<String>,
+    pub points: Vec<Vec<[String; 2]>>,
+    pub evaluations: Vec<[String; 2]>,
+    pub root: String,
+    pub transcript: String,
+    pub proof: String,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>, String> {
+    if !s.is_ascii() || s.len() % 2 != 0 {
+        return Err(format!("Invalid hex string: {}", s));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|e| e.to_string()))
+        .collect()
+}
+
+// The configuration of the parameters of a vector or a case.
+fn config(
+    domain_separator: &str,
+    num_variables: usize,
+    folding_factor: usize,
+    starting_log_inv_rate: usize,
+    security_level: usize,
+    pow_bits: usize,
+    soundness_type: &str,
+) -> Result<Config, String> {
+    if domain_separator != DOMAIN_SEPARATOR {
+        return Err(format!(
+            "Unsupported domain separator: {}",
+            domain_separator
+        ));
+    }
+    let soundness_type: SoundnessType = soundness_type.parse()?;
+    new_config(
+        num_variables,
+        folding_factor,
+        starting_log_inv_rate,
+        security_level,
+        pow_bits,
+        soundness_type,
+    )
+    .ok_or_else(|| "Invalid parameters".to_string())
+}
+
+fn statement(
+    num_variables: usize,
+    points: &[Vec<[String; 2]>],
+    evaluations: &[[String; 2]],
+) -> Result<Statement<F>, String> {
+    if points.len() != evaluations.len() {
+        return Err("Mismatched points and evaluations".to_string());
+    }
+    let points = points
+        .iter()
+        .enumerate()
+        .map(|(i, point)| {
+            if point.len() != num_variables {
+                return Err(format!("Point {} of the wrong dimension", i));
+            }
+            point
+                .iter()
+                .map(decode_extension)
+                .collect::<Result<_, _>>()
+                .map(MultilinearPoint)
+                .map_err(|e| format!("Point {}: {}", i, e))
+        })
+        .collect::<Result<_, _>>()?;
+    let evaluations = evaluations
+        .iter()
+        .enumerate()
+        .map(|(i, evaluation)| {
+            decode_extension(evaluation).map_err(|e| format!("Evaluation {}: {}", i, e))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Statement::new(points, evaluations))
+}
+
+impl TestVector {
+    fn config(&self) -> Result<Config, String> {
+        config(
+            &self.domain_separator,
+            self.num_variables,
+            self.folding_factor,
+            self.starting_log_inv_rate,
+            self.security_level,
+            self.pow_bits,
+            &self.soundness_type,
+        )
+    }
+
+    fn statement(&self) -> Result<Statement<F>, String> {
+        statement(self.num_variables, &self.points, &self.evaluations)
+    }
+
+    /// Commits to a random polynomial and proves its evaluations at `num_points` random points.
+    /// Deterministic in `seed`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate(
+        num_variables: usize,
+        folding_factor: usize,
+        starting_log_inv_rate: usize,
+        security_level: usize,
+        pow_bits: usize,
+        soundness_type: SoundnessType,
+        num_points: usize,
+        seed: u64,
+    ) -> Option<Self> {
+        let params = new_config(
+            num_variables,
+            folding_factor,
+            starting_log_inv_rate,
+            security_level,
+            pow_bits,
+            soundness_type,
+        )?;
+
+        let mut rng = <rand_chacha::ChaCha20Rng as rand::SeedableRng>::seed_from_u64(seed);
+        let coeffs: Vec<Field64> = (0..1 << num_variables)
+            .map(|_| Field64::rand(&mut rng))
+            .collect();
+        let polynomial = CoefficientList::new(coeffs.clone());
+        let points: Vec<_> = (0..num_points)
+            .map(|_| MultilinearPoint::rand(&mut rng, num_variables))
+            .collect();
+        let evaluations: Vec<_> = points
+            .iter()
+            .map(|p| polynomial.evaluate_at_extension(p))
+            .collect();
+
+        let statement = Statement::new(points.clone(), evaluations.clone());
+        let (root, transcript, proof) = Self::generate_from(&params, polynomial, statement).ok()?;
+
+        Some(TestVector {
+            num_variables,
+            folding_factor,
+            starting_log_inv_rate,
+            security_level,
+            pow_bits,
+            soundness_type: soundness_type.to_string(),
+            domain_separator: DOMAIN_SEPARATOR.to_string(),
+            polynomial: coeffs.iter().map(|c| c.to_string()).collect(),
+            points: points
+                .iter()
+                .map(|p| p.0.iter().map(encode_extension).collect())
+                .collect(),
+            evaluations: evaluations.iter().map(encode_extension).collect(),
+            root,
+            transcript,
+            proof,
+        })
+    }
+
+    /// Checks an (externally produced) vector: the proof must verify, and committing to the
+    /// polynomial and proving must reproduce the root, the transcript and the proof bit for bit.
+    pub fn check(&self) -> Result<(), String> {
+        let params = self.config()?;
+        let statement = self.statement()?;
+        let transcript = from_hex(&self.transcript)?;
+        let proof_bytes = from_hex(&self.proof)?;
+        let proof = WhirProof::<MerkleConfig, F>::deserialize_for_config(&proof_bytes[..], &params)
+            .map_err(|e| e.to_string())?;
+
+        let io = io_pattern(&params);
+        let verifier = Verifier::new(params.clone());
+        verifier
+            .verify(&mut io.to_arthur(&transcript), &statement, &proof)
+            .map_err(|e| format!("Proof does not verify: {:?}", e))?;
+
+        if self.polynomial.len() != 1 << self.num_variables {
+            return Err("Polynomial of the wrong size".to_string());
+        }
+        let coeffs = self
+            .polynomial
+            .iter()
+            .map(|c| decode_base(c))
+            .collect::<Result<_, _>>()?;
+        let polynomial = CoefficientList::new(coeffs);
+        if statement
+            .points
+            .iter()
+            .zip(&statement.evaluations)
+            .any(|(point, &eval)| polynomial.evaluate_at_extension(point) != eval)
+        {
+            return Err("Evaluations do not match the polynomial".to_string());
+        }
+
+        let expected = Self::generate_from(&params, polynomial, statement)?;
+        if expected.0 != self.root {
+            return Err("Root mismatch".to_string());
+        }
+        if expected.1 != self.transcript {
+            return Err("Transcript mismatch".to_string());
+        }
+        if expected.2 != self.proof {
+            return Err("Proof mismatch".to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the hex encoded root, transcript and proof.
+    fn generate_from(
+        params: &Config,
+        polynomial: CoefficientList<Field64>,
+        statement: Statement<F>,
+    ) -> Result<(String, String, String), String> {
+        let mut merlin = io_pattern(params).to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial)
+            .map_err(|e| e.to_string())?;
+        let root = to_hex(witness.merkle_tree.root().as_ref());
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement, witness)
+            .map_err(|e| e.to_string())?;
+        let mut proof_bytes = vec![];
+        proof
+            .serialize_compressed(&mut proof_bytes)
+            .map_err(|e| e.to_string())?;
+        Ok((root, to_hex(merlin.transcript()), to_hex(&proof_bytes)))
+    }
+}
+
+/// A proof produced by another implementation, to be checked by this verifier: the parameters
+/// and the statement, as in a `TestVector`, and the path of the proof file, holding
+/// `proof_to_bytes`, relative to the file of the case.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofCase {
+    pub num_variables: usize,
+    pub folding_factor: usize,
+    pub starting_log_inv_rate: usize,
+    pub security_level: usize,
+    pub pow_bits: usize,
+    pub soundness_type: String,
+    pub domain_separator: String,
+    pub points: Vec<Vec<[String; 2]>>,
+    pub evaluations: Vec<[String; 2]>,
+    pub proof: String,
+    /// Whether the proof should verify, `false` for negative cases.
+    #[serde(default = "default_valid")]
+    pub valid: bool,
+}
+
+fn default_valid() -> bool {
+    true
+}
+
+/// The steps of checking a `ProofCase`, each with what was found or why it failed. Checking
+/// stops at the first failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostics {
+    pub steps: Vec<(&'static str, Result<String, String>)>,
+}
+
+impl Diagnostics {
+    /// Whether the verifier accepted the proof.
+    pub fn accepted(&self) -> bool {
+        self.steps.iter().all(|(_, step)| step.is_ok())
+    }
+}
+
+impl ProofCase {
+    /// The case of the proof of `vector`, and the contents of its proof file.
+    pub fn from_vector(vector: &TestVector, proof: &str) -> Result<(Self, Vec<u8>), String> {
+        let params = vector.config()?;
+        let transcript = from_hex(&vector.transcript)?;
+        let whir_proof = WhirProof::<MerkleConfig, F>::deserialize_for_config(
+            &from_hex(&vector.proof)?[..],
+            &params,
+        )
+        .map_err(|e| e.to_string())?;
+        let proof_bytes = proof_to_bytes(&transcript, &whir_proof).map_err(|e| e.to_string())?;
+        let case = ProofCase {
+            num_variables: vector.num_variables,
+            folding_factor: vector.folding_factor,
+            starting_log_inv_rate: vector.starting_log_inv_rate,
+            security_level: vector.security_level,
+            pow_bits: vector.pow_bits,
+            soundness_type: vector.soundness_type.clone(),
+            domain_separator: vector.domain_separator.clone(),
+            points: vector.points.clone(),
+            evaluations: vector.evaluations.clone(),
+            proof: proof.to_string(),
+            valid: true,
+        };
+        Ok((case, proof_bytes))
+    }
+
+    /// Verifies `proof_bytes` for the case, step by step: the parameters and the statement are
+    /// parsed, the proof is decoded, its transcript is replayed against the IOPattern and the
+    /// opened leaves are compared with the queries it samples, and finally the proof is verified.
+    pub fn diagnose(&self, proof_bytes: &[u8]) -> Diagnostics {
+        let mut steps = vec![];
+        let _ = self.run_steps(proof_bytes, &mut steps);
+        Diagnostics { steps }
+    }
+
+    fn run_steps(
+        &self,
+        proof_bytes: &[u8],
+        steps: &mut Vec<(&'static str, Result<String, String>)>,
+    ) -> Option<()> {
+        let mut step = |name, result: Result<String, String>| {
+            let ok = result.is_ok();
+            steps.push((name, result));
+            ok.then_some(())
+        };
+
+        let params = config(
+            &self.domain_separator,
+            self.num_variables,
+            self.folding_factor,
+            self.starting_log_inv_rate,
+            self.security_level,
+            self.pow_bits,
+            &self.soundness_type,
+        );
+        step(
+            "parameters",
+            params
+                .as_ref()
+                .map(|params| {
+                    format!(
+                        "{} rounds, proofs of at most {} bytes",
+                        params.n_rounds(),
+                        params.proof_size_bound()
+                    )
+                })
+                .map_err(Clone::clone),
+        )?;
+        let params = params.ok()?;
+
+        let statement = statement(self.num_variables, &self.points, &self.evaluations);
+        step(
+            "statement",
+            statement
+                .as_ref()
+                .map(|statement| format!("{} claims", statement.points.len()))
+                .map_err(Clone::clone),
+        )?;
+        let statement = statement.ok()?;
+
+        let decoded = proof_from_bytes_for_config(proof_bytes, &params).map_err(|e| {
+            format!(
+                "{} bytes do not hold a transcript and a proof of {} rounds: {}",
+                proof_bytes.len(),
+                params.n_rounds() + 1,
+                e
+            )
+        });
+        step(
+            "decoding",
+            decoded
+                .as_ref()
+                .map(|(transcript, whir_proof)| {
+                    format!(
+                        "transcript of {} bytes, proof of {} bytes",
+                        transcript.len(),
+                        whir_proof_size(transcript, whir_proof) - transcript.len()
+                    )
+                })
+                .map_err(Clone::clone),
+        )?;
+        let (transcript, whir_proof) = decoded.ok()?;
+
+        let io = io_pattern(&params);
+        let report = inspect(&params, &io, &transcript, &whir_proof).map_err(|_| {
+            format!(
+                "the transcript of {} bytes does not follow the IOPattern of the parameters",
+                transcript.len()
+            )
+        });
+        step(
+            "transcript",
+            report
+                .as_ref()
+                .map(|report| to_hex(&report.root))
+                .map(|root| format!("commitment root {}", root))
+                .map_err(Clone::clone),
+        )?;
+        let report = report.ok()?;
+
+        let queries = report
+            .rounds
+            .iter()
+            .map(|round| &round.queries)
+            .chain([&report.final_queries]);
+        let mismatch = queries
+            .zip(&whir_proof.0)
+            .enumerate()
+            .find(|(_, (queries, (path, _)))| **queries != path.leaf_indexes)
+            .map(|(round, (queries, (path, _)))| {
+                format!(
+                    "round {} opens the leaves {:?}, but the transcript samples {:?}",
+                    round, path.leaf_indexes, queries
+                )
+            });
+        step(
+            "queries",
+            match mismatch {
+                None => Ok(format!("{} rounds of openings", whir_proof.0.len())),
+                Some(mismatch) => Err(mismatch),
+            },
+        )?;
+
+        let verified = Verifier::new(params)
+            .verify(&mut io.to_arthur(&transcript), &statement, &whir_proof)
+            .map(|()| "accepted".to_string())
+            .map_err(|e| format!("rejected: {:?}", e));
+        step("verification", verified)
+    }
+}
+
+/// The vectors shipped to other implementations: a small grid over the parameters.
+pub fn default_vectors() -> Vec<TestVector> {
+    let mut vectors = vec![];
+    let mut seed = 0;
+    for (num_variables, folding_factor) in [(4, 1), (6, 2), (8, 4), (9, 3)] {
+        for soundness_type in [
+            SoundnessType::UniqueDecoding,
+            SoundnessType::ProvableList,
+            SoundnessType::ConjectureList,
+        ] {
+            for (pow_bits, num_points) in [(0, 1), (5, 2)] {
+                vectors.extend(TestVector::generate(
+                    num_variables,
+                    folding_factor,
+                    1,
+                    32,
+                    pow_bits,
+                    soundness_type,
+                    num_points,
+                    seed,
+                ));
+                seed += 1;
+            }
+        }
+    }
+    vectors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors_roundtrip() {
+        let vector =
+            TestVector::generate(6, 2, 1, 32, 5, SoundnessType::ConjectureList, 2, 42).unwrap();
+        let json = serde_json::to_string(&vector).unwrap();
+        let parsed: TestVector = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, vector);
+        assert!(parsed.check().is_ok());
+
+        let mut tampered = parsed.clone();
+        tampered.evaluations[0][0] = "1".to_string();
+        assert!(tampered.check().is_err());
+
+        let mut non_canonical = parsed;
+        non_canonical.polynomial[0] = "18446744069414584321".to_string();
+        assert!(non_canonical.check().is_err());
+    }
+
+    #[test]
+    fn test_proof_case() {
+        let vector =
+            TestVector::generate(6, 2, 1, 32, 5, SoundnessType::ConjectureList, 2, 43).unwrap();
+        let (case, proof_bytes) = ProofCase::from_vector(&vector, "proof.bin").unwrap();
+        let json = serde_json::to_string(&case).unwrap();
+        assert_eq!(serde_json::from_str::<ProofCase>(&json).unwrap(), case);
+        let diagnostics = case.diagnose(&proof_bytes);
+        assert!(diagnostics.accepted());
+        assert_eq!(diagnostics.steps.len(), 6);
+
+        // Each failure is reported at its step
+        let failed_at = |case: &ProofCase, proof_bytes: &[u8]| {
+            let diagnostics = case.diagnose(proof_bytes);
+            assert!(!diagnostics.accepted());
+            diagnostics.steps.last().unwrap().0
+        };
+        let mut wrong_evaluation = case.clone();
+        wrong_evaluation.evaluations[0][0] = "1".to_string();
+        assert_eq!(failed_at(&wrong_evaluation, &proof_bytes), "verification");
+        assert_eq!(
+            failed_at(&case, &proof_bytes[..proof_bytes.len() - 1]),
+            "decoding"
+        );
+        let mut wrong_fold = case.clone();
+        wrong_fold.folding_factor = 3;
+        assert_eq!(failed_at(&wrong_fold, &proof_bytes), "decoding");
+        let mut wrong_dimension = case;
+        wrong_dimension.points[0].pop();
+        assert_eq!(failed_at(&wrong_dimension, &proof_bytes), "statement");
+    }
+}
@@ -0,0 +1,143 @@
+//! The sizes from which the recursive evaluations split their work across threads with
+//! `rayon::join`: `eval_eq` of the sumcheck, and the evaluation of a polynomial at a point of an
+//! extension field. Below them the overhead of forking exceeds the work.
+//!
+//! The defaults can be overridden with the environment variables `WHIR_EVAL_EQ_THRESHOLD` and
+//! `WHIR_EVAL_EXTENSION_THRESHOLD`, read on first use, or measured on the host with `calibrate`.
+
+use std::{env, hint::black_box, sync::OnceLock, time::Instant};
+
+use ark_ff::Field;
+
+use crate::{poly_utils::coeffs::CoefficientList, sumcheck::prover_single::SumcheckSingle};
+
+/// A recursion forks when more variables than the threshold are left below the current one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Thresholds {
+    pub eval_eq: usize,
+    pub eval_extension: usize,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            eval_eq: 10,
+            eval_extension: 10,
+        }
+    }
+}
+
+static THRESHOLDS: OnceLock<Thresholds> = OnceLock::new();
+
+/// The thresholds in use: the ones set by `set_thresholds` or `calibrate`, else the defaults with
+/// the overrides of the environment.
+pub fn thresholds() -> Thresholds {
+    *THRESHOLDS.get_or_init(|| {
+        let default = Thresholds::default();
+        Thresholds {
+            eval_eq: env_threshold("WHIR_EVAL_EQ_THRESHOLD").unwrap_or(default.eval_eq),
+            eval_extension: env_threshold("WHIR_EVAL_EXTENSION_THRESHOLD")
+                .unwrap_or(default.eval_extension),
+        }
+    })
+}
+
+/// Sets the thresholds, which is only possible before they are first used. Returns whether they
+/// were set.
+pub fn set_thresholds(thresholds: Thresholds) -> bool {
+    THRESHOLDS.set(thresholds).is_ok()
+}
+
+/// Measures the thresholds on the host, for elements of `F`, and uses them from then on unless
+/// the thresholds were already in use. The overrides of the environment take precedence over the
+/// measurements. Takes in the order of a second, so run it once at startup. Returns the
+/// thresholds in use.
+pub fn calibrate<F: Field>() -> Thresholds {
+    let eval_eq = env_threshold("WHIR_EVAL_EQ_THRESHOLD").unwrap_or_else(|| {
+        crossover(|num_variables, threshold| {
+            let point = vec![F::from(3); num_variables];
+            let mut out = vec![F::ZERO; 1 << num_variables];
+            SumcheckSingle::eval_eq_with(&point, &mut out, F::ONE, threshold);
+            black_box(out);
+        })
+    });
+    let eval_extension = env_threshold("WHIR_EVAL_EXTENSION_THRESHOLD").unwrap_or_else(|| {
+        crossover(|num_variables, threshold| {
+            let coeffs = vec![F::BasePrimeField::from(3); 1 << num_variables];
+            let point = vec![F::from(5); num_variables];
+            black_box(CoefficientList::eval_extension_parallel(
+                &coeffs,
+                &point,
+                F::ONE,
+                threshold,
+            ));
+        })
+    });
+    *THRESHOLDS.get_or_init(|| Thresholds {
+        eval_eq,
+        eval_extension,
+    })
+}
+
+fn env_threshold(name: &str) -> Option<usize> {
+    env::var(name).ok()?.parse().ok()
+}
+
+// The first threshold at which forking beats the sequential evaluation, where `run` evaluates in
+// `num_variables` variables with the given threshold. With `num_variables - 2`, only the top
+// call forks.
+fn crossover(run: impl Fn(usize, usize)) -> usize {
+    const REPETITIONS: u32 = 16;
+    const MAX_VARIABLES: usize = 20;
+    for num_variables in 4..=MAX_VARIABLES {
+        let time = |threshold| {
+            let start = Instant::now();
+            for _ in 0..REPETITIONS {
+                run(num_variables, threshold);
+            }
+            start.elapsed()
+        };
+        if time(num_variables - 2) < time(usize::MAX) {
+            return num_variables - 2;
+        }
+    }
+    MAX_VARIABLES - 2
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread::sleep, time::Duration};
+
+    use super::{calibrate, crossover, thresholds};
+    use crate::{crypto::fields::Field64 as F, sumcheck::prover_single::SumcheckSingle};
+    use ark_ff::AdditiveGroup;
+
+    // Runs the full measurement, and fixes the thresholds for the rest of the process.
+    #[test]
+    #[ignore = "takes about a second, and sets the thresholds of the other tests"]
+    fn test_calibrate() {
+        assert_eq!(calibrate::<F>(), thresholds());
+    }
+
+    #[test]
+    fn test_crossover() {
+        // Forking is slower below 6 variables, and faster from there on
+        let threshold = crossover(|num_variables, threshold| {
+            if (threshold == usize::MAX) == (num_variables >= 6) {
+                sleep(Duration::from_micros(100));
+            }
+        });
+        assert_eq!(threshold, 4);
+    }
+
+    #[test]
+    fn test_thresholds_agree() {
+        let point: Vec<_> = (0..8).map(|i| F::from(i + 2)).collect();
+        let evaluate = |threshold| {
+            let mut out = vec![F::ZERO; 1 << point.len()];
+            SumcheckSingle::eval_eq_with(&point, &mut out, F::from(7), threshold);
+            out
+        };
+        assert_eq!(evaluate(0), evaluate(usize::MAX));
+    }
+}
@@ -1,12 +1,50 @@
 use crate::ntt::transpose;
-use ark_ff::Field;
-use std::collections::BTreeSet;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_std::{borrow::Cow, collections::BTreeSet, vec::Vec};
 
 // checks whether the given number n is a power of two.
 pub fn is_power_of_two(n: usize) -> bool {
     n != 0 && (n & (n - 1) == 0)
 }
 
+/// returns `2^exp`, or `None` if it does not fit in a usize (i.e. `exp >= usize::BITS`).
+pub fn checked_pow2(exp: usize) -> Option<usize> {
+    1usize.checked_shl(u32::try_from(exp).ok()?)
+}
+
+/// returns the inverses of `values` with a single field inversion (Montgomery's trick), zeros
+/// staying zero.
+pub fn batch_inverse<F: Field>(values: &[F]) -> Vec<F> {
+    let mut inverses = values.to_vec();
+    ark_ff::batch_inversion(&mut inverses);
+    inverses
+}
+
+/// samples a query index in `0..bound`. The index is drawn as a u64, so that the indexes of a
+/// transcript are the same on 32-bit targets, where rand draws a usize from 32-bit words.
+pub fn sample_index(rng: &mut impl rand::Rng, bound: usize) -> usize {
+    rng.gen_range(0..bound as u64) as usize
+}
+
+/// scales the coefficients `c_i` of a polynomial to `c_i * offset^i`, so that the evaluations of
+/// the result over a subgroup `<w>` are the evaluations of the polynomial over `offset * <w>`.
+pub fn coset_shift<F: Field>(coeffs: &[F], offset: F) -> Cow<'_, [F]> {
+    if offset.is_one() {
+        return Cow::Borrowed(coeffs);
+    }
+    let mut power = F::ONE;
+    Cow::Owned(
+        coeffs
+            .iter()
+            .map(|coeff| {
+                let value = *coeff * power;
+                power *= offset;
+                value
+            })
+            .collect(),
+    )
+}
+
 /// performs big-endian binary decomposition of `value` and returns the result.
 ///
 /// `n_bits` must be at must usize::BITS. If it is strictly smaller, the most significant bits of `value` are ignored.
@@ -83,11 +121,188 @@ pub fn stack_evaluations<F: Field>(mut evals: Vec<F>, folding_factor: usize) ->
     evals
 }
 
+/// `stack_evaluations` followed by `convert` on each element, e.g. into an extension field, in
+/// one pass that writes each stack from the rows of `evals`. Unlike the transpose of
+/// `stack_evaluations`, this allocates nothing but the output.
+pub fn stack_evaluations_into<B, F>(
+    evals: &[B],
+    folding_factor: usize,
+    convert: impl Fn(B) -> F + Sync,
+) -> Vec<F>
+where
+    B: Copy + Sync,
+    F: Field,
+{
+    let folding_factor_exp = 1 << folding_factor;
+    assert!(evals.len() % folding_factor_exp == 0);
+    let size_of_new_domain = evals.len() / folding_factor_exp;
+
+    let mut stacked = vec![F::ZERO; evals.len()];
+    let fill = |(i, stack): (usize, &mut [F])| {
+        for (j, value) in stack.iter_mut().enumerate() {
+            *value = convert(evals[i + j * size_of_new_domain]);
+        }
+    };
+    #[cfg(not(feature = "parallel"))]
+    stacked
+        .chunks_exact_mut(folding_factor_exp)
+        .enumerate()
+        .for_each(fill);
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        stacked
+            .par_chunks_exact_mut(folding_factor_exp)
+            .enumerate()
+            .for_each(fill);
+    }
+    stacked
+}
+
+// Encodings of bytes as field elements, fixed so that proofs over encoded data are portable.
+
+/// The number of bytes packed in each element by `bytes_to_field_elements`: the largest `k`
+/// such that every `k`-byte string is smaller than the modulus.
+pub fn bytes_per_field_element<F: PrimeField>() -> usize {
+    ((F::MODULUS_BIT_SIZE - 1) / 8) as usize
+}
+
+/// Splits `bytes` into chunks of `bytes_per_field_element` bytes, each read as a little-endian
+/// integer. The last chunk is implicitly padded with zeros, and the length of `bytes` is not
+/// encoded: it has to be known to decode.
+pub fn bytes_to_field_elements<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+    bytes
+        .chunks(bytes_per_field_element::<F>())
+        .map(F::from_le_bytes_mod_order)
+        .collect()
+}
+
+/// Inverse of `bytes_to_field_elements` on inputs of `num_bytes` bytes. Returns `None` if
+/// `elements` is not the encoding of any such input.
+pub fn field_elements_to_bytes<F: PrimeField>(elements: &[F], num_bytes: usize) -> Option<Vec<u8>> {
+    let chunk_size = bytes_per_field_element::<F>();
+    if elements.len() != num_bytes.div_ceil(chunk_size) {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(elements.len() * chunk_size);
+    for element in elements {
+        let element_bytes = element.into_bigint().to_bytes_le();
+        if element_bytes[chunk_size..].iter().any(|&b| b != 0) {
+            return None;
+        }
+        bytes.extend_from_slice(&element_bytes[..chunk_size]);
+    }
+    if bytes[num_bytes..].iter().any(|&b| b != 0) {
+        return None;
+    }
+    bytes.truncate(num_bytes);
+    Some(bytes)
+}
+
+/// The canonical encoding of a field element: the little-endian encodings of its coefficients
+/// over the base prime field, each on `ceil(MODULUS_BIT_SIZE / 8)` bytes.
+pub fn field_to_bytes<F: Field>(element: &F) -> Vec<u8> {
+    let width = F::BasePrimeField::MODULUS_BIT_SIZE.div_ceil(8) as usize;
+    element
+        .to_base_prime_field_elements()
+        .flat_map(|coefficient| {
+            let mut bytes = coefficient.into_bigint().to_bytes_le();
+            bytes.truncate(width);
+            bytes
+        })
+        .collect()
+}
+
+/// Inverse of `field_to_bytes`, rejecting non-canonical encodings.
+pub fn field_from_bytes<F: Field>(bytes: &[u8]) -> Option<F> {
+    let width = F::BasePrimeField::MODULUS_BIT_SIZE.div_ceil(8) as usize;
+    if bytes.len() != width * F::extension_degree() as usize {
+        return None;
+    }
+    let element = F::from_base_prime_field_elems(
+        bytes
+            .chunks_exact(width)
+            .map(F::BasePrimeField::from_le_bytes_mod_order),
+    )?;
+    (field_to_bytes(&element) == bytes).then_some(element)
+}
+
+/// Hashes `message` to `count` field elements, with Blake3 in key derivation mode under the
+/// context `domain_separator`. Each base field coefficient is reduced from 16 bytes more than
+/// its size, so that it is statistically close to uniform.
+pub fn hash_to_field<F: Field>(domain_separator: &str, message: &[u8], count: usize) -> Vec<F> {
+    let width = F::BasePrimeField::MODULUS_BIT_SIZE.div_ceil(8) as usize + 16;
+    let mut hasher = blake3::Hasher::new_derive_key(domain_separator);
+    hasher.update(message);
+    let mut reader = hasher.finalize_xof();
+    let mut buffer = vec![0; width];
+    (0..count)
+        .map(|_| {
+            F::from_base_prime_field_elems((0..F::extension_degree()).map(|_| {
+                reader.fill(&mut buffer);
+                F::BasePrimeField::from_le_bytes_mod_order(&buffer)
+            }))
+            .unwrap()
+        })
+        .collect()
+}
+
+// Floating point functions that are not available in `core`, used by the parameter selection.
+#[cfg(feature = "std")]
+pub(crate) fn log2_f64(x: f64) -> f64 {
+    x.log2()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn log2_f64(x: f64) -> f64 {
+    libm::log2(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn exp2_f64(x: f64) -> f64 {
+    x.exp2()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn exp2_f64(x: f64) -> f64 {
+    libm::exp2(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn ceil_f64(x: f64) -> f64 {
+    x.ceil()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn ceil_f64(x: f64) -> f64 {
+    libm::ceil(x)
+}
+
+/// Hex display of bytes, e.g. of the roots and nonces in the `tracing` events.
+pub(crate) struct Hex<'a>(pub(crate) &'a [u8]);
+
+impl ark_std::fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
+
+// The PoW nonce is the last prover message after a PoW challenge.
+#[cfg(feature = "tracing")]
+pub(crate) fn pow_nonce(transcript: &[u8]) -> Hex<'_> {
+    Hex(&transcript[transcript.len().saturating_sub(8)..])
+}
+
 #[cfg(test)]
 mod tests {
     use crate::utils::base_decomposition;
 
-    use super::{is_power_of_two, stack_evaluations, to_binary};
+    use super::{
+        batch_inverse, bytes_to_field_elements, checked_pow2, field_elements_to_bytes,
+        field_from_bytes, field_to_bytes, hash_to_field, is_power_of_two, stack_evaluations,
+        stack_evaluations_into, to_binary,
+    };
+    use crate::crypto::fields::{Field64, Field64_2};
 
     #[test]
     fn test_evaluations_stack() {
@@ -99,8 +314,10 @@ mod tests {
         assert_eq!(num % fold_size, 0);
         let evals: Vec<_> = (0..num as u64).map(F::from).collect();
 
+        let stacked_into = stack_evaluations_into(&evals, folding_factor, |e| e);
         let stacked = stack_evaluations(evals, folding_factor);
         assert_eq!(stacked.len(), num);
+        assert_eq!(stacked_into, stacked);
 
         for (i, fold) in stacked.chunks_exact(fold_size).enumerate() {
             assert_eq!(fold.len(), fold_size);
@@ -128,6 +345,18 @@ mod tests {
         assert_eq!(is_power_of_two(usize::MAX), false);
     }
 
+    #[test]
+    fn test_checked_pow2() {
+        assert_eq!(checked_pow2(0), Some(1));
+        assert_eq!(checked_pow2(10), Some(1024));
+        assert_eq!(
+            checked_pow2(usize::BITS as usize - 1),
+            Some(1 << (usize::BITS - 1))
+        );
+        assert_eq!(checked_pow2(usize::BITS as usize), None);
+        assert_eq!(checked_pow2(usize::MAX), None);
+    }
+
     #[test]
     fn test_base_decomposition() {
         assert_eq!(base_decomposition(0b1011, 2, 6), vec![0, 0, 1, 0, 1, 1]);
@@ -136,4 +365,71 @@ mod tests {
         // If we actually specify the API to have a different behaviour, this test should change.
         assert_eq!(base_decomposition(15 + 81, 3, 3), vec![1, 2, 0]);
     }
+
+    #[test]
+    fn test_bytes_to_field_elements() {
+        let bytes: Vec<u8> = (1..=16).collect();
+        let elements = bytes_to_field_elements::<Field64>(&bytes);
+        assert_eq!(
+            elements,
+            vec![
+                Field64::from(0x07060504030201u64),
+                Field64::from(0x0e0d0c0b0a0908u64),
+                Field64::from(0x100fu64),
+            ]
+        );
+        assert_eq!(field_elements_to_bytes(&elements, 16), Some(bytes.clone()));
+        assert!(bytes_to_field_elements::<Field64>(&[]).is_empty());
+
+        // Wrong length, non-zero padding and elements out of range are rejected
+        assert_eq!(field_elements_to_bytes(&elements, 13), None);
+        assert_eq!(field_elements_to_bytes(&elements, 15), None);
+        assert_eq!(
+            field_elements_to_bytes(&[Field64::from(1u64 << 56)], 7),
+            None
+        );
+    }
+
+    #[test]
+    fn test_field_to_bytes() {
+        let element = Field64::from(0x0102u64);
+        assert_eq!(field_to_bytes(&element), vec![2, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            field_from_bytes::<Field64>(&field_to_bytes(&element)),
+            Some(element)
+        );
+
+        let element = Field64_2::new(Field64::from(3u64), Field64::from(4u64));
+        let bytes = field_to_bytes(&element);
+        assert_eq!(bytes.len(), 16);
+        assert_eq!((bytes[0], bytes[8]), (3, 4));
+        assert_eq!(field_from_bytes::<Field64_2>(&bytes), Some(element));
+
+        // The modulus is not a canonical encoding
+        let modulus = 0xffffffff00000001u64.to_le_bytes();
+        assert_eq!(field_from_bytes::<Field64>(&modulus), None);
+        assert_eq!(field_from_bytes::<Field64>(&bytes), None);
+    }
+
+    #[test]
+    fn test_hash_to_field() {
+        let a: Vec<Field64_2> = hash_to_field("whir test", b"message", 3);
+        assert_eq!(a.len(), 3);
+        assert_eq!(a, hash_to_field("whir test", b"message", 3));
+        assert_ne!(a, hash_to_field("whir test", b"other message", 3));
+        assert_ne!(a, hash_to_field("other test", b"message", 3));
+        assert_ne!(a[0], a[1]);
+    }
+
+    #[test]
+    fn test_batch_inverse() {
+        use ark_ff::{AdditiveGroup, Field};
+
+        let values = [3, 0, 1, 12345].map(Field64::from);
+        let inverses = batch_inverse(&values);
+        assert_eq!(inverses[1], Field64::ZERO);
+        for (value, inverse) in values.iter().zip(&inverses) {
+            assert_eq!(value.inverse().unwrap_or(Field64::ZERO), *inverse);
+        }
+    }
 }
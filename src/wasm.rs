@@ -0,0 +1,85 @@
+//! JavaScript bindings for the verifier, for light clients running in the browser.
+//!
+//! Build with `--no-default-features --features wasm` for `wasm32-unknown-unknown`, as the
+//! `cdylib` that `wasm-bindgen` takes; the prover stays native-only:
+//! ```text
+//! cargo rustc --release --lib --target wasm32-unknown-unknown --no-default-features \
+//!     --features wasm --crate-type cdylib
+//! ```
+//! Proofs are the byte buffers produced by `whir::proof_to_bytes` (for instance by `whir_prove`
+//! in the C bindings), points and evaluations are passed as `BigUint64Array`s, two limbs per
+//! extension element.
+
+use nimue::IOPattern;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    binding_utils::{
+        io_pattern, new_config, read_extension_elements, read_points, soundness_type_from_code,
+        MerkleConfig, PowStrategy, F,
+    },
+    whir::{verifier::Verifier, Statement},
+};
+
+#[wasm_bindgen]
+pub struct WhirVerifier {
+    num_variables: usize,
+    io: IOPattern,
+    verifier: Verifier<F, MerkleConfig, PowStrategy>,
+}
+
+#[wasm_bindgen]
+impl WhirVerifier {
+    /// `soundnessType` is 0 for `UniqueDecoding`, 1 for `ProvableList` and 2 for `ConjectureList`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        num_variables: usize,
+        folding_factor: usize,
+        starting_log_inv_rate: usize,
+        security_level: usize,
+        pow_bits: usize,
+        soundness_type: u32,
+    ) -> Result<WhirVerifier, JsError> {
+        let params = soundness_type_from_code(soundness_type)
+            .and_then(|soundness_type| {
+                new_config(
+                    num_variables,
+                    folding_factor,
+                    starting_log_inv_rate,
+                    security_level,
+                    pow_bits,
+                    soundness_type,
+                )
+            })
+            .ok_or_else(|| JsError::new("invalid WHIR parameters"))?;
+
+        Ok(WhirVerifier {
+            num_variables,
+            io: io_pattern(&params),
+            verifier: Verifier::new(params),
+        })
+    }
+
+    /// Returns whether `proof` proves that the committed polynomial evaluates to `evaluations`
+    /// at `points`. Throws if the points and evaluations have mismatched lengths.
+    #[wasm_bindgen(js_name = verifyBytes)]
+    pub fn verify_bytes(
+        &self,
+        points: &[u64],
+        evaluations: &[u64],
+        proof: &[u8],
+    ) -> Result<bool, JsError> {
+        if evaluations.len() % 2 != 0 || points.len() != evaluations.len() * self.num_variables {
+            return Err(JsError::new("mismatched points and evaluations"));
+        }
+
+        let statement = Statement::new(
+            read_points(points, self.num_variables),
+            read_extension_elements(evaluations),
+        );
+        Ok(self
+            .verifier
+            .verify_bytes(&self.io, &statement, proof)
+            .is_ok())
+    }
+}
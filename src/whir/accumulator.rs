@@ -0,0 +1,227 @@
+//! Split accumulation of evaluation claims on a committed polynomial, for IVC-style usage.
+//!
+//! Instead of proving every claim `p(z_i) = v_i` as it arises, both parties fold it into a
+//! running linear claim `sum_b w(b) * p(b) = s` with `w = sum_i alpha_i * eq(z_i, .)`, for
+//! random `alpha_i` drawn from the transcript. The accumulator has the size of the hypercube
+//! whatever the number of claims, and a single WHIR proof of the linear claim, see
+//! `finalize_proof`, discharges all of them. The prover keeps the witness of the commitment on
+//! its side. Claims on different commitments go to different accumulators.
+
+use ark_crypto_primitives::merkle_tree::Config;
+use ark_ff::{FftField, Field};
+use nimue::{
+    plugins::ark::{FieldChallenges, FieldIOPattern, FieldReader, FieldWriter},
+    Arthur, ByteWriter, IOPattern, Merlin, ProofResult,
+};
+
+use super::{
+    committer::Witness,
+    parameters::WhirConfig,
+    prover::Prover,
+    verifier::{ParsedCommitment, Verifier},
+    LinearClaim, Statement, WhirProof,
+};
+use crate::{
+    errors::WhirResult,
+    poly_utils::{evals::EvaluationsList, MultilinearPoint},
+    sumcheck::prover_single::SumcheckSingle,
+};
+
+pub trait AccumulatorIOPattern<F: Field> {
+    /// One claim folded by `Accumulator::fold`.
+    fn add_fold(self) -> Self;
+}
+
+impl<F> AccumulatorIOPattern<F> for IOPattern
+where
+    F: Field,
+    IOPattern: FieldIOPattern<F>,
+{
+    fn add_fold(self) -> Self {
+        self.add_scalars(1, "accumulated_evaluation")
+            .challenge_scalars(1, "accumulation_randomness")
+    }
+}
+
+/// The running claim on the committed polynomial.
+#[derive(Debug, Clone)]
+pub struct Accumulator<F> {
+    weights: EvaluationsList<F>,
+    sum: F,
+    num_claims: usize,
+}
+
+impl<F: Field> Accumulator<F> {
+    /// The empty accumulator, for a polynomial in `num_variables` variables.
+    pub fn new(num_variables: usize) -> Self {
+        Accumulator {
+            weights: EvaluationsList::new(vec![F::ZERO; 1 << num_variables]),
+            sum: F::ZERO,
+            num_claims: 0,
+        }
+    }
+
+    pub fn num_claims(&self) -> usize {
+        self.num_claims
+    }
+
+    fn absorb(&mut self, point: &MultilinearPoint<F>, value: F, randomness: F) {
+        assert_eq!(point.n_variables(), self.weights.num_variables());
+        SumcheckSingle::eval_eq(&point.0, self.weights.evals_mut(), randomness);
+        self.sum += randomness * value;
+        self.num_claims += 1;
+    }
+
+    /// Prover side: folds the claim that the committed polynomial evaluates to `value` at `point`.
+    /// The value is sent to the verifier.
+    pub fn fold(
+        &mut self,
+        merlin: &mut Merlin,
+        point: &MultilinearPoint<F>,
+        value: F,
+    ) -> ProofResult<()>
+    where
+        Merlin: FieldWriter<F> + FieldChallenges<F>,
+    {
+        merlin.add_scalars(&[value])?;
+        let [randomness] = merlin.challenge_scalars()?;
+        self.absorb(point, value, randomness);
+        Ok(())
+    }
+
+    /// Verifier side of `fold`: returns the value claimed at `point`.
+    pub fn fold_verifier(
+        &mut self,
+        arthur: &mut Arthur,
+        point: &MultilinearPoint<F>,
+    ) -> ProofResult<F> {
+        let [value] = arthur.next_scalars()?;
+        let [randomness] = arthur.challenge_scalars()?;
+        self.absorb(point, value, randomness);
+        Ok(value)
+    }
+
+    /// The single linear claim equivalent to all the claims folded so far.
+    pub fn statement(&self) -> Statement<F> {
+        let mut statement = Statement::new(vec![], vec![]);
+        statement.linear_claims.push(LinearClaim {
+            weights: self.weights.clone(),
+            sum: self.sum,
+        });
+        statement
+    }
+}
+
+impl<F: FftField> Accumulator<F> {
+    /// Opens the commitment once, proving all the claims folded so far.
+    pub fn finalize_proof<MerkleConfig, PowStrategy>(
+        &self,
+        merlin: &mut Merlin,
+        config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+        witness: Witness<F, MerkleConfig>,
+    ) -> WhirResult<WhirProof<MerkleConfig, F>>
+    where
+        MerkleConfig: Config<Leaf = [F]>,
+        MerkleConfig::InnerDigest: AsRef<[u8]>,
+        PowStrategy: nimue_pow::PowStrategy,
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        Prover(config.clone()).prove(merlin, self.statement(), witness)
+    }
+
+    /// Verifier side of `finalize_proof`.
+    pub fn verify_final_proof<MerkleConfig, PowStrategy>(
+        &self,
+        arthur: &mut Arthur,
+        verifier: &Verifier<F, MerkleConfig, PowStrategy>,
+        commitment: &ParsedCommitment<F, MerkleConfig::InnerDigest>,
+        whir_proof: &WhirProof<MerkleConfig, F>,
+    ) -> ProofResult<()>
+    where
+        MerkleConfig: Config<Leaf = [F]>,
+        MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+        PowStrategy: nimue_pow::PowStrategy,
+    {
+        verifier.verify_with_commitment(arthur, commitment, &self.statement(), whir_proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimue::DefaultHash;
+    use nimue_pow::blake3::Blake3PoW;
+
+    use super::*;
+    use crate::{
+        crypto::merkle_tree::blake3 as merkle_tree,
+        parameters::MultivariateParameters,
+        poly_utils::coeffs::CoefficientList,
+        whir::{
+            committer::Committer,
+            fixtures::{whir_parameters, MerkleConfig, F},
+            iopattern::WhirIOPattern,
+        },
+    };
+
+    #[test]
+    fn test_accumulator() {
+        let num_variables = 6;
+        let num_claims = 5;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params =
+            whir_parameters::<MerkleConfig, Blake3PoW>((leaf_hash_params, two_to_one_params));
+        let params = WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+
+        let mut io = IOPattern::<DefaultHash>::new("🌪️").commit_statement(&params);
+        for _ in 0..num_claims {
+            io = AccumulatorIOPattern::<F>::add_fold(io);
+        }
+        let io = io.add_whir_proof(&params);
+
+        let polynomial = CoefficientList::new((0..1u64 << num_variables).map(F::from).collect());
+        let points: Vec<_> = (0..num_claims)
+            .map(|_| MultilinearPoint::rand(&mut rng, num_variables))
+            .collect();
+
+        for wrong_claim in [None, Some(3)] {
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit(&mut merlin, polynomial.clone())
+                .unwrap();
+            let mut accumulator = Accumulator::new(num_variables);
+            for (i, point) in points.iter().enumerate() {
+                let mut value = polynomial.evaluate(point);
+                if wrong_claim == Some(i) {
+                    value += F::from(1);
+                }
+                accumulator.fold(&mut merlin, point, value).unwrap();
+            }
+            assert_eq!(accumulator.num_claims(), num_claims);
+            let proof = accumulator
+                .finalize_proof(&mut merlin, &params, witness)
+                .unwrap();
+
+            let verifier = Verifier::new(params.clone());
+            let mut arthur = io.to_arthur(merlin.transcript());
+            let commitment = verifier.parse_commitment(&mut arthur).unwrap();
+            let mut verifier_accumulator = Accumulator::new(num_variables);
+            for point in &points {
+                verifier_accumulator
+                    .fold_verifier(&mut arthur, point)
+                    .unwrap();
+            }
+            let result = verifier_accumulator.verify_final_proof(
+                &mut arthur,
+                &verifier,
+                &commitment,
+                &proof,
+            );
+            assert_eq!(result.is_ok(), wrong_claim.is_none());
+        }
+    }
+}
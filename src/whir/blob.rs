@@ -0,0 +1,284 @@
+//! Blob commitments, for data availability sampling (DAS) experiments over WHIR.
+//!
+//! The bytes of the blob are packed into base field elements (see
+//! `utils::bytes_to_field_elements`), which are the coefficients of
+//! the committed polynomial. Light clients sample random positions of the committed
+//! Reed-Solomon codeword, each checked against the root with a Merkle path, while a single
+//! WHIR proof without evaluation claims shows the codeword is close to the code, so that
+//! enough samples allow to reconstruct the blob.
+
+use ark_crypto_primitives::merkle_tree::{Config, MultiPath};
+use ark_ff::FftField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::collections::BTreeSet;
+use nimue::{
+    plugins::ark::{FieldChallenges, FieldWriter},
+    Arthur, ByteWriter, Merlin, ProofError, ProofResult,
+};
+use rand::Rng;
+
+use super::{
+    committer::{Committer, Witness},
+    parameters::WhirConfig,
+    prover::Prover,
+    verifier::Verifier,
+    Statement, WhirProof,
+};
+use crate::{
+    errors::{WhirError, WhirResult},
+    poly_utils::coeffs::CoefficientList,
+    utils::{bytes_per_field_element, bytes_to_field_elements, dedup},
+};
+
+/// The root of the committed codeword, together with the length of the blob.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlobCommitment<D> {
+    pub root: D,
+    pub num_bytes: usize,
+}
+
+/// Openings of positions of the codeword: one leaf (the stacked evaluations) per position.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Samples<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    pub merkle_proof: MultiPath<MerkleConfig>,
+    pub leaves: Vec<Vec<F>>,
+}
+
+pub struct BlobProver<F, MerkleConfig, PowStrategy>(WhirConfig<F, MerkleConfig, PowStrategy>)
+where
+    F: FftField,
+    MerkleConfig: Config;
+
+impl<F, MerkleConfig, PowStrategy> BlobProver<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    pub fn new(config: WhirConfig<F, MerkleConfig, PowStrategy>) -> Self {
+        Self(config)
+    }
+
+    /// The maximum length of a blob.
+    pub fn capacity(&self) -> usize {
+        bytes_per_field_element::<F::BasePrimeField>() << self.0.mv_parameters.num_variables
+    }
+
+    /// Commits to `data`, which must be at most `capacity` bytes long.
+    pub fn commit_blob(
+        &self,
+        merlin: &mut Merlin,
+        data: &[u8],
+    ) -> WhirResult<(
+        BlobCommitment<MerkleConfig::InnerDigest>,
+        Witness<F, MerkleConfig>,
+    )>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        if data.len() > self.capacity() {
+            return Err(WhirError::TooLong {
+                max: self.capacity(),
+                found: data.len(),
+            });
+        }
+        let mut coeffs = bytes_to_field_elements(data);
+        coeffs.resize(
+            1 << self.0.mv_parameters.num_variables,
+            F::BasePrimeField::ZERO,
+        );
+        let polynomial = CoefficientList::new(coeffs);
+        let witness = Committer::new(self.0.clone()).commit(merlin, polynomial)?;
+        let commitment = BlobCommitment {
+            root: witness.merkle_tree.root(),
+            num_bytes: data.len(),
+        };
+        Ok((commitment, witness))
+    }
+
+    /// Opens the codeword at the positions `indexes`, which are sorted and deduplicated.
+    /// Returns `None` if one is out of range.
+    pub fn open_samples(
+        &self,
+        witness: &Witness<F, MerkleConfig>,
+        indexes: &[usize],
+    ) -> Option<Samples<MerkleConfig, F>> {
+        let leaf_size = self.0.leaf_size();
+        let indexes = dedup(indexes.iter().copied());
+        if indexes
+            .iter()
+            .any(|&i| i >= witness.merkle_leaves.len() / leaf_size)
+        {
+            return None;
+        }
+        let merkle_proof = witness
+            .merkle_tree
+            .generate_multi_proof(indexes.clone())
+            .ok()?;
+        let leaves = indexes
+            .iter()
+            .map(|i| witness.merkle_leaves[i * leaf_size..(i + 1) * leaf_size].to_vec())
+            .collect();
+        Some(Samples {
+            merkle_proof,
+            leaves,
+        })
+    }
+
+    /// Proves that the committed codeword is close to the Reed-Solomon code. Consumes the
+    /// witness, so all samples must be opened before.
+    pub fn prove_proximity(
+        &self,
+        merlin: &mut Merlin,
+        witness: Witness<F, MerkleConfig>,
+    ) -> WhirResult<WhirProof<MerkleConfig, F>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        let statement = Statement::new(vec![], vec![]);
+        Prover(self.0.clone()).prove(merlin, statement, witness)
+    }
+}
+
+pub struct BlobVerifier<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    params: WhirConfig<F, MerkleConfig, PowStrategy>,
+    verifier: Verifier<F, MerkleConfig, PowStrategy>,
+}
+
+impl<F, MerkleConfig, PowStrategy> BlobVerifier<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    pub fn new(params: WhirConfig<F, MerkleConfig, PowStrategy>) -> Self {
+        BlobVerifier {
+            verifier: Verifier::new(params.clone()),
+            params,
+        }
+    }
+
+    /// The number of positions of the codeword that can be sampled, one per Merkle leaf.
+    pub fn num_positions(&self) -> usize {
+        self.params.starting_domain.size() / self.params.leaf_size()
+    }
+
+    /// Draws `num_samples` distinct positions, uniformly at random.
+    pub fn sample_indexes(&self, rng: &mut impl Rng, num_samples: usize) -> Vec<usize> {
+        let num_positions = self.num_positions();
+        assert!(num_samples <= num_positions);
+        let mut indexes = BTreeSet::new();
+        while indexes.len() < num_samples {
+            indexes.insert(rng.gen_range(0..num_positions));
+        }
+        indexes.into_iter().collect()
+    }
+
+    /// Checks that `samples` opens the codeword committed to at `indexes`.
+    pub fn verify_samples(
+        &self,
+        commitment: &BlobCommitment<MerkleConfig::InnerDigest>,
+        indexes: &[usize],
+        samples: &Samples<MerkleConfig, F>,
+    ) -> bool {
+        let leaf_size = self.params.leaf_size();
+        samples.merkle_proof.leaf_indexes == dedup(indexes.iter().copied())
+            && samples.leaves.len() == samples.merkle_proof.leaf_indexes.len()
+            && samples.leaves.iter().all(|leaf| leaf.len() == leaf_size)
+            && samples
+                .merkle_proof
+                .verify(
+                    &self.params.leaf_hash_params,
+                    &self.params.two_to_one_params,
+                    &commitment.root,
+                    samples.leaves.iter().map(|leaf| leaf.as_ref()),
+                )
+                .unwrap_or(false)
+    }
+
+    /// Checks the proximity proof, and that its transcript starts with the commitment.
+    pub fn verify_proximity(
+        &self,
+        arthur: &mut Arthur,
+        commitment: &BlobCommitment<MerkleConfig::InnerDigest>,
+        whir_proof: &WhirProof<MerkleConfig, F>,
+    ) -> ProofResult<()> {
+        let parsed_commitment = self.verifier.parse_commitment(arthur)?;
+        if parsed_commitment.root() != &commitment.root {
+            return Err(ProofError::InvalidProof);
+        }
+        let statement = Statement::new(vec![], vec![]);
+        self.verifier
+            .verify_with_commitment(arthur, &parsed_commitment, &statement, whir_proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimue_pow::blake3::Blake3PoW;
+
+    use ark_ff::Field;
+
+    use super::*;
+    use crate::{
+        crypto::{fields::Field64_2, merkle_tree::blake3 as merkle_tree},
+        parameters::MultivariateParameters,
+        whir::fixtures::{io_pattern, whir_parameters},
+    };
+
+    type F = Field64_2;
+    type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+
+    #[test]
+    fn test_blob() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params =
+            whir_parameters::<MerkleConfig, Blake3PoW>((leaf_hash_params, two_to_one_params));
+        let params = WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+            MultivariateParameters::new(6),
+            whir_params,
+        )
+        .unwrap();
+        let io = io_pattern(&params);
+
+        let prover = BlobProver::new(params.clone());
+        let verifier = BlobVerifier::new(params);
+        assert_eq!(prover.capacity(), 7 * 64);
+        let data: Vec<u8> = (0..400).map(|i| (i * 7) as u8).collect();
+
+        let mut merlin = io.to_merlin();
+        assert!(prover
+            .commit_blob(&mut merlin, &vec![0; prover.capacity() + 1])
+            .is_err());
+        let mut merlin = io.to_merlin();
+        let (commitment, witness) = prover.commit_blob(&mut merlin, &data).unwrap();
+
+        let indexes = verifier.sample_indexes(&mut rng, 5);
+        assert_eq!(indexes.len(), 5);
+        let samples = prover.open_samples(&witness, &indexes).unwrap();
+        assert!(verifier.verify_samples(&commitment, &indexes, &samples));
+        let mut tampered = samples.clone();
+        tampered.leaves[0][0] += F::ONE;
+        assert!(!verifier.verify_samples(&commitment, &indexes, &tampered));
+        assert!(prover
+            .open_samples(&witness, &[verifier.num_positions()])
+            .is_none());
+
+        let proof = prover.prove_proximity(&mut merlin, witness).unwrap();
+        let mut arthur = io.to_arthur(merlin.transcript());
+        assert!(verifier
+            .verify_proximity(&mut arthur, &commitment, &proof)
+            .is_ok());
+    }
+}
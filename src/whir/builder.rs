@@ -0,0 +1,250 @@
+//! Assembling a `Statement` from claims tagged by their origin in the frontend (a column, a
+//! gate), so that a claim which does not hold can be traced back to the tag that made it.
+//!
+//! A WHIR proof of a statement is rejected as a whole, without saying which claim is wrong.
+//! The builder instead checks each claim against the polynomial before proving, and reports the
+//! failing claims per tag.
+
+use ark_ff::Field;
+use ark_std::{collections::BTreeMap, vec, vec::Vec};
+
+use super::{ClaimType, LinearClaim, Statement};
+use crate::{
+    errors::{WhirError, WhirResult},
+    poly_utils::{coeffs::CoefficientList, evals::EvaluationsList, MultilinearPoint},
+};
+
+/// The claims of a tag, by index in the statement, and those that do not hold. Evaluation and
+/// rotated claims come first, then the linear claims, indexed after them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagReport {
+    pub claims: Vec<usize>,
+    pub failed: Vec<usize>,
+}
+
+impl TagReport {
+    pub fn passed(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+pub struct StatementBuilder<F, T> {
+    statement: Statement<F>,
+    // The tags of each point claim, several when identical claims were merged.
+    point_tags: Vec<Vec<T>>,
+    linear_tags: Vec<T>,
+    claims: BTreeMap<(bool, Vec<F>), usize>,
+}
+
+impl<F: Field, T: Clone + Ord> Default for StatementBuilder<F, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Field, T: Clone + Ord> StatementBuilder<F, T> {
+    pub fn new() -> Self {
+        StatementBuilder {
+            statement: Statement::new(vec![], vec![]),
+            point_tags: vec![],
+            linear_tags: vec![],
+            claims: BTreeMap::new(),
+        }
+    }
+
+    /// Claims that the polynomial evaluates to `evaluation` at `point`, see
+    /// `add_claim`.
+    pub fn add_evaluation(
+        &mut self,
+        tag: T,
+        point: MultilinearPoint<F>,
+        evaluation: F,
+    ) -> WhirResult<usize> {
+        self.add_claim(tag, ClaimType::Evaluation, point, evaluation)
+    }
+
+    /// Adds a claim of `claim_type` and returns its index in the statement. A claim identical to
+    /// an earlier one is merged into it, with both tags; one at the same point with another
+    /// evaluation is rejected, since they cannot both hold.
+    pub fn add_claim(
+        &mut self,
+        tag: T,
+        claim_type: ClaimType,
+        point: MultilinearPoint<F>,
+        evaluation: F,
+    ) -> WhirResult<usize> {
+        let key = (claim_type == ClaimType::Rotated, point.0);
+        if let Some(&index) = self.claims.get(&key) {
+            if self.statement.evaluations[index] != evaluation {
+                return Err(WhirError::ConflictingClaim(index));
+            }
+            if !self.point_tags[index].contains(&tag) {
+                self.point_tags[index].push(tag);
+            }
+            return Ok(index);
+        }
+        let index = self.statement.points.len();
+        self.statement.points.push(MultilinearPoint(key.1.clone()));
+        self.statement.evaluations.push(evaluation);
+        self.statement.claim_types.push(claim_type);
+        self.point_tags.push(vec![tag]);
+        self.claims.insert(key, index);
+        Ok(index)
+    }
+
+    /// Adds a linear claim, which is never merged, and returns its index.
+    pub fn add_linear(&mut self, tag: T, claim: LinearClaim<F>) -> usize {
+        self.statement.linear_claims.push(claim);
+        self.linear_tags.push(tag);
+        self.num_claims() - 1
+    }
+
+    pub fn num_claims(&self) -> usize {
+        self.statement.points.len() + self.statement.linear_claims.len()
+    }
+
+    /// The tags of the claim at `index`.
+    pub fn tags(&self, index: usize) -> &[T] {
+        match self.point_tags.get(index) {
+            Some(tags) => tags,
+            None => core::slice::from_ref(&self.linear_tags[index - self.point_tags.len()]),
+        }
+    }
+
+    pub fn statement(&self) -> &Statement<F> {
+        &self.statement
+    }
+
+    pub fn into_statement(self) -> Statement<F> {
+        self.statement
+    }
+
+    /// Checks each claim against `polynomial`, and reports the claims of each tag.
+    pub fn check(&self, polynomial: &CoefficientList<F::BasePrimeField>) -> BTreeMap<T, TagReport> {
+        let evals: Vec<F> = EvaluationsList::from(polynomial.clone())
+            .evals()
+            .iter()
+            .map(|&e| F::from_base_prime_field(e))
+            .collect();
+        let mut next_rows = evals.clone();
+        next_rows.rotate_left(1);
+        let (evals, next_rows) = (EvaluationsList::new(evals), EvaluationsList::new(next_rows));
+
+        let point_claims = self
+            .statement
+            .points
+            .iter()
+            .zip(&self.statement.claim_types)
+            .zip(&self.statement.evaluations)
+            .map(|((point, claim_type), &evaluation)| match claim_type {
+                ClaimType::Evaluation => evals.evaluate(point) == evaluation,
+                ClaimType::Rotated => next_rows.evaluate(point) == evaluation,
+            });
+        let linear_claims = self.statement.linear_claims.iter().map(|claim| {
+            claim.weights.num_variables() == evals.num_variables()
+                && claim
+                    .weights
+                    .evals()
+                    .iter()
+                    .zip(evals.evals())
+                    .map(|(&w, &e)| w * e)
+                    .sum::<F>()
+                    == claim.sum
+        });
+
+        let mut reports = BTreeMap::<T, TagReport>::new();
+        for (index, holds) in point_claims.chain(linear_claims).enumerate() {
+            for tag in self.tags(index) {
+                let report = reports.entry(tag.clone()).or_default();
+                report.claims.push(index);
+                if !holds {
+                    report.failed.push(index);
+                }
+            }
+        }
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::fields::{Field64, Field64_2};
+
+    type F = Field64_2;
+
+    #[test]
+    fn test_statement_builder() {
+        let num_variables = 4;
+        let mut rng = ark_std::test_rng();
+        let polynomial = CoefficientList::new(
+            (0..1 << num_variables)
+                .map(|i| Field64::from(i as u64))
+                .collect(),
+        );
+        let evaluate = |point: &MultilinearPoint<F>| polynomial.evaluate_at_extension(point);
+
+        let mut builder = StatementBuilder::new();
+        let (a, b) = (
+            MultilinearPoint::rand(&mut rng, num_variables),
+            MultilinearPoint::rand(&mut rng, num_variables),
+        );
+        assert_eq!(
+            builder
+                .add_evaluation("x", a.clone(), evaluate(&a))
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            builder
+                .add_claim("x", ClaimType::Rotated, a.clone(), F::ONE)
+                .unwrap(),
+            1
+        );
+        // The same claim from another column is merged
+        assert_eq!(
+            builder
+                .add_evaluation("y", a.clone(), evaluate(&a))
+                .unwrap(),
+            0
+        );
+        assert!(matches!(
+            builder.add_evaluation("y", a.clone(), evaluate(&a) + F::ONE),
+            Err(WhirError::ConflictingClaim(0))
+        ));
+        assert_eq!(
+            builder
+                .add_evaluation("y", b.clone(), evaluate(&b))
+                .unwrap(),
+            2
+        );
+        let weights = EvaluationsList::new(vec![F::ONE; 1 << num_variables]);
+        let sum = EvaluationsList::from(polynomial.clone())
+            .evals()
+            .iter()
+            .map(|&e| F::from_base_prime_field(e))
+            .sum();
+        assert_eq!(builder.add_linear("z", LinearClaim { weights, sum }), 3);
+
+        assert_eq!(builder.num_claims(), 4);
+        assert_eq!(builder.tags(0), ["x", "y"]);
+        assert_eq!(builder.tags(3), ["z"]);
+        assert!(builder
+            .statement()
+            .validate_num_variables(num_variables)
+            .is_ok());
+
+        let reports = builder.check(&polynomial);
+        assert_eq!(
+            reports["x"],
+            TagReport {
+                claims: vec![0, 1],
+                failed: vec![1]
+            }
+        );
+        assert!(reports["y"].passed());
+        assert_eq!(reports["y"].claims, [0, 2]);
+        assert_eq!(reports["z"].claims, [3]);
+        assert!(reports["z"].passed());
+    }
+}
@@ -0,0 +1,110 @@
+//! Cooperative cancellation of a proving job, see `Prover::prove_cancellable`.
+//!
+//! The prover checks the token at the start of each round, once the folded polynomial is
+//! committed, and before each proof of work, the steps that run for long on large instances. An
+//! aborted prover returns `WhirError::ProvingAborted` and drops its tables and codewords on the
+//! way out; the transcript is left halfway and must be discarded.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// A flag shared between the prover and the caller, raised by `cancel` or once the deadline
+/// passes. Clones share the flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// A token that is only cancelled by `cancel`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that is also cancelled once `deadline` passes.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        CancellationToken {
+            cancelled: Arc::default(),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// A token that is also cancelled once `timeout` has elapsed from now.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        errors::WhirError,
+        poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+        whir::{
+            committer::Committer,
+            fixtures::{config, io_pattern, F},
+            prover::Prover,
+            verifier::Verifier,
+            Statement,
+        },
+    };
+
+    #[test]
+    fn test_cancellation() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables);
+        let io = io_pattern(&params);
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+        let prove = |cancel: &CancellationToken| {
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit(&mut merlin, polynomial.clone())
+                .unwrap();
+            Prover(params.clone())
+                .prove_cancellable(&mut merlin, statement.clone(), witness, cancel)
+                .map(|proof| (merlin, proof))
+        };
+
+        let cancel = CancellationToken::new();
+        let (merlin, proof) = prove(&cancel).unwrap();
+        let mut arthur = io.to_arthur(merlin.transcript());
+        assert!(Verifier::new(params.clone())
+            .verify(&mut arthur, &statement, &proof)
+            .is_ok());
+
+        // Cancelling any clone cancels the token
+        cancel.clone().cancel();
+        assert!(cancel.is_cancelled());
+        assert!(matches!(
+            prove(&cancel),
+            Err(WhirError::ProvingAborted { round: 0 })
+        ));
+        let expired = CancellationToken::with_deadline(Instant::now());
+        assert!(matches!(
+            prove(&expired),
+            Err(WhirError::ProvingAborted { round: 0 })
+        ));
+        assert!(!CancellationToken::with_timeout(Duration::from_secs(3600)).is_cancelled());
+    }
+}
@@ -1,19 +1,29 @@
-use super::parameters::WhirConfig;
+use super::{parameters::WhirConfig, Statement};
 use crate::{
-    ntt::expand_from_coeff,
-    poly_utils::{coeffs::CoefficientList, fold::restructure_evaluations, MultilinearPoint},
+    errors::{WhirError, WhirResult},
+    parameters::FoldType,
+    poly_utils::{
+        coeffs::CoefficientList, fold::encode_folded_rows, virtual_poly::VirtualPolynomial,
+        MultilinearPoint,
+    },
     utils,
 };
 use ark_crypto_primitives::merkle_tree::{Config, MerkleTree};
-use ark_ff::FftField;
+use ark_ff::{FftField, Field};
 use ark_poly::EvaluationDomain;
+use ark_serialize::CanonicalSerialize;
+use ark_std::UniformRand;
 use nimue::{
     plugins::ark::{FieldChallenges, FieldWriter},
-    ByteWriter, Merlin, ProofResult,
+    ByteWriter, Merlin,
 };
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 pub struct Witness<F, MerkleConfig>
 where
@@ -24,6 +34,77 @@ where
     pub(crate) merkle_leaves: Vec<F>,
     pub(crate) ood_points: Vec<F>,
     pub(crate) ood_answers: Vec<F>,
+    // The claims of the OOD samples left to the statement, see
+    // `WhirConfig::with_statement_ood_samples`.
+    pub(crate) statement_ood: Statement<F>,
+}
+
+impl<F, MerkleConfig> Witness<F, MerkleConfig>
+where
+    MerkleConfig: Config,
+{
+    /// The evaluation claims at the points squeezed after the commitment for the OOD samples
+    /// left to the statement (see `WhirConfig::with_statement_ood_samples`), which the statement
+    /// of the proof must start with. Empty for other configurations.
+    pub fn statement_ood(&self) -> Statement<F>
+    where
+        F: Clone,
+    {
+        self.statement_ood.clone()
+    }
+
+    /// The length of the committed codeword, over the starting domain.
+    pub fn codeword_len(&self) -> usize {
+        self.merkle_leaves.len()
+    }
+
+    /// The rate of the code, `2^-log_inv_rate`. In ZK mode the committed polynomial has the
+    /// mask variable, see `WhirConfig::num_variables`.
+    pub fn log_inv_rate(&self) -> usize {
+        self.codeword_len().ilog2() as usize - self.polynomial.num_variables()
+    }
+
+    /// The number of leaves of the Merkle tree, each the evaluations over a coset folded
+    /// together.
+    pub fn num_leaves(&self) -> usize {
+        1 << self.merkle_depth()
+    }
+
+    /// The length of the authentication paths of the Merkle tree.
+    pub fn merkle_depth(&self) -> usize {
+        self.merkle_tree.height() - 1
+    }
+}
+
+// Wipes the polynomial and its encoding. The Merkle tree only holds hashes of the leaves, and
+// the OOD answers are sent in the clear.
+#[cfg(feature = "zeroize")]
+impl<F, MerkleConfig> Zeroize for Witness<F, MerkleConfig>
+where
+    F: Zeroize,
+    MerkleConfig: Config,
+{
+    fn zeroize(&mut self) {
+        self.polynomial.zeroize();
+        self.merkle_leaves.zeroize();
+    }
+}
+
+/// The randomness of the prover (the mask in ZK mode, the blinding of `whir::hiding`...) derived
+/// from a secret `seed` and the polynomial, so that proofs can be reproduced, e.g. to compare
+/// those of redundant provers.
+pub fn derive_rng<F: CanonicalSerialize>(
+    seed: &[u8; 32],
+    polynomial: &CoefficientList<F>,
+) -> ChaCha20Rng {
+    let mut hasher = blake3::Hasher::new_keyed(seed);
+    hasher.update(b"whir prover randomness");
+    for coeff in polynomial.coeffs() {
+        coeff
+            .serialize_compressed(&mut hasher)
+            .expect("writing to a hasher cannot fail");
+    }
+    ChaCha20Rng::from_seed(*hasher.finalize().as_bytes())
 }
 
 pub struct Committer<F, MerkleConfig, PowStrategy>(WhirConfig<F, MerkleConfig, PowStrategy>)
@@ -45,70 +126,301 @@ where
         &self,
         merlin: &mut Merlin,
         polynomial: CoefficientList<F::BasePrimeField>,
-    ) -> ProofResult<Witness<F, MerkleConfig>>
+    ) -> WhirResult<Witness<F, MerkleConfig>>
     where
         Merlin: FieldChallenges<F> + ByteWriter,
     {
-        let base_domain = self.0.starting_domain.base_domain.unwrap();
-        let expansion = base_domain.size() / polynomial.num_coeffs();
-        let evals = expand_from_coeff(polynomial.coeffs(), expansion);
-        // TODO: `stack_evaluations` and `restructure_evaluations` are really in-place algorithms.
-        // They also partially overlap and undo one another. We should merge them.
-        let folded_evals = utils::stack_evaluations(evals, self.0.folding_factor);
-        let folded_evals = restructure_evaluations(
+        if self.0.zk {
+            return Err(WhirError::InvalidParameters(
+                "the mask is sampled by `commit_with_rng`",
+            ));
+        }
+        self.commit_polynomial(merlin, polynomial)
+    }
+
+    /// Commits to `polynomial`, in ZK mode masked with a random polynomial drawn from `rng`.
+    pub fn commit_with_rng(
+        &self,
+        merlin: &mut Merlin,
+        polynomial: CoefficientList<F::BasePrimeField>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> WhirResult<Witness<F, MerkleConfig>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        if !self.0.zk {
+            return self.commit_polynomial(merlin, polynomial);
+        }
+        let polynomial = self.mask_polynomial(polynomial, rng)?;
+        self.commit_polynomial(merlin, polynomial)
+    }
+
+    /// Same as `commit_with_rng`, with the randomness derived from `seed` and the polynomial (see
+    /// `derive_rng`).
+    pub fn commit_with_seed(
+        &self,
+        merlin: &mut Merlin,
+        polynomial: CoefficientList<F::BasePrimeField>,
+        seed: &[u8; 32],
+    ) -> WhirResult<Witness<F, MerkleConfig>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        let mut rng = derive_rng(seed, &polynomial);
+        self.commit_with_rng(merlin, polynomial, &mut rng)
+    }
+
+    // The committed polynomial f(X) + X_0 * r(X) for a random r, in ZK mode.
+    pub(crate) fn mask_polynomial(
+        &self,
+        polynomial: CoefficientList<F::BasePrimeField>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> WhirResult<CoefficientList<F::BasePrimeField>> {
+        if polynomial.num_variables() != self.0.num_variables() {
+            return Err(WhirError::NumVariables {
+                expected: self.0.num_variables(),
+                found: polynomial.num_variables(),
+            });
+        }
+        // X_0 is the most significant variable, so the coefficients of f(X) + X_0 * r(X) are
+        // those of f followed by those of r
+        let mut coeffs = polynomial.coeffs().to_vec();
+        coeffs.extend((0..polynomial.num_coeffs()).map(|_| F::BasePrimeField::rand(rng)));
+        Ok(CoefficientList::new(coeffs))
+    }
+
+    fn commit_polynomial(
+        &self,
+        merlin: &mut Merlin,
+        polynomial: CoefficientList<F::BasePrimeField>,
+    ) -> WhirResult<Witness<F, MerkleConfig>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        if polynomial.num_variables() != self.0.mv_parameters.num_variables {
+            return Err(WhirError::NumVariables {
+                expected: self.0.mv_parameters.num_variables,
+                found: polynomial.num_variables(),
+            });
+        }
+        let (merkle_tree, folded_evals) = self.encode(&polynomial);
+        self.absorb_commitment(
+            merlin,
+            merkle_tree,
             folded_evals,
-            self.0.fold_optimisation,
-            base_domain.group_gen(),
-            base_domain.group_gen_inv(),
-            self.0.folding_factor,
-        );
+            |_, point| polynomial.evaluate_at_extension(point),
+            polynomial.to_extension(),
+        )
+    }
 
-        // Convert to extension field.
-        // This is not necessary for the commit, but in further rounds
-        // we will need the extension field. For symplicity we do it here too.
-        // TODO: Commit to base field directly.
-        let folded_evals = folded_evals
-            .into_iter()
-            .map(F::from_base_prime_field)
-            .collect::<Vec<_>>();
+    /// Same as `commit`, for a configuration leaving OOD samples of the commitment to the
+    /// statement (see `WhirConfig::with_statement_ood_samples`). Also returns the claims at the
+    /// points squeezed after the commitment, as `Witness::statement_ood`: the statement of the
+    /// proof must start with them, and the outer protocol extends it with its own claims.
+    pub fn commit_with_statement_ood(
+        &self,
+        merlin: &mut Merlin,
+        polynomial: CoefficientList<F::BasePrimeField>,
+    ) -> WhirResult<(Witness<F, MerkleConfig>, Statement<F>)>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        let witness = self.commit(merlin, polynomial)?;
+        let statement = witness.statement_ood();
+        Ok((witness, statement))
+    }
 
-        // Group folds together as a leaf.
-        let fold_size = 1 << self.0.folding_factor;
-        #[cfg(not(feature = "parallel"))]
-        let leafs_iter = folded_evals.chunks_exact(fold_size);
-        #[cfg(feature = "parallel")]
-        let leafs_iter = folded_evals.par_chunks_exact(fold_size);
+    /// Commits to the polynomial given by `polynomial` on the hypercube, e.g. the product of
+    /// committed columns. With `FoldType::ProverHelps` each polynomial `h_r` of the encoding is
+    /// computed from the closure in turn and written straight into the leaves, so neither the
+    /// coefficients over the base field nor the unstacked evaluations are materialized. The
+    /// witness still holds the coefficients over the extension field and the codeword, which the
+    /// rounds of the prover read: the memory saved is that of the encoding, not of the witness.
+    /// With `FoldType::Naive` the coefficients are materialized and committed as with `commit`.
+    pub fn commit_virtual(
+        &self,
+        merlin: &mut Merlin,
+        polynomial: &VirtualPolynomial<'_, F::BasePrimeField>,
+    ) -> WhirResult<Witness<F, MerkleConfig>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        if self.0.zk {
+            return Err(WhirError::InvalidParameters(
+                "virtual polynomials cannot be masked",
+            ));
+        }
+        if polynomial.num_variables() != self.0.mv_parameters.num_variables {
+            return Err(WhirError::NumVariables {
+                expected: self.0.mv_parameters.num_variables,
+                found: polynomial.num_variables(),
+            });
+        }
+        if matches!(self.0.fold_optimisation, FoldType::Naive) {
+            return self.commit_polynomial(merlin, polynomial.to_coefficients());
+        }
 
-        let merkle_tree = MerkleTree::<MerkleConfig>::new(
-            &self.0.leaf_hash_params,
-            &self.0.two_to_one_params,
-            leafs_iter,
+        let base_domain = self.0.starting_domain.base_domain.unwrap();
+        let expansion = base_domain.size() / polynomial.num_coeffs();
+        let folding_size = 1 << self.0.folding_factor;
+        let folded_offset = base_domain.coset_offset().pow([folding_size as u64]);
+        // The rows of `encode_folded_rows`, one `h_r` at a time, each stored at position `r` of
+        // the leaves as `utils::stack_evaluations_into` lays them out
+        let mut coeffs = vec![F::ZERO; polynomial.num_coeffs()];
+        let mut folded_evals = vec![F::ZERO; base_domain.size()];
+        phase!(encode, {
+            for r in 0..folding_size {
+                let row = polynomial.coefficient_row(r, self.0.folding_factor);
+                for (j, coeff) in row.iter().enumerate() {
+                    coeffs[r + j * folding_size] = F::from_base_prime_field(*coeff);
+                }
+                let evals = self
+                    .0
+                    .base_fft
+                    .expand_from_coeff(&utils::coset_shift(&row, folded_offset), expansion);
+                for (i, eval) in evals.into_iter().enumerate() {
+                    folded_evals[i * folding_size + r] = F::from_base_prime_field(eval);
+                }
+            }
+        });
+        let merkle_tree = self.merkle_tree(&folded_evals);
+
+        self.absorb_commitment(
+            merlin,
+            merkle_tree,
+            folded_evals,
+            CoefficientList::evaluate,
+            CoefficientList::new(coeffs),
         )
-        .unwrap();
+    }
 
+    // Absorbs the root and the OOD answers, with `evaluate` evaluating the committed polynomial,
+    // given with its coefficients over the extension field.
+    fn absorb_commitment(
+        &self,
+        merlin: &mut Merlin,
+        merkle_tree: MerkleTree<MerkleConfig>,
+        folded_evals: Vec<F>,
+        evaluate: impl Fn(&CoefficientList<F>, &MultilinearPoint<F>) -> F,
+        polynomial: CoefficientList<F>,
+    ) -> WhirResult<Witness<F, MerkleConfig>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
         let root = merkle_tree.root();
 
         merlin.add_bytes(root.as_ref())?;
+        event!(
+            root = %crate::utils::Hex(root.as_ref()),
+            num_variables = polynomial.num_variables(),
+            "committed"
+        );
 
         let mut ood_points = vec![F::ZERO; self.0.committment_ood_samples];
         let mut ood_answers = Vec::with_capacity(self.0.committment_ood_samples);
         if self.0.committment_ood_samples > 0 {
             merlin.fill_challenge_scalars(&mut ood_points)?;
             ood_answers.extend(ood_points.iter().map(|ood_point| {
-                polynomial.evaluate_at_extension(&MultilinearPoint::expand_from_univariate(
-                    *ood_point,
-                    self.0.mv_parameters.num_variables,
-                ))
+                evaluate(
+                    &polynomial,
+                    &MultilinearPoint::expand_from_univariate(
+                        *ood_point,
+                        self.0.mv_parameters.num_variables,
+                    ),
+                )
             }));
             merlin.add_scalars(&ood_answers)?;
         }
 
+        // The points of the OOD samples left to the statement are random points of the
+        // hypercube's extension rather than expansions of univariate ones
+        let num_variables = self.0.mv_parameters.num_variables;
+        let mut statement_ood = Statement::new(vec![], vec![]);
+        if self.0.statement_ood_samples > 0 {
+            let mut coords = vec![F::ZERO; self.0.statement_ood_samples * num_variables];
+            merlin.fill_challenge_scalars(&mut coords)?;
+            let points: Vec<_> = coords
+                .chunks_exact(num_variables)
+                .map(|coords| MultilinearPoint(coords.to_vec()))
+                .collect();
+            let answers: Vec<_> = points
+                .iter()
+                .map(|point| evaluate(&polynomial, point))
+                .collect();
+            merlin.add_scalars(&answers)?;
+            statement_ood = Statement::new(points, answers);
+        }
+
         Ok(Witness {
-            polynomial: polynomial.to_extension(),
+            polynomial,
             merkle_tree,
             merkle_leaves: folded_evals,
             ood_points,
             ood_answers,
+            statement_ood,
         })
     }
+
+    // Encodes `polynomial` over the starting domain, with the evaluations over a coset in each
+    // leaf. Returns the tree and the leaves.
+    pub(crate) fn encode(
+        &self,
+        polynomial: &CoefficientList<F::BasePrimeField>,
+    ) -> (MerkleTree<MerkleConfig>, Vec<F>) {
+        let base_domain = self.0.starting_domain.base_domain.unwrap();
+        let expansion = base_domain.size() / polynomial.num_coeffs();
+        // The evaluations before stacking, one row per position in the leaves
+        let evals = phase!(
+            encode,
+            match self.0.fold_optimisation {
+                FoldType::Naive => {
+                    let coeffs =
+                        utils::coset_shift(polynomial.coeffs(), base_domain.coset_offset());
+                    self.0.base_fft.expand_from_coeff(&coeffs, expansion)
+                }
+                FoldType::ProverHelps => encode_folded_rows(
+                    &*self.0.base_fft,
+                    polynomial.coeffs(),
+                    expansion,
+                    base_domain.coset_offset(),
+                    self.0.folding_factor,
+                ),
+            }
+        );
+        self.commit_rows(evals)
+    }
+
+    // Stacks the evaluations, one row per position in the leaves, and builds the tree.
+    fn commit_rows(&self, evals: Vec<F::BasePrimeField>) -> (MerkleTree<MerkleConfig>, Vec<F>) {
+        // Stacked straight into the leaves, converted to the extension field on the way, so that
+        // the transpose needs no codeword-sized scratch buffer.
+        // The conversion is not necessary for the commit, but in further rounds
+        // we will need the extension field. For symplicity we do it here too.
+        // TODO: Commit to base field directly.
+        let folded_evals = phase!(
+            stack,
+            utils::stack_evaluations_into(&evals, self.0.folding_factor, F::from_base_prime_field)
+        );
+        drop(evals);
+        (self.merkle_tree(&folded_evals), folded_evals)
+    }
+
+    // The tree of the stacked evaluations.
+    fn merkle_tree(&self, folded_evals: &[F]) -> MerkleTree<MerkleConfig> {
+        // Group folds together as a leaf.
+        #[cfg(not(feature = "parallel"))]
+        let leafs_iter = folded_evals.chunks_exact(self.0.leaf_size());
+        #[cfg(feature = "parallel")]
+        let leafs_iter = folded_evals.par_chunks_exact(self.0.leaf_size());
+
+        phase!(
+            merkle_tree,
+            MerkleTree::<MerkleConfig>::new(
+                &self.0.leaf_hash_params,
+                &self.0.two_to_one_params,
+                leafs_iter,
+            )
+            .unwrap()
+        )
+    }
 }
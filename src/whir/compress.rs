@@ -0,0 +1,318 @@
+//! A denser layout of `WhirProof`, flagged by `COMPRESSED_PROOF_FORMAT_VERSION` in place of
+//! `PROOF_FORMAT_VERSION`, for the same proof: nothing changes in the protocol.
+//!
+//! Against the layout of `PROOF_FORMAT_VERSION`, for each round:
+//! - lengths are LEB128 varints instead of 8 bytes, and the leaf length is written once;
+//! - the leaf indexes, which are sorted and distinct, are written as the first index then the
+//!   gaps between consecutive ones;
+//! - the hash of the sibling of a leaf is dropped when the sibling is opened too, since the
+//!   verifier recomputes it from that leaf.
+//!
+//! The authentication paths keep the prefix sharing of `MultiPath`.
+
+use ark_crypto_primitives::{
+    crh::CRHScheme,
+    merkle_tree::{Config, MultiPath},
+};
+use ark_ff::FftField;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Validate, Write,
+};
+use ark_std::{vec, vec::Vec};
+
+use super::{hints::ProofHints, opening_bounds, parameters::WhirConfig, OpeningBound, WhirProof};
+
+/// Version of the layout written by `WhirProof::compress`, which it starts with.
+pub const COMPRESSED_PROOF_FORMAT_VERSION: u32 = 3;
+
+fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> Result<(), SerializationError> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return Ok(writer.write_all(&[byte])?);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+// Reads a varint, rejecting it if it exceeds `max` or is not in its shortest encoding.
+fn read_varint<R: Read>(reader: &mut R, max: usize) -> Result<usize, SerializationError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8];
+        reader.read_exact(&mut byte)?;
+        let bits = u64::from(byte[0] & 0x7f);
+        if shift == 63 && bits > 1 {
+            break;
+        }
+        value |= bits << shift;
+        if byte[0] & 0x80 == 0 {
+            if byte[0] == 0 && shift > 0 {
+                break;
+            }
+            return usize::try_from(value)
+                .ok()
+                .filter(|&value| value <= max)
+                .ok_or(SerializationError::InvalidData);
+        }
+    }
+    Err(SerializationError::InvalidData)
+}
+
+fn read_element<T: CanonicalDeserialize, R: Read>(reader: &mut R) -> Result<T, SerializationError> {
+    T::deserialize_with_mode(reader, Compress::Yes, Validate::Yes)
+}
+
+// Whether the sibling of the leaf at `i` of the sorted `indexes` is opened as well.
+fn sibling_opened(indexes: &[usize], i: usize) -> bool {
+    let sibling = indexes[i] ^ 1;
+    (i > 0 && indexes[i - 1] == sibling) || indexes.get(i + 1) == Some(&sibling)
+}
+
+impl<MerkleConfig, F> WhirProof<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    /// Serializes the proof in the layout of `COMPRESSED_PROOF_FORMAT_VERSION`, without its hints
+    /// (see `whir::hints`). Fails on a proof whose openings are inconsistent, e.g. with unsorted
+    /// indexes.
+    pub fn compress(&self) -> Result<Vec<u8>, SerializationError> {
+        let mut bytes = Vec::new();
+        COMPRESSED_PROOF_FORMAT_VERSION.serialize_compressed(&mut bytes)?;
+        write_varint(&mut bytes, self.0.len() as u64)?;
+        for (merkle_proof, answers) in &self.0 {
+            let indexes = &merkle_proof.leaf_indexes;
+            let num_openings = answers.len();
+            let leaf_len = answers.first().map_or(0, Vec::len);
+            if indexes.len() != num_openings
+                || merkle_proof.leaf_siblings_hashes.len() != num_openings
+                || merkle_proof.auth_paths_prefix_lenghts.len() != num_openings
+                || merkle_proof.auth_paths_suffixes.len() != num_openings
+                || indexes.windows(2).any(|pair| pair[0] >= pair[1])
+                || answers.iter().any(|answer| answer.len() != leaf_len)
+            {
+                return Err(SerializationError::InvalidData);
+            }
+            write_varint(&mut bytes, num_openings as u64)?;
+            write_varint(&mut bytes, leaf_len as u64)?;
+            let mut next = 0;
+            for &index in indexes {
+                write_varint(&mut bytes, (index - next) as u64)?;
+                next = index + 1;
+            }
+            for i in 0..num_openings {
+                if !sibling_opened(indexes, i) {
+                    merkle_proof.leaf_siblings_hashes[i].serialize_compressed(&mut bytes)?;
+                }
+                write_varint(&mut bytes, merkle_proof.auth_paths_prefix_lenghts[i] as u64)?;
+                let suffix = &merkle_proof.auth_paths_suffixes[i];
+                write_varint(&mut bytes, suffix.len() as u64)?;
+                for digest in suffix {
+                    digest.serialize_compressed(&mut bytes)?;
+                }
+                for element in &answers[i] {
+                    element.serialize_compressed(&mut bytes)?;
+                }
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+impl<MerkleConfig, F> WhirProof<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: FftField,
+{
+    /// Inverse of `compress`, with every length bounded by `config` as in
+    /// `deserialize_for_config`, which reads both layouts.
+    pub fn decompress<PowStrategy>(
+        mut bytes: &[u8],
+        config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    ) -> Result<Self, SerializationError> {
+        if u32::deserialize_compressed(&mut bytes)? != COMPRESSED_PROOF_FORMAT_VERSION {
+            return Err(SerializationError::InvalidData);
+        }
+        let proof = Self::read_compressed(&mut bytes, config)?;
+        if !bytes.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(proof)
+    }
+
+    // Reads the layout of `COMPRESSED_PROOF_FORMAT_VERSION` after its version.
+    pub(super) fn read_compressed<PowStrategy, R: Read>(
+        mut reader: R,
+        config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    ) -> Result<Self, SerializationError> {
+        let bounds = opening_bounds(config);
+        if read_varint(&mut reader, bounds.len())? != bounds.len() {
+            return Err(SerializationError::InvalidData);
+        }
+        let openings = bounds
+            .iter()
+            .enumerate()
+            .map(|(tree, bound)| Self::read_compressed_opening(&mut reader, config, tree, bound))
+            .collect::<Result<_, _>>()?;
+        Ok(WhirProof(openings, ProofHints::default()))
+    }
+
+    fn read_compressed_opening<PowStrategy, R: Read>(
+        mut reader: R,
+        config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+        tree: usize,
+        bound: &OpeningBound,
+    ) -> Result<(MultiPath<MerkleConfig>, Vec<Vec<F>>), SerializationError> {
+        let num_openings = read_varint(&mut reader, bound.num_queries)?;
+        let leaf_len = read_varint(&mut reader, bound.leaf_len)?;
+        let mut indexes = Vec::with_capacity(num_openings);
+        let mut next = 0usize;
+        for _ in 0..num_openings {
+            let index = next
+                .checked_add(read_varint(&mut reader, bound.num_leaves)?)
+                .filter(|&index| index < bound.num_leaves)
+                .ok_or(SerializationError::InvalidData)?;
+            indexes.push(index);
+            next = index + 1;
+        }
+
+        let max_path_len = bound.max_path_len();
+        let mut siblings = Vec::with_capacity(num_openings);
+        let mut merkle_proof = MultiPath::<MerkleConfig> {
+            leaf_siblings_hashes: vec![],
+            auth_paths_prefix_lenghts: Vec::with_capacity(num_openings),
+            auth_paths_suffixes: Vec::with_capacity(num_openings),
+            leaf_indexes: vec![],
+        };
+        let mut answers = Vec::with_capacity(num_openings);
+        for i in 0..num_openings {
+            siblings.push(if sibling_opened(&indexes, i) {
+                None
+            } else {
+                Some(read_element(&mut reader)?)
+            });
+            merkle_proof
+                .auth_paths_prefix_lenghts
+                .push(read_varint(&mut reader, max_path_len)?);
+            let suffix_len = read_varint(&mut reader, max_path_len)?;
+            merkle_proof.auth_paths_suffixes.push(
+                (0..suffix_len)
+                    .map(|_| read_element(&mut reader))
+                    .collect::<Result<_, _>>()?,
+            );
+            answers.push(
+                (0..leaf_len)
+                    .map(|_| read_element(&mut reader))
+                    .collect::<Result<Vec<F>, _>>()?,
+            );
+        }
+
+        // The dropped sibling hashes are those of opened leaves, next to them in the order.
+        for (i, sibling) in siblings.into_iter().enumerate() {
+            let digest = match sibling {
+                Some(digest) => digest,
+                None => {
+                    let j = if i > 0 && indexes[i - 1] == indexes[i] ^ 1 {
+                        i - 1
+                    } else {
+                        i + 1
+                    };
+                    MerkleConfig::LeafHash::evaluate(config.merkle_params(tree).0, &answers[j][..])
+                        .map_err(|_| SerializationError::InvalidData)?
+                }
+            };
+            merkle_proof.leaf_siblings_hashes.push(digest);
+        }
+        merkle_proof.leaf_indexes = indexes;
+
+        if !bound.check(&merkle_proof, &answers) {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok((merkle_proof, answers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimue_pow::blake3::Blake3PoW;
+
+    use super::*;
+    use crate::{
+        crypto::merkle_tree::blake3 as merkle_tree,
+        parameters::MultivariateParameters,
+        poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+        whir::{
+            committer::Committer,
+            fixtures::{io_pattern, whir_parameters, MerkleConfig, F},
+            prover::Prover,
+            verifier::Verifier,
+            Statement,
+        },
+    };
+
+    #[test]
+    fn test_varint() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut bytes = Vec::new();
+            write_varint(&mut bytes, value).unwrap();
+            assert_eq!(
+                read_varint(&mut &bytes[..], usize::MAX).unwrap() as u64,
+                value
+            );
+            assert!(value == 0 || read_varint(&mut &bytes[..], value as usize - 1).is_err());
+        }
+        // Not the shortest encoding of 1.
+        assert!(read_varint(&mut &[0x81, 0x00][..], usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_compress() {
+        let num_variables = 8;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params =
+            whir_parameters::<MerkleConfig, Blake3PoW>((leaf_hash_params, two_to_one_params));
+        let params = WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+        let io = io_pattern(&params);
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial)
+            .unwrap();
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+
+        let compressed = proof.compress().unwrap();
+        assert!(compressed.len() < proof.compressed_size());
+        let decompressed = WhirProof::decompress(&compressed, &params).unwrap();
+        let mut plain = Vec::new();
+        proof.serialize_compressed(&mut plain).unwrap();
+        let mut roundtrip = Vec::new();
+        decompressed.serialize_compressed(&mut roundtrip).unwrap();
+        assert_eq!(plain, roundtrip);
+
+        // Both layouts are read by `deserialize_for_config`, hence by the verifier
+        for bytes in [&plain, &compressed] {
+            let proof = WhirProof::deserialize_for_config(&bytes[..], &params).unwrap();
+            let mut arthur = io.to_arthur(merlin.transcript());
+            assert!(Verifier::new(params.clone())
+                .verify(&mut arthur, &statement, &proof)
+                .is_ok());
+        }
+
+        assert!(WhirProof::decompress(&compressed[..compressed.len() - 1], &params).is_err());
+        let mut trailing = compressed.clone();
+        trailing.push(0);
+        assert!(WhirProof::decompress(&trailing, &params).is_err());
+        assert!(WhirProof::decompress(&plain, &params).is_err());
+    }
+}
@@ -0,0 +1,924 @@
+//! Distributed proving, with the tables of the prover sharded across machines.
+//!
+//! With `2^log_num_shards` workers, worker `j` holds the part of every table whose index has
+//! its top `log_num_shards` bits equal to `j`, i.e. the restriction of the polynomial to
+//! `X_0, ..., X_{log_num_shards - 1}` fixed to the bits of `j`. The sumcheck binds, and
+//! `CoefficientList::fold` folds, the last variables, so both are local to the workers, which
+//! only send their partial sums to the coordinator. A worker supports as many sumcheck rounds as
+//! it has local variables.
+//!
+//! The codeword is instead cut into contiguous ranges of leaves, encoded with a distributed NTT
+//! in two exchanges. Write the polynomial as `f(X) = sum_u X^u g_u(X^(2^k))` for the folding
+//! factor `k`, so that leaf `r` holds the NTT of size `2^k` of `w^(u r) g_u(w^(2^k r))` over `u`,
+//! and the coefficients of each `g_u` by their index modulo the number of shards. Each worker
+//! first sends every worker the coefficients of its shard in the class of the receiver
+//! (`PartialCoefficients`). Each worker then runs the NTTs of its class, of size the number of
+//! leaves of a worker, and broadcasts them (`PartialLeaves`). Each worker combines these into its
+//! own range of leaves, and hashes them into a sub-tree, whose root the coordinator merges with
+//! the others into the Merkle root. Openings are answered by the worker holding the leaf, and
+//! extended by the coordinator into paths to the root.
+//!
+//! The coordinator drives the transcript and broadcasts the challenges. All the messages are
+//! (de)serializable, so that workers can run on other machines, e.g. behind a gRPC service, and
+//! those received are checked, failing with `WhirError::InvalidMessage`.
+
+use ark_crypto_primitives::{
+    crh::TwoToOneCRHScheme,
+    merkle_tree::{Config, LeafParam, MerkleTree, Path, TwoToOneParam},
+};
+use ark_ff::FftField;
+use ark_poly::EvaluationDomain;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::{
+    domain::Domain,
+    errors::{WhirError, WhirResult},
+    ntt::{expand_from_coeff, ntt_batch},
+    parameters::FoldType,
+    poly_utils::{
+        coeffs::CoefficientList, eq_poly, evals::EvaluationsList, fold::restructure_evaluations,
+        hypercube::BinaryHypercubePoint, MultilinearPoint,
+    },
+    sumcheck::{proof::SumcheckPolynomial, prover_single::SumcheckSingle},
+    utils,
+};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Worker to worker: the evaluations over its local hypercube of the shard of the coefficients
+/// of `from_shard`, see `Worker::partial_table`.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PartialTable<F: CanonicalSerialize + CanonicalDeserialize> {
+    pub from_shard: usize,
+    pub evaluations: Vec<F>,
+}
+
+/// Coordinator to workers: new equality claims, combined with the given randomness.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EqualityClaims<F: CanonicalSerialize + CanonicalDeserialize> {
+    pub points: Vec<Vec<F>>,
+    pub combination_randomness: Vec<F>,
+}
+
+/// Worker to coordinator: the contribution of a shard to the constant and quadratic
+/// coefficients of the sumcheck polynomial of the round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SumcheckPartial<F: CanonicalSerialize + CanonicalDeserialize> {
+    pub c0: F,
+    pub c2: F,
+}
+
+/// Coordinator to workers: the randomness binding the last variable of the sumcheck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct FoldingChallenge<F: CanonicalSerialize + CanonicalDeserialize> {
+    pub folding_randomness: F,
+}
+
+/// Worker to coordinator: the contribution of a shard to evaluations of the polynomial.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PartialEvaluations<F: CanonicalSerialize + CanonicalDeserialize> {
+    pub values: Vec<F>,
+}
+
+/// Worker to worker: the coefficients of the shard of `from_shard` in the class of the receiver,
+/// see `Worker::partial_coefficients`.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PartialCoefficients<F: CanonicalSerialize + CanonicalDeserialize> {
+    pub from_shard: usize,
+    pub coefficients: Vec<F>,
+}
+
+/// Worker to all workers: the NTTs of the class of `from_shard`, from which each worker combines
+/// its range of leaves, see `Worker::partial_leaves`.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PartialLeaves<F: CanonicalSerialize + CanonicalDeserialize> {
+    pub from_shard: usize,
+    pub leaves: Vec<F>,
+}
+
+/// Worker to coordinator: the root of the sub-tree over the range of leaves of `shard`.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SubtreeRoot<D: CanonicalSerialize + CanonicalDeserialize> {
+    pub shard: usize,
+    pub root: D,
+}
+
+/// The opening of a leaf. As sent by the worker, the authentication path stops at the root of
+/// its sub-tree; `Coordinator::complete_opening` extends it to the Merkle root.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LeafOpening<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    pub leaf_index: usize,
+    pub leaf: Vec<F>,
+    pub leaf_sibling_hash: MerkleConfig::LeafDigest,
+    pub auth_path: Vec<MerkleConfig::InnerDigest>,
+}
+
+impl<MerkleConfig, F> LeafOpening<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    pub fn path(&self) -> Path<MerkleConfig> {
+        Path {
+            leaf_sibling_hash: self.leaf_sibling_hash.clone(),
+            auth_path: self.auth_path.clone(),
+            leaf_index: self.leaf_index,
+        }
+    }
+}
+
+pub struct Worker<F, MerkleConfig>
+where
+    MerkleConfig: Config,
+{
+    shard: usize,
+    log_num_shards: usize,
+    coefficients: CoefficientList<F>,
+    evaluation_of_p: EvaluationsList<F>,
+    evaluation_of_equality: EvaluationsList<F>,
+    // The coefficients of the `g_u` in the class of the shard, see `receive_coefficients`.
+    class_coefficients: Vec<F>,
+    leaves: Vec<F>,
+    merkle_tree: Option<MerkleTree<MerkleConfig>>,
+}
+
+impl<F, MerkleConfig> Worker<F, MerkleConfig>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+{
+    /// The worker of `shard`, holding the coefficients whose index has `shard` as top bits.
+    pub fn new(shard: usize, log_num_shards: usize, coefficients: CoefficientList<F>) -> Self {
+        assert!(shard < 1 << log_num_shards);
+        let num_coeffs = coefficients.num_coeffs();
+        Worker {
+            shard,
+            log_num_shards,
+            coefficients,
+            evaluation_of_p: EvaluationsList::new(vec![F::ZERO; num_coeffs]),
+            evaluation_of_equality: EvaluationsList::new(vec![F::ZERO; num_coeffs]),
+            class_coefficients: Vec::new(),
+            leaves: Vec::new(),
+            merkle_tree: None,
+        }
+    }
+
+    pub fn coefficients(&self) -> &CoefficientList<F> {
+        &self.coefficients
+    }
+
+    fn num_variables(&self) -> usize {
+        self.log_num_shards + self.coefficients.num_variables()
+    }
+
+    fn num_shards(&self) -> usize {
+        1 << self.log_num_shards
+    }
+
+    // The number of leaves (blocks of `2^folding_factor` coefficients) of a shard, and of leaves
+    // of the codeword over `domain` of each worker.
+    fn encoding_shape(
+        &self,
+        domain: &Domain<F>,
+        folding_factor: usize,
+    ) -> WhirResult<(usize, usize)> {
+        let fold_size = 1 << folding_factor;
+        let shard_size = self.coefficients.num_coeffs();
+        if shard_size % fold_size != 0 {
+            return Err(WhirError::InvalidParameters(
+                "a shard holds fewer coefficients than a leaf",
+            ));
+        }
+        if domain.size() % (shard_size << self.log_num_shards) != 0 {
+            return Err(WhirError::InvalidParameters(
+                "the domain is no multiple of the polynomial",
+            ));
+        }
+        let leaves_per_worker = domain.size() / (fold_size << self.log_num_shards);
+        if leaves_per_worker < 2 {
+            return Err(WhirError::InvalidParameters(
+                "each worker needs at least two leaves of the codeword",
+            ));
+        }
+        Ok((shard_size / fold_size, leaves_per_worker))
+    }
+
+    /// The shards to send `partial_table` to. On the hypercube `X^j` for `j` the top bits of the
+    /// index is one if and only if the point contains the bits of `j`, so the evaluations of
+    /// shard `s` are the sum of the partial tables of the shards whose bits are in `s`.
+    pub fn table_destinations(&self) -> Vec<usize> {
+        (0..1 << self.log_num_shards)
+            .filter(|destination| destination & self.shard == self.shard)
+            .collect()
+    }
+
+    pub fn partial_table(&self) -> PartialTable<F> {
+        PartialTable {
+            from_shard: self.shard,
+            evaluations: EvaluationsList::from(self.coefficients.clone())
+                .evals()
+                .to_vec(),
+        }
+    }
+
+    /// Sets up the evaluations of the polynomial from the partial tables of all the shards
+    /// contained in this one, including itself.
+    pub fn receive_tables(&mut self, tables: &[PartialTable<F>]) -> WhirResult<()> {
+        let num_evals = self.evaluation_of_p.num_evals();
+        let mut received = vec![false; self.num_shards()];
+        for table in tables {
+            if table.from_shard & self.shard != table.from_shard {
+                return Err(WhirError::InvalidMessage(
+                    "a table of a shard not contained in this one",
+                ));
+            }
+            if table.evaluations.len() != num_evals {
+                return Err(WhirError::InvalidMessage("a table of the wrong size"));
+            }
+            if std::mem::replace(&mut received[table.from_shard], true) {
+                return Err(WhirError::InvalidMessage("two tables of the same shard"));
+            }
+        }
+        if tables.len() != 1 << self.shard.count_ones() {
+            return Err(WhirError::InvalidMessage("a table is missing"));
+        }
+
+        let evals = self.evaluation_of_p.evals_mut();
+        evals.iter_mut().for_each(|eval| *eval = F::ZERO);
+        for table in tables {
+            for (eval, partial) in evals.iter_mut().zip(&table.evaluations) {
+                *eval += partial;
+            }
+        }
+        Ok(())
+    }
+
+    /// As `SumcheckSingle::add_new_equality`, on the shard: eq(z, (j, b)) factors as
+    /// eq(z_top, j) * eq(z_low, b).
+    pub fn add_equality(&mut self, claims: &EqualityClaims<F>) -> WhirResult<()> {
+        if claims.points.len() != claims.combination_randomness.len() {
+            return Err(WhirError::InvalidMessage(
+                "a claim without combination randomness",
+            ));
+        }
+        for point in &claims.points {
+            if point.len() != self.num_variables() {
+                return Err(WhirError::NumVariables {
+                    expected: self.num_variables(),
+                    found: point.len(),
+                });
+            }
+        }
+        for (point, rand) in claims.points.iter().zip(&claims.combination_randomness) {
+            let (top, low) = point.split_at(self.log_num_shards);
+            let scalar = *rand
+                * eq_poly(
+                    &MultilinearPoint(top.to_vec()),
+                    BinaryHypercubePoint(self.shard),
+                );
+            SumcheckSingle::eval_eq(low, self.evaluation_of_equality.evals_mut(), scalar);
+        }
+        Ok(())
+    }
+
+    /// The contribution of the shard to `SumcheckSingle::compute_sumcheck_polynomial`.
+    pub fn sumcheck_partial(&self) -> SumcheckPartial<F> {
+        assert!(self.evaluation_of_p.num_evals() >= 2);
+        let (c0, c2) = self
+            .evaluation_of_p
+            .evals()
+            .chunks_exact(2)
+            .zip(self.evaluation_of_equality.evals().chunks_exact(2))
+            .map(|(p_at, eq_at)| {
+                (
+                    p_at[0] * eq_at[0],
+                    (p_at[1] - p_at[0]) * (eq_at[1] - eq_at[0]),
+                )
+            })
+            .fold((F::ZERO, F::ZERO), |(a0, a2), (b0, b2)| (a0 + b0, a2 + b2));
+        SumcheckPartial { c0, c2 }
+    }
+
+    /// Binds the last variable of the tables, as `SumcheckSingle::compress`.
+    pub fn compress(&mut self, challenge: &FoldingChallenge<F>) {
+        let randomness = challenge.folding_randomness;
+        let compress = |evals: &EvaluationsList<F>| {
+            EvaluationsList::new(
+                evals
+                    .evals()
+                    .chunks_exact(2)
+                    .map(|at| (at[1] - at[0]) * randomness + at[0])
+                    .collect(),
+            )
+        };
+        self.evaluation_of_p = compress(&self.evaluation_of_p);
+        self.evaluation_of_equality = compress(&self.evaluation_of_equality);
+    }
+
+    /// Folds the shard of the coefficients, as `CoefficientList::fold`.
+    pub fn fold(&mut self, folding_randomness: &MultilinearPoint<F>) {
+        self.coefficients = self.coefficients.fold(folding_randomness);
+    }
+
+    /// The contribution of the shard to the evaluations of the polynomial at `points`: the
+    /// monomial `X^j` given by the top bits times the shard evaluated at the other coordinates.
+    pub fn partial_evaluations(
+        &self,
+        points: &[MultilinearPoint<F>],
+    ) -> WhirResult<PartialEvaluations<F>> {
+        if let Some(point) = points
+            .iter()
+            .find(|point| point.n_variables() != self.num_variables())
+        {
+            return Err(WhirError::NumVariables {
+                expected: self.num_variables(),
+                found: point.n_variables(),
+            });
+        }
+        let values = points
+            .iter()
+            .map(|point| {
+                let (top, low) = point.0.split_at(self.log_num_shards);
+                let monomial = top
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| (self.shard >> (self.log_num_shards - 1 - i)) & 1 == 1)
+                    .map(|(_, z)| *z)
+                    .product::<F>();
+                monomial * self.coefficients.evaluate(&MultilinearPoint(low.to_vec()))
+            })
+            .collect();
+        Ok(PartialEvaluations { values })
+    }
+
+    /// The coefficients of the shard, shifted to the coset of `domain`, to send to each worker in
+    /// turn: those of the `g_u` (see the module documentation) whose index is the receiver
+    /// modulo the number of shards, as leaves of `2^folding_factor` coefficients, one per `u`.
+    pub fn partial_coefficients(
+        &self,
+        domain: &Domain<F>,
+        folding_factor: usize,
+    ) -> WhirResult<Vec<PartialCoefficients<F>>> {
+        let (blocks, _) = self.encoding_shape(domain, folding_factor)?;
+        let shard_offset = domain
+            .offset()
+            .pow([(self.shard * self.coefficients.num_coeffs()) as u64]);
+        let coeffs = utils::coset_shift(self.coefficients.coeffs(), domain.offset());
+        let mut partials: Vec<_> = (0..self.num_shards())
+            .map(|_| PartialCoefficients {
+                from_shard: self.shard,
+                coefficients: Vec::with_capacity(coeffs.len() >> self.log_num_shards),
+            })
+            .collect();
+        for (block, coeffs) in coeffs.chunks_exact(1 << folding_factor).enumerate() {
+            let class = (self.shard * blocks + block) % self.num_shards();
+            partials[class]
+                .coefficients
+                .extend(coeffs.iter().map(|coeff| *coeff * shard_offset));
+        }
+        Ok(partials)
+    }
+
+    /// Keeps the coefficients of the class of this worker, from the `partial_coefficients` of all
+    /// the shards, including itself.
+    pub fn receive_coefficients(
+        &mut self,
+        partials: &[PartialCoefficients<F>],
+        domain: &Domain<F>,
+        folding_factor: usize,
+    ) -> WhirResult<()> {
+        let (blocks, _) = self.encoding_shape(domain, folding_factor)?;
+        let partials = by_shard(partials, self.num_shards(), |p| p.from_shard)?;
+        let mut class_coefficients = Vec::with_capacity(self.coefficients.num_coeffs());
+        for (from_shard, partial) in partials.into_iter().enumerate() {
+            let in_class = (0..blocks)
+                .filter(|block| (from_shard * blocks + block) % self.num_shards() == self.shard)
+                .count();
+            if partial.coefficients.len() != in_class << folding_factor {
+                return Err(WhirError::InvalidMessage("coefficients of the wrong size"));
+            }
+            // The shards and their leaves are in order, so the coefficients are too
+            class_coefficients.extend_from_slice(&partial.coefficients);
+        }
+        self.class_coefficients = class_coefficients;
+        Ok(())
+    }
+
+    /// The NTTs of the class of this worker, to broadcast to all the workers: for each leaf `r`
+    /// of a worker, and each `u`, the evaluation of the part of `g_u` in the class at
+    /// `w^(2^folding_factor r)`, times `w^(2^folding_factor r c)` for the class `c`.
+    pub fn partial_leaves(
+        &self,
+        domain: &Domain<F>,
+        folding_factor: usize,
+    ) -> WhirResult<PartialLeaves<F>> {
+        let (blocks, num_leaves) = self.encoding_shape(domain, folding_factor)?;
+        let fold_size = 1 << folding_factor;
+        if self.class_coefficients.len() != blocks * fold_size {
+            return Err(WhirError::InvalidMessage(
+                "the coefficients of the class are not received",
+            ));
+        }
+        let expansion = num_leaves / blocks;
+        let root = domain.generator().pow([(fold_size * self.shard) as u64]);
+        let mut leaves = vec![F::ZERO; num_leaves * fold_size];
+        for u in 0..fold_size {
+            let coeffs: Vec<_> = self
+                .class_coefficients
+                .iter()
+                .skip(u)
+                .step_by(fold_size)
+                .copied()
+                .collect();
+            let mut twiddle = F::ONE;
+            for (r, eval) in expand_from_coeff(&coeffs, expansion)
+                .into_iter()
+                .enumerate()
+            {
+                leaves[r * fold_size + u] = eval * twiddle;
+                twiddle *= root;
+            }
+        }
+        Ok(PartialLeaves {
+            from_shard: self.shard,
+            leaves,
+        })
+    }
+
+    /// Combines the `partial_leaves` of all the shards, including itself, into the range of
+    /// leaves of this worker, as the committer stacks and restructures them for `fold_type`.
+    pub fn receive_leaves(
+        &mut self,
+        partial_leaves: &[PartialLeaves<F>],
+        domain: &Domain<F>,
+        folding_factor: usize,
+        fold_type: FoldType,
+    ) -> WhirResult<()> {
+        let (_, num_leaves) = self.encoding_shape(domain, folding_factor)?;
+        let fold_size = 1 << folding_factor;
+        let partial_leaves = by_shard(partial_leaves, self.num_shards(), |p| p.from_shard)?;
+        if partial_leaves
+            .iter()
+            .any(|partial| partial.leaves.len() != num_leaves * fold_size)
+        {
+            return Err(WhirError::InvalidMessage("leaves of the wrong size"));
+        }
+
+        // The NTT over the classes, at the range of this worker
+        let generator = domain.generator();
+        let root = generator.pow([(num_leaves * fold_size * self.shard) as u64]);
+        let mut leaves = vec![F::ZERO; num_leaves * fold_size];
+        let mut scale = F::ONE;
+        for partial in partial_leaves {
+            for (leaf, contribution) in leaves.iter_mut().zip(&partial.leaves) {
+                *leaf += scale * contribution;
+            }
+            scale *= root;
+        }
+
+        // The NTT over `u` of each leaf, at the coset of the leaf
+        let first_leaf = num_leaves * self.shard;
+        let mut leaf_root = generator.pow([first_leaf as u64]);
+        for leaf in leaves.chunks_exact_mut(fold_size) {
+            let mut power = F::ONE;
+            for value in leaf.iter_mut() {
+                *value *= power;
+                power *= leaf_root;
+            }
+            leaf_root *= generator;
+        }
+        ntt_batch(&mut leaves, fold_size);
+        self.leaves = restructure_evaluations(
+            leaves,
+            fold_type,
+            domain.backing_domain.coset_offset_inv()
+                * domain
+                    .backing_domain
+                    .group_gen_inv()
+                    .pow([first_leaf as u64]),
+            domain.backing_domain.group_gen_inv(),
+            folding_factor,
+        );
+        self.merkle_tree = None;
+        Ok(())
+    }
+
+    /// Hashes the range of leaves into a sub-tree, which needs at least two leaves.
+    pub fn commit_leaves(
+        &mut self,
+        leaf_hash_params: &LeafParam<MerkleConfig>,
+        two_to_one_params: &TwoToOneParam<MerkleConfig>,
+        folding_factor: usize,
+    ) -> SubtreeRoot<MerkleConfig::InnerDigest> {
+        #[cfg(not(feature = "parallel"))]
+        let leafs_iter = self.leaves.chunks_exact(1 << folding_factor);
+        #[cfg(feature = "parallel")]
+        let leafs_iter = self.leaves.par_chunks_exact(1 << folding_factor);
+        let merkle_tree =
+            MerkleTree::<MerkleConfig>::new(leaf_hash_params, two_to_one_params, leafs_iter)
+                .unwrap();
+        let root = merkle_tree.root();
+        self.merkle_tree = Some(merkle_tree);
+        SubtreeRoot {
+            shard: self.shard,
+            root,
+        }
+    }
+
+    /// Opens the leaf of (global) index `leaf_index`, which must be in the range of the worker.
+    pub fn open(
+        &self,
+        leaf_index: usize,
+        folding_factor: usize,
+    ) -> WhirResult<LeafOpening<MerkleConfig, F>> {
+        let merkle_tree = self
+            .merkle_tree
+            .as_ref()
+            .ok_or(WhirError::InvalidMessage("the leaves are not committed"))?;
+        let fold_size = 1 << folding_factor;
+        let num_leaves = self.leaves.len() / fold_size;
+        if leaf_index / num_leaves != self.shard {
+            return Err(WhirError::InvalidMessage(
+                "a leaf out of the range of the worker",
+            ));
+        }
+        let local_index = leaf_index % num_leaves;
+        let path = merkle_tree.generate_proof(local_index).unwrap();
+        Ok(LeafOpening {
+            leaf_index,
+            leaf: self.leaves[local_index * fold_size..(local_index + 1) * fold_size].to_vec(),
+            leaf_sibling_hash: path.leaf_sibling_hash,
+            auth_path: path.auth_path,
+        })
+    }
+}
+
+// The messages of all the shards, one each, in the order of the shards.
+fn by_shard<T>(
+    messages: &[T],
+    num_shards: usize,
+    from_shard: impl Fn(&T) -> usize,
+) -> WhirResult<Vec<&T>> {
+    let mut ordered = vec![None; num_shards];
+    for message in messages {
+        let slot = ordered
+            .get_mut(from_shard(message))
+            .ok_or(WhirError::InvalidMessage("a message of an unknown shard"))?;
+        if slot.replace(message).is_some() {
+            return Err(WhirError::InvalidMessage("two messages of the same shard"));
+        }
+    }
+    ordered
+        .into_iter()
+        .collect::<Option<_>>()
+        .ok_or(WhirError::InvalidMessage("a message is missing"))
+}
+
+pub struct Coordinator<F, MerkleConfig>
+where
+    MerkleConfig: Config,
+{
+    log_num_shards: usize,
+    sum: F,
+    // The layers of the top of the Merkle tree, from the sub-tree roots to the root.
+    top_layers: Vec<Vec<MerkleConfig::InnerDigest>>,
+}
+
+impl<F, MerkleConfig> Coordinator<F, MerkleConfig>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+{
+    pub fn new(log_num_shards: usize) -> Self {
+        Coordinator {
+            log_num_shards,
+            sum: F::ZERO,
+            top_layers: Vec::new(),
+        }
+    }
+
+    /// The message adding the claims that the polynomial evaluates to `evaluations` at `points`.
+    pub fn equality_claims(
+        &mut self,
+        points: &[MultilinearPoint<F>],
+        combination_randomness: &[F],
+        evaluations: &[F],
+    ) -> EqualityClaims<F> {
+        assert_eq!(points.len(), combination_randomness.len());
+        assert_eq!(points.len(), evaluations.len());
+        for (rand, eval) in combination_randomness.iter().zip(evaluations) {
+            self.sum += *rand * eval;
+        }
+        EqualityClaims {
+            points: points.iter().map(|point| point.0.clone()).collect(),
+            combination_randomness: combination_randomness.to_vec(),
+        }
+    }
+
+    /// Merges the partial sums of all the shards, as `SumcheckSingle::compute_sumcheck_polynomial`.
+    pub fn sumcheck_polynomial(
+        &self,
+        partials: &[SumcheckPartial<F>],
+    ) -> WhirResult<SumcheckPolynomial<F>> {
+        if partials.len() != 1 << self.log_num_shards {
+            return Err(WhirError::InvalidMessage(
+                "expected one partial sum per shard",
+            ));
+        }
+        let (c0, c2) = partials
+            .iter()
+            .fold((F::ZERO, F::ZERO), |(c0, c2), partial| {
+                (c0 + partial.c0, c2 + partial.c2)
+            });
+
+        // Use the fact that self.sum = p(0) + p(1) = 2 * c0 + c1 + c2
+        let c1 = self.sum - c0.double() - c2;
+
+        let eval_0 = c0;
+        let eval_1 = c0 + c1 + c2;
+        let eval_2 = eval_1 + c1 + c2 + c2.double();
+        Ok(SumcheckPolynomial::new(vec![eval_0, eval_1, eval_2], 1))
+    }
+
+    /// The message binding the last variable to `folding_randomness`, as
+    /// `SumcheckSingle::compress`.
+    pub fn compress(
+        &mut self,
+        combination_randomness: F,
+        folding_randomness: F,
+        sumcheck_poly: &SumcheckPolynomial<F>,
+    ) -> FoldingChallenge<F> {
+        self.sum = combination_randomness
+            * sumcheck_poly.evaluate_at_point(&MultilinearPoint(vec![folding_randomness]));
+        FoldingChallenge { folding_randomness }
+    }
+
+    /// Merges the contributions of all the shards to the evaluations of the polynomial.
+    pub fn evaluations(&self, partials: &[PartialEvaluations<F>]) -> WhirResult<Vec<F>> {
+        if partials.len() != 1 << self.log_num_shards {
+            return Err(WhirError::InvalidMessage(
+                "expected one partial evaluation per shard",
+            ));
+        }
+        let num_values = partials[0].values.len();
+        if partials
+            .iter()
+            .any(|partial| partial.values.len() != num_values)
+        {
+            return Err(WhirError::InvalidMessage(
+                "partial evaluations of different sizes",
+            ));
+        }
+        let mut values = vec![F::ZERO; num_values];
+        for partial in partials {
+            for (value, contribution) in values.iter_mut().zip(&partial.values) {
+                *value += contribution;
+            }
+        }
+        Ok(values)
+    }
+
+    /// Merges the roots of the sub-trees of all the workers into the Merkle root.
+    pub fn merge_roots(
+        &mut self,
+        two_to_one_params: &TwoToOneParam<MerkleConfig>,
+        roots: &[SubtreeRoot<MerkleConfig::InnerDigest>],
+    ) -> WhirResult<MerkleConfig::InnerDigest> {
+        let mut layer: Vec<_> = by_shard(roots, 1 << self.log_num_shards, |root| root.shard)?
+            .into_iter()
+            .map(|root| root.root.clone())
+            .collect();
+
+        // The sub-tree roots are inner nodes, so the upper layers are hashed with `compress`
+        self.top_layers.clear();
+        while layer.len() > 1 {
+            let next = layer
+                .chunks_exact(2)
+                .map(|pair| {
+                    MerkleConfig::TwoToOneHash::compress(two_to_one_params, &pair[0], &pair[1])
+                        .unwrap()
+                })
+                .collect();
+            self.top_layers.push(layer);
+            layer = next;
+        }
+        let root = layer[0].clone();
+        self.top_layers.push(layer);
+        Ok(root)
+    }
+
+    /// Extends the authentication path of `opening`, which stops at the root of the sub-tree of
+    /// its worker, to the Merkle root merged by `merge_roots`.
+    pub fn complete_opening(
+        &self,
+        mut opening: LeafOpening<MerkleConfig, F>,
+    ) -> WhirResult<LeafOpening<MerkleConfig, F>> {
+        if self.top_layers.len() != self.log_num_shards + 1 {
+            return Err(WhirError::InvalidMessage("the roots are not merged"));
+        }
+        // A sub-tree with `2^d` leaves has `d - 1` nodes in its authentication paths
+        let mut index = opening
+            .leaf_index
+            .checked_shr(opening.auth_path.len() as u32 + 1)
+            .unwrap_or(0);
+        if index >> self.log_num_shards != 0 {
+            return Err(WhirError::InvalidMessage(
+                "an opening out of the range of the workers",
+            ));
+        }
+        let mut top_path = Vec::with_capacity(self.log_num_shards);
+        for layer in &self.top_layers[..self.log_num_shards] {
+            top_path.push(layer[index ^ 1].clone());
+            index >>= 1;
+        }
+        // The authentication path goes from the root down to the leaf
+        top_path.reverse();
+        top_path.append(&mut opening.auth_path);
+        opening.auth_path = top_path;
+        Ok(opening)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::Field;
+    use ark_std::UniformRand;
+
+    use super::*;
+    use crate::crypto::{fields::Field64, merkle_tree::blake3 as merkle_tree};
+
+    type F = Field64;
+    type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+
+    #[test]
+    fn test_distributed() {
+        let num_variables = 6;
+        let log_num_shards = 2;
+        let folding_factor = 2;
+        let fold_type = FoldType::ProverHelps;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+
+        let polynomial =
+            CoefficientList::new((0..1 << num_variables).map(|_| F::rand(&mut rng)).collect());
+        let shard_size = polynomial.num_coeffs() >> log_num_shards;
+        let mut workers: Vec<Worker<F, MerkleConfig>> = polynomial
+            .coeffs()
+            .chunks_exact(shard_size)
+            .enumerate()
+            .map(|(shard, coeffs)| {
+                Worker::new(shard, log_num_shards, CoefficientList::new(coeffs.to_vec()))
+            })
+            .collect();
+        let mut coordinator = Coordinator::<F, MerkleConfig>::new(log_num_shards);
+
+        // Tables
+        let tables: Vec<_> = workers.iter().map(Worker::partial_table).collect();
+        for (shard, worker) in workers.iter_mut().enumerate() {
+            let received: Vec<_> = tables
+                .iter()
+                .filter(|table| table.from_shard & shard == table.from_shard)
+                .cloned()
+                .collect();
+            worker.receive_tables(&received).unwrap();
+        }
+        assert!(workers[3].receive_tables(&tables[..3]).is_err());
+        assert!(workers[1].receive_tables(&tables[1..3]).is_err());
+        assert_eq!(workers[1].table_destinations(), vec![1, 3]);
+
+        // Sumcheck
+        let points: Vec<_> = (0..2)
+            .map(|_| MultilinearPoint::rand(&mut rng, num_variables))
+            .collect();
+        let evaluations: Vec<_> = points.iter().map(|p| polynomial.evaluate(p)).collect();
+        let combination_randomness = [F::from(1), F::from(7)];
+        let mut prover = SumcheckSingle::new(
+            polynomial.clone(),
+            &points,
+            &combination_randomness,
+            &evaluations,
+        );
+        let claims = coordinator.equality_claims(&points, &combination_randomness, &evaluations);
+        workers
+            .iter_mut()
+            .for_each(|w| w.add_equality(&claims).unwrap());
+        let mut short_claims = claims.clone();
+        short_claims.points[1].pop();
+        assert!(workers[0].add_equality(&short_claims).is_err());
+        for round in 0..2 {
+            let partials: Vec<_> = workers.iter().map(Worker::sumcheck_partial).collect();
+            let sumcheck_poly = coordinator.sumcheck_polynomial(&partials).unwrap();
+            let expected = prover.compute_sumcheck_polynomial();
+            assert_eq!(sumcheck_poly.evaluations(), expected.evaluations());
+
+            let combination_randomness = F::from(3 + round);
+            let folding_randomness = F::from(11 + round);
+            let challenge =
+                coordinator.compress(combination_randomness, folding_randomness, &sumcheck_poly);
+            workers.iter_mut().for_each(|w| w.compress(&challenge));
+            prover.compress(
+                combination_randomness,
+                &MultilinearPoint(vec![folding_randomness]),
+                &expected,
+            );
+        }
+
+        // Evaluations
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let partials: Vec<_> = workers
+            .iter()
+            .map(|w| w.partial_evaluations(&[point.clone()]).unwrap())
+            .collect();
+        assert_eq!(
+            coordinator.evaluations(&partials).unwrap(),
+            vec![polynomial.evaluate(&point)]
+        );
+
+        // Commitment
+        let domain = Domain::<F>::new(1 << num_variables, 1).unwrap();
+        let partial_coefficients: Vec<_> = workers
+            .iter()
+            .map(|w| w.partial_coefficients(&domain, folding_factor).unwrap())
+            .collect();
+        for (shard, worker) in workers.iter_mut().enumerate() {
+            let received: Vec<_> = partial_coefficients
+                .iter()
+                .map(|partials| partials[shard].clone())
+                .collect();
+            worker
+                .receive_coefficients(&received, &domain, folding_factor)
+                .unwrap();
+        }
+        let partial_leaves: Vec<_> = workers
+            .iter()
+            .map(|w| w.partial_leaves(&domain, folding_factor).unwrap())
+            .collect();
+        assert!(workers[0]
+            .receive_leaves(&partial_leaves[1..], &domain, folding_factor, fold_type)
+            .is_err());
+        assert!(workers[0]
+            .receive_leaves(&[], &domain, folding_factor, fold_type)
+            .is_err());
+        let roots: Vec<_> = workers
+            .iter_mut()
+            .map(|worker| {
+                worker
+                    .receive_leaves(&partial_leaves, &domain, folding_factor, fold_type)
+                    .unwrap();
+                worker.commit_leaves(&leaf_hash_params, &two_to_one_params, folding_factor)
+            })
+            .collect();
+        let root = coordinator.merge_roots(&two_to_one_params, &roots).unwrap();
+
+        let evals = expand_from_coeff(polynomial.coeffs(), 2);
+        let folded_evals = utils::stack_evaluations(evals, folding_factor);
+        let folded_evals = restructure_evaluations(
+            folded_evals,
+            fold_type,
+            domain.backing_domain.coset_offset_inv(),
+            domain.backing_domain.group_gen_inv(),
+            folding_factor,
+        );
+        #[cfg(not(feature = "parallel"))]
+        let leafs_iter = folded_evals.chunks_exact(1 << folding_factor);
+        #[cfg(feature = "parallel")]
+        let leafs_iter = folded_evals.par_chunks_exact(1 << folding_factor);
+        let merkle_tree =
+            MerkleTree::<MerkleConfig>::new(&leaf_hash_params, &two_to_one_params, leafs_iter)
+                .unwrap();
+        assert_eq!(root, merkle_tree.root());
+
+        // Openings
+        for leaf_index in [0, 13, 31] {
+            let opening = workers[leaf_index / 8]
+                .open(leaf_index, folding_factor)
+                .unwrap();
+            let mut opening = coordinator.complete_opening(opening).unwrap();
+            assert!(opening
+                .path()
+                .verify(
+                    &leaf_hash_params,
+                    &two_to_one_params,
+                    &root,
+                    &opening.leaf[..]
+                )
+                .unwrap());
+            opening.leaf[0] += F::ONE;
+            assert!(!opening
+                .path()
+                .verify(
+                    &leaf_hash_params,
+                    &two_to_one_params,
+                    &root,
+                    &opening.leaf[..]
+                )
+                .unwrap());
+        }
+        assert!(workers[0].open(13, folding_factor).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! Checking that two provers, e.g. an optimised one and the reference, or two configurations
+//! that should not change the proof, produce the same proof of the same statement. Outside of
+//! ZK mode the prover is deterministic, so any difference is a difference in behaviour, and the
+//! first one is reported.
+//!
+//! The proof-of-work nonces are only the same if both provers grind them in the same order. To
+//! compare provers that do not, set the difficulties to zero with `WhirConfig::with_pow_bits`.
+
+use std::fmt::{self, Display};
+
+use ark_crypto_primitives::merkle_tree::Config;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress};
+use nimue::{IOPattern, Merlin};
+
+use super::{write_opening, WhirProof};
+use crate::errors::WhirResult;
+
+/// Where two proofs first differ, see `first_divergence`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Divergence {
+    /// The transcripts differ at byte `offset`. A byte is `None` past the end of its
+    /// transcript.
+    Transcript {
+        offset: usize,
+        first: Option<u8>,
+        second: Option<u8>,
+    },
+    /// The transcripts are equal, but the openings of `round` (the final round last) differ at
+    /// byte `offset` of their serialization.
+    Openings { round: usize, offset: usize },
+    /// The transcripts are equal, but the proofs have different numbers of rounds.
+    NumRounds { first: usize, second: usize },
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let byte = |byte: &Option<u8>| match byte {
+            Some(byte) => format!("{byte:#04x}"),
+            None => "the end".into(),
+        };
+        match self {
+            Divergence::Transcript {
+                offset,
+                first,
+                second,
+            } => write!(
+                f,
+                "transcripts diverge at byte {offset}: {} against {}",
+                byte(first),
+                byte(second)
+            ),
+            Divergence::Openings { round, offset } => write!(
+                f,
+                "openings of round {round} diverge at byte {offset} of their serialization"
+            ),
+            Divergence::NumRounds { first, second } => {
+                write!(f, "proofs have {first} and {second} rounds")
+            }
+        }
+    }
+}
+
+// The first index at which `first` and `second` differ, if any, counting the end of the shorter
+// one as a difference.
+fn first_difference(first: &[u8], second: &[u8]) -> Option<usize> {
+    first
+        .iter()
+        .zip(second)
+        .position(|(a, b)| a != b)
+        .or_else(|| (first.len() != second.len()).then(|| first.len().min(second.len())))
+}
+
+/// Compares two proofs, each a transcript and its openings, and returns where they first differ,
+/// or `None` if they are the same. The transcript is compared first, since the openings follow
+/// from it.
+pub fn first_divergence<MerkleConfig, F>(
+    (first_transcript, first_proof): (&[u8], &WhirProof<MerkleConfig, F>),
+    (second_transcript, second_proof): (&[u8], &WhirProof<MerkleConfig, F>),
+) -> Option<Divergence>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    if let Some(offset) = first_difference(first_transcript, second_transcript) {
+        return Some(Divergence::Transcript {
+            offset,
+            first: first_transcript.get(offset).copied(),
+            second: second_transcript.get(offset).copied(),
+        });
+    }
+    // An opening that does not serialize has inconsistent lengths; it differs from any other
+    // at its start.
+    let serialize = |opening| {
+        let mut bytes = vec![];
+        write_opening(&mut bytes, opening, Compress::Yes).map(|()| bytes)
+    };
+    for (round, (first, second)) in first_proof.0.iter().zip(&second_proof.0).enumerate() {
+        let offset = match (serialize(first), serialize(second)) {
+            (Ok(first), Ok(second)) => first_difference(&first, &second),
+            _ => Some(0),
+        };
+        if let Some(offset) = offset {
+            return Some(Divergence::Openings { round, offset });
+        }
+    }
+    (first_proof.0.len() != second_proof.0.len()).then(|| Divergence::NumRounds {
+        first: first_proof.0.len(),
+        second: second_proof.0.len(),
+    })
+}
+
+/// Runs both provers on a fresh transcript of `io` and compares their proofs, see
+/// `first_divergence`. Each prover commits and proves, so that an optimised committer is
+/// compared as well.
+pub fn compare_provers<MerkleConfig, F>(
+    io: &IOPattern,
+    first: impl FnOnce(&mut Merlin) -> WhirResult<WhirProof<MerkleConfig, F>>,
+    second: impl FnOnce(&mut Merlin) -> WhirResult<WhirProof<MerkleConfig, F>>,
+) -> WhirResult<Option<Divergence>>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    let (mut first_merlin, mut second_merlin) = (io.to_merlin(), io.to_merlin());
+    let first_proof = first(&mut first_merlin)?;
+    let second_proof = second(&mut second_merlin)?;
+    Ok(first_divergence(
+        (first_merlin.transcript(), &first_proof),
+        (second_merlin.transcript(), &second_proof),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+        whir::{
+            committer::Committer,
+            fixtures::{config, io_pattern, MerkleConfig, PowStrategy, F},
+            parameters::WhirConfig,
+            prover::Prover,
+            Statement,
+        },
+    };
+
+    #[test]
+    fn test_first_divergence() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables);
+        let io = io_pattern(&params);
+
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+        let prover = |params: WhirConfig<F, MerkleConfig, PowStrategy>,
+                      polynomial: CoefficientList<F>| {
+            let statement = statement.clone();
+            move |merlin: &mut Merlin| {
+                let witness = Committer::new(params.clone()).commit(merlin, polynomial)?;
+                Prover(params).prove(merlin, statement, witness)
+            }
+        };
+
+        // A precomputed domain does not change the proof
+        assert_eq!(
+            compare_provers(
+                &io,
+                prover(params.clone(), polynomial.clone()),
+                prover(params.clone().with_precomputed_domain(), polynomial.clone()),
+            )
+            .unwrap(),
+            None
+        );
+
+        // Another polynomial changes the root, the first message
+        let mut other = polynomial.coeffs().to_vec();
+        other[0] += F::from(1);
+        let divergence = compare_provers(
+            &io,
+            prover(params.clone(), polynomial.clone()),
+            prover(params.clone(), CoefficientList::new(other)),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(matches!(divergence, Divergence::Transcript { offset, .. } if offset < 32));
+
+        // The same transcript with other openings
+        let mut merlin = io.to_merlin();
+        let proof = prover(params.clone(), polynomial)(&mut merlin).unwrap();
+        let mut tampered = proof.clone();
+        tampered.0[0].1[0][0] += F::from(1);
+        let divergence = first_divergence(
+            (merlin.transcript(), &proof),
+            (merlin.transcript(), &tampered),
+        );
+        assert!(matches!(
+            divergence,
+            Some(Divergence::Openings { round: 0, .. })
+        ));
+        tampered.0.truncate(1);
+        assert_eq!(
+            first_divergence(
+                (merlin.transcript(), &proof),
+                (merlin.transcript(), &tampered)
+            ),
+            Some(Divergence::NumRounds {
+                first: params.n_rounds() + 1,
+                second: 1
+            })
+        );
+    }
+}
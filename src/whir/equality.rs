@@ -0,0 +1,245 @@
+//! Proofs that two WHIR commitments, possibly under different parameters (e.g. rates), are to
+//! the same multilinear polynomial. Used when re-committing data under new parameters.
+//!
+//! Both commitments are in the transcript before the proof. The verifier then draws a random
+//! point, the prover sends the evaluation of its polynomial there, and proves it against both
+//! commitments. If the polynomials differ, they agree on the random point only with probability
+//! `num_variables / |F|`.
+
+use ark_crypto_primitives::merkle_tree::Config;
+use ark_ff::FftField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use nimue::{
+    plugins::ark::{FieldChallenges, FieldIOPattern, FieldReader, FieldWriter},
+    Arthur, ByteWriter, IOPattern, Merlin, ProofResult,
+};
+
+use super::{
+    committer::Witness,
+    iopattern::WhirIOPattern,
+    parameters::WhirConfig,
+    prover::Prover,
+    verifier::{ParsedCommitment, Verifier},
+    Statement, WhirProof,
+};
+use crate::{
+    errors::WhirResult,
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+};
+
+pub trait EqualityIOPattern<F: FftField> {
+    fn add_equality_proof<MerkleConfig: Config, PowStrategy>(
+        self,
+        first: &WhirConfig<F, MerkleConfig, PowStrategy>,
+        second: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    ) -> Self;
+}
+
+impl<F> EqualityIOPattern<F> for IOPattern
+where
+    F: FftField,
+    IOPattern: WhirIOPattern<F> + FieldIOPattern<F>,
+{
+    fn add_equality_proof<MerkleConfig: Config, PowStrategy>(
+        self,
+        first: &WhirConfig<F, MerkleConfig, PowStrategy>,
+        second: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    ) -> Self {
+        self.challenge_scalars(first.num_variables(), "equality_point")
+            .add_scalars(1, "equality_evaluation")
+            .add_whir_proof(first)
+            .add_whir_proof(second)
+    }
+}
+
+/// The openings of the random point against both commitments.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EqualityProof<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    pub first: WhirProof<MerkleConfig, F>,
+    pub second: WhirProof<MerkleConfig, F>,
+}
+
+pub struct EqualityProver<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    first: WhirConfig<F, MerkleConfig, PowStrategy>,
+    second: WhirConfig<F, MerkleConfig, PowStrategy>,
+}
+
+impl<F, MerkleConfig, PowStrategy> EqualityProver<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    pub fn new(
+        first: WhirConfig<F, MerkleConfig, PowStrategy>,
+        second: WhirConfig<F, MerkleConfig, PowStrategy>,
+    ) -> Self {
+        assert_eq!(first.num_variables(), second.num_variables());
+        Self { first, second }
+    }
+
+    /// Proves that the witnesses of the two commitments, in this order in the transcript, are of
+    /// the same polynomial.
+    pub fn prove(
+        &self,
+        merlin: &mut Merlin,
+        first: Witness<F, MerkleConfig>,
+        second: Witness<F, MerkleConfig>,
+    ) -> WhirResult<EqualityProof<MerkleConfig, F>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        let num_variables = self.first.num_variables();
+        let mut point = vec![F::ZERO; num_variables];
+        merlin.fill_challenge_scalars(&mut point)?;
+        let point = MultilinearPoint(point);
+
+        // In ZK mode, the mask follows the coefficients of the polynomial
+        let polynomial =
+            CoefficientList::new(first.polynomial.coeffs()[..1 << num_variables].to_vec());
+        let evaluation = polynomial.evaluate(&point);
+        merlin.add_scalars(&[evaluation])?;
+
+        let statement = Statement::new(vec![point], vec![evaluation]);
+        Ok(EqualityProof {
+            first: Prover(self.first.clone()).prove(merlin, statement.clone(), first)?,
+            second: Prover(self.second.clone()).prove(merlin, statement, second)?,
+        })
+    }
+}
+
+pub struct EqualityVerifier<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    num_variables: usize,
+    first: Verifier<F, MerkleConfig, PowStrategy>,
+    second: Verifier<F, MerkleConfig, PowStrategy>,
+}
+
+impl<F, MerkleConfig, PowStrategy> EqualityVerifier<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    pub fn new(
+        first: WhirConfig<F, MerkleConfig, PowStrategy>,
+        second: WhirConfig<F, MerkleConfig, PowStrategy>,
+    ) -> Self {
+        assert_eq!(first.num_variables(), second.num_variables());
+        EqualityVerifier {
+            num_variables: first.num_variables(),
+            first: Verifier::new(first),
+            second: Verifier::new(second),
+        }
+    }
+
+    /// Reads both commitments from the transcript, see `Verifier::parse_commitment`.
+    pub fn parse_commitments(
+        &self,
+        arthur: &mut Arthur,
+    ) -> ProofResult<(
+        ParsedCommitment<F, MerkleConfig::InnerDigest>,
+        ParsedCommitment<F, MerkleConfig::InnerDigest>,
+    )> {
+        let first = self.first.parse_commitment(arthur)?;
+        let second = self.second.parse_commitment(arthur)?;
+        Ok((first, second))
+    }
+
+    pub fn verify(
+        &self,
+        arthur: &mut Arthur,
+        first: &ParsedCommitment<F, MerkleConfig::InnerDigest>,
+        second: &ParsedCommitment<F, MerkleConfig::InnerDigest>,
+        proof: &EqualityProof<MerkleConfig, F>,
+    ) -> ProofResult<()> {
+        let mut point = vec![F::ZERO; self.num_variables];
+        arthur.fill_challenge_scalars(&mut point)?;
+        let [evaluation] = arthur.next_scalars()?;
+
+        let statement = Statement::new(vec![MultilinearPoint(point)], vec![evaluation]);
+        self.first
+            .verify_with_commitment(arthur, first, &statement, &proof.first)?;
+        self.second
+            .verify_with_commitment(arthur, second, &statement, &proof.second)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimue::DefaultHash;
+    use nimue_pow::blake3::Blake3PoW;
+
+    use super::*;
+    use crate::{
+        crypto::merkle_tree::blake3 as merkle_tree,
+        parameters::{MultivariateParameters, WhirParameters},
+        whir::{
+            committer::Committer,
+            fixtures::{config, whir_parameters, MerkleConfig, F},
+        },
+    };
+
+    #[test]
+    fn test_equality() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let config = |starting_log_inv_rate, folding_factor| {
+            let whir_params = WhirParameters::<MerkleConfig, Blake3PoW> {
+                folding_factor,
+                starting_log_inv_rate,
+                ..whir_parameters((leaf_hash_params.clone(), two_to_one_params.clone()))
+            };
+            WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params,
+            )
+            .unwrap()
+        };
+        let (first, second) = (config(1, 2), config(3, 3));
+        let io = IOPattern::<DefaultHash>::new("🌪️")
+            .commit_statement(&first)
+            .commit_statement(&second)
+            .add_equality_proof(&first, &second);
+        let prover = EqualityProver::new(first.clone(), second.clone());
+        let verifier = EqualityVerifier::new(first.clone(), second.clone());
+
+        let polynomial = CoefficientList::new((0..1u64 << num_variables).map(F::from).collect());
+        let mut other_coeffs = polynomial.coeffs().to_vec();
+        other_coeffs[0] += F::from(1);
+
+        for (coeffs, equal) in [(polynomial.coeffs().to_vec(), true), (other_coeffs, false)] {
+            let mut merlin = io.to_merlin();
+            let first_witness = Committer::new(first.clone())
+                .commit(&mut merlin, polynomial.clone())
+                .unwrap();
+            let second_witness = Committer::new(second.clone())
+                .commit(&mut merlin, CoefficientList::new(coeffs))
+                .unwrap();
+            let proof = prover
+                .prove(&mut merlin, first_witness, second_witness)
+                .unwrap();
+
+            let mut arthur = io.to_arthur(merlin.transcript());
+            let (first_commitment, second_commitment) =
+                verifier.parse_commitments(&mut arthur).unwrap();
+            let result =
+                verifier.verify(&mut arthur, &first_commitment, &second_commitment, &proof);
+            assert_eq!(result.is_ok(), equal);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! The fixtures of the tests: the parameters most tests start from, changing the fields they
+//! exercise with `..whir_parameters(...)`, and a round trip through the prover and the verifier.
+
+use ark_crypto_primitives::merkle_tree::{Config, LeafParam, TwoToOneParam};
+use ark_ff::FftField;
+use nimue::{DefaultHash, IOPattern};
+use nimue_pow::blake3::Blake3PoW;
+
+#[cfg(feature = "std")]
+use super::{committer::Committer, prover::Prover, verifier::Verifier, Statement, WhirProof};
+use super::{iopattern::WhirIOPattern, parameters::WhirConfig};
+#[cfg(feature = "std")]
+use crate::poly_utils::coeffs::CoefficientList;
+use crate::{
+    crypto::{fields::Field64, merkle_tree::blake3 as merkle_tree},
+    parameters::{FoldType, MultivariateParameters, SoundnessType, WhirParameters},
+};
+
+pub(crate) type F = Field64;
+pub(crate) type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+pub(crate) type PowStrategy = Blake3PoW;
+
+/// 32 bits of conjectured security without PoW, folding factor 2, starting rate 1/2 and
+/// `ProverHelps` folding, with the given Merkle parameters, e.g. those of
+/// `merkle_tree::blake3::default_config`.
+pub(crate) fn whir_parameters<MerkleConfig, PowStrategy>(
+    (leaf_hash_params, two_to_one_params): (LeafParam<MerkleConfig>, TwoToOneParam<MerkleConfig>),
+) -> WhirParameters<MerkleConfig, PowStrategy>
+where
+    MerkleConfig: Config,
+{
+    WhirParameters {
+        security_level: 32,
+        pow_bits: 0,
+        folding_factor: 2,
+        leaf_hash_params,
+        two_to_one_params,
+        soundness_type: SoundnessType::ConjectureList,
+        _pow_parameters: Default::default(),
+        starting_log_inv_rate: 1,
+        fold_optimisation: FoldType::ProverHelps,
+        zk: false,
+        final_form: Default::default(),
+        max_proof_size: None,
+        round_merkle_params: vec![],
+    }
+}
+
+/// The configuration of `whir_parameters` for `num_variables` over `Field64`, with Blake3 Merkle
+/// trees.
+pub(crate) fn config(num_variables: usize) -> WhirConfig<F, MerkleConfig, PowStrategy> {
+    let mut rng = ark_std::test_rng();
+    WhirConfig::try_new(
+        MultivariateParameters::new(num_variables),
+        whir_parameters(merkle_tree::default_config::<F>(&mut rng)),
+    )
+    .unwrap()
+}
+
+/// The IOPattern of a commitment and a proof for `params`.
+pub(crate) fn io_pattern<F, MerkleConfig, PowStrategy>(
+    params: &WhirConfig<F, MerkleConfig, PowStrategy>,
+) -> IOPattern
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    IOPattern::<DefaultHash>::new("🌪️")
+        .commit_statement(params)
+        .add_whir_proof(params)
+}
+
+/// Commits to `polynomial` and proves `statement` with `params`, checks that the proof verifies,
+/// and returns its transcript and openings, e.g. to tamper with them.
+#[cfg(feature = "std")]
+pub(crate) fn prove_and_verify<F, MerkleConfig, PowStrategy>(
+    params: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    polynomial: CoefficientList<F::BasePrimeField>,
+    statement: &Statement<F>,
+) -> (Vec<u8>, WhirProof<MerkleConfig, F>)
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    let io = io_pattern(params);
+    let mut merlin = io.to_merlin();
+    let witness = Committer::new(params.clone())
+        .commit(&mut merlin, polynomial)
+        .unwrap();
+    let proof = Prover(params.clone())
+        .prove(&mut merlin, statement.clone(), witness)
+        .unwrap();
+    let mut arthur = io.to_arthur(merlin.transcript());
+    Verifier::new(params.clone())
+        .verify(&mut arthur, statement, &proof)
+        .unwrap();
+    (merlin.transcript().to_vec(), proof)
+}
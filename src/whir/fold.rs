@@ -0,0 +1,222 @@
+//! The folds of the answers to the queries of a round, as the verifier computes them, for
+//! alternative verifiers and circuits to share the definition of the prover and the verifier.
+//!
+//! A round has a domain `offset * <w>` of size `N`, a folding factor `k` and folding randomness
+//! `r`. The answer at position `i < N / 2^k` is the leaf of the coset
+//! `offset * w^i * <w^(N / 2^k)>`: the evaluations of the polynomial `f` over the coset with
+//! `FoldType::Naive`, or with `FoldType::ProverHelps` the coefficients of the fold of `f` on the
+//! coset (see `restructure_evaluations`). In both cases, the fold at position `i` is the
+//! evaluation of `f` folded in `r` at `(offset * w^i)^(2^k)`, the `i`-th element of the folded
+//! domain.
+
+use ark_ff::FftField;
+use ark_poly::EvaluationDomain;
+use ark_std::vec::Vec;
+
+use crate::{
+    domain::Domain,
+    parameters::FoldType,
+    poly_utils::{coeffs::CoefficientList, fold, MultilinearPoint},
+};
+
+/// The domain of a round, with the inverses the folds take, see the module documentation.
+#[derive(Debug, Clone, Copy)]
+pub struct FoldDomain<F> {
+    /// The inverse of the offset of the domain.
+    pub offset_inv: F,
+    /// The inverse of the generator `w` of the domain.
+    pub gen_inv: F,
+    /// The inverse of the generator `w^(N / 2^k)` of the cosets.
+    pub coset_generator_inv: F,
+    pub two_inv: F,
+    pub folding_factor: usize,
+    pub fold_type: FoldType,
+}
+
+impl<F: FftField> FoldDomain<F> {
+    /// The folds of the cosets of `domain` by `folding_factor`, laid out as by `fold_type`.
+    pub fn new(domain: &Domain<F>, folding_factor: usize, fold_type: FoldType) -> Self {
+        let gen_inv = domain.backing_domain.group_gen_inv();
+        FoldDomain {
+            offset_inv: domain.backing_domain.coset_offset_inv(),
+            gen_inv,
+            coset_generator_inv: gen_inv.pow([domain.folded_size(folding_factor) as u64]),
+            two_inv: F::from(2).inverse().unwrap(),
+            folding_factor,
+            fold_type,
+        }
+    }
+}
+
+/// The fold at position `index` of the `answers` of its coset, which has `2^k` elements.
+///
+/// # Panics
+///
+/// Panics if `answers` does not have `2^k` elements, or `folding_randomness` `k` coordinates.
+pub fn compute_fold<F: FftField>(
+    domain: &FoldDomain<F>,
+    folding_randomness: &MultilinearPoint<F>,
+    index: usize,
+    answers: &[F],
+) -> F {
+    // The coset is offset * w^index * <w_coset_generator>
+    let coset_offset_inv = || domain.offset_inv * domain.gen_inv.pow([index as u64]);
+    fold_coset(domain, folding_randomness, coset_offset_inv, answers)
+}
+
+// The fold of the coset with the inverse of its offset, only computed by the naive fold.
+fn fold_coset<F: FftField>(
+    domain: &FoldDomain<F>,
+    folding_randomness: &MultilinearPoint<F>,
+    coset_offset_inv: impl FnOnce() -> F,
+    answers: &[F],
+) -> F {
+    assert_eq!(answers.len(), 1 << domain.folding_factor);
+    assert_eq!(folding_randomness.0.len(), domain.folding_factor);
+    match domain.fold_type {
+        FoldType::Naive => fold::compute_fold(
+            answers,
+            &folding_randomness.0,
+            coset_offset_inv(),
+            domain.coset_generator_inv,
+            domain.two_inv,
+            domain.folding_factor,
+        ),
+        FoldType::ProverHelps => {
+            CoefficientList::new(answers.to_vec()).evaluate(folding_randomness)
+        }
+    }
+}
+
+/// The folds at all the positions `indexes`, of the answers `answers`, as `compute_fold`.
+pub fn compute_folds<F: FftField, A: AsRef<[F]>>(
+    domain: &FoldDomain<F>,
+    folding_randomness: &MultilinearPoint<F>,
+    indexes: &[usize],
+    answers: &[A],
+) -> Vec<F> {
+    assert_eq!(indexes.len(), answers.len());
+    indexes
+        .iter()
+        .zip(answers)
+        .map(|(&index, answers)| compute_fold(domain, folding_randomness, index, answers.as_ref()))
+        .collect()
+}
+
+/// Same as `compute_folds`, with the inverses of the offsets `offset * w^index` of the cosets
+/// given, e.g. by the hints of the proof (see `whir::hints`), instead of exponentiated.
+pub fn compute_folds_with_inverses<F: FftField, A: AsRef<[F]>>(
+    domain: &FoldDomain<F>,
+    folding_randomness: &MultilinearPoint<F>,
+    coset_offsets_inv: &[F],
+    answers: &[A],
+) -> Vec<F> {
+    assert_eq!(coset_offsets_inv.len(), answers.len());
+    coset_offsets_inv
+        .iter()
+        .zip(answers)
+        .map(|(&inverse, answers)| {
+            fold_coset(domain, folding_randomness, || inverse, answers.as_ref())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::Field;
+
+    use super::*;
+    use crate::{
+        crypto::fields::{Field64, Field64_2},
+        poly_utils::fold::encode_leaves,
+    };
+
+    fn check_folds<F: FftField>(offset: F::BasePrimeField, folding_factor: usize) {
+        let num_variables = 6;
+        let coeffs: Vec<F> = (0..1u64 << num_variables)
+            .map(|i| F::from(i * i + 5))
+            .collect();
+        let polynomial = CoefficientList::new(coeffs.clone());
+        let domain = Domain::<F>::new(1 << num_variables, 2)
+            .unwrap()
+            .with_offset(offset)
+            .unwrap();
+        let folding_randomness = MultilinearPoint(
+            (0..folding_factor)
+                .map(|i| F::from(3 * i as u64 + 7))
+                .collect(),
+        );
+        let folded = polynomial.fold(&folding_randomness);
+        let folded_domain = domain.scale(1 << folding_factor);
+        let num_positions = domain.folded_size(folding_factor);
+        let indexes: Vec<_> = (0..num_positions).collect();
+        let expected: Vec<_> = indexes
+            .iter()
+            .map(|&i| {
+                folded.evaluate(&MultilinearPoint::expand_from_univariate(
+                    folded_domain.element(i),
+                    folded.num_variables(),
+                ))
+            })
+            .collect();
+
+        for fold_type in [FoldType::Naive, FoldType::ProverHelps] {
+            let fold_domain = FoldDomain::new(&domain, folding_factor, fold_type);
+            let leaves = encode_leaves(
+                &coeffs,
+                &domain,
+                folding_factor,
+                fold_type,
+                0..num_positions,
+            );
+            let answers: Vec<_> = leaves.chunks_exact(1 << folding_factor).collect();
+            for &i in &[0, 1, num_positions / 2, num_positions - 1] {
+                assert_eq!(
+                    compute_fold(&fold_domain, &folding_randomness, i, answers[i]),
+                    expected[i]
+                );
+            }
+            assert_eq!(
+                compute_folds(&fold_domain, &folding_randomness, &indexes, &answers),
+                expected
+            );
+            // A subset of the positions, as queried
+            let queried = [3, 5, num_positions - 2];
+            let queried_answers: Vec<_> = queried.iter().map(|&i| answers[i]).collect();
+            assert_eq!(
+                compute_folds(
+                    &fold_domain,
+                    &folding_randomness,
+                    &queried,
+                    &queried_answers
+                ),
+                queried.map(|i| expected[i])
+            );
+            // A wrong answer changes the fold
+            let mut wrong = answers[1].to_vec();
+            wrong[0] += F::ONE;
+            assert_ne!(
+                compute_fold(&fold_domain, &folding_randomness, 1, &wrong),
+                expected[1]
+            );
+        }
+    }
+
+    #[test]
+    fn test_compute_fold() {
+        for folding_factor in 1..=4 {
+            check_folds::<Field64>(Field64::ONE, folding_factor);
+            check_folds::<Field64>(Field64::GENERATOR, folding_factor);
+        }
+        check_folds::<Field64_2>(Field64::GENERATOR, 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_compute_fold_wrong_length() {
+        let domain = Domain::<Field64>::new(16, 1).unwrap();
+        let fold_domain = FoldDomain::new(&domain, 2, FoldType::Naive);
+        let folding_randomness = MultilinearPoint(vec![Field64::ONE; 2]);
+        compute_fold(&fold_domain, &folding_randomness, 0, &[Field64::ONE; 3]);
+    }
+}
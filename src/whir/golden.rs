@@ -0,0 +1,166 @@
+//! Golden IOPatterns, transcripts and proofs. A proof only verifies against the exact IOPattern and
+//! transcript layout it was produced with, so any change to them breaks every proof issued so
+//! far and must come with a bump of `PROTOCOL_VERSION`. A change of the serialized layout of the
+//! proof alone bumps `PROOF_FORMAT_VERSION` instead.
+//!
+//! Each case is recorded on x86_64 in `src/whir/golden/<case>.txt`, which the tests compare their
+//! proofs against, and a missing recording fails the test. No recording is committed yet, so the
+//! tests are ignored by default until they are; CI records them on x86_64 and compares the 32-bit
+//! and big-endian targets against them in the same run. New cases are recorded, and after an
+//! intended change all the cases are re-recorded, with
+//! `WHIR_BLESS_GOLDEN=1 cargo test golden -- --ignored` (bumping `PROTOCOL_VERSION` first).
+
+use std::{env, fmt::Write, fs, path::PathBuf};
+
+use ark_crypto_primitives::{
+    crh::{CRHScheme, TwoToOneCRHScheme},
+    merkle_tree::Config,
+};
+use ark_ff::{FftField, Field};
+use ark_serialize::CanonicalSerialize;
+use nimue_pow::blake3::Blake3PoW;
+
+use super::{
+    committer::Committer,
+    fixtures::{io_pattern, whir_parameters},
+    parameters::WhirConfig,
+    prover::Prover,
+    verifier::Verifier,
+    Statement, PROTOCOL_VERSION,
+};
+use crate::{
+    crypto::{
+        fields::{Field64, Field64_2},
+        merkle_tree::{blake3, keccak},
+    },
+    parameters::{FoldType, MultivariateParameters, WhirParameters},
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+};
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, b| {
+        write!(hex, "{b:02x}").unwrap();
+        hex
+    })
+}
+
+// Proves a fixed polynomial at a fixed point (both drawn from `test_rng`) and returns the
+// recording of the IOPattern, the transcript and the proof.
+fn record<F, MerkleConfig>(
+    leaf_hash_params: <MerkleConfig::LeafHash as CRHScheme>::Parameters,
+    two_to_one_params: <MerkleConfig::TwoToOneHash as TwoToOneCRHScheme>::Parameters,
+    num_variables: usize,
+    folding_factor: usize,
+    fold_optimisation: FoldType,
+) -> String
+where
+    F: FftField + CanonicalSerialize,
+    MerkleConfig: Config<Leaf = [F]> + Clone,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+{
+    let mut rng = ark_std::test_rng();
+    let whir_params = WhirParameters::<MerkleConfig, Blake3PoW> {
+        pow_bits: 5,
+        folding_factor,
+        fold_optimisation,
+        ..whir_parameters((leaf_hash_params, two_to_one_params))
+    };
+    let params = WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+        MultivariateParameters::new(num_variables),
+        whir_params,
+    )
+    .unwrap();
+    let io = io_pattern(&params);
+
+    let polynomial = CoefficientList::new(
+        (0..1 << num_variables)
+            .map(<F as Field>::BasePrimeField::from)
+            .collect(),
+    );
+    let point = MultilinearPoint::rand(&mut rng, num_variables);
+    let evaluation = polynomial.evaluate_at_extension(&point);
+    let statement = Statement::new(vec![point], vec![evaluation]);
+
+    let mut merlin = io.to_merlin();
+    let witness = Committer::new(params.clone())
+        .commit(&mut merlin, polynomial)
+        .unwrap();
+    let proof = Prover(params.clone())
+        .prove(&mut merlin, statement.clone(), witness)
+        .unwrap();
+    let mut arthur = io.to_arthur(merlin.transcript());
+    assert!(Verifier::new(params)
+        .verify(&mut arthur, &statement, &proof)
+        .is_ok());
+
+    let mut proof_bytes = Vec::new();
+    proof.serialize_compressed(&mut proof_bytes).unwrap();
+    format!(
+        "version: {PROTOCOL_VERSION}\niopattern: {}\ntranscript: {}\nproof: {}\n",
+        to_hex(io.as_bytes()),
+        to_hex(merlin.transcript()),
+        to_hex(&proof_bytes),
+    )
+}
+
+fn check(case: &str, recording: String) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "src", "whir", "golden"]
+        .iter()
+        .collect::<PathBuf>()
+        .join(format!("{case}.txt"));
+    let bless = env::var_os("WHIR_BLESS_GOLDEN").is_some();
+    if bless {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, recording).unwrap();
+        return;
+    }
+    let golden = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "`{}` is missing: record it with `WHIR_BLESS_GOLDEN=1 cargo test golden -- --ignored` \
+             on x86_64 and commit it.",
+            path.display()
+        )
+    });
+    assert!(
+        golden == recording,
+        "The IOPattern or the transcript of `{case}` changed, which invalidates all earlier \
+         proofs. If this is intended, bump `PROTOCOL_VERSION` and re-record with \
+         `WHIR_BLESS_GOLDEN=1 cargo test golden -- --ignored`."
+    );
+}
+
+#[test]
+#[ignore = "no recording committed, see the module documentation"]
+fn golden_goldilocks2_blake3() {
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) = blake3::default_config::<Field64_2>(&mut rng);
+    for (folding_factor, fold_optimisation) in [(2, FoldType::ProverHelps), (3, FoldType::Naive)] {
+        check(
+            &format!("goldilocks2_blake3_fold{folding_factor}"),
+            record::<Field64_2, blake3::MerkleTreeParams<Field64_2>>(
+                leaf_hash_params.clone(),
+                two_to_one_params.clone(),
+                8,
+                folding_factor,
+                fold_optimisation,
+            ),
+        );
+    }
+}
+
+#[test]
+#[ignore = "no recording committed, see the module documentation"]
+fn golden_goldilocks_keccak() {
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) = keccak::default_config::<Field64>(&mut rng);
+    check(
+        "goldilocks_keccak_fold2",
+        record::<Field64, keccak::MerkleTreeParams<Field64>>(
+            leaf_hash_params,
+            two_to_one_params,
+            6,
+            2,
+            FoldType::ProverHelps,
+        ),
+    );
+}
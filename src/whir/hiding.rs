@@ -0,0 +1,337 @@
+//! Openings whose claimed evaluations stay hidden, for when WHIR feeds into a larger
+//! zero-knowledge argument.
+//!
+//! The prover commits to the evaluations `v_i = f(z_i)` with a salted hash, and only a random
+//! combination `s = m + sum_i alpha^i * v_i` is sent, where `m = g(1, z_0)` is the evaluation of
+//! the masked polynomial `g(X_0, X) = f(X) + X_0 * r(X)` at a random point `z_0` of the mask.
+//! As `r(z_0)` is uniform, so is `s`. The WHIR proof shows the linear claim on `g` behind `s`,
+//! and the larger argument proves, given the opening of the commitment, that `s` is consistent
+//! with it, see `HidingStatement::check_opening`. This reveals one more linear functional of
+//! the mask than `WhirConfig::zk_revealed_evaluations` accounts for.
+
+use ark_crypto_primitives::merkle_tree::Config;
+use ark_ff::{FftField, Field};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::iter;
+use nimue::{
+    plugins::ark::{FieldChallenges, FieldIOPattern, FieldReader, FieldWriter},
+    Arthur, ByteIOPattern, ByteReader, ByteWriter, IOPattern, Merlin, ProofError, ProofResult,
+};
+use rand::{CryptoRng, RngCore};
+
+use super::{
+    committer::Witness,
+    iopattern::WhirIOPattern,
+    parameters::WhirConfig,
+    prover::Prover,
+    verifier::{ParsedCommitment, Verifier},
+    LinearClaim, Statement, WhirProof,
+};
+use crate::{
+    errors::{WhirError, WhirResult},
+    poly_utils::{evals::EvaluationsList, MultilinearPoint},
+    sumcheck::prover_single::SumcheckSingle,
+    utils::expand_randomness,
+};
+
+pub trait HidingIOPattern<F: FftField> {
+    fn add_hiding_proof<MerkleConfig: Config, PowStrategy>(
+        self,
+        params: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    ) -> Self;
+}
+
+impl<F> HidingIOPattern<F> for IOPattern
+where
+    F: FftField,
+    IOPattern: WhirIOPattern<F> + FieldIOPattern<F>,
+{
+    fn add_hiding_proof<MerkleConfig: Config, PowStrategy>(
+        self,
+        params: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    ) -> Self {
+        self.add_bytes(32, "evaluations_commitment")
+            .add_scalars(params.num_variables(), "blinding_point")
+            .challenge_scalars(1, "hiding_combination_randomness")
+            .add_scalars(1, "hiding_sum")
+            .add_whir_proof(params)
+    }
+}
+
+/// The opening of the commitment to the evaluations, kept by the prover.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EvaluationsOpening<F: CanonicalSerialize + CanonicalDeserialize> {
+    pub evaluations: Vec<F>,
+    pub blinding_evaluation: F,
+    pub salt: [u8; 32],
+}
+
+impl<F: CanonicalSerialize + CanonicalDeserialize> EvaluationsOpening<F> {
+    pub fn commitment(&self) -> [u8; 32] {
+        let mut buf = self.salt.to_vec();
+        self.evaluations.serialize_compressed(&mut buf).unwrap();
+        self.blinding_evaluation
+            .serialize_compressed(&mut buf)
+            .unwrap();
+        *blake3::hash(&buf).as_bytes()
+    }
+}
+
+/// What the verifier learns: the claims are that the polynomial evaluates at `points` to the
+/// evaluations committed to in `commitment`.
+#[derive(Debug, Clone)]
+pub struct HidingStatement<F> {
+    pub points: Vec<MultilinearPoint<F>>,
+    pub commitment: [u8; 32],
+    pub blinding_point: MultilinearPoint<F>,
+    pub combination_randomness: F,
+    pub sum: F,
+}
+
+impl<F: Field> HidingStatement<F> {
+    /// The relation the larger argument has to prove about the opening of the commitment.
+    pub fn check_opening(&self, opening: &EvaluationsOpening<F>) -> bool {
+        let randomness = expand_randomness(self.combination_randomness, self.points.len() + 1);
+        opening.evaluations.len() == self.points.len()
+            && opening.commitment() == self.commitment
+            && self.sum
+                == iter::once(&opening.blinding_evaluation)
+                    .chain(&opening.evaluations)
+                    .zip(randomness)
+                    .map(|(eval, rand)| rand * eval)
+                    .sum::<F>()
+    }
+
+    // The linear claim on the masked polynomial, with weights
+    // eq((1, z_0), .) + sum_i alpha^i * eq((0, z_i), .).
+    fn masked_statement(&self) -> Statement<F> {
+        let num_variables = self.blinding_point.n_variables() + 1;
+        let randomness = expand_randomness(self.combination_randomness, self.points.len() + 1);
+        let mut weights = vec![F::ZERO; 1 << num_variables];
+        for ((mask_coordinate, point), rand) in iter::once((F::ONE, &self.blinding_point))
+            .chain(self.points.iter().map(|point| (F::ZERO, point)))
+            .zip(randomness)
+        {
+            let point: Vec<_> = iter::once(mask_coordinate)
+                .chain(point.0.iter().copied())
+                .collect();
+            SumcheckSingle::eval_eq(&point, &mut weights, rand);
+        }
+        let mut statement = Statement::new(vec![], vec![]);
+        statement.linear_claims.push(LinearClaim {
+            weights: EvaluationsList::new(weights),
+            sum: self.sum,
+        });
+        statement
+    }
+}
+
+pub struct HidingProver<F, MerkleConfig, PowStrategy>(WhirConfig<F, MerkleConfig, PowStrategy>)
+where
+    F: FftField,
+    MerkleConfig: Config;
+
+impl<F, MerkleConfig, PowStrategy> HidingProver<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    pub fn new(config: WhirConfig<F, MerkleConfig, PowStrategy>) -> Self {
+        assert!(
+            config.zk,
+            "hiding the evaluations needs the mask of ZK mode"
+        );
+        Self(config)
+    }
+
+    /// Proves that the committed polynomial evaluates to `evaluations` at `points`, sending only
+    /// a commitment to the evaluations. Returns its opening, for the larger argument.
+    pub fn prove(
+        &self,
+        merlin: &mut Merlin,
+        points: Vec<MultilinearPoint<F>>,
+        evaluations: Vec<F>,
+        witness: Witness<F, MerkleConfig>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> WhirResult<(EvaluationsOpening<F>, WhirProof<MerkleConfig, F>)>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        if points.len() != evaluations.len() {
+            return Err(WhirError::StatementLength {
+                points: points.len(),
+                found: evaluations.len(),
+            });
+        }
+        let blinding_point = MultilinearPoint::rand(rng, self.0.num_variables());
+        let blinding_evaluation = witness.polynomial.evaluate(&MultilinearPoint(
+            iter::once(F::ONE)
+                .chain(blinding_point.0.iter().copied())
+                .collect(),
+        ));
+        let mut salt = [0; 32];
+        rng.fill_bytes(&mut salt);
+        let opening = EvaluationsOpening {
+            evaluations,
+            blinding_evaluation,
+            salt,
+        };
+
+        let commitment = opening.commitment();
+        merlin.add_bytes(&commitment)?;
+        merlin.add_scalars(&blinding_point.0)?;
+        let [combination_randomness] = merlin.challenge_scalars()?;
+        let sum = iter::once(&opening.blinding_evaluation)
+            .chain(&opening.evaluations)
+            .zip(expand_randomness(combination_randomness, points.len() + 1))
+            .map(|(eval, rand)| rand * eval)
+            .sum();
+        merlin.add_scalars(&[sum])?;
+
+        let statement = HidingStatement {
+            points,
+            commitment,
+            blinding_point,
+            combination_randomness,
+            sum,
+        };
+        let proof =
+            Prover(self.0.clone()).prove_masked(merlin, statement.masked_statement(), witness)?;
+        Ok((opening, proof))
+    }
+}
+
+pub struct HidingVerifier<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    num_variables: usize,
+    verifier: Verifier<F, MerkleConfig, PowStrategy>,
+}
+
+impl<F, MerkleConfig, PowStrategy> HidingVerifier<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    pub fn new(config: WhirConfig<F, MerkleConfig, PowStrategy>) -> Self {
+        assert!(
+            config.zk,
+            "hiding the evaluations needs the mask of ZK mode"
+        );
+        HidingVerifier {
+            num_variables: config.num_variables(),
+            verifier: Verifier::new(config),
+        }
+    }
+
+    /// Verifies the proof for claims at `points`. On success, the committed polynomial evaluates
+    /// at `points` to the evaluations of any opening passing `HidingStatement::check_opening`.
+    pub fn verify(
+        &self,
+        arthur: &mut Arthur,
+        parsed_commitment: &ParsedCommitment<F, MerkleConfig::InnerDigest>,
+        points: Vec<MultilinearPoint<F>>,
+        whir_proof: &WhirProof<MerkleConfig, F>,
+    ) -> ProofResult<HidingStatement<F>> {
+        if points
+            .iter()
+            .any(|point| point.n_variables() != self.num_variables)
+        {
+            return Err(ProofError::InvalidIO);
+        }
+        let commitment: [u8; 32] = arthur.next_bytes()?;
+        let mut blinding_point = vec![F::ZERO; self.num_variables];
+        arthur.fill_next_scalars(&mut blinding_point)?;
+        let [combination_randomness] = arthur.challenge_scalars()?;
+        let [sum] = arthur.next_scalars()?;
+
+        let statement = HidingStatement {
+            points,
+            commitment,
+            blinding_point: MultilinearPoint(blinding_point),
+            combination_randomness,
+            sum,
+        };
+        self.verifier.verify_masked(
+            arthur,
+            parsed_commitment,
+            &statement.masked_statement(),
+            whir_proof,
+        )?;
+        Ok(statement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimue::DefaultHash;
+    use nimue_pow::blake3::Blake3PoW;
+
+    use super::*;
+    use crate::{
+        crypto::merkle_tree::blake3 as merkle_tree,
+        parameters::{MultivariateParameters, WhirParameters},
+        poly_utils::coeffs::CoefficientList,
+        whir::{
+            committer::Committer,
+            fixtures::{whir_parameters, MerkleConfig, F},
+        },
+    };
+
+    #[test]
+    fn test_hiding() {
+        let num_variables = 9;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params = WhirParameters::<MerkleConfig, Blake3PoW> {
+            zk: true,
+            ..whir_parameters((leaf_hash_params, two_to_one_params))
+        };
+        let params = WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+        let io = IOPattern::<DefaultHash>::new("🌪️")
+            .commit_statement(&params)
+            .add_hiding_proof(&params);
+
+        let polynomial = CoefficientList::new((0..1u64 << num_variables).map(F::from).collect());
+        let points: Vec<_> = (0..2)
+            .map(|_| MultilinearPoint::rand(&mut rng, num_variables))
+            .collect();
+        let evaluations: Vec<_> = points.iter().map(|p| polynomial.evaluate(p)).collect();
+
+        for wrong_evaluation in [false, true] {
+            let mut claimed = evaluations.clone();
+            if wrong_evaluation {
+                claimed[1] += F::ONE;
+            }
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit_with_rng(&mut merlin, polynomial.clone(), &mut rng)
+                .unwrap();
+            let (opening, proof) = HidingProver::new(params.clone())
+                .prove(&mut merlin, points.clone(), claimed, witness, &mut rng)
+                .unwrap();
+
+            let verifier = HidingVerifier::new(params.clone());
+            let mut arthur = io.to_arthur(merlin.transcript());
+            let commitment = verifier.verifier.parse_commitment(&mut arthur).unwrap();
+            let result = verifier.verify(&mut arthur, &commitment, points.clone(), &proof);
+            assert_eq!(result.is_ok(), !wrong_evaluation);
+            if let Ok(statement) = result {
+                assert!(statement.check_opening(&opening));
+                let mut tampered = opening.clone();
+                tampered.evaluations[0] += F::ONE;
+                assert!(!statement.check_opening(&tampered));
+            }
+        }
+    }
+}
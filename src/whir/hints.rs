@@ -0,0 +1,90 @@
+//! Hints appended to a `WhirProof`, which the verifier checks as it uses them, for verifiers
+//! whose field operations are expensive, e.g. in a circuit.
+//!
+//! With `FoldType::Naive`, the fold of a queried coset takes the inverse of its offset
+//! `offset * w^index`, which the verifier exponentiates for each query on top of the query point
+//! `(offset * w^index)^(2^k)`. The hints give the inverses: the verifier then computes the
+//! offsets of the sorted indexes from one another, by the powers of `w` of the gaps between them,
+//! squares each into the query point, and checks each inverse with one multiplication. The
+//! sorted leaf indexes and the deduplicated authentication paths are already in the openings of
+//! the proof, so they are not hinted; neither are the weights of the fold, which take no
+//! inversion once the offset's is given. The `whir` benchmark compares the verification of a
+//! proof with and without hints.
+//!
+//! The hints are computed from a valid proof by `Verifier::hint_proof`, so the prover needs no
+//! changes, and proofs without hints verify as before. `FoldType::ProverHelps` folds take no
+//! inverse, so their proofs get no hints.
+
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Validate, Write,
+};
+use ark_std::vec::Vec;
+
+use super::{read_len, read_vec};
+
+/// Version of the serialized layout of a `WhirProof` with hints: that of `PROOF_FORMAT_VERSION`
+/// after the version, then the hints. Proofs without hints keep `PROOF_FORMAT_VERSION`.
+pub const HINTED_PROOF_FORMAT_VERSION: u32 = 4;
+
+/// The hints of a proof, see the module documentation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofHints<F> {
+    // For each opening, the inverses of the offsets of the queried cosets, by increasing index.
+    pub(crate) coset_offsets_inv: Vec<Vec<F>>,
+}
+
+// Not derived, which would require `F: Default`.
+impl<F> Default for ProofHints<F> {
+    fn default() -> Self {
+        ProofHints {
+            coset_offsets_inv: Vec::new(),
+        }
+    }
+}
+
+impl<F: CanonicalSerialize + CanonicalDeserialize> ProofHints<F> {
+    /// Whether there are no hints, as for a proof fresh from the prover.
+    pub fn is_empty(&self) -> bool {
+        self.coset_offsets_inv.is_empty()
+    }
+
+    // The layout after the openings of `HINTED_PROOF_FORMAT_VERSION`: the number of openings,
+    // then for each the inverses as a vector.
+    pub(crate) fn write<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        (self.coset_offsets_inv.len() as u64).serialize_with_mode(&mut writer, compress)?;
+        for inverses in &self.coset_offsets_inv {
+            inverses.serialize_with_mode(&mut writer, compress)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn serialized_size(&self, compress: Compress) -> usize {
+        8 + self
+            .coset_offsets_inv
+            .iter()
+            .map(|inverses| inverses.serialized_size(compress))
+            .sum::<usize>()
+    }
+
+    // With `max_queries`, one bound per opening, every length is checked before anything is
+    // read for it.
+    pub(crate) fn read<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+        max_queries: Option<&[usize]>,
+    ) -> Result<Self, SerializationError> {
+        let num_openings = read_len(&mut reader, max_queries.map_or(usize::MAX, <[_]>::len))?;
+        let coset_offsets_inv = (0..num_openings)
+            .map(|opening| {
+                let max = max_queries.map_or(usize::MAX, |max| max[opening]);
+                read_vec(&mut reader, max, compress, validate)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(ProofHints { coset_offsets_inv })
+    }
+}
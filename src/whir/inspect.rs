@@ -0,0 +1,348 @@
+//! Decoding of a transcript and a proof into a report of what the prover sent, round by round,
+//! for debugging proofs produced by another implementation. Nothing is checked beyond the layout:
+//! the report of an invalid proof is still produced, as long as the transcript has the expected
+//! length.
+
+use std::fmt::{self, Display};
+
+use ark_crypto_primitives::merkle_tree::Config;
+use ark_ff::FftField;
+use ark_serialize::CanonicalSerialize;
+use nimue::{
+    plugins::ark::{FieldChallenges, FieldReader},
+    Arthur, ByteChallenges, ByteReader, IOPattern, ProofError, ProofResult,
+};
+
+use super::{parameters::WhirConfig, WhirProof};
+use crate::utils::Hex;
+
+/// What the prover sent, see `inspect`.
+#[derive(Debug, Clone)]
+pub struct Report<F> {
+    pub root: [u8; 32],
+    /// The answers of the OOD samples of the commitment, then of those left to the statement.
+    pub ood_answers: Vec<F>,
+    pub initial_sumcheck: Vec<SumcheckRound<F>>,
+    pub rounds: Vec<RoundReport<F>>,
+    pub final_coefficients: Vec<F>,
+    pub final_queries: Vec<usize>,
+    pub final_pow_nonce: Option<[u8; 8]>,
+    pub final_sumcheck: Vec<SumcheckRound<F>>,
+    /// The bytes of the openings of the final round.
+    pub final_openings_size: usize,
+    pub transcript_size: usize,
+    pub proof_size: usize,
+}
+
+/// A round of WHIR, opening the codeword committed in the previous round.
+#[derive(Debug, Clone)]
+pub struct RoundReport<F> {
+    pub root: [u8; 32],
+    pub ood_answers: Vec<F>,
+    pub queries: Vec<usize>,
+    pub pow_nonce: Option<[u8; 8]>,
+    pub sumcheck: Vec<SumcheckRound<F>>,
+    /// The bytes of the openings of the round.
+    pub openings_size: usize,
+}
+
+/// A round of sumcheck: the evaluations of its polynomial at 0, 1 and 2, the folding randomness
+/// and the PoW nonce.
+#[derive(Debug, Clone)]
+pub struct SumcheckRound<F> {
+    pub evaluations: [F; 3],
+    pub randomness: F,
+    pub pow_nonce: Option<[u8; 8]>,
+}
+
+// Reads a PoW without checking it. `challenge_pow` squeezes a 32-byte challenge and reads an
+// 8-byte nonce.
+fn read_pow(arthur: &mut Arthur, bits: f64) -> ProofResult<Option<[u8; 8]>> {
+    if bits <= 0. {
+        return Ok(None);
+    }
+    arthur.fill_challenge_bytes(&mut [0u8; 32])?;
+    Ok(Some(arthur.next_bytes()?))
+}
+
+fn read_sumcheck<F: FftField>(
+    arthur: &mut Arthur,
+    rounds: usize,
+    pow_bits: f64,
+) -> ProofResult<Vec<SumcheckRound<F>>> {
+    (0..rounds)
+        .map(|_| {
+            let evaluations: [F; 3] = arthur.next_scalars()?;
+            let [randomness] = arthur.challenge_scalars()?;
+            let pow_nonce = read_pow(arthur, pow_bits)?;
+            Ok(SumcheckRound {
+                evaluations,
+                randomness,
+                pow_nonce,
+            })
+        })
+        .collect()
+}
+
+fn read_ood<F: FftField>(arthur: &mut Arthur, samples: usize) -> ProofResult<Vec<F>> {
+    let mut ood_points = vec![F::ZERO; samples];
+    let mut ood_answers = vec![F::ZERO; samples];
+    if samples > 0 {
+        arthur.fill_challenge_scalars(&mut ood_points)?;
+        arthur.fill_next_scalars(&mut ood_answers)?;
+    }
+    Ok(ood_answers)
+}
+
+fn read_queries<F, MerkleConfig, PowStrategy>(
+    arthur: &mut Arthur,
+    params: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    num_queries: usize,
+    bound: usize,
+) -> ProofResult<Vec<usize>>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    let mut seed = [0u8; 32];
+    arthur.fill_challenge_bytes(&mut seed)?;
+    params.query_indexes(&seed, num_queries, bound)
+}
+
+/// Decodes the commitment and the proof of `transcript`, laid out by `io`, and the openings of
+/// `whir_proof`. Fails if the transcript is too short, or the proof has the wrong number of
+/// rounds.
+pub fn inspect<F, MerkleConfig, PowStrategy>(
+    params: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    io: &IOPattern,
+    transcript: &[u8],
+    whir_proof: &WhirProof<MerkleConfig, F>,
+) -> ProofResult<Report<F>>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+{
+    if whir_proof.0.len() != params.n_rounds() + 1 {
+        return Err(ProofError::InvalidProof);
+    }
+    let openings_size = |round: usize| {
+        let (merkle_proof, answers) = &whir_proof.0[round];
+        merkle_proof.compressed_size() + answers.compressed_size()
+    };
+    let mut arthur = io.to_arthur(transcript);
+
+    let root = arthur.next_bytes()?;
+    let mut ood_answers = read_ood(&mut arthur, params.committment_ood_samples)?;
+    if params.statement_ood_samples > 0 {
+        let mut coords = vec![F::ZERO; params.statement_ood_samples * params.num_variables()];
+        let mut answers = vec![F::ZERO; params.statement_ood_samples];
+        arthur.fill_challenge_scalars(&mut coords)?;
+        arthur.fill_next_scalars(&mut answers)?;
+        ood_answers.extend(answers);
+    }
+    let _: [F; 1] = arthur.challenge_scalars()?;
+    let initial_sumcheck = read_sumcheck(
+        &mut arthur,
+        params.folding_factor,
+        params.starting_folding_pow_bits,
+    )?;
+
+    let mut domain_size = params.starting_domain.size();
+    let mut rounds = Vec::with_capacity(params.n_rounds());
+    for (r, round_params) in params.round_parameters.iter().enumerate() {
+        let root = arthur.next_bytes()?;
+        let ood_answers = read_ood(&mut arthur, round_params.ood_samples)?;
+        let queries = read_queries(
+            &mut arthur,
+            params,
+            round_params.num_queries,
+            domain_size >> params.folding_factor,
+        )?;
+        let pow_nonce = read_pow(&mut arthur, round_params.pow_bits)?;
+        let _: [F; 1] = arthur.challenge_scalars()?;
+        let sumcheck = read_sumcheck(
+            &mut arthur,
+            params.folding_factor,
+            round_params.folding_pow_bits,
+        )?;
+        rounds.push(RoundReport {
+            root,
+            ood_answers,
+            queries,
+            pow_nonce,
+            sumcheck,
+            openings_size: openings_size(r),
+        });
+        domain_size /= 2;
+    }
+
+    let mut final_coefficients = vec![F::ZERO; 1 << params.final_sumcheck_rounds];
+    arthur.fill_next_scalars(&mut final_coefficients)?;
+    let final_queries = read_queries(
+        &mut arthur,
+        params,
+        params.final_queries,
+        domain_size >> params.folding_factor,
+    )?;
+    let final_pow_nonce = read_pow(&mut arthur, params.final_pow_bits)?;
+    let final_sumcheck = read_sumcheck(
+        &mut arthur,
+        params.final_sumcheck_rounds,
+        params.final_folding_pow_bits,
+    )?;
+
+    Ok(Report {
+        root,
+        ood_answers,
+        initial_sumcheck,
+        rounds,
+        final_coefficients,
+        final_queries,
+        final_pow_nonce,
+        final_sumcheck,
+        final_openings_size: openings_size(params.n_rounds()),
+        transcript_size: transcript.len(),
+        proof_size: whir_proof.compressed_size(),
+    })
+}
+
+fn fmt_list<T: Display>(f: &mut fmt::Formatter<'_>, items: &[T]) -> fmt::Result {
+    write!(f, "[")?;
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{item}")?;
+    }
+    write!(f, "]")
+}
+
+fn fmt_nonce(f: &mut fmt::Formatter<'_>, nonce: &Option<[u8; 8]>) -> fmt::Result {
+    match nonce {
+        Some(nonce) => writeln!(f, "  pow nonce: {}", Hex(nonce)),
+        None => Ok(()),
+    }
+}
+
+fn fmt_sumcheck<F: Display>(
+    f: &mut fmt::Formatter<'_>,
+    rounds: &[SumcheckRound<F>],
+) -> fmt::Result {
+    for (i, round) in rounds.iter().enumerate() {
+        write!(f, "  sumcheck {i}: ")?;
+        fmt_list(f, &round.evaluations)?;
+        write!(f, ", randomness {}", round.randomness)?;
+        match &round.pow_nonce {
+            Some(nonce) => writeln!(f, ", pow nonce {}", Hex(nonce)),
+            None => writeln!(f),
+        }?;
+    }
+    Ok(())
+}
+
+impl<F: Display> Display for Report<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "transcript: {} bytes, proof: {} bytes",
+            self.transcript_size, self.proof_size
+        )?;
+        writeln!(f, "commitment")?;
+        writeln!(f, "  root: {}", Hex(&self.root))?;
+        if !self.ood_answers.is_empty() {
+            write!(f, "  ood answers: ")?;
+            fmt_list(f, &self.ood_answers)?;
+            writeln!(f)?;
+        }
+        fmt_sumcheck(f, &self.initial_sumcheck)?;
+
+        for (r, round) in self.rounds.iter().enumerate() {
+            writeln!(f, "round {r}")?;
+            writeln!(f, "  root: {}", Hex(&round.root))?;
+            if !round.ood_answers.is_empty() {
+                write!(f, "  ood answers: ")?;
+                fmt_list(f, &round.ood_answers)?;
+                writeln!(f)?;
+            }
+            write!(f, "  queries: ")?;
+            fmt_list(f, &round.queries)?;
+            writeln!(f, ", openings: {} bytes", round.openings_size)?;
+            fmt_nonce(f, &round.pow_nonce)?;
+            fmt_sumcheck(f, &round.sumcheck)?;
+        }
+
+        writeln!(f, "final round")?;
+        write!(f, "  coefficients: ")?;
+        fmt_list(f, &self.final_coefficients)?;
+        writeln!(f)?;
+        write!(f, "  queries: ")?;
+        fmt_list(f, &self.final_queries)?;
+        writeln!(f, ", openings: {} bytes", self.final_openings_size)?;
+        fmt_nonce(f, &self.final_pow_nonce)?;
+        fmt_sumcheck(f, &self.final_sumcheck)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::inspect;
+    use crate::{
+        crypto::merkle_tree::blake3 as merkle_tree,
+        parameters::{MultivariateParameters, WhirParameters},
+        poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+        whir::{
+            committer::Committer,
+            fixtures::{io_pattern, whir_parameters, MerkleConfig, PowStrategy, F},
+            parameters::WhirConfig,
+            prover::Prover,
+            Statement,
+        },
+    };
+
+    #[test]
+    fn test_inspect() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+            pow_bits: 5,
+            ..whir_parameters((leaf_hash_params, two_to_one_params))
+        };
+        let params = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+        let io = io_pattern(&params);
+
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial)
+            .unwrap();
+        let root = witness.merkle_tree.root();
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement, witness)
+            .unwrap();
+
+        let report = inspect(&params, &io, merlin.transcript(), &proof).unwrap();
+        assert_eq!(report.root.as_ref(), root.as_ref());
+        assert_eq!(report.rounds.len(), params.n_rounds());
+        assert_eq!(report.initial_sumcheck.len(), 2);
+        assert_eq!(
+            report.final_coefficients.len(),
+            1 << params.final_sumcheck_rounds
+        );
+        for (round, (merkle_proof, _)) in report.rounds.iter().zip(&proof.0) {
+            assert_eq!(round.queries, merkle_proof.leaf_indexes);
+        }
+        assert_eq!(report.final_queries, proof.0.last().unwrap().0.leaf_indexes);
+        assert!(!report.to_string().is_empty());
+
+        // A truncated transcript cannot be decoded
+        let transcript = merlin.transcript();
+        assert!(inspect(&params, &io, &transcript[..transcript.len() - 1], &proof).is_err());
+    }
+}
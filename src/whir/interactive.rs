@@ -0,0 +1,885 @@
+//! WHIR as an interactive protocol, with explicit messages instead of a nimue transcript.
+//!
+//! The prover and the verifier are state machines, each step consuming the message of the other
+//! party: the prover starts with the root of its commitment, and the verifier answers every
+//! prover message with a challenge drawn from its own randomness, until it accepts. This allows
+//! to run WHIR inside an actually interactive protocol (e.g. an MPC or an optimistic protocol),
+//! or to derive the challenges with a custom Fiat-Shamir layer.
+//!
+//! The messages are those of the Fiat-Shamir prover, in the same order, except that the
+//! proofs of work are skipped and that the first polynomial of the final sumcheck comes with
+//! the final coefficients. As there is no grinding, the number of queries must not rely on the
+//! proofs of work, i.e. the configuration should be built with `pow_bits: 0`. Configurations
+//! leaving OOD samples to the statement are not supported.
+
+use ark_crypto_primitives::merkle_tree::{Config, MultiPath};
+use ark_ff::FftField;
+use ark_std::{mem, UniformRand};
+use nimue::{ProofError, ProofResult};
+use rand::{CryptoRng, Rng, RngCore};
+
+use super::{
+    committer::{Committer, Witness},
+    parameters::WhirConfig,
+    prover::Prover,
+    verifier::{FinalPolynomial, ParsedCommitment, ParsedProof, ParsedRound, Verifier},
+    Statement,
+};
+use crate::{
+    domain::Domain,
+    errors::{WhirError, WhirResult},
+    poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+    sumcheck::{proof::SumcheckPolynomial, prover_not_skipping::SumcheckProverNotSkipping},
+    utils::{self, expand_randomness},
+};
+
+// The OOD samples left to the statement are squeezed after the commitment, see
+// `WhirConfig::with_statement_ood_samples`, which has no message here.
+const STATEMENT_OOD_UNSUPPORTED: &str = "the OOD samples of the statement need a transcript";
+
+pub enum ProverMessage<F, MerkleConfig>
+where
+    MerkleConfig: Config,
+{
+    /// The root of the commitment to the next folded polynomial.
+    Root(MerkleConfig::InnerDigest),
+    /// The evaluations at the out-of-domain points.
+    OodAnswers(Vec<F>),
+    /// The evaluations at 0, 1 and 2 of the sumcheck polynomial.
+    SumcheckPolynomial([F; 3]),
+    /// The leaves at the query indexes, with their authentication paths.
+    Queries {
+        merkle_proof: MultiPath<MerkleConfig>,
+        answers: Vec<Vec<F>>,
+    },
+    /// The coefficients of the last folded polynomial, with the first polynomial of the final
+    /// sumcheck if there is one.
+    FinalCoefficients {
+        coefficients: Vec<F>,
+        sumcheck_polynomial: Option<[F; 3]>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifierMessage<F> {
+    OodPoints(Vec<F>),
+    /// The sorted and deduplicated indexes of the queried leaves.
+    QueryIndexes(Vec<usize>),
+    CombinationRandomness(F),
+    FoldingRandomness(F),
+}
+
+enum ProverPhase<F> {
+    CommitmentOod,
+    InitialCombination,
+    // `remaining` is the number of polynomials of the batch still to be sent
+    Sumcheck {
+        remaining: usize,
+    },
+    RoundOod,
+    RoundQueries,
+    RoundCombination {
+        points: Vec<MultilinearPoint<F>>,
+        evaluations: Vec<F>,
+    },
+    FinalQueries,
+    FinalSumcheck {
+        remaining: usize,
+    },
+    Done,
+}
+
+/// The prover, waiting for the next verifier message.
+pub struct ProverState<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    prover: Prover<F, MerkleConfig, PowStrategy>,
+    statement: Statement<F>,
+    phase: ProverPhase<F>,
+    round: usize,
+    // The last commitment, and the domain of its codeword
+    commitment: Witness<F, MerkleConfig>,
+    domain: Domain<F>,
+    // The commitment queried in the current round, when it is not the last one
+    queried: Option<(Witness<F, MerkleConfig>, Domain<F>)>,
+    sumcheck_prover: Option<SumcheckProverNotSkipping<F>>,
+    sumcheck_polynomial: Option<SumcheckPolynomial<F>>,
+    sumcheck_randomness: Vec<F>,
+    folding_randomness: MultilinearPoint<F>,
+}
+
+impl<F, MerkleConfig, PowStrategy> ProverState<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    /// Commits to `polynomial`, to prove `statement` on it. Returns the first message.
+    pub fn new(
+        config: WhirConfig<F, MerkleConfig, PowStrategy>,
+        statement: Statement<F>,
+        polynomial: CoefficientList<F::BasePrimeField>,
+    ) -> WhirResult<(Self, ProverMessage<F, MerkleConfig>)> {
+        if config.zk {
+            return Err(WhirError::InvalidParameters(
+                "the mask is sampled by `new_with_rng`",
+            ));
+        }
+        Self::commit(config, statement, polynomial)
+    }
+
+    /// Same as `new`, in ZK mode masking the polynomial with a random polynomial drawn from `rng`.
+    pub fn new_with_rng(
+        config: WhirConfig<F, MerkleConfig, PowStrategy>,
+        statement: Statement<F>,
+        polynomial: CoefficientList<F::BasePrimeField>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> WhirResult<(Self, ProverMessage<F, MerkleConfig>)> {
+        let polynomial = if config.zk {
+            Committer::new(config.clone()).mask_polynomial(polynomial, rng)?
+        } else {
+            polynomial
+        };
+        Self::commit(config, statement, polynomial)
+    }
+
+    fn commit(
+        config: WhirConfig<F, MerkleConfig, PowStrategy>,
+        statement: Statement<F>,
+        polynomial: CoefficientList<F::BasePrimeField>,
+    ) -> WhirResult<(Self, ProverMessage<F, MerkleConfig>)> {
+        if config.statement_ood_samples > 0 {
+            return Err(WhirError::InvalidParameters(STATEMENT_OOD_UNSUPPORTED));
+        }
+        statement.validate(&config)?;
+        let statement = config.mask_statement(&statement)?;
+        let prover = Prover(config);
+        prover.validate_parameters()?;
+        if polynomial.num_variables() != prover.0.mv_parameters.num_variables {
+            return Err(WhirError::NumVariables {
+                expected: prover.0.mv_parameters.num_variables,
+                found: polynomial.num_variables(),
+            });
+        }
+        let (merkle_tree, merkle_leaves) = Committer::new(prover.0.clone()).encode(&polynomial);
+        let root = merkle_tree.root();
+
+        let state = ProverState {
+            domain: prover.0.starting_domain.clone(),
+            prover,
+            statement,
+            phase: ProverPhase::CommitmentOod,
+            round: 0,
+            commitment: Witness {
+                polynomial: polynomial.to_extension(),
+                merkle_tree,
+                merkle_leaves,
+                ood_points: vec![],
+                ood_answers: vec![],
+                statement_ood: Statement::new(vec![], vec![]),
+            },
+            queried: None,
+            sumcheck_prover: None,
+            sumcheck_polynomial: None,
+            sumcheck_randomness: vec![],
+            folding_randomness: MultilinearPoint(vec![]),
+        };
+        Ok((state, ProverMessage::Root(root)))
+    }
+
+    /// A prover of `statement` on the same commitment, sharing its root and its codeword. Fails
+    /// once the prover has answered a challenge.
+    pub fn with_statement(&self, statement: Statement<F>) -> WhirResult<Self> {
+        if !matches!(self.phase, ProverPhase::CommitmentOod) {
+            return Err(WhirError::InvalidParameters(
+                "the prover has already answered a challenge",
+            ));
+        }
+        statement.validate(&self.prover.0)?;
+        Ok(ProverState {
+            prover: Prover(self.prover.0.clone()),
+            statement: self.prover.0.mask_statement(&statement)?,
+            phase: ProverPhase::CommitmentOod,
+            round: 0,
+            commitment: Witness {
+                polynomial: self.commitment.polynomial.clone(),
+                merkle_tree: self.commitment.merkle_tree.clone(),
+                merkle_leaves: self.commitment.merkle_leaves.clone(),
+                ood_points: vec![],
+                ood_answers: vec![],
+                statement_ood: Statement::new(vec![], vec![]),
+            },
+            domain: self.domain.clone(),
+            queried: None,
+            sumcheck_prover: None,
+            sumcheck_polynomial: None,
+            sumcheck_randomness: vec![],
+            folding_randomness: MultilinearPoint(vec![]),
+        })
+    }
+
+    /// Whether the prover has sent its last message.
+    pub fn is_done(&self) -> bool {
+        matches!(self.phase, ProverPhase::Done)
+    }
+
+    /// Answers the verifier message. Fails if it is not the expected one, or if it asks for
+    /// leaves out of the domain.
+    pub fn next(
+        mut self,
+        message: VerifierMessage<F>,
+    ) -> ProofResult<(Self, ProverMessage<F, MerkleConfig>)> {
+        let phase = mem::replace(&mut self.phase, ProverPhase::Done);
+        let message = match (phase, message) {
+            (ProverPhase::CommitmentOod, VerifierMessage::OodPoints(points))
+            | (ProverPhase::RoundOod, VerifierMessage::OodPoints(points)) => {
+                let num_variables = self.commitment.polynomial.num_variables();
+                let answers: Vec<_> = points
+                    .iter()
+                    .map(|point| {
+                        self.commitment.polynomial.evaluate(
+                            &MultilinearPoint::expand_from_univariate(*point, num_variables),
+                        )
+                    })
+                    .collect();
+                self.commitment.ood_points = points;
+                self.commitment.ood_answers = answers.clone();
+                self.phase = if self.round == 0 {
+                    ProverPhase::InitialCombination
+                } else {
+                    ProverPhase::RoundQueries
+                };
+                ProverMessage::OodAnswers(answers)
+            }
+            (ProverPhase::InitialCombination, VerifierMessage::CombinationRandomness(gen)) => {
+                let sumcheck_prover = self.prover.initial_sumcheck_prover(
+                    &self.statement,
+                    self.commitment.polynomial.clone(),
+                    &self.commitment.ood_points,
+                    &self.commitment.ood_answers,
+                    gen,
+                );
+                self.start_sumcheck(sumcheck_prover)
+            }
+            (ProverPhase::Sumcheck { remaining }, VerifierMessage::FoldingRandomness(r)) => {
+                self.compress(r);
+                if remaining > 0 {
+                    self.phase = ProverPhase::Sumcheck {
+                        remaining: remaining - 1,
+                    };
+                    self.send_sumcheck_polynomial()
+                } else {
+                    self.end_sumcheck()
+                }
+            }
+            (ProverPhase::RoundQueries, VerifierMessage::QueryIndexes(indexes)) => {
+                let (queried, domain) = self.queried.as_ref().unwrap();
+                let (merkle_proof, answers) = self.open(queried, domain, &indexes)?;
+                let cosets = self
+                    .prover
+                    .0
+                    .query_cosets(&indexes, &merkle_proof.leaf_indexes, &answers)
+                    .unwrap();
+                let folds =
+                    self.prover
+                        .fold_answers(domain, &self.folding_randomness, &indexes, &cosets);
+                let num_variables = self.commitment.polynomial.num_variables();
+                let folded_domain = domain.scale(1 << self.prover.0.folding_factor);
+                let points = self
+                    .commitment
+                    .ood_points
+                    .iter()
+                    .copied()
+                    .chain(indexes.iter().map(|i| folded_domain.element(*i)))
+                    .map(|univariate| {
+                        MultilinearPoint::expand_from_univariate(univariate, num_variables)
+                    })
+                    .collect();
+                let evaluations = self
+                    .commitment
+                    .ood_answers
+                    .iter()
+                    .copied()
+                    .chain(folds)
+                    .collect();
+                self.phase = ProverPhase::RoundCombination {
+                    points,
+                    evaluations,
+                };
+                ProverMessage::Queries {
+                    merkle_proof,
+                    answers,
+                }
+            }
+            (
+                ProverPhase::RoundCombination {
+                    points,
+                    evaluations,
+                },
+                VerifierMessage::CombinationRandomness(gen),
+            ) => {
+                let combination_randomness = expand_randomness(gen, points.len());
+                let mut sumcheck_prover = self.sumcheck_prover.take().unwrap();
+                sumcheck_prover.add_new_equality(&points, &combination_randomness, &evaluations);
+                self.queried = None;
+                self.start_sumcheck(sumcheck_prover)
+            }
+            (ProverPhase::FinalQueries, VerifierMessage::QueryIndexes(indexes)) => {
+                let (merkle_proof, answers) =
+                    self.open(&self.commitment, &self.domain, &indexes)?;
+                let remaining = self.prover.0.final_sumcheck_rounds.saturating_sub(1);
+                self.phase = if remaining > 0 {
+                    ProverPhase::FinalSumcheck { remaining }
+                } else {
+                    ProverPhase::Done
+                };
+                ProverMessage::Queries {
+                    merkle_proof,
+                    answers,
+                }
+            }
+            (ProverPhase::FinalSumcheck { remaining }, VerifierMessage::FoldingRandomness(r)) => {
+                self.compress(r);
+                self.phase = if remaining > 1 {
+                    ProverPhase::FinalSumcheck {
+                        remaining: remaining - 1,
+                    }
+                } else {
+                    ProverPhase::Done
+                };
+                self.send_sumcheck_polynomial()
+            }
+            _ => return Err(ProofError::InvalidIO),
+        };
+        Ok((self, message))
+    }
+
+    fn start_sumcheck(
+        &mut self,
+        sumcheck_prover: SumcheckProverNotSkipping<F>,
+    ) -> ProverMessage<F, MerkleConfig> {
+        self.sumcheck_prover = Some(sumcheck_prover);
+        self.phase = ProverPhase::Sumcheck {
+            remaining: self.prover.0.folding_factor - 1,
+        };
+        self.send_sumcheck_polynomial()
+    }
+
+    fn send_sumcheck_polynomial(&mut self) -> ProverMessage<F, MerkleConfig> {
+        let sumcheck_polynomial = self
+            .sumcheck_prover
+            .as_ref()
+            .unwrap()
+            .compute_sumcheck_polynomial();
+        let evaluations = sumcheck_polynomial.evaluations().try_into().unwrap();
+        self.sumcheck_polynomial = Some(sumcheck_polynomial);
+        ProverMessage::SumcheckPolynomial(evaluations)
+    }
+
+    fn compress(&mut self, folding_randomness: F) {
+        let sumcheck_polynomial = self.sumcheck_polynomial.take().unwrap();
+        self.sumcheck_prover
+            .as_mut()
+            .unwrap()
+            .compress(folding_randomness, &sumcheck_polynomial);
+        self.sumcheck_randomness.push(folding_randomness);
+    }
+
+    // Folds the polynomial, and either commits to the folded polynomial or sends it
+    fn end_sumcheck(&mut self) -> ProverMessage<F, MerkleConfig> {
+        let mut randomness = mem::take(&mut self.sumcheck_randomness);
+        randomness.reverse();
+        self.folding_randomness = MultilinearPoint(randomness);
+        let folded_coefficients = self.commitment.polynomial.fold(&self.folding_randomness);
+
+        if self.round == self.prover.0.n_rounds() {
+            let sumcheck_polynomial = if self.prover.0.final_sumcheck_rounds > 0 {
+                match self.send_sumcheck_polynomial() {
+                    ProverMessage::SumcheckPolynomial(evaluations) => Some(evaluations),
+                    _ => unreachable!(),
+                }
+            } else {
+                None
+            };
+            self.phase = ProverPhase::FinalQueries;
+            return ProverMessage::FinalCoefficients {
+                coefficients: folded_coefficients.coeffs().to_vec(),
+                sumcheck_polynomial,
+            };
+        }
+
+        let new_domain = self.domain.scale(2);
+        let (merkle_tree, merkle_leaves) =
+            self.prover
+                .commit_folded(self.round + 1, &folded_coefficients, &new_domain);
+        let root = merkle_tree.root();
+        let queried = mem::replace(
+            &mut self.commitment,
+            Witness {
+                polynomial: folded_coefficients,
+                merkle_tree,
+                merkle_leaves,
+                ood_points: vec![],
+                ood_answers: vec![],
+                statement_ood: Statement::new(vec![], vec![]),
+            },
+        );
+        self.queried = Some((queried, mem::replace(&mut self.domain, new_domain)));
+        self.round += 1;
+        self.phase = ProverPhase::RoundOod;
+        ProverMessage::Root(root)
+    }
+
+    fn open(
+        &self,
+        witness: &Witness<F, MerkleConfig>,
+        domain: &Domain<F>,
+        indexes: &[usize],
+    ) -> ProofResult<(MultiPath<MerkleConfig>, Vec<Vec<F>>)> {
+        let folded_size = domain.folded_size(self.prover.0.folding_factor);
+        if indexes.is_empty()
+            || indexes.windows(2).any(|w| w[0] >= w[1])
+            || indexes[indexes.len() - 1] >= folded_size
+        {
+            return Err(ProofError::InvalidIO);
+        }
+        let (merkle_proof, answers) =
+            self.prover
+                .open(&witness.merkle_tree, &witness.merkle_leaves, indexes);
+        Ok((
+            merkle_proof,
+            answers.iter().map(|leaf| leaf.to_vec()).collect(),
+        ))
+    }
+}
+
+enum VerifierPhase<F> {
+    Commitment,
+    CommitmentOod,
+    // `remaining` is the number of polynomials of the batch still to be received
+    Sumcheck { remaining: usize },
+    Root,
+    RoundOod,
+    RoundQueries,
+    FinalCoefficients,
+    FinalQueries { sumcheck_polynomial: Option<[F; 3]> },
+    FinalSumcheck,
+    Done,
+}
+
+/// The verifier, waiting for the next prover message.
+pub struct VerifierState<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    config: WhirConfig<F, MerkleConfig, PowStrategy>,
+    statement: Statement<F>,
+    phase: VerifierPhase<F>,
+    commitment: ParsedCommitment<F, MerkleConfig::InnerDigest>,
+    // The root queried in the current round
+    prev_root: MerkleConfig::InnerDigest,
+    new_root: Option<MerkleConfig::InnerDigest>,
+    initial_combination_randomness: Vec<F>,
+    initial_sumcheck_rounds: Vec<(SumcheckPolynomial<F>, F)>,
+    sumcheck_rounds: Vec<(SumcheckPolynomial<F>, F)>,
+    folding_randomness: MultilinearPoint<F>,
+    rounds: Vec<ParsedRound<F>>,
+    query_indexes: Vec<usize>,
+    final_coefficients: Vec<F>,
+    final_randomness_answers: Vec<Vec<F>>,
+    exp_domain_gen: F,
+    domain_gen_inv: F,
+    exp_domain_offset: F,
+    domain_offset_inv: F,
+    domain_size: usize,
+}
+
+impl<F, MerkleConfig, PowStrategy> VerifierState<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    /// The verifier of `statement`, waiting for the root of the commitment.
+    pub fn new(
+        config: WhirConfig<F, MerkleConfig, PowStrategy>,
+        statement: &Statement<F>,
+    ) -> ProofResult<Self> {
+        if config.statement_ood_samples > 0 {
+            return Err(WhirError::InvalidParameters(STATEMENT_OOD_UNSUPPORTED).into());
+        }
+        statement.validate(&config)?;
+        let statement = config.mask_statement(statement)?;
+        let domain_gen = config.starting_domain.backing_domain.group_gen();
+        let domain_offset = config.starting_domain.offset();
+        Ok(VerifierState {
+            statement,
+            phase: VerifierPhase::Commitment,
+            commitment: ParsedCommitment {
+                root: [0; 32].into(),
+                ood_points: vec![],
+                ood_answers: vec![],
+                statement_ood: Statement::new(vec![], vec![]),
+            },
+            prev_root: [0; 32].into(),
+            new_root: None,
+            initial_combination_randomness: vec![],
+            initial_sumcheck_rounds: vec![],
+            sumcheck_rounds: vec![],
+            folding_randomness: MultilinearPoint(vec![]),
+            rounds: vec![],
+            query_indexes: vec![],
+            final_coefficients: vec![],
+            final_randomness_answers: vec![],
+            exp_domain_gen: domain_gen.pow([1 << config.folding_factor]),
+            domain_gen_inv: config.starting_domain.backing_domain.group_gen_inv(),
+            exp_domain_offset: domain_offset.pow([1 << config.folding_factor]),
+            domain_offset_inv: config.starting_domain.backing_domain.coset_offset_inv(),
+            domain_size: config.starting_domain.size(),
+            config,
+        })
+    }
+
+    /// Answers the prover message with a challenge drawn from `rng`. Returns no challenge once
+    /// the proof is accepted, and fails if it is rejected.
+    pub fn next(
+        mut self,
+        message: ProverMessage<F, MerkleConfig>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> ProofResult<(Self, Option<VerifierMessage<F>>)> {
+        let folding_factor = self.config.folding_factor;
+        let phase = mem::replace(&mut self.phase, VerifierPhase::Done);
+        let challenge = match (phase, message) {
+            (VerifierPhase::Commitment, ProverMessage::Root(root)) => {
+                let ood_points: Vec<_> = (0..self.config.committment_ood_samples)
+                    .map(|_| F::rand(rng))
+                    .collect();
+                self.commitment.root = root.clone();
+                self.commitment.ood_points = ood_points.clone();
+                self.prev_root = root;
+                self.phase = VerifierPhase::CommitmentOod;
+                VerifierMessage::OodPoints(ood_points)
+            }
+            (VerifierPhase::CommitmentOod, ProverMessage::OodAnswers(answers)) => {
+                if answers.len() != self.commitment.ood_points.len() {
+                    return Err(ProofError::InvalidProof);
+                }
+                self.commitment.ood_answers = answers;
+                let gen = F::rand(rng);
+                self.initial_combination_randomness = expand_randomness(
+                    gen,
+                    self.commitment.ood_points.len()
+                        + self.statement.points.len()
+                        + self.statement.linear_claims.len(),
+                );
+                self.phase = VerifierPhase::Sumcheck {
+                    remaining: folding_factor,
+                };
+                VerifierMessage::CombinationRandomness(gen)
+            }
+            (VerifierPhase::Sumcheck { remaining }, ProverMessage::SumcheckPolynomial(evals)) => {
+                let r = F::rand(rng);
+                self.sumcheck_rounds
+                    .push((SumcheckPolynomial::new(evals.to_vec(), 1), r));
+                self.phase = if remaining > 1 {
+                    VerifierPhase::Sumcheck {
+                        remaining: remaining - 1,
+                    }
+                } else {
+                    let sumcheck_rounds = mem::take(&mut self.sumcheck_rounds);
+                    self.folding_randomness =
+                        MultilinearPoint(sumcheck_rounds.iter().map(|&(_, r)| r).rev().collect());
+                    match self.rounds.last_mut() {
+                        Some(round) => round.sumcheck_rounds = sumcheck_rounds,
+                        None => self.initial_sumcheck_rounds = sumcheck_rounds,
+                    }
+                    if self.rounds.len() < self.config.n_rounds() {
+                        VerifierPhase::Root
+                    } else {
+                        VerifierPhase::FinalCoefficients
+                    }
+                };
+                VerifierMessage::FoldingRandomness(r)
+            }
+            (VerifierPhase::Root, ProverMessage::Root(root)) => {
+                let ood_samples = self.config.round_parameters[self.rounds.len()].ood_samples;
+                let ood_points: Vec<_> = (0..ood_samples).map(|_| F::rand(rng)).collect();
+                self.rounds.push(ParsedRound {
+                    folding_randomness: self.folding_randomness.clone(),
+                    ood_points: ood_points.clone(),
+                    ood_answers: vec![],
+                    stir_challenges_indexes: vec![],
+                    stir_challenges_points: vec![],
+                    stir_challenges_answers: vec![],
+                    combination_randomness: vec![],
+                    sumcheck_rounds: vec![],
+                    domain_gen_inv: self.domain_gen_inv,
+                    domain_offset_inv: self.domain_offset_inv,
+                });
+                self.new_root = Some(root);
+                self.phase = VerifierPhase::RoundOod;
+                VerifierMessage::OodPoints(ood_points)
+            }
+            (VerifierPhase::RoundOod, ProverMessage::OodAnswers(answers)) => {
+                let num_queries = self.config.round_parameters[self.rounds.len() - 1].num_queries;
+                let round = self.rounds.last_mut().unwrap();
+                if answers.len() != round.ood_points.len() {
+                    return Err(ProofError::InvalidProof);
+                }
+                round.ood_answers = answers;
+                self.query_indexes = self.sample_indexes(num_queries, rng);
+                self.phase = VerifierPhase::RoundQueries;
+                VerifierMessage::QueryIndexes(self.query_indexes.clone())
+            }
+            (
+                VerifierPhase::RoundQueries,
+                ProverMessage::Queries {
+                    merkle_proof,
+                    answers,
+                },
+            ) => {
+                let tree = self.rounds.len() - 1;
+                let answers = self.check_queries(tree, &merkle_proof, &answers)?;
+                let indexes = mem::take(&mut self.query_indexes);
+                let gen = F::rand(rng);
+                let round = self.rounds.last_mut().unwrap();
+                round.stir_challenges_points = indexes
+                    .iter()
+                    .map(|index| self.exp_domain_offset * self.exp_domain_gen.pow([*index as u64]))
+                    .collect();
+                round.combination_randomness =
+                    expand_randomness(gen, indexes.len() + round.ood_points.len());
+                round.stir_challenges_indexes = indexes;
+                round.stir_challenges_answers = answers;
+
+                self.prev_root = self.new_root.take().unwrap();
+                self.exp_domain_gen = self.exp_domain_gen * self.exp_domain_gen;
+                self.domain_gen_inv = self.domain_gen_inv * self.domain_gen_inv;
+                self.exp_domain_offset = self.exp_domain_offset * self.exp_domain_offset;
+                self.domain_offset_inv = self.domain_offset_inv * self.domain_offset_inv;
+                self.domain_size /= 2;
+                self.phase = VerifierPhase::Sumcheck {
+                    remaining: folding_factor,
+                };
+                VerifierMessage::CombinationRandomness(gen)
+            }
+            (
+                VerifierPhase::FinalCoefficients,
+                ProverMessage::FinalCoefficients {
+                    coefficients,
+                    sumcheck_polynomial,
+                },
+            ) => {
+                if coefficients.len() != 1 << self.config.final_sumcheck_rounds
+                    || sumcheck_polynomial.is_some() != (self.config.final_sumcheck_rounds > 0)
+                {
+                    return Err(ProofError::InvalidProof);
+                }
+                self.final_coefficients = coefficients;
+                self.query_indexes = self.sample_indexes(self.config.final_queries, rng);
+                self.phase = VerifierPhase::FinalQueries {
+                    sumcheck_polynomial,
+                };
+                VerifierMessage::QueryIndexes(self.query_indexes.clone())
+            }
+            (
+                VerifierPhase::FinalQueries {
+                    sumcheck_polynomial,
+                },
+                ProverMessage::Queries {
+                    merkle_proof,
+                    answers,
+                },
+            ) => {
+                self.final_randomness_answers =
+                    self.check_queries(self.rounds.len(), &merkle_proof, &answers)?;
+                match sumcheck_polynomial {
+                    Some(evals) => return self.final_sumcheck(evals, rng),
+                    None => return self.finish(),
+                }
+            }
+            (VerifierPhase::FinalSumcheck, ProverMessage::SumcheckPolynomial(evals)) => {
+                return self.final_sumcheck(evals, rng);
+            }
+            _ => return Err(ProofError::InvalidProof),
+        };
+        Ok((self, Some(challenge)))
+    }
+
+    fn sample_indexes(&self, num_queries: usize, rng: &mut impl Rng) -> Vec<usize> {
+        let folded_domain_size = self.domain_size / (1 << self.config.folding_factor);
+        utils::dedup((0..num_queries).map(|_| utils::sample_index(rng, folded_domain_size)))
+    }
+
+    // Checks the opened leaves of tree `tree` (see `WhirConfig::merkle_params`), and returns the
+    // queried cosets in them.
+    fn check_queries(
+        &self,
+        tree: usize,
+        merkle_proof: &MultiPath<MerkleConfig>,
+        answers: &[Vec<F>],
+    ) -> ProofResult<Vec<Vec<F>>> {
+        let leaf_indexes = self.config.leaf_indexes(&self.query_indexes);
+        let (leaf_hash_params, two_to_one_params) = self.config.merkle_params(tree);
+        if merkle_proof.leaf_indexes != leaf_indexes
+            || answers.len() != leaf_indexes.len()
+            || answers
+                .iter()
+                .any(|answer| answer.len() != self.config.leaf_size())
+            || !merkle_proof
+                .verify(
+                    leaf_hash_params,
+                    two_to_one_params,
+                    &self.prev_root,
+                    answers.iter().map(|a| a.as_ref()),
+                )
+                .unwrap_or(false)
+        {
+            return Err(ProofError::InvalidProof);
+        }
+        let cosets = self
+            .config
+            .query_cosets(&self.query_indexes, &leaf_indexes, answers)
+            .ok_or(ProofError::InvalidProof)?;
+        Ok(cosets.into_iter().map(<[F]>::to_vec).collect())
+    }
+
+    fn final_sumcheck(
+        mut self,
+        evals: [F; 3],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> ProofResult<(Self, Option<VerifierMessage<F>>)> {
+        let r = F::rand(rng);
+        self.sumcheck_rounds
+            .push((SumcheckPolynomial::new(evals.to_vec(), 1), r));
+        if self.sumcheck_rounds.len() < self.config.final_sumcheck_rounds {
+            self.phase = VerifierPhase::FinalSumcheck;
+            return Ok((self, Some(VerifierMessage::FoldingRandomness(r))));
+        }
+        self.finish()
+    }
+
+    fn finish(mut self) -> ProofResult<(Self, Option<VerifierMessage<F>>)> {
+        let final_sumcheck_rounds = mem::take(&mut self.sumcheck_rounds);
+        let final_randomness_indexes = mem::take(&mut self.query_indexes);
+        let parsed = ParsedProof {
+            initial_combination_randomness: mem::take(&mut self.initial_combination_randomness),
+            initial_sumcheck_rounds: mem::take(&mut self.initial_sumcheck_rounds),
+            rounds: mem::take(&mut self.rounds),
+            final_domain_gen_inv: self.domain_gen_inv,
+            final_domain_offset_inv: self.domain_offset_inv,
+            final_randomness_points: final_randomness_indexes
+                .iter()
+                .map(|index| self.exp_domain_offset * self.exp_domain_gen.pow([*index as u64]))
+                .collect(),
+            final_randomness_indexes,
+            final_randomness_answers: mem::take(&mut self.final_randomness_answers),
+            final_folding_randomness: self.folding_randomness.clone(),
+            final_sumcheck_randomness: MultilinearPoint(
+                final_sumcheck_rounds
+                    .iter()
+                    .map(|&(_, r)| r)
+                    .rev()
+                    .collect(),
+            ),
+            final_sumcheck_rounds,
+            final_polynomial: FinalPolynomial::Coefficients(CoefficientList::new(mem::take(
+                &mut self.final_coefficients,
+            ))),
+            coset_offsets_inv: vec![],
+        };
+        Verifier::new(self.config.clone()).check_proof(
+            &self.commitment,
+            &self.statement,
+            &parsed,
+        )?;
+        self.phase = VerifierPhase::Done;
+        Ok((self, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimue_pow::blake3::Blake3PoW;
+
+    use super::*;
+    use crate::{
+        crypto::merkle_tree::blake3 as merkle_tree,
+        parameters::{FoldType, MultivariateParameters, WhirParameters},
+        whir::fixtures::{whir_parameters, MerkleConfig, F},
+    };
+
+    fn run(
+        config: &WhirConfig<F, MerkleConfig, Blake3PoW>,
+        prover_statement: Statement<F>,
+        verifier_statement: &Statement<F>,
+        polynomial: CoefficientList<F>,
+    ) -> ProofResult<()> {
+        let mut rng = ark_std::test_rng();
+        let (mut prover, mut message) =
+            ProverState::new_with_rng(config.clone(), prover_statement, polynomial, &mut rng)
+                .unwrap();
+        let mut verifier = VerifierState::new(config.clone(), verifier_statement)?;
+        loop {
+            let (new_verifier, challenge) = verifier.next(message, &mut rng)?;
+            verifier = new_verifier;
+            let Some(challenge) = challenge else {
+                assert!(prover.is_done());
+                return Ok(());
+            };
+            (prover, message) = prover.next(challenge)?;
+        }
+    }
+
+    #[test]
+    fn test_interactive() {
+        let num_variables = 7;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let polynomial = CoefficientList::new((0..1u64 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let evaluation = polynomial.evaluate(&point);
+        let statement = Statement::new(vec![point.clone()], vec![evaluation]);
+        let wrong_statement = Statement::new(vec![point], vec![evaluation + F::from(1)]);
+
+        for (folding_factor, fold_optimisation, zk) in [
+            (2, FoldType::ProverHelps, false),
+            (3, FoldType::Naive, false),
+            (2, FoldType::ProverHelps, true),
+        ] {
+            let whir_params = WhirParameters::<MerkleConfig, Blake3PoW> {
+                folding_factor,
+                fold_optimisation,
+                zk,
+                ..whir_parameters((leaf_hash_params.clone(), two_to_one_params.clone()))
+            };
+            let params = WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params,
+            )
+            .unwrap();
+
+            assert!(run(&params, statement.clone(), &statement, polynomial.clone()).is_ok());
+            assert!(run(
+                &params,
+                statement.clone(),
+                &wrong_statement,
+                polynomial.clone()
+            )
+            .is_err());
+
+            if !zk {
+                let (prover, _) =
+                    ProverState::new(params, statement.clone(), polynomial.clone()).unwrap();
+                assert!(prover
+                    .next(VerifierMessage::FoldingRandomness(F::from(1)))
+                    .is_err());
+            }
+        }
+    }
+}
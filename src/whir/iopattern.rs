@@ -1,19 +1,72 @@
 use ark_crypto_primitives::merkle_tree::Config;
 use ark_ff::FftField;
-use nimue::plugins::ark::*;
+use ark_std::{borrow::Cow, vec::Vec};
+use nimue::{plugins::ark::*, BytePublic, ProofError, ProofResult};
 
 use crate::{
     fs_utils::{OODIOPattern, WhirPoWIOPattern},
+    parameters::FinalForm,
     sumcheck::prover_not_skipping::SumcheckNotSkippingIOPattern,
+    utils::Hex,
 };
 
-use super::parameters::WhirConfig;
+use super::{parameters::WhirConfig, queries::ChaChaSampler, ClaimType, Statement};
+
+/// How the statement is bound to the transcript, between the commitment and the proof. It is
+/// absorbed as a public input, so it does not add to the transcript sent to the verifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementBinding {
+    /// The points and the evaluations of `num_claims` evaluation claims, absorbed with
+    /// `StatementTranscript::public_statement`.
+    Full { num_claims: usize },
+    /// A 32-byte digest of the statement computed by the caller, absorbed with
+    /// `StatementTranscript::public_statement_digest`. For outer protocols that already committed
+    /// to the statement, and for statements too large to absorb, such as linear claims.
+    Digest,
+}
+
+// Suffixes the label of the first operation with the application label of `params`, its coset
+// offset, the packing of its leaves, its query sampler and the form of its final polynomial, if
+// not the default ones. The labels are part of the IOPattern, which seeds the sponge.
+fn bind_label<'a, F: FftField, MerkleConfig: Config, PowStrategy>(
+    params: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    label: &'a str,
+) -> Cow<'a, str> {
+    let mut label = Cow::Borrowed(label);
+    if let Some(application_label) = &params.application_label {
+        label = Cow::Owned(format!("{label}:{application_label}"));
+    }
+    let offset = params.coset_offset();
+    if !offset.is_one() {
+        let mut bytes = Vec::new();
+        offset.serialize_compressed(&mut bytes).unwrap();
+        label = Cow::Owned(format!("{label}:coset={}", Hex(&bytes)));
+    }
+    if params.log_cosets_per_leaf > 0 {
+        label = Cow::Owned(format!("{label}:leaf=2^{}", params.log_cosets_per_leaf));
+    }
+    let sampler = params.query_sampler.name();
+    if sampler != ChaChaSampler::NAME {
+        label = Cow::Owned(format!("{label}:sampler={sampler}"));
+    }
+    if params.final_form != FinalForm::Coefficients {
+        label = Cow::Owned(format!("{label}:final={}", params.final_form));
+    }
+    label
+}
 
 pub trait WhirIOPattern<F: FftField> {
     fn commit_statement<MerkleConfig: Config, PowStrategy>(
         self,
         params: &WhirConfig<F, MerkleConfig, PowStrategy>,
     ) -> Self;
+    /// Binds the statement to the transcript, after `commit_statement`. Without it, the
+    /// statement is only bound through the claims the proof is checked against.
+    fn add_statement<MerkleConfig: Config, PowStrategy>(
+        self,
+        params: &WhirConfig<F, MerkleConfig, PowStrategy>,
+        binding: StatementBinding,
+    ) -> Self;
     fn add_whir_proof<MerkleConfig: Config, PowStrategy>(
         self,
         params: &WhirConfig<F, MerkleConfig, PowStrategy>,
@@ -34,8 +87,30 @@ where
         params: &WhirConfig<F, MerkleConfig, PowStrategy>,
     ) -> Self {
         // TODO: Add params
-        self.add_bytes(32, "merkle_digest")
-            .add_ood(params.committment_ood_samples)
+        let io = self
+            .add_bytes(32, &bind_label(params, "merkle_digest"))
+            .add_ood(params.committment_ood_samples);
+        // The OOD samples left to the statement, at points of the hypercube's extension
+        match params.statement_ood_samples {
+            0 => io,
+            n => io
+                .challenge_scalars(n * params.num_variables(), "statement_ood_query")
+                .add_scalars(n, "statement_ood_ans"),
+        }
+    }
+
+    fn add_statement<MerkleConfig: Config, PowStrategy>(
+        self,
+        params: &WhirConfig<F, MerkleConfig, PowStrategy>,
+        binding: StatementBinding,
+    ) -> Self {
+        match binding {
+            StatementBinding::Full { num_claims: 0 } => self,
+            StatementBinding::Full { num_claims } => {
+                self.add_scalars(num_claims * (params.num_variables() + 1), "statement")
+            }
+            StatementBinding::Digest => self.add_bytes(32, "statement_digest"),
+        }
     }
 
     fn add_whir_proof<MerkleConfig: Config, PowStrategy>(
@@ -43,23 +118,80 @@ where
         params: &WhirConfig<F, MerkleConfig, PowStrategy>,
     ) -> Self {
         // TODO: Add statement
+        let opening_sizes = params
+            .openings_in_transcript
+            .then(|| super::transcript_opening_sizes(params));
+        let add_opening = |io: Self, round: usize| match &opening_sizes {
+            Some(sizes) => io.add_bytes(sizes[round], "merkle_opening"),
+            None => io,
+        };
         self = self
-            .challenge_scalars(1, "initial_combination_randomness")
+            .challenge_scalars(1, &bind_label(params, "initial_combination_randomness"))
             .add_sumcheck(params.folding_factor, params.starting_folding_pow_bits);
 
-        for r in &params.round_parameters {
+        for (round, r) in params.round_parameters.iter().enumerate() {
             self = self
                 .add_bytes(32, "merkle_digest")
                 .add_ood(r.ood_samples)
-                .challenge_bytes(32, "stir_queries_seed")
+                .challenge_bytes(32, "stir_queries_seed");
+            self = add_opening(self, round)
                 .pow(r.pow_bits)
                 .challenge_scalars(1, "combination_randomness")
                 .add_sumcheck(params.folding_factor, r.folding_pow_bits);
         }
 
-        self.add_scalars(1 << params.final_sumcheck_rounds, "final_coeffs")
-            .challenge_bytes(32, "final_queries_seed")
+        let final_label = match params.final_form {
+            FinalForm::Coefficients => "final_coeffs",
+            FinalForm::Evaluations => "final_evals",
+        };
+        self = self
+            .add_scalars(1 << params.final_sumcheck_rounds, final_label)
+            .challenge_bytes(32, "final_queries_seed");
+        add_opening(self, params.n_rounds())
             .pow(params.final_pow_bits)
             .add_sumcheck(params.final_sumcheck_rounds, params.final_folding_pow_bits)
     }
 }
+
+/// Absorbs the statement as laid out by `WhirIOPattern::add_statement`, on the side of the
+/// prover (after committing) and of the verifier (after `Verifier::parse_commitment`) alike.
+pub trait StatementTranscript<F> {
+    /// Absorbs the points and then the evaluations, for `StatementBinding::Full`. Fails if the
+    /// statement has claims other than evaluation claims, which must be bound with a digest.
+    fn public_statement(&mut self, statement: &Statement<F>) -> ProofResult<()>;
+
+    /// Absorbs `digest`, for `StatementBinding::Digest`.
+    fn public_statement_digest(&mut self, digest: &[u8; 32]) -> ProofResult<()>;
+}
+
+impl<F, T> StatementTranscript<F> for T
+where
+    F: FftField,
+    T: FieldPublic<F> + BytePublic,
+{
+    fn public_statement(&mut self, statement: &Statement<F>) -> ProofResult<()> {
+        if !statement.linear_claims.is_empty()
+            || statement
+                .claim_types
+                .iter()
+                .any(|claim_type| *claim_type != ClaimType::Evaluation)
+        {
+            return Err(ProofError::InvalidProof);
+        }
+        let points: Vec<F> = statement
+            .points
+            .iter()
+            .flat_map(|point| point.0.iter().copied())
+            .collect();
+        if !points.is_empty() {
+            self.public_scalars(&points)?;
+            self.public_scalars(&statement.evaluations)?;
+        }
+        Ok(())
+    }
+
+    fn public_statement_digest(&mut self, digest: &[u8; 32]) -> ProofResult<()> {
+        self.public_bytes(digest)?;
+        Ok(())
+    }
+}
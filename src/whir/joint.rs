@@ -0,0 +1,254 @@
+//! Proofs of several statements on one commitment, run in lockstep with the same challenges, so
+//! that they share their folding randomness and can later be aggregated.
+//!
+//! The provers of the statements are the interactive provers of `interactive`, which take their
+//! challenges from the caller: `JointProver` forwards each challenge to all of them, and
+//! `JointVerifier` answers the messages of all the provers with a single challenge. To run the
+//! proofs non-interactively, the challenges must be derived from a joint transcript, i.e. the
+//! randomness given to `JointVerifier::next` must depend on the messages of all the provers so
+//! far (and not on those of one proof only), as a Fiat-Shamir layer over the joint protocol.
+//!
+//! Each proof on its own is a proof of its statement, accepted by a `VerifierState` sent the
+//! shared challenges. The proofs share the root and the out-of-domain answers of the commitment,
+//! and from then on differ as their statements do.
+
+use ark_crypto_primitives::merkle_tree::Config;
+use ark_ff::FftField;
+use ark_std::vec::Vec;
+use nimue::{ProofError, ProofResult};
+use rand::{CryptoRng, RngCore, SeedableRng};
+
+use super::{
+    interactive::{ProverMessage, ProverState, VerifierMessage, VerifierState},
+    parameters::WhirConfig,
+    Statement,
+};
+use crate::{
+    errors::{WhirError, WhirResult},
+    poly_utils::coeffs::CoefficientList,
+};
+
+/// The provers of several statements on the same polynomial, waiting for the next challenge.
+pub struct JointProver<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    provers: Vec<ProverState<F, MerkleConfig, PowStrategy>>,
+}
+
+impl<F, MerkleConfig, PowStrategy> JointProver<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    /// Commits once to `polynomial`, to prove each of `statements` on it. Returns the first
+    /// messages of the proofs, the root of the commitment.
+    pub fn new(
+        config: WhirConfig<F, MerkleConfig, PowStrategy>,
+        statements: Vec<Statement<F>>,
+        polynomial: CoefficientList<F::BasePrimeField>,
+    ) -> WhirResult<(Self, Vec<ProverMessage<F, MerkleConfig>>)> {
+        let mut statements = statements.into_iter();
+        let first = statements.next().ok_or(WhirError::InvalidParameters(
+            "there is no statement to prove",
+        ))?;
+        let (prover, message) = ProverState::new(config, first, polynomial)?;
+        let ProverMessage::Root(root) = message else {
+            unreachable!("the first message is the root")
+        };
+        let mut provers = vec![];
+        for statement in statements {
+            provers.push(prover.with_statement(statement)?);
+        }
+        provers.insert(0, prover);
+        let messages = provers
+            .iter()
+            .map(|_| ProverMessage::Root(root.clone()))
+            .collect();
+        Ok((JointProver { provers }, messages))
+    }
+
+    pub fn num_proofs(&self) -> usize {
+        self.provers.len()
+    }
+
+    /// Whether the provers have sent their last messages.
+    pub fn is_done(&self) -> bool {
+        self.provers.iter().all(ProverState::is_done)
+    }
+
+    /// Answers the shared challenge in every proof, and returns the messages of the provers, in
+    /// the order of the statements.
+    pub fn next(
+        self,
+        message: VerifierMessage<F>,
+    ) -> ProofResult<(Self, Vec<ProverMessage<F, MerkleConfig>>)> {
+        let (provers, messages) = self
+            .provers
+            .into_iter()
+            .map(|prover| prover.next(message.clone()))
+            .collect::<ProofResult<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        Ok((JointProver { provers }, messages))
+    }
+}
+
+/// The verifiers of several statements on the same commitment, waiting for the next messages.
+pub struct JointVerifier<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    verifiers: Vec<VerifierState<F, MerkleConfig, PowStrategy>>,
+}
+
+impl<F, MerkleConfig, PowStrategy> JointVerifier<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    /// The verifiers of `statements`, waiting for the root of the commitment.
+    pub fn new(
+        config: WhirConfig<F, MerkleConfig, PowStrategy>,
+        statements: &[Statement<F>],
+    ) -> ProofResult<Self> {
+        if statements.is_empty() {
+            return Err(ProofError::InvalidIO);
+        }
+        let verifiers = statements
+            .iter()
+            .map(|statement| VerifierState::new(config.clone(), statement))
+            .collect::<ProofResult<_>>()?;
+        Ok(JointVerifier { verifiers })
+    }
+
+    /// Answers the messages of all the provers, in the order of the statements, with a challenge
+    /// drawn from `rng` and shared by all the proofs. Returns no challenge once all the proofs
+    /// are accepted, and fails if one of them is rejected.
+    pub fn next(
+        self,
+        messages: Vec<ProverMessage<F, MerkleConfig>>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> ProofResult<(Self, Option<VerifierMessage<F>>)> {
+        if messages.len() != self.verifiers.len() {
+            return Err(ProofError::InvalidProof);
+        }
+        // The verifiers are in the same phase, and draw the same challenge from the same seed
+        let mut seed = [0; 32];
+        rng.fill_bytes(&mut seed);
+        let mut verifiers = Vec::with_capacity(self.verifiers.len());
+        let mut challenges = Vec::with_capacity(self.verifiers.len());
+        for (verifier, message) in self.verifiers.into_iter().zip(messages) {
+            let mut rng = rand_chacha::ChaCha20Rng::from_seed(seed);
+            let (verifier, challenge) = verifier.next(message, &mut rng)?;
+            verifiers.push(verifier);
+            challenges.push(challenge);
+        }
+        if challenges.windows(2).any(|pair| pair[0] != pair[1]) {
+            return Err(ProofError::InvalidProof);
+        }
+        Ok((JointVerifier { verifiers }, challenges.swap_remove(0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimue_pow::blake3::Blake3PoW;
+
+    use super::*;
+    use crate::{
+        crypto::merkle_tree::blake3 as merkle_tree,
+        parameters::MultivariateParameters,
+        poly_utils::MultilinearPoint,
+        whir::fixtures::{whir_parameters, MerkleConfig, F},
+    };
+
+    #[test]
+    fn test_joint_proofs() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params =
+            whir_parameters::<MerkleConfig, Blake3PoW>((leaf_hash_params, two_to_one_params));
+        let params = WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+        let polynomial = CoefficientList::new((0..1u64 << num_variables).map(F::from).collect());
+        let statements: Vec<_> = (0..3)
+            .map(|_| {
+                let point = MultilinearPoint::rand(&mut rng, num_variables);
+                Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)])
+            })
+            .collect();
+
+        // The joint verifier draws a seed per step, from which every proof takes its challenge
+        let joint_rng = rand_chacha::ChaCha20Rng::seed_from_u64(1);
+        let (mut prover, mut messages) =
+            JointProver::new(params.clone(), statements.clone(), polynomial.clone()).unwrap();
+        assert_eq!(prover.num_proofs(), 3);
+        let mut verifier = JointVerifier::new(params.clone(), &statements).unwrap();
+        let mut challenges = vec![];
+        let mut verifier_rng = joint_rng.clone();
+        loop {
+            let (new_verifier, challenge) = verifier.next(messages, &mut verifier_rng).unwrap();
+            verifier = new_verifier;
+            let Some(challenge) = challenge else {
+                assert!(prover.is_done());
+                break;
+            };
+            challenges.push(challenge.clone());
+            (prover, messages) = prover.next(challenge).unwrap();
+        }
+        assert!(challenges
+            .iter()
+            .any(|challenge| matches!(challenge, VerifierMessage::FoldingRandomness(_))));
+
+        // Each proof is the proof of its statement with the shared challenges
+        for statement in &statements {
+            let mut single_rng = joint_rng.clone();
+            let (mut prover, mut message) =
+                ProverState::new(params.clone(), statement.clone(), polynomial.clone()).unwrap();
+            let mut verifier = VerifierState::new(params.clone(), statement).unwrap();
+            for shared in &challenges {
+                let mut seed = [0; 32];
+                single_rng.fill_bytes(&mut seed);
+                let mut rng = rand_chacha::ChaCha20Rng::from_seed(seed);
+                let (new_verifier, challenge) = verifier.next(message, &mut rng).unwrap();
+                verifier = new_verifier;
+                assert_eq!(challenge.as_ref(), Some(shared));
+                (prover, message) = prover.next(challenge.unwrap()).unwrap();
+            }
+            let mut seed = [0; 32];
+            single_rng.fill_bytes(&mut seed);
+            let mut rng = rand_chacha::ChaCha20Rng::from_seed(seed);
+            assert!(verifier.next(message, &mut rng).unwrap().1.is_none());
+        }
+
+        // A false statement is rejected in the joint proof
+        let mut wrong = statements.clone();
+        wrong[1].evaluations[0] += F::from(1);
+        let (mut prover, mut messages) =
+            JointProver::new(params.clone(), statements, polynomial.clone()).unwrap();
+        let mut verifier = JointVerifier::new(params.clone(), &wrong).unwrap();
+        let rejected = loop {
+            match verifier.next(messages, &mut rng) {
+                Err(_) => break true,
+                Ok((_, None)) => break false,
+                Ok((new_verifier, Some(challenge))) => {
+                    verifier = new_verifier;
+                    (prover, messages) = prover.next(challenge).unwrap();
+                }
+            }
+        };
+        assert!(rejected);
+        assert!(JointProver::new(params, vec![], polynomial).is_err());
+    }
+}
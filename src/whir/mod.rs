@@ -1,27 +1,671 @@
 use ark_crypto_primitives::merkle_tree::{Config, MultiPath};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_ff::{FftField, Field};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
+use ark_std::{collections::BTreeSet, iter, vec::Vec};
+use nimue::{ProofError, ProofResult};
 
-use crate::poly_utils::MultilinearPoint;
+use crate::{
+    errors::{WhirError, WhirResult},
+    poly_utils::{evals::EvaluationsList, MultilinearPoint},
+    whir::{
+        compress::COMPRESSED_PROOF_FORMAT_VERSION,
+        hints::{ProofHints, HINTED_PROOF_FORMAT_VERSION},
+        parameters::WhirConfig,
+    },
+};
 
+#[cfg(feature = "std")]
+pub mod accumulator;
+#[cfg(feature = "std")]
+pub mod blob;
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod cancel;
+#[cfg(feature = "std")]
 pub mod committer;
+pub mod compress;
+#[cfg(feature = "std")]
+pub mod distributed;
+#[cfg(feature = "std")]
+pub mod divergence;
+#[cfg(feature = "std")]
+pub mod equality;
+#[cfg(test)]
+pub(crate) mod fixtures;
+pub mod fold;
+#[cfg(all(test, feature = "std"))]
+mod golden;
+#[cfg(feature = "std")]
+pub mod hiding;
+pub mod hints;
+#[cfg(feature = "std")]
+pub mod inspect;
+#[cfg(feature = "std")]
+pub mod interactive;
 pub mod iopattern;
+#[cfg(feature = "std")]
+pub mod joint;
 pub mod parameters;
+#[cfg(feature = "std")]
+pub mod pedersen;
+pub mod pow;
+#[cfg(feature = "protobuf")]
+pub mod proto;
+#[cfg(feature = "std")]
 pub mod prover;
+pub mod queries;
+pub mod range;
+pub mod recursion;
+#[cfg(feature = "std")]
+pub mod sweep;
 pub mod verifier;
+#[cfg(feature = "std")]
+pub mod views;
+
+/// Version of the IOPattern and transcript layout. Proofs only verify against the version they
+/// were produced with, so this is bumped with any change to either (the golden tests of
+/// `whir::golden` catch such changes).
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Version of the serialized layout of `WhirProof`, which it starts with. Version 1, the layout
+/// before the version was written, is read by `WhirProof::deserialize_legacy`.
+pub const PROOF_FORMAT_VERSION: u32 = 2;
+
+/// How a claim of a `Statement` relates its point to the polynomial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimType {
+    /// The polynomial evaluates to the evaluation at the point.
+    Evaluation,
+    /// The polynomial whose evaluations over the hypercube are rotated by one, `q(b) = p(b + 1)`
+    /// with `b` the big-endian index of the row, evaluates to the evaluation at the point. This
+    /// is the next-row relation of AIRs.
+    Rotated,
+}
+
+/// The claim that the sum over the hypercube of `weights(b) * p(b)` is `sum`, for any linear
+/// functional of the polynomial given by the evaluations of its weight multilinear.
+#[derive(Debug, Clone)]
+pub struct LinearClaim<F> {
+    pub weights: EvaluationsList<F>,
+    pub sum: F,
+}
 
 #[derive(Debug, Clone)]
 pub struct Statement<F> {
     pub points: Vec<MultilinearPoint<F>>,
     pub evaluations: Vec<F>,
+    pub claim_types: Vec<ClaimType>,
+    pub linear_claims: Vec<LinearClaim<F>>,
+}
+
+impl<F> Statement<F> {
+    /// A statement made only of evaluation claims.
+    pub fn new(points: Vec<MultilinearPoint<F>>, evaluations: Vec<F>) -> Self {
+        Statement {
+            claim_types: vec![ClaimType::Evaluation; points.len()],
+            points,
+            evaluations,
+            linear_claims: vec![],
+        }
+    }
+
+    /// The number of evaluation claims of `claims` that the statement starts with, e.g. those of
+    /// the OOD samples left to the statement (see `WhirConfig::with_statement_ood_samples`).
+    pub(crate) fn common_prefix(&self, claims: &Statement<F>) -> usize
+    where
+        F: PartialEq,
+    {
+        self.points
+            .iter()
+            .zip(&self.evaluations)
+            .zip(&self.claim_types)
+            .zip(claims.points.iter().zip(&claims.evaluations))
+            .take_while(|(((point, evaluation), &claim_type), (claim, claimed))| {
+                claim_type == ClaimType::Evaluation && point.0 == claim.0 && evaluation == claimed
+            })
+            .count()
+    }
+
+    /// Appends the claims of `other`, e.g. those of a `range::RangeClaim`.
+    pub fn extend(&mut self, other: Statement<F>) {
+        self.points.extend(other.points);
+        self.evaluations.extend(other.evaluations);
+        self.claim_types.extend(other.claim_types);
+        self.linear_claims.extend(other.linear_claims);
+    }
 }
 
-// Only includes the authentication paths
-#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
-pub struct WhirProof<MerkleConfig, F>(Vec<(MultiPath<MerkleConfig>, Vec<Vec<F>>)>)
+impl<F: Field> Statement<F> {
+    /// Checks that the statement is about polynomials of `config`: one claim type and one
+    /// evaluation per point, points and weights with the number of variables of the
+    /// configuration, and no claim of the same type twice at the same point.
+    pub fn validate<MerkleConfig: Config, PowStrategy>(
+        &self,
+        config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    ) -> WhirResult<()>
+    where
+        F: FftField,
+    {
+        self.validate_num_variables(config.num_variables())?;
+        let found = self
+            .claim_types
+            .iter()
+            .filter(|&&claim_type| claim_type == ClaimType::Evaluation)
+            .count();
+        if found < config.statement_ood_samples {
+            return Err(WhirError::StatementOodSamples {
+                expected: config.statement_ood_samples,
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn validate_num_variables(&self, num_variables: usize) -> WhirResult<()> {
+        for found in [self.claim_types.len(), self.evaluations.len()] {
+            if found != self.points.len() {
+                return Err(WhirError::StatementLength {
+                    points: self.points.len(),
+                    found,
+                });
+            }
+        }
+        let found = self
+            .points
+            .iter()
+            .map(|point| point.n_variables())
+            .chain(
+                self.linear_claims
+                    .iter()
+                    .map(|claim| claim.weights.num_variables()),
+            )
+            .find(|&found| found != num_variables);
+        if let Some(found) = found {
+            return Err(WhirError::NumVariables {
+                expected: num_variables,
+                found,
+            });
+        }
+        let mut claims = BTreeSet::new();
+        for (i, (point, claim_type)) in self.points.iter().zip(&self.claim_types).enumerate() {
+            if !claims.insert((*claim_type == ClaimType::Rotated, &point.0)) {
+                return Err(WhirError::DuplicateClaim(i));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The openings of the Merkle trees, one per round and a final one, with the leaves sorted by
+/// index. It serializes (see `PROOF_FORMAT_VERSION`) as the version, then for each tree
+/// the number of openings and for each opening, by increasing index, its index, the hash of the
+/// sibling leaf, the authentication path (length of the prefix shared with the previous path
+/// and suffix) and the leaf, so that the verifier reads each opening in one sequential pass.
+/// A proof with hints (see `whir::hints`) is flagged by `HINTED_PROOF_FORMAT_VERSION`, and
+/// followed by them.
+#[derive(Clone)]
+pub struct WhirProof<MerkleConfig, F>(Vec<(MultiPath<MerkleConfig>, Vec<Vec<F>>)>, ProofHints<F>)
 where
     MerkleConfig: Config<Leaf = [F]>,
     F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize;
 
+/// The largest opening of one round that a configuration accepts.
+struct OpeningBound {
+    num_queries: usize,
+    num_leaves: usize,
+    leaf_len: usize,
+}
+
+impl OpeningBound {
+    // Authentication paths are never longer than the height of the tree.
+    fn max_path_len(&self) -> usize {
+        self.num_leaves.max(1).ilog2() as usize
+    }
+
+    // The size of the largest opening `write_opening` writes within the bound, with no two
+    // queries on the same leaf and no prefix shared by the authentication paths.
+    fn max_size<F: Field, MerkleConfig: Config>(&self) -> usize {
+        let field = F::ZERO.compressed_size();
+        let leaf_digest = MerkleConfig::LeafDigest::default().compressed_size();
+        let inner_digest = MerkleConfig::InnerDigest::default().compressed_size();
+        let opening = 2 * 8
+            + leaf_digest
+            + (8 + self.max_path_len() * inner_digest)
+            + (8 + self.leaf_len * field);
+        8 + self.num_queries.min(self.num_leaves) * opening
+    }
+
+    // Each path reuses a prefix of the previous one (the first path is all suffix), so the
+    // prefixes must fit in the previous path.
+    fn check_paths<MerkleConfig: Config>(&self, merkle_proof: &MultiPath<MerkleConfig>) -> bool {
+        let mut prev_len = 0;
+        merkle_proof
+            .auth_paths_prefix_lenghts
+            .iter()
+            .zip(&merkle_proof.auth_paths_suffixes)
+            .all(|(&prefix, suffix)| {
+                let len = prefix.saturating_add(suffix.len());
+                let fits = prefix <= prev_len && len <= self.max_path_len();
+                prev_len = len;
+                fits
+            })
+    }
+
+    fn check<MerkleConfig, F>(
+        &self,
+        merkle_proof: &MultiPath<MerkleConfig>,
+        answers: &[Vec<F>],
+    ) -> bool
+    where
+        MerkleConfig: Config<Leaf = [F]>,
+    {
+        let num_answers = answers.len();
+        num_answers <= self.num_queries
+            && merkle_proof.leaf_indexes.len() == num_answers
+            && merkle_proof.leaf_siblings_hashes.len() == num_answers
+            && merkle_proof.auth_paths_prefix_lenghts.len() == num_answers
+            && merkle_proof.auth_paths_suffixes.len() == num_answers
+            && merkle_proof
+                .leaf_indexes
+                .windows(2)
+                .all(|pair| pair[0] < pair[1])
+            && merkle_proof
+                .leaf_indexes
+                .iter()
+                .all(|&index| index < self.num_leaves)
+            && self.check_paths(merkle_proof)
+            && answers.iter().all(|answer| answer.len() == self.leaf_len)
+    }
+}
+
+fn opening_bounds<F, MerkleConfig, PowStrategy>(
+    config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+) -> Vec<OpeningBound>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    let mut domain_size = config.starting_domain.size();
+    let mut bounds = Vec::with_capacity(config.n_rounds() + 1);
+    for num_queries in config
+        .round_parameters
+        .iter()
+        .map(|round| round.num_queries)
+        .chain(iter::once(config.final_queries))
+    {
+        bounds.push(OpeningBound {
+            num_queries,
+            num_leaves: domain_size / config.leaf_size(),
+            leaf_len: config.leaf_size(),
+        });
+        domain_size /= 2;
+    }
+    bounds
+}
+
+/// The sizes of the openings of the rounds when they are absorbed into the transcript, see
+/// `WhirConfig::with_openings_in_transcript`.
+pub(crate) fn transcript_opening_sizes<F, MerkleConfig, PowStrategy>(
+    config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+) -> Vec<usize>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    opening_bounds(config)
+        .iter()
+        .map(OpeningBound::max_size::<F, MerkleConfig>)
+        .collect()
+}
+
+// An opening absorbed into the transcript: as written by `write_opening`, then zeros up to
+// `size`.
+pub(crate) fn opening_to_transcript<MerkleConfig, F>(
+    opening: &(MultiPath<MerkleConfig>, Vec<Vec<F>>),
+    size: usize,
+) -> ProofResult<Vec<u8>>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    let mut bytes = Vec::with_capacity(size);
+    write_opening(&mut bytes, opening, Compress::Yes)
+        .map_err(|_| ProofError::SerializationError)?;
+    if bytes.len() > size {
+        return Err(ProofError::SerializationError);
+    }
+    bytes.resize(size, 0);
+    Ok(bytes)
+}
+
+// Inverse of `opening_to_transcript` for the opening of round `round`, rejecting openings out
+// of the bound of the round and padding that is not all zeros.
+pub(crate) fn opening_from_transcript<F, MerkleConfig, PowStrategy>(
+    config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    round: usize,
+    bytes: &[u8],
+) -> ProofResult<(MultiPath<MerkleConfig>, Vec<Vec<F>>)>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+{
+    let bounds = opening_bounds(config);
+    let mut reader = bytes;
+    let opening = read_opening(
+        &mut reader,
+        Compress::Yes,
+        Validate::Yes,
+        Some(&bounds[round]),
+    )
+    .map_err(|_| ProofError::InvalidProof)?;
+    if reader.iter().any(|&byte| byte != 0) {
+        return Err(ProofError::InvalidProof);
+    }
+    Ok(opening)
+}
+
+// Reads a length prefix, rejecting it before anything is allocated if it exceeds `max`.
+fn read_len<R: Read>(reader: &mut R, max: usize) -> Result<usize, SerializationError> {
+    let len = u64::deserialize_compressed(&mut *reader)?;
+    usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= max)
+        .ok_or(SerializationError::InvalidData)
+}
+
+fn read_vec<T: CanonicalDeserialize, R: Read>(
+    reader: &mut R,
+    max: usize,
+    compress: Compress,
+    validate: Validate,
+) -> Result<Vec<T>, SerializationError> {
+    let len = read_len(reader, max)?;
+    (0..len)
+        .map(|_| T::deserialize_with_mode(&mut *reader, compress, validate))
+        .collect()
+}
+
+// Reads one opening of the layout of `PROOF_FORMAT_VERSION`, bounded by `bound` as in
+// `WhirProof::read`.
+fn read_opening<MerkleConfig, F, R>(
+    mut reader: R,
+    compress: Compress,
+    validate: Validate,
+    bound: Option<&OpeningBound>,
+) -> Result<(MultiPath<MerkleConfig>, Vec<Vec<F>>), SerializationError>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+    R: Read,
+{
+    let max_path_len = bound.map_or(usize::MAX, OpeningBound::max_path_len);
+    let max_leaf_len = bound.map_or(usize::MAX, |bound| bound.leaf_len);
+    let num_openings = read_len(&mut reader, bound.map_or(usize::MAX, |b| b.num_queries))?;
+    // Unbounded lengths only allocate as the openings are read.
+    let capacity = if bound.is_some() { num_openings } else { 0 };
+    let mut merkle_proof = MultiPath::<MerkleConfig> {
+        leaf_siblings_hashes: Vec::with_capacity(capacity),
+        auth_paths_prefix_lenghts: Vec::with_capacity(capacity),
+        auth_paths_suffixes: Vec::with_capacity(capacity),
+        leaf_indexes: Vec::with_capacity(capacity),
+    };
+    let mut answers = Vec::with_capacity(capacity);
+    for _ in 0..num_openings {
+        merkle_proof
+            .leaf_indexes
+            .push(read_len(&mut reader, usize::MAX)?);
+        merkle_proof
+            .leaf_siblings_hashes
+            .push(CanonicalDeserialize::deserialize_with_mode(
+                &mut reader,
+                compress,
+                validate,
+            )?);
+        merkle_proof
+            .auth_paths_prefix_lenghts
+            .push(read_len(&mut reader, max_path_len)?);
+        merkle_proof.auth_paths_suffixes.push(read_vec(
+            &mut reader,
+            max_path_len,
+            compress,
+            validate,
+        )?);
+        answers.push(read_vec(&mut reader, max_leaf_len, compress, validate)?);
+    }
+    if bound.is_some_and(|bound| !bound.check(&merkle_proof, &answers)) {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok((merkle_proof, answers))
+}
+
+// Writes one opening in the layout of `PROOF_FORMAT_VERSION`.
+fn write_opening<MerkleConfig, F, W>(
+    mut writer: W,
+    (merkle_proof, answers): &(MultiPath<MerkleConfig>, Vec<Vec<F>>),
+    compress: Compress,
+) -> Result<(), SerializationError>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+    W: Write,
+{
+    let num_openings = answers.len();
+    if merkle_proof.leaf_indexes.len() != num_openings
+        || merkle_proof.leaf_siblings_hashes.len() != num_openings
+        || merkle_proof.auth_paths_prefix_lenghts.len() != num_openings
+        || merkle_proof.auth_paths_suffixes.len() != num_openings
+    {
+        return Err(SerializationError::InvalidData);
+    }
+    (num_openings as u64).serialize_with_mode(&mut writer, compress)?;
+    for i in 0..num_openings {
+        (merkle_proof.leaf_indexes[i] as u64).serialize_with_mode(&mut writer, compress)?;
+        merkle_proof.leaf_siblings_hashes[i].serialize_with_mode(&mut writer, compress)?;
+        (merkle_proof.auth_paths_prefix_lenghts[i] as u64)
+            .serialize_with_mode(&mut writer, compress)?;
+        merkle_proof.auth_paths_suffixes[i].serialize_with_mode(&mut writer, compress)?;
+        answers[i].serialize_with_mode(&mut writer, compress)?;
+    }
+    Ok(())
+}
+
+impl<MerkleConfig, F> WhirProof<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    /// The hints of the proof, empty unless returned by `Verifier::hint_proof`.
+    pub fn hints(&self) -> &ProofHints<F> {
+        &self.1
+    }
+
+    /// Deserializes a proof in the layout of version 1, to convert it: serializing the result
+    /// writes it in the current layout.
+    pub fn deserialize_legacy<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        Vec::deserialize_compressed(reader)
+            .map(|openings| WhirProof(openings, ProofHints::default()))
+    }
+
+    // Reads the layouts of `PROOF_FORMAT_VERSION` and `HINTED_PROOF_FORMAT_VERSION`. With
+    // `bounds`, every length is checked against them before anything is allocated for it, and
+    // each opening must pass `OpeningBound::check`.
+    fn read<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+        bounds: Option<&[OpeningBound]>,
+    ) -> Result<Self, SerializationError> {
+        match u32::deserialize_with_mode(&mut reader, compress, validate)? {
+            PROOF_FORMAT_VERSION => Self::read_openings(reader, compress, validate, bounds),
+            HINTED_PROOF_FORMAT_VERSION => Self::read_hinted(reader, compress, validate, bounds),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    // Reads the layout of `HINTED_PROOF_FORMAT_VERSION` after its version.
+    fn read_hinted<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+        bounds: Option<&[OpeningBound]>,
+    ) -> Result<Self, SerializationError> {
+        let mut proof = Self::read_openings(&mut reader, compress, validate, bounds)?;
+        let max_queries: Option<Vec<usize>> =
+            bounds.map(|bounds| bounds.iter().map(|bound| bound.num_queries).collect());
+        proof.1 = ProofHints::read(reader, compress, validate, max_queries.as_deref())?;
+        Ok(proof)
+    }
+
+    // Reads the layout of `PROOF_FORMAT_VERSION` after its version.
+    fn read_openings<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+        bounds: Option<&[OpeningBound]>,
+    ) -> Result<Self, SerializationError> {
+        let num_rounds = read_len(&mut reader, bounds.map_or(usize::MAX, <[_]>::len))?;
+        if bounds.is_some_and(|bounds| bounds.len() != num_rounds) {
+            return Err(SerializationError::InvalidData);
+        }
+        let openings = (0..num_rounds)
+            .map(|round| read_opening(&mut reader, compress, validate, bounds.map(|b| &b[round])))
+            .collect::<Result<_, _>>()?;
+        Ok(WhirProof(openings, ProofHints::default()))
+    }
+}
+
+impl<MerkleConfig, F> WhirProof<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: FftField,
+{
+    /// Checks that the proof has the shape `config` expects: one opening per round and a final
+    /// one, each with at most as many leaves as queries, sorted leaf indexes within the committed
+    /// domain, leaves of `WhirConfig::leaf_size` elements and paths no longer than the tree
+    /// height.
+    pub fn check_shape<PowStrategy>(
+        &self,
+        config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    ) -> bool {
+        let bounds = opening_bounds(config);
+        self.0.len() == bounds.len()
+            && self
+                .0
+                .iter()
+                .zip(&bounds)
+                .all(|((merkle_proof, answers), bound)| bound.check(merkle_proof, answers))
+    }
+
+    /// Deserializes a proof for `config`. Unlike `deserialize_compressed`, every length prefix is
+    /// checked against the configuration before anything is allocated for it, so that a short
+    /// malicious input cannot claim billions of leaves.
+    ///
+    /// Reads the layouts of `PROOF_FORMAT_VERSION` and of `COMPRESSED_PROOF_FORMAT_VERSION`
+    /// alike, see `WhirProof::compress`.
+    pub fn deserialize_for_config<PowStrategy, R: Read>(
+        mut reader: R,
+        config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    ) -> Result<Self, SerializationError> {
+        match u32::deserialize_compressed(&mut reader)? {
+            PROOF_FORMAT_VERSION => Self::read_openings(
+                reader,
+                Compress::Yes,
+                Validate::Yes,
+                Some(&opening_bounds(config)),
+            ),
+            HINTED_PROOF_FORMAT_VERSION => Self::read_hinted(
+                reader,
+                Compress::Yes,
+                Validate::Yes,
+                Some(&opening_bounds(config)),
+            ),
+            COMPRESSED_PROOF_FORMAT_VERSION => Self::read_compressed(reader, config),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+impl<MerkleConfig, F> CanonicalSerialize for WhirProof<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        let version = if self.1.is_empty() {
+            PROOF_FORMAT_VERSION
+        } else {
+            HINTED_PROOF_FORMAT_VERSION
+        };
+        version.serialize_with_mode(&mut writer, compress)?;
+        (self.0.len() as u64).serialize_with_mode(&mut writer, compress)?;
+        for opening in &self.0 {
+            write_opening(&mut writer, opening, compress)?;
+        }
+        if !self.1.is_empty() {
+            self.1.write(&mut writer, compress)?;
+        }
+        Ok(())
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        let openings = self.0.iter().map(|(merkle_proof, answers)| {
+            let opening_sizes = merkle_proof
+                .leaf_siblings_hashes
+                .iter()
+                .zip(&merkle_proof.auth_paths_suffixes)
+                .zip(answers)
+                .map(|((sibling, suffix), leaf)| {
+                    2 * 8
+                        + sibling.serialized_size(compress)
+                        + suffix.serialized_size(compress)
+                        + leaf.serialized_size(compress)
+                });
+            8 + opening_sizes.sum::<usize>()
+        });
+        let hints = if self.1.is_empty() {
+            0
+        } else {
+            self.1.serialized_size(compress)
+        };
+        4 + 8 + openings.sum::<usize>() + hints
+    }
+}
+
+impl<MerkleConfig, F> Valid for WhirProof<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    fn check(&self) -> Result<(), SerializationError> {
+        self.0.iter().try_for_each(|(merkle_proof, answers)| {
+            merkle_proof.check()?;
+            answers.check()
+        })?;
+        self.1.coset_offsets_inv.check()
+    }
+}
+
+impl<MerkleConfig, F> CanonicalDeserialize for WhirProof<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::read(reader, compress, validate, None)
+    }
+}
+
 pub fn whir_proof_size<MerkleConfig, F>(
     transcript: &[u8],
     whir_proof: &WhirProof<MerkleConfig, F>,
@@ -33,25 +677,132 @@ where
     transcript.len() + whir_proof.serialized_size(ark_serialize::Compress::Yes)
 }
 
+/// Packs the transcript and the proof into a single buffer, so that it can cross a process
+/// or language boundary as one opaque blob.
+pub fn proof_to_bytes<MerkleConfig, F>(
+    transcript: &[u8],
+    whir_proof: &WhirProof<MerkleConfig, F>,
+) -> Result<Vec<u8>, SerializationError>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    let mut bytes = Vec::with_capacity(whir_proof_size(transcript, whir_proof) + 8);
+    transcript.serialize_compressed(&mut bytes)?;
+    whir_proof.serialize_compressed(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Inverse of `proof_to_bytes`, returns the transcript and the proof.
+pub fn proof_from_bytes<MerkleConfig, F>(
+    mut bytes: &[u8],
+) -> Result<(Vec<u8>, WhirProof<MerkleConfig, F>), SerializationError>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    let transcript = Vec::<u8>::deserialize_compressed(&mut bytes)?;
+    let whir_proof = WhirProof::deserialize_compressed(&mut bytes)?;
+    Ok((transcript, whir_proof))
+}
+
+/// Like `proof_from_bytes`, but bounds every length by the input or by `config` (see
+/// `WhirProof::deserialize_for_config`) and rejects trailing bytes.
+pub fn proof_from_bytes_for_config<MerkleConfig, F, PowStrategy>(
+    mut bytes: &[u8],
+    config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+) -> Result<(Vec<u8>, WhirProof<MerkleConfig, F>), SerializationError>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: FftField,
+{
+    let len = read_len(&mut bytes, usize::MAX)?;
+    if len > bytes.len() {
+        return Err(SerializationError::InvalidData);
+    }
+    let (transcript, mut bytes) = bytes.split_at(len);
+    let whir_proof = WhirProof::deserialize_for_config(&mut bytes, config)?;
+    if !bytes.is_empty() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok((transcript.to_vec(), whir_proof))
+}
+
+/// Reads the stream written by `Prover::prove_to_writer` and returns the transcript and the
+/// proof. The stream is the version, then for each opening the bytes the transcript gained
+/// since the previous one and the opening (in the layout of `PROOF_FORMAT_VERSION`), then the
+/// rest of the transcript; the openings are bounded by `config` as in
+/// `WhirProof::deserialize_for_config`.
+pub fn proof_from_stream_for_config<MerkleConfig, F, PowStrategy, R>(
+    mut reader: R,
+    config: &WhirConfig<F, MerkleConfig, PowStrategy>,
+) -> Result<(Vec<u8>, WhirProof<MerkleConfig, F>), SerializationError>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: FftField,
+    R: Read,
+{
+    if u32::deserialize_compressed(&mut reader)? != PROOF_FORMAT_VERSION {
+        return Err(SerializationError::InvalidData);
+    }
+    let mut transcript = Vec::new();
+    let mut openings = Vec::new();
+    for bound in opening_bounds(config) {
+        transcript.extend(read_vec::<u8, _>(
+            &mut reader,
+            usize::MAX,
+            Compress::Yes,
+            Validate::Yes,
+        )?);
+        openings.push(read_opening(
+            &mut reader,
+            Compress::Yes,
+            Validate::Yes,
+            Some(&bound),
+        )?);
+    }
+    transcript.extend(read_vec::<u8, _>(
+        &mut reader,
+        usize::MAX,
+        Compress::Yes,
+        Validate::Yes,
+    )?);
+    Ok((transcript, WhirProof(openings, ProofHints::default())))
+}
+
 #[cfg(test)]
 mod tests {
-    use nimue::{DefaultHash, IOPattern};
-    use nimue_pow::blake3::Blake3PoW;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::{sync::Arc, UniformRand};
+    use nimue::{DefaultHash, IOPattern, Merlin};
 
-    use crate::crypto::fields::Field64;
     use crate::crypto::merkle_tree::blake3 as merkle_tree;
-    use crate::parameters::{FoldType, MultivariateParameters, SoundnessType, WhirParameters};
+    use crate::crypto::merkle_tree::hybrid::{self, AlgebraicHash};
+    use crate::errors::WhirError;
+    use crate::ntt::ArkPolyBackend;
+    use crate::parameters::{
+        FinalForm, FoldType, MultivariateParameters, ProofSizeBudget, SoundnessType, WhirParameters,
+    };
     use crate::poly_utils::coeffs::CoefficientList;
+    use crate::poly_utils::evals::EvaluationsList;
+    use crate::poly_utils::virtual_poly::VirtualPolynomial;
     use crate::poly_utils::MultilinearPoint;
-    use crate::whir::Statement;
     use crate::whir::{
-        committer::Committer, iopattern::WhirIOPattern, parameters::WhirConfig, prover::Prover,
+        committer::Committer,
+        divergence::compare_provers,
+        fixtures::{
+            config, io_pattern, prove_and_verify, whir_parameters, MerkleConfig, PowStrategy, F,
+        },
+        iopattern::{StatementBinding, StatementTranscript, WhirIOPattern},
+        parameters::{ProverMode, RoundPowBits, WhirConfig},
+        prover::Prover,
+        queries::QuerySampler,
         verifier::Verifier,
     };
-
-    type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
-    type PowStrategy = Blake3PoW;
-    type F = Field64;
+    use crate::whir::{
+        proof_from_stream_for_config, proof_to_bytes, transcript_opening_sizes, whir_proof_size,
+        ClaimType, LinearClaim, Statement, WhirProof, PROOF_FORMAT_VERSION,
+    };
 
     fn make_whir_things(
         num_variables: usize,
@@ -69,18 +820,15 @@ mod tests {
         let mv_params = MultivariateParameters::<F>::new(num_variables);
 
         let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
-            security_level: 32,
             pow_bits,
             folding_factor,
-            leaf_hash_params,
-            two_to_one_params,
             soundness_type,
-            _pow_parameters: Default::default(),
-            starting_log_inv_rate: 1,
             fold_optimisation: fold_type,
+            ..whir_parameters((leaf_hash_params, two_to_one_params))
         };
 
-        let params = WhirConfig::<F, MerkleConfig, PowStrategy>::new(mv_params, whir_params);
+        let params =
+            WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(mv_params, whir_params).unwrap();
 
         let polynomial = CoefficientList::new(vec![F::from(1); num_coeffs]);
 
@@ -88,33 +836,20 @@ mod tests {
             .map(|_| MultilinearPoint::rand(&mut rng, num_variables))
             .collect();
 
-        let statement = Statement {
-            points: points.clone(),
-            evaluations: points
+        let statement = Statement::new(
+            points.clone(),
+            points
                 .iter()
                 .map(|point| polynomial.evaluate(point))
                 .collect(),
-        };
+        );
 
-        let io = IOPattern::<DefaultHash>::new("🌪️")
-            .commit_statement(&params)
-            .add_whir_proof(&params)
-            .clone();
-
-        let mut merlin = io.to_merlin();
-
-        let committer = Committer::new(params.clone());
-        let witness = committer.commit(&mut merlin, polynomial).unwrap();
-
-        let prover = Prover(params.clone());
-
-        let proof = prover
-            .prove(&mut merlin, statement.clone(), witness)
-            .unwrap();
+        let io = io_pattern(&params);
+        let (transcript, proof) = prove_and_verify(&params, polynomial, &statement);
 
+        let proof_bytes = proof_to_bytes(&transcript, &proof).unwrap();
         let verifier = Verifier::new(params);
-        let mut arthur = io.to_arthur(merlin.transcript());
-        assert!(verifier.verify(&mut arthur, &statement, &proof).is_ok());
+        assert!(verifier.verify_bytes(&io, &statement, &proof_bytes).is_ok());
     }
 
     #[test]
@@ -151,4 +886,1215 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_whir_rotated() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables);
+
+        let polynomial = CoefficientList::new((0..1u64 << num_variables).map(F::from).collect());
+        let mut next_rows = EvaluationsList::from(polynomial.clone()).evals().to_vec();
+        next_rows.rotate_left(1);
+        let next_rows = EvaluationsList::new(next_rows);
+
+        let (point, rotated_point) = (
+            MultilinearPoint::rand(&mut rng, num_variables),
+            MultilinearPoint::rand(&mut rng, num_variables),
+        );
+        let statement = Statement {
+            evaluations: vec![
+                polynomial.evaluate(&point),
+                next_rows.evaluate(&rotated_point),
+            ],
+            points: vec![point, rotated_point],
+            claim_types: vec![ClaimType::Evaluation, ClaimType::Rotated],
+            linear_claims: vec![],
+        };
+
+        let io = io_pattern(&params);
+        let (transcript, proof) = prove_and_verify(&params, polynomial, &statement);
+        let verifier = Verifier::new(params);
+
+        let mut unrotated = statement;
+        unrotated.claim_types[1] = ClaimType::Evaluation;
+        let mut arthur = io.to_arthur(&transcript);
+        assert!(verifier.verify(&mut arthur, &unrotated, &proof).is_err());
+    }
+
+    #[test]
+    fn test_whir_linear_claims() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables);
+
+        let polynomial = CoefficientList::new((0..1u64 << num_variables).map(F::from).collect());
+        let evaluations = EvaluationsList::from(polynomial.clone());
+        // An inner product with a random vector, and a sum over the first half of the hypercube
+        let linear_claims: Vec<_> = [
+            (0..1 << num_variables).map(|_| F::rand(&mut rng)).collect(),
+            (0..1 << num_variables)
+                .map(|b| F::from((b < 1 << (num_variables - 1)) as u64))
+                .collect::<Vec<_>>(),
+        ]
+        .into_iter()
+        .map(|weights| LinearClaim {
+            sum: weights
+                .iter()
+                .zip(evaluations.evals())
+                .map(|(w, p)| *w * p)
+                .sum(),
+            weights: EvaluationsList::new(weights),
+        })
+        .collect();
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let evaluation = polynomial.evaluate(&point);
+        let mut statement = Statement::new(vec![point], vec![evaluation]);
+        statement.linear_claims = linear_claims;
+
+        let io = io_pattern(&params);
+        let (transcript, proof) = prove_and_verify(&params, polynomial, &statement);
+        let verifier = Verifier::new(params);
+
+        let mut wrong_statement = statement;
+        wrong_statement.linear_claims[1].sum += F::from(1);
+        let mut arthur = io.to_arthur(&transcript);
+        assert!(verifier
+            .verify(&mut arthur, &wrong_statement, &proof)
+            .is_err());
+    }
+
+    #[test]
+    fn test_whir_zk() {
+        let num_variables = 9;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+
+        let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+            zk: true,
+            ..whir_parameters((leaf_hash_params, two_to_one_params))
+        };
+        let params = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+        assert_eq!(params.num_variables(), num_variables);
+        assert!(params.check_zk());
+
+        let polynomial = CoefficientList::new((0..1u64 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let evaluation = polynomial.evaluate(&point);
+        let statement = Statement::new(vec![point], vec![evaluation]);
+
+        let io = io_pattern(&params);
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit_with_rng(&mut merlin, polynomial, &mut rng)
+            .unwrap();
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+
+        let verifier = Verifier::new(params);
+        let mut arthur = io.to_arthur(merlin.transcript());
+        assert!(verifier.verify(&mut arthur, &statement, &proof).is_ok());
+
+        let mut wrong_statement = statement;
+        wrong_statement.evaluations[0] += F::from(1);
+        let mut arthur = io.to_arthur(merlin.transcript());
+        assert!(verifier
+            .verify(&mut arthur, &wrong_statement, &proof)
+            .is_err());
+    }
+
+    #[test]
+    fn test_zk_rejected() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+            zk: true,
+            ..whir_parameters((leaf_hash_params, two_to_one_params))
+        };
+        // With the mask, 5 variables: the final coefficients would depend on the polynomial.
+        assert!(matches!(
+            WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(4),
+                whir_params
+            ),
+            Err(WhirError::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_inputs() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params = |folding_factor, pow_bits| WhirParameters::<MerkleConfig, PowStrategy> {
+            pow_bits,
+            folding_factor,
+            ..whir_parameters((leaf_hash_params.clone(), two_to_one_params.clone()))
+        };
+        let mv_params = MultivariateParameters::<F>::new(num_variables);
+        for (folding_factor, pow_bits) in [(0, 0), (7, 0), (2, 33)] {
+            assert!(matches!(
+                WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                    mv_params,
+                    whir_params(folding_factor, pow_bits)
+                ),
+                Err(WhirError::InvalidParameters(_))
+            ));
+        }
+        // Instances too large for a usize are rejected instead of overflowing.
+        assert!(matches!(
+            MultivariateParameters::<F>::try_new(usize::BITS as usize),
+            Err(WhirError::TooManyVariables(_))
+        ));
+        for num_variables in [usize::BITS as usize - 1, usize::BITS as usize, usize::MAX] {
+            assert!(WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params(2, 0)
+            )
+            .is_err());
+        }
+        let params =
+            WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(mv_params, whir_params(2, 0))
+                .unwrap();
+
+        assert!(matches!(
+            CoefficientList::try_new(vec![F::from(1); 3]),
+            Err(WhirError::NotPowerOfTwo(3))
+        ));
+        let io = io_pattern(&params);
+        let mut merlin = io.to_merlin();
+        let committer = Committer::new(params.clone());
+        assert!(matches!(
+            committer.commit(&mut merlin, CoefficientList::new(vec![F::from(1); 32])),
+            Err(WhirError::NumVariables {
+                expected: 6,
+                found: 5
+            })
+        ));
+
+        let polynomial = CoefficientList::new(vec![F::from(1); 1 << num_variables]);
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let mut merlin = io.to_merlin();
+        let witness = committer.commit(&mut merlin, polynomial.clone()).unwrap();
+        let statement = Statement::new(vec![point], vec![]);
+        assert!(matches!(
+            Prover(params.clone()).prove(&mut merlin, statement, witness),
+            Err(WhirError::StatementLength {
+                points: 1,
+                found: 0
+            })
+        ));
+
+        // A configuration whose rounds do not fold all the variables
+        let mut merlin = io.to_merlin();
+        let witness = committer.commit(&mut merlin, polynomial).unwrap();
+        let mut params = params;
+        params.final_sumcheck_rounds += 1;
+        assert!(matches!(
+            Prover(params).prove(&mut merlin, Statement::new(vec![], vec![]), witness),
+            Err(WhirError::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn test_proof_shape() {
+        let num_variables = 4;
+        let params = config(num_variables);
+        let io = io_pattern(&params);
+        let mut merlin = io.to_merlin();
+        let polynomial = CoefficientList::new(vec![F::from(1); 1 << num_variables]);
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial)
+            .unwrap();
+        let statement = Statement::new(vec![], vec![]);
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+        assert!(proof.check_shape(&params));
+        assert!(whir_proof_size(merlin.transcript(), &proof) <= params.proof_size_bound());
+
+        let mut proof_bytes = Vec::new();
+        proof.serialize_compressed(&mut proof_bytes).unwrap();
+        let deserialized =
+            WhirProof::<MerkleConfig, F>::deserialize_for_config(&proof_bytes[..], &params)
+                .unwrap();
+        let mut reserialized = Vec::new();
+        deserialized
+            .serialize_compressed(&mut reserialized)
+            .unwrap();
+        assert_eq!(proof_bytes, reserialized);
+        assert_eq!(proof_bytes.len(), proof.compressed_size());
+
+        // Proofs in the layout of version 1 convert to the current one.
+        let mut legacy = Vec::new();
+        proof.0.serialize_compressed(&mut legacy).unwrap();
+        let mut converted = Vec::new();
+        WhirProof::<MerkleConfig, F>::deserialize_legacy(&legacy[..])
+            .unwrap()
+            .serialize_compressed(&mut converted)
+            .unwrap();
+        assert_eq!(proof_bytes, converted);
+
+        // A handful of bytes claiming 2^32 openings of the first tree.
+        let mut malicious = Vec::new();
+        PROOF_FORMAT_VERSION
+            .serialize_compressed(&mut malicious)
+            .unwrap();
+        ((params.n_rounds() + 1) as u64)
+            .serialize_compressed(&mut malicious)
+            .unwrap();
+        (1u64 << 32).serialize_compressed(&mut malicious).unwrap();
+        assert!(
+            WhirProof::<MerkleConfig, F>::deserialize_for_config(&malicious[..], &params).is_err()
+        );
+
+        let mut truncated = proof.clone();
+        truncated.0.pop();
+        assert!(!truncated.check_shape(&params));
+        let mut short_leaf = proof.clone();
+        short_leaf.0[0].1[0].pop();
+        assert!(!short_leaf.check_shape(&params));
+        let mut out_of_domain = proof;
+        out_of_domain.0[0].0.leaf_indexes[0] = usize::MAX;
+        assert!(!out_of_domain.check_shape(&params));
+        let verifier = Verifier::new(params);
+        let mut arthur = io.to_arthur(merlin.transcript());
+        assert!(verifier
+            .verify(&mut arthur, &statement, &out_of_domain)
+            .is_err());
+    }
+
+    #[test]
+    fn test_proof_stream() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables);
+        let io = io_pattern(&params);
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial.clone())
+            .unwrap();
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+
+        let mut stream = Vec::new();
+        let mut streamed_merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut streamed_merlin, polynomial)
+            .unwrap();
+        Prover(params.clone())
+            .prove_to_writer(
+                &mut streamed_merlin,
+                statement.clone(),
+                witness,
+                &mut stream,
+            )
+            .unwrap();
+        let (transcript, streamed_proof) =
+            proof_from_stream_for_config(&stream[..], &params).unwrap();
+        assert_eq!(transcript, merlin.transcript());
+        assert_eq!(
+            proof_to_bytes(&transcript, &streamed_proof).unwrap(),
+            proof_to_bytes(merlin.transcript(), &proof).unwrap()
+        );
+        let mut arthur = io.to_arthur(&transcript);
+        assert!(Verifier::new(params.clone())
+            .verify(&mut arthur, &statement, &streamed_proof)
+            .is_ok());
+
+        assert!(proof_from_stream_for_config(&stream[..stream.len() - 1], &params).is_err());
+    }
+
+    #[test]
+    fn test_openings_in_transcript() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables).with_openings_in_transcript();
+        let io = io_pattern(&params);
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial.clone())
+            .unwrap();
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+        assert!(proof.0.is_empty());
+        assert_eq!(
+            whir_proof_size(merlin.transcript(), &proof),
+            params.proof_size_bound()
+        );
+        let mut arthur = io.to_arthur(merlin.transcript());
+        assert!(Verifier::new(params.clone())
+            .verify(&mut arthur, &statement, &proof)
+            .is_ok());
+
+        // The openings are bound by the transcript: the first one follows the first root and
+        // its out-of-domain answers
+        let field = F::ZERO.compressed_size();
+        let opening_start = 32
+            + params.committment_ood_samples * field
+            + 3 * field * params.folding_factor
+            + 32
+            + params.round_parameters[0].ood_samples * field;
+        let mut transcript = merlin.transcript().to_vec();
+        transcript[opening_start + transcript_opening_sizes(&params)[0] / 2] ^= 1;
+        let mut arthur = io.to_arthur(&transcript);
+        assert!(Verifier::new(params.clone())
+            .verify(&mut arthur, &statement, &proof)
+            .is_err());
+
+        // The prover does not stream an empty proof
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial)
+            .unwrap();
+        assert!(Prover(params)
+            .prove_to_writer(&mut merlin, statement, witness, &mut Vec::new())
+            .is_err());
+    }
+
+    // A stand-in for an algebraic hash, whose digests encode one element.
+    struct LinearHash;
+
+    fn digest(element: F) -> [u8; 32] {
+        let mut digest = [0; 32];
+        element.serialize_compressed(&mut digest[..]).unwrap();
+        digest
+    }
+
+    impl AlgebraicHash<F> for LinearHash {
+        fn hash_leaf(&self, leaf: &[F]) -> [u8; 32] {
+            digest(leaf.iter().fold(F::from(1), |acc, x| acc * F::from(7) + x))
+        }
+
+        fn compress(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+            let [left, right] =
+                [left, right].map(|digest| F::deserialize_compressed(&digest[..8]).unwrap());
+            digest(left * F::from(3) + right + F::from(1))
+        }
+    }
+
+    #[test]
+    fn test_round_merkle_params() {
+        type MerkleConfig = hybrid::MerkleTreeParams<F>;
+
+        let num_variables = 8;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = hybrid::default_config::<F>(&mut rng);
+        let config = |round_merkle_params| {
+            let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+                round_merkle_params,
+                ..whir_parameters((leaf_hash_params.clone(), two_to_one_params.clone()))
+            };
+            WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params,
+            )
+            .unwrap()
+        };
+        // Blake3 for the commitment, the algebraic hash for the later trees
+        let params = config(vec![hybrid::algebraic_config(Arc::new(LinearHash))]);
+        assert!(params.n_rounds() > 0);
+        let blake3_params = config(vec![]);
+
+        let io = io_pattern(&params);
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial.clone())
+            .unwrap();
+        let root = witness.merkle_tree.root();
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+        let mut arthur = io.to_arthur(merlin.transcript());
+        assert!(Verifier::new(params.clone())
+            .verify(&mut arthur, &statement, &proof)
+            .is_ok());
+
+        // The commitment is that of Blake3, the later trees are not
+        let mut blake3_merlin = io.to_merlin();
+        let blake3_witness = Committer::new(blake3_params.clone())
+            .commit(&mut blake3_merlin, polynomial)
+            .unwrap();
+        assert_eq!(blake3_witness.merkle_tree.root(), root);
+        let mut arthur = io.to_arthur(merlin.transcript());
+        assert!(Verifier::new(blake3_params)
+            .verify(&mut arthur, &statement, &proof)
+            .is_err());
+    }
+
+    #[test]
+    fn test_final_evaluations() {
+        let num_variables = 7;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let config = |final_form| {
+            let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+                final_form,
+                ..whir_parameters((leaf_hash_params.clone(), two_to_one_params.clone()))
+            };
+            WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params,
+            )
+            .unwrap()
+        };
+        let (params, coefficients_params) = (
+            config(FinalForm::Evaluations),
+            config(FinalForm::Coefficients),
+        );
+        assert_eq!(params.final_sumcheck_rounds, 1);
+        let io = io_pattern(&params);
+        assert_ne!(io.as_bytes(), io_pattern(&coefficients_params).as_bytes());
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial)
+            .unwrap();
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+        assert!(whir_proof_size(merlin.transcript(), &proof) <= params.proof_size_bound());
+        for (params, accepted) in [(params, true), (coefficients_params, false)] {
+            let mut arthur = io.to_arthur(merlin.transcript());
+            assert_eq!(
+                Verifier::new(params)
+                    .verify(&mut arthur, &statement, &proof)
+                    .is_ok(),
+                accepted
+            );
+        }
+    }
+
+    #[test]
+    fn test_pow_bits_override() {
+        let num_variables = 8;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+            pow_bits: 8,
+            ..whir_parameters((leaf_hash_params, two_to_one_params))
+        };
+        let params = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+        let mut pow_bits = params.pow_bits();
+        assert_eq!(pow_bits.rounds.len(), params.n_rounds() + 1);
+        assert_eq!(
+            params.clone().with_pow_bits(&pow_bits).unwrap().pow_bits(),
+            pow_bits
+        );
+
+        // All the proof of work in the first round, none in the others
+        for (r, round) in pow_bits.rounds.iter_mut().enumerate() {
+            round.queries = if r == 0 { 8. } else { 0. };
+            round.folding = 0.;
+        }
+        let params = params.with_pow_bits(&pow_bits).unwrap();
+        assert_eq!(params.pow_bits(), pow_bits);
+        assert!(params.check_pow_bits());
+
+        let io = io_pattern(&params);
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial)
+            .unwrap();
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+        let mut arthur = io.to_arthur(merlin.transcript());
+        assert!(Verifier::new(params.clone())
+            .verify(&mut arthur, &statement, &proof)
+            .is_ok());
+
+        pow_bits.rounds.pop();
+        assert!(params.clone().with_pow_bits(&pow_bits).is_err());
+        pow_bits.rounds.push(RoundPowBits {
+            queries: -1.,
+            folding: 0.,
+        });
+        assert!(params.with_pow_bits(&pow_bits).is_err());
+    }
+
+    #[test]
+    fn test_proof_size_budget() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let try_new = |max_proof_size| {
+            let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+                max_proof_size,
+                ..whir_parameters((leaf_hash_params.clone(), two_to_one_params.clone()))
+            };
+            WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(12),
+                whir_params,
+            )
+        };
+        let size = try_new(None).unwrap().proof_size_bound();
+
+        // A budget that the parameters meet changes nothing
+        let budget = ProofSizeBudget {
+            max_bytes: size,
+            max_pow_bits: 20,
+            max_folding_factor: 4,
+        };
+        let params = try_new(Some(budget)).unwrap();
+        assert_eq!((params.max_pow_bits, params.folding_factor), (0, 2));
+
+        // A smaller one takes more PoW or a larger folding factor
+        let budget = ProofSizeBudget {
+            max_bytes: size - 1,
+            ..budget
+        };
+        let params = try_new(Some(budget)).unwrap();
+        assert!(params.proof_size_bound() < size);
+        assert!(params.check_pow_bits());
+        assert!(params.max_pow_bits > 0 || params.folding_factor > 2);
+
+        // Otherwise, the smallest proof within the bounds is reported
+        let budget = ProofSizeBudget {
+            max_bytes: size - 1,
+            max_pow_bits: 0,
+            max_folding_factor: 2,
+        };
+        assert!(matches!(
+            try_new(Some(budget)),
+            Err(WhirError::ProofTooLarge { smallest, pow_bits: 0, folding_factor: 2, .. })
+                if smallest == size
+        ));
+        let budget = ProofSizeBudget {
+            max_bytes: 1000,
+            max_pow_bits: 20,
+            max_folding_factor: 4,
+        };
+        assert!(matches!(
+            try_new(Some(budget)),
+            Err(WhirError::ProofTooLarge { smallest, .. }) if smallest > 1000 && smallest < size
+        ));
+    }
+
+    #[test]
+    fn test_prover_modes() {
+        let num_variables = 8;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        for fold_optimisation in [FoldType::Naive, FoldType::ProverHelps] {
+            let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+                fold_optimisation,
+                ..whir_parameters((leaf_hash_params.clone(), two_to_one_params.clone()))
+            };
+            let params = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params,
+            )
+            .unwrap();
+            let io = io_pattern(&params);
+            let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+            let point = MultilinearPoint::rand(&mut rng, num_variables);
+            let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+            let prover = |params: WhirConfig<F, MerkleConfig, PowStrategy>| {
+                let (polynomial, statement) = (polynomial.clone(), statement.clone());
+                move |merlin: &mut Merlin| {
+                    let witness = Committer::new(params.clone()).commit(merlin, polynomial)?;
+                    Prover(params).prove(merlin, statement, witness)
+                }
+            };
+
+            // The same proofs in all modes, including with leaves of several cosets
+            for params in [
+                params.clone(),
+                params.clone().with_log_cosets_per_leaf(1).unwrap(),
+            ] {
+                assert_eq!(
+                    compare_provers(
+                        &io,
+                        prover(params.clone()),
+                        prover(params.clone().with_prover_mode(ProverMode::LowMemory)),
+                    )
+                    .unwrap(),
+                    None
+                );
+            }
+
+            let low_memory = params.clone().with_prover_mode(ProverMode::LowMemory);
+            assert!(
+                low_memory.prover_memory_estimate().peak < params.prover_memory_estimate().peak
+            );
+        }
+    }
+
+    #[test]
+    fn test_cosets_per_leaf() {
+        let num_variables = 8;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        for fold_optimisation in [FoldType::Naive, FoldType::ProverHelps] {
+            let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+                fold_optimisation,
+                ..whir_parameters((leaf_hash_params.clone(), two_to_one_params.clone()))
+            };
+            let unpacked = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params,
+            )
+            .unwrap();
+            assert!(unpacked.clone().with_log_cosets_per_leaf(8).is_err());
+            let params = unpacked.clone().with_log_cosets_per_leaf(1).unwrap();
+            assert_eq!(params.leaf_size(), 8);
+
+            let io = io_pattern(&params);
+            let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+            let point = MultilinearPoint::rand(&mut rng, num_variables);
+            let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit(&mut merlin, polynomial)
+                .unwrap();
+            assert_eq!(witness.num_leaves(), params.starting_domain().size() / 8);
+            let proof = Prover(params.clone())
+                .prove(&mut merlin, statement.clone(), witness)
+                .unwrap();
+            assert!(proof.check_shape(&params));
+            assert!(whir_proof_size(merlin.transcript(), &proof) <= params.proof_size_bound());
+
+            let mut arthur = io.to_arthur(merlin.transcript());
+            assert!(Verifier::new(params.clone())
+                .verify(&mut arthur, &statement, &proof)
+                .is_ok());
+            // The leaves do not have the shape of the unpacked configuration
+            assert!(!proof.check_shape(&unpacked));
+        }
+    }
+
+    #[test]
+    fn test_witness_shape() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+            starting_log_inv_rate: 2,
+            ..whir_parameters((leaf_hash_params, two_to_one_params))
+        };
+        let params = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+        let io = IOPattern::<DefaultHash>::new("🌪️").commit_statement(&params);
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let witness = Committer::new(params.clone())
+            .commit(&mut io.to_merlin(), polynomial)
+            .unwrap();
+
+        assert_eq!(witness.codeword_len(), params.starting_domain().size());
+        assert_eq!(witness.codeword_len(), 1 << (num_variables + 2));
+        assert_eq!(witness.log_inv_rate(), 2);
+        assert_eq!(witness.num_leaves(), witness.codeword_len() >> 2);
+        assert_eq!(witness.merkle_depth(), num_variables);
+    }
+
+    #[test]
+    fn test_application_label() {
+        let num_variables = 4;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables);
+        assert!(params.clone().with_application_label("").is_err());
+        let params_a = params.clone().with_application_label("app-a").unwrap();
+        let params_b = params.with_application_label("app-b").unwrap();
+        // The clones share the round tables and the hash parameters.
+        assert!(Arc::ptr_eq(
+            &params_a.round_parameters,
+            &params_b.round_parameters
+        ));
+        assert!(Arc::ptr_eq(
+            &params_a.leaf_hash_params,
+            &params_b.leaf_hash_params
+        ));
+        let io = |params: &WhirConfig<F, MerkleConfig, PowStrategy>| {
+            IOPattern::<DefaultHash>::new("🌪️")
+                .commit_statement(params)
+                .add_whir_proof(params)
+        };
+
+        let polynomial = CoefficientList::new(vec![F::from(1); 1 << num_variables]);
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+        let mut merlin = io(&params_a).to_merlin();
+        let witness = Committer::new(params_a.clone())
+            .commit(&mut merlin, polynomial)
+            .unwrap();
+        let proof = Prover(params_a.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+
+        let mut arthur = io(&params_a).to_arthur(merlin.transcript());
+        assert!(Verifier::new(params_a)
+            .verify(&mut arthur, &statement, &proof)
+            .is_ok());
+        let mut arthur = io(&params_b).to_arthur(merlin.transcript());
+        assert!(Verifier::new(params_b)
+            .verify(&mut arthur, &statement, &proof)
+            .is_err());
+    }
+
+    #[test]
+    fn test_prover_memory_estimate() {
+        let params = config(8);
+        let estimate = params.prover_memory_estimate();
+        assert_eq!(estimate.rounds.len(), params.n_rounds() + 1);
+        assert!(estimate.peak >= estimate.witness);
+
+        let io = IOPattern::<DefaultHash>::new("🌪️").commit_statement(&params);
+        let witness = Committer::new(params)
+            .commit(
+                &mut io.to_merlin(),
+                CoefficientList::new(vec![F::from(1); 1 << 8]),
+            )
+            .unwrap();
+        let committed = (witness.polynomial.num_coeffs() + witness.merkle_leaves.len())
+            * core::mem::size_of::<F>();
+        assert!(estimate.witness >= committed);
+
+        assert!(config(12).prover_memory_estimate().peak > estimate.peak);
+    }
+
+    #[test]
+    fn test_seeded_commitment() {
+        let num_variables = 9;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+            zk: true,
+            ..whir_parameters((leaf_hash_params, two_to_one_params))
+        };
+        let params = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+        let io = io_pattern(&params);
+        let polynomial = CoefficientList::new(vec![F::from(1); 1 << num_variables]);
+        let committer = Committer::new(params);
+        let commit = |seed| {
+            let mut merlin = io.to_merlin();
+            committer
+                .commit_with_seed(&mut merlin, polynomial.clone(), &seed)
+                .unwrap();
+            merlin.transcript().to_vec()
+        };
+        assert_eq!(commit([1; 32]), commit([1; 32]));
+        assert_ne!(commit([1; 32]), commit([2; 32]));
+    }
+
+    #[test]
+    fn test_fft_backend() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables);
+        let io = io_pattern(&params);
+        let polynomial =
+            CoefficientList::new((0..1 << num_variables).map(|i| F::from(i as u64)).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+
+        // Swapping the encoder for an equivalent one gives the same transcript.
+        let prove = |params: WhirConfig<F, MerkleConfig, PowStrategy>| {
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit(&mut merlin, polynomial.clone())
+                .unwrap();
+            Prover(params)
+                .prove(&mut merlin, statement.clone(), witness)
+                .unwrap();
+            merlin.transcript().to_vec()
+        };
+        assert_eq!(
+            prove(params.clone()),
+            prove(params.with_fft_backend(ArkPolyBackend))
+        );
+    }
+
+    // Reads each query from two bytes of the seed, as a verifier without ChaCha20 might.
+    struct ByteSampler;
+
+    impl QuerySampler for ByteSampler {
+        fn name(&self) -> &str {
+            "bytes"
+        }
+
+        fn sample(&self, seed: &[u8; 32], num_queries: usize, domain_size: usize) -> Vec<usize> {
+            seed.chunks_exact(2)
+                .cycle()
+                .take(num_queries)
+                .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as usize % domain_size)
+                .collect()
+        }
+    }
+
+    // Draws one index too few, or one out of the domain.
+    struct FaultySampler {
+        short: bool,
+    }
+
+    impl QuerySampler for FaultySampler {
+        fn name(&self) -> &str {
+            "faulty"
+        }
+
+        fn sample(&self, seed: &[u8; 32], num_queries: usize, domain_size: usize) -> Vec<usize> {
+            let mut indexes = ByteSampler.sample(seed, num_queries, domain_size);
+            if self.short {
+                indexes.pop();
+            } else {
+                indexes[0] = domain_size;
+            }
+            indexes
+        }
+    }
+
+    #[test]
+    fn test_query_sampler() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let default_params = config(num_variables);
+        let params = default_params.clone().with_query_sampler(ByteSampler);
+        let io = io_pattern(&params);
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial.clone())
+            .unwrap();
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+        for (params, accepted) in [(params.clone(), true), (default_params.clone(), false)] {
+            let mut arthur = io.to_arthur(merlin.transcript());
+            assert_eq!(
+                Verifier::new(params)
+                    .verify(&mut arthur, &statement, &proof)
+                    .is_ok(),
+                accepted
+            );
+        }
+
+        // The sampler is part of the IOPattern
+        let default_io = io_pattern(&default_params);
+        assert_ne!(io.as_bytes(), default_io.as_bytes());
+
+        // A sampler drawing too few indexes, or out of the domain, fails the proof
+        for short in [true, false] {
+            let faulty = default_params
+                .clone()
+                .with_query_sampler(FaultySampler { short });
+            let io = io_pattern(&faulty);
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(faulty.clone())
+                .commit(&mut merlin, polynomial.clone())
+                .unwrap();
+            assert!(Prover(faulty.clone())
+                .prove(&mut merlin, statement.clone(), witness)
+                .is_err());
+            let mut arthur = io.to_arthur(merlin.transcript());
+            assert!(Verifier::new(faulty)
+                .verify(&mut arthur, &statement, &proof)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_coset_offset() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let polynomial =
+            CoefficientList::new((0..1 << num_variables).map(|i| F::from(i as u64)).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+
+        for fold_optimisation in [FoldType::Naive, FoldType::ProverHelps] {
+            let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+                fold_optimisation,
+                ..whir_parameters((leaf_hash_params.clone(), two_to_one_params.clone()))
+            };
+            let default_params = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params,
+            )
+            .unwrap();
+            assert!(default_params.clone().with_coset_offset(F::ZERO).is_err());
+            let params = default_params
+                .clone()
+                .with_coset_offset(F::from(7))
+                .unwrap();
+            assert_eq!(params.coset_offset(), F::from(7));
+
+            let io = io_pattern(&params);
+            let default_io = io_pattern(&default_params);
+            assert_ne!(io.as_bytes(), default_io.as_bytes());
+
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit(&mut merlin, polynomial.clone())
+                .unwrap();
+            let proof = Prover(params.clone())
+                .prove(&mut merlin, statement.clone(), witness)
+                .unwrap();
+            let verifier = Verifier::new(params);
+            let mut arthur = io.to_arthur(merlin.transcript());
+            assert!(verifier.verify(&mut arthur, &statement, &proof).is_ok());
+
+            // The codeword is over the coset, so the proof does not verify over the subgroup.
+            let mut arthur = default_io.to_arthur(merlin.transcript());
+            assert!(Verifier::new(default_params)
+                .verify(&mut arthur, &statement, &proof)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_statement_binding() {
+        let num_variables = 4;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables);
+        let polynomial = CoefficientList::new(vec![F::from(1); 1 << num_variables]);
+        let points: Vec<_> = (0..2)
+            .map(|_| MultilinearPoint::rand(&mut rng, num_variables))
+            .collect();
+        let evaluations = points.iter().map(|p| polynomial.evaluate(p)).collect();
+        let statement = Statement::new(points, evaluations);
+        let io = |binding| {
+            IOPattern::<DefaultHash>::new("🌪️")
+                .commit_statement(&params)
+                .add_statement(&params, binding)
+                .add_whir_proof(&params)
+        };
+        // Binds with `bind` in both the prover and the verifier.
+        let prove =
+            |io: &IOPattern, bind: &dyn Fn(&mut nimue::Merlin) -> nimue::ProofResult<()>| {
+                let mut merlin = io.to_merlin();
+                let witness = Committer::new(params.clone())
+                    .commit(&mut merlin, polynomial.clone())
+                    .unwrap();
+                bind(&mut merlin).unwrap();
+                let proof = Prover(params.clone())
+                    .prove(&mut merlin, statement.clone(), witness)
+                    .unwrap();
+                (merlin.transcript().to_vec(), proof)
+            };
+        let verify =
+            |io: &IOPattern,
+             transcript: &[u8],
+             proof: &WhirProof<MerkleConfig, F>,
+             bind: &dyn Fn(&mut nimue::Arthur) -> nimue::ProofResult<()>| {
+                let verifier = Verifier::new(params.clone());
+                let mut arthur = io.to_arthur(transcript);
+                let commitment = verifier.parse_commitment(&mut arthur)?;
+                bind(&mut arthur)?;
+                verifier.verify_with_commitment(&mut arthur, &commitment, &statement, proof)
+            };
+
+        let full = io(StatementBinding::Full { num_claims: 2 });
+        let (transcript, proof) = prove(&full, &|merlin| merlin.public_statement(&statement));
+        assert!(verify(&full, &transcript, &proof, &|arthur| arthur
+            .public_statement(&statement))
+        .is_ok());
+        // The statement is absorbed, not sent
+        let unbound = io_pattern(&params);
+        let (unbound_transcript, _) = prove(&unbound, &|_| Ok(()));
+        assert_eq!(transcript.len(), unbound_transcript.len());
+        // Too many claims for the IOPattern
+        let mut larger = statement.clone();
+        larger
+            .points
+            .push(MultilinearPoint::rand(&mut rng, num_variables));
+        larger.evaluations.push(F::from(0));
+        larger.claim_types.push(ClaimType::Evaluation);
+        assert!(verify(&full, &transcript, &proof, &|arthur| arthur
+            .public_statement(&larger))
+        .is_err());
+
+        let digest = io(StatementBinding::Digest);
+        let (transcript, proof) =
+            prove(&digest, &|merlin| merlin.public_statement_digest(&[1; 32]));
+        assert!(verify(&digest, &transcript, &proof, &|arthur| arthur
+            .public_statement_digest(&[1; 32]))
+        .is_ok());
+        assert!(verify(&digest, &transcript, &proof, &|arthur| arthur
+            .public_statement_digest(&[2; 32]))
+        .is_err());
+    }
+
+    #[test]
+    fn test_statement_validation() {
+        let num_variables = 4;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables);
+
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let mut statement = Statement::new(vec![point.clone()], vec![F::from(1)]);
+        assert!(statement.validate(&params).is_ok());
+
+        statement.points.push(point.clone());
+        statement.evaluations.push(F::from(1));
+        statement.claim_types.push(ClaimType::Rotated);
+        assert!(statement.validate(&params).is_ok());
+
+        statement.points.push(point);
+        statement.evaluations.push(F::from(2));
+        statement.claim_types.push(ClaimType::Evaluation);
+        assert!(matches!(
+            statement.validate(&params),
+            Err(WhirError::DuplicateClaim(2))
+        ));
+
+        let statement = Statement::new(
+            vec![MultilinearPoint::rand(&mut rng, num_variables + 1)],
+            vec![F::from(1)],
+        );
+        assert!(matches!(
+            statement.validate(&params),
+            Err(WhirError::NumVariables {
+                expected: 4,
+                found: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn test_commit_virtual() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let a: Vec<F> = (0..1 << num_variables).map(|_| F::rand(&mut rng)).collect();
+        let b: Vec<F> = (0..1 << num_variables).map(|_| F::rand(&mut rng)).collect();
+        // The product of two columns, materialized for the reference commitment
+        let product = VirtualPolynomial::new(num_variables, |index| a[index] * b[index]);
+        let polynomial = CoefficientList::from(EvaluationsList::new(
+            a.iter().zip(&b).map(|(a, b)| *a * b).collect(),
+        ));
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+
+        for fold_optimisation in [FoldType::ProverHelps, FoldType::Naive] {
+            let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+            let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+                fold_optimisation,
+                ..whir_parameters((leaf_hash_params, two_to_one_params))
+            };
+            let params = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params,
+            )
+            .unwrap();
+            let io = io_pattern(&params);
+
+            let mut reference = io.to_merlin();
+            Committer::new(params.clone())
+                .commit(&mut reference, polynomial.clone())
+                .unwrap();
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit_virtual(&mut merlin, &product)
+                .unwrap();
+            // The same root and OOD answers
+            assert_eq!(merlin.transcript(), reference.transcript());
+
+            let proof = Prover(params.clone())
+                .prove(&mut merlin, statement.clone(), witness)
+                .unwrap();
+            let mut arthur = io.to_arthur(merlin.transcript());
+            assert!(Verifier::new(params)
+                .verify(&mut arthur, &statement, &proof)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_statement_ood_samples() {
+        let num_variables = 6;
+        let params = config(num_variables);
+        let required = params.committment_ood_samples;
+        assert!(required > 0);
+        let params = params.with_statement_ood_samples(required).unwrap();
+        assert_eq!(params.committment_ood_samples, 0);
+        // Idempotent, from the samples the soundness requires
+        let params = params.with_statement_ood_samples(required).unwrap();
+        assert_eq!(params.committment_ood_samples, 0);
+
+        // The points are squeezed in the commitment, by the prover and the verifier alike
+        let io = io_pattern(&params);
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let outer_point = MultilinearPoint(vec![F::from(3); num_variables]);
+        let outer_claim = Statement::new(
+            vec![outer_point.clone()],
+            vec![polynomial.evaluate_at_extension(&outer_point)],
+        );
+
+        let mut merlin = io.to_merlin();
+        let (witness, mut statement) = Committer::new(params.clone())
+            .commit_with_statement_ood(&mut merlin, polynomial.clone())
+            .unwrap();
+        assert_eq!(statement.points.len(), required);
+        statement.extend(outer_claim.clone());
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+
+        let verifier = Verifier::new(params.clone());
+        let mut arthur = io.to_arthur(merlin.transcript());
+        let commitment = verifier.parse_commitment(&mut arthur).unwrap();
+        assert_eq!(commitment.num_ood_samples(), 0);
+        let mut verifier_statement = verifier.statement_ood(&commitment);
+        assert_eq!(verifier_statement.points, statement.points[..required]);
+        verifier_statement.extend(outer_claim.clone());
+        assert!(verifier
+            .verify_with_commitment(&mut arthur, &commitment, &verifier_statement, &proof)
+            .is_ok());
+
+        // The statement must start with the claims at the squeezed points
+        let mut arthur = io.to_arthur(merlin.transcript());
+        assert!(verifier.verify(&mut arthur, &outer_claim, &proof).is_err());
+        let mut arthur = io.to_arthur(merlin.transcript());
+        let mut forged = statement.clone();
+        forged.points[0] = outer_point.clone();
+        assert!(verifier.verify(&mut arthur, &forged, &proof).is_err());
+        let mut merlin = io.to_merlin();
+        let (witness, _) = Committer::new(params.clone())
+            .commit_with_statement_ood(&mut merlin, polynomial)
+            .unwrap();
+        let mut reordered = outer_claim;
+        reordered.extend(statement);
+        assert!(matches!(
+            Prover(params.clone()).prove(&mut merlin, reordered, witness),
+            Err(WhirError::StatementOodSamples { expected, found: 0 }) if expected == required
+        ));
+        assert!(params.to_string().contains("OOD samples of the commitment"));
+    }
 }
@@ -1,15 +1,30 @@
+use ark_std::{fmt::Display, marker::PhantomData, mem::size_of, sync::Arc, vec::Vec};
 use core::panic;
-use std::{f64::consts::LOG2_10, fmt::Display, marker::PhantomData};
 
 use ark_crypto_primitives::merkle_tree::{Config, LeafParam, TwoToOneParam};
 use ark_ff::FftField;
+use ark_serialize::CanonicalSerialize;
+use nimue::{ProofError, ProofResult};
 
+use super::{
+    pow::PowService,
+    queries::{ChaChaSampler, QuerySampler},
+    ClaimType, LinearClaim, Statement,
+};
+#[cfg(feature = "std")]
+use crate::ntt::{FftBackend, NttBackend};
 use crate::{
     crypto::fields::FieldWithSize,
     domain::Domain,
-    parameters::{FoldType, MultivariateParameters, SoundnessType, WhirParameters},
+    errors::{WhirError, WhirResult},
+    parameters::{FinalForm, FoldType, MultivariateParameters, SoundnessType, WhirParameters},
+    poly_utils::{evals::EvaluationsList, MultilinearPoint},
+    soundness,
+    utils::{self, checked_pow2},
 };
 
+/// The configuration of WHIR. The round tables and the hash parameters are shared, so that
+/// cloning it for each `Committer`, `Prover` and `Verifier` is cheap.
 #[derive(Clone)]
 pub struct WhirConfig<F, MerkleConfig, PowStrategy>
 where
@@ -22,26 +37,84 @@ where
     pub(crate) max_pow_bits: usize,
 
     pub(crate) committment_ood_samples: usize,
+    // The OOD samples of the commitment left to the statement, see
+    // `with_statement_ood_samples`.
+    pub(crate) statement_ood_samples: usize,
     pub(crate) starting_domain: Domain<F>,
     pub(crate) starting_log_inv_rate: usize,
     pub(crate) starting_folding_pow_bits: f64,
 
     pub(crate) folding_factor: usize,
-    pub(crate) round_parameters: Vec<RoundConfig>,
+    // Each Merkle leaf holds `2^log_cosets_per_leaf` consecutive cosets, see
+    // `with_log_cosets_per_leaf`.
+    pub(crate) log_cosets_per_leaf: usize,
+    pub(crate) prover_mode: ProverMode,
+    // The openings are absorbed into the transcript, see `with_openings_in_transcript`.
+    pub(crate) openings_in_transcript: bool,
+    pub(crate) round_parameters: Arc<[RoundConfig]>,
     pub(crate) fold_optimisation: FoldType,
+    pub(crate) zk: bool,
+    pub(crate) application_label: Option<Arc<str>>,
 
     pub(crate) final_queries: usize,
     pub(crate) final_pow_bits: f64,
     pub(crate) final_log_inv_rate: usize,
     pub(crate) final_sumcheck_rounds: usize,
     pub(crate) final_folding_pow_bits: f64,
+    pub(crate) final_form: FinalForm,
 
     // PoW parameters
     pub(crate) pow_strategy: PhantomData<PowStrategy>,
 
+    // Derivation of the query indexes from their seed, see `whir::queries`
+    pub(crate) query_sampler: Arc<dyn QuerySampler>,
+
+    // Grinding of the round PoWs away from the prover, see `whir::pow`
+    pub(crate) pow_service: Option<Arc<dyn PowService>>,
+
     // Merkle tree parameters
-    pub(crate) leaf_hash_params: LeafParam<MerkleConfig>,
-    pub(crate) two_to_one_params: TwoToOneParam<MerkleConfig>,
+    pub(crate) leaf_hash_params: Arc<LeafParam<MerkleConfig>>,
+    pub(crate) two_to_one_params: Arc<TwoToOneParam<MerkleConfig>>,
+    // Those of the trees of the rounds, see `merkle_params`
+    pub(crate) round_merkle_params: Arc<[(LeafParam<MerkleConfig>, TwoToOneParam<MerkleConfig>)]>,
+
+    // Reed-Solomon encoders of the committed polynomial and of the folded polynomials
+    #[cfg(feature = "std")]
+    pub(crate) base_fft: Arc<dyn FftBackend<F::BasePrimeField>>,
+    #[cfg(feature = "std")]
+    pub(crate) fft: Arc<dyn FftBackend<F>>,
+}
+
+/// How the prover trades memory for speed, see `WhirConfig::with_prover_mode`. The proofs are the
+/// same in all modes. The powers of the generator of the domain are precomputed separately, with
+/// `WhirConfig::with_precomputed_domain`, and the openings are streamed with
+/// `Prover::prove_to_writer`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProverMode {
+    /// Keeps the codeword of each round to answer its queries.
+    #[default]
+    Balanced,
+    /// Drops the codeword of each round once committed, keeping the coefficients instead, and
+    /// recomputes the queried leaves from them, each in a pass over them. This saves
+    /// `2^log_inv_rate - 1` field elements per coefficient, in each round.
+    LowMemory,
+}
+
+/// The proof-of-work difficulties of a configuration, in bits, see `WhirConfig::pow_bits`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowBits {
+    /// In each round of the initial sumcheck.
+    pub starting_folding: f64,
+    /// Those of each round, then of the final round.
+    pub rounds: Vec<RoundPowBits>,
+}
+
+/// The proof-of-work difficulties of a round, in bits: once after its queries, then in each
+/// round of its sumcheck.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundPowBits {
+    pub queries: f64,
+    pub folding: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -58,26 +131,130 @@ where
     F: FftField + FieldWithSize,
     MerkleConfig: Config,
 {
+    /// The configuration of the parameters, failing on parameters for which there is none.
+    ///
+    /// With a `WhirParameters::max_proof_size` budget that the configuration of the parameters
+    /// exceeds, tries the PoW bits then the folding factors up to those of the budget, and returns
+    /// the first configuration that meets it within its PoW bits (see `check_pow_bits`). Fails
+    /// with `WhirError::ProofTooLarge` and the smallest proof found otherwise.
+    pub fn try_new(
+        mv_parameters: MultivariateParameters<F>,
+        whir_parameters: WhirParameters<MerkleConfig, PowStrategy>,
+    ) -> WhirResult<Self> {
+        let Some(budget) = whir_parameters.max_proof_size else {
+            return Self::try_new_exact(mv_parameters, whir_parameters);
+        };
+        let config = Self::try_new_exact(mv_parameters, whir_parameters.clone())?;
+        if config.proof_size_bound() <= budget.max_bytes {
+            return Ok(config);
+        }
+        // The smallest proof, with its PoW bits and folding factor
+        let mut smallest = (
+            config.proof_size_bound(),
+            whir_parameters.pow_bits,
+            whir_parameters.folding_factor,
+        );
+        let max_pow_bits = budget.max_pow_bits.min(whir_parameters.security_level);
+        for pow_bits in whir_parameters.pow_bits..=max_pow_bits {
+            for folding_factor in whir_parameters.folding_factor..=budget.max_folding_factor {
+                let whir_parameters = WhirParameters {
+                    pow_bits,
+                    folding_factor,
+                    ..whir_parameters.clone()
+                };
+                let Ok(config) = Self::try_new_exact(mv_parameters, whir_parameters) else {
+                    continue;
+                };
+                if !config.check_pow_bits() {
+                    continue;
+                }
+                let size = config.proof_size_bound();
+                if size <= budget.max_bytes {
+                    return Ok(config);
+                }
+                smallest = smallest.min((size, pow_bits, folding_factor));
+            }
+        }
+        let (smallest, pow_bits, folding_factor) = smallest;
+        Err(WhirError::ProofTooLarge {
+            max: budget.max_bytes,
+            smallest,
+            pow_bits,
+            folding_factor,
+        })
+    }
+
+    // `try_new` without the proof size budget.
+    fn try_new_exact(
+        mv_parameters: MultivariateParameters<F>,
+        whir_parameters: WhirParameters<MerkleConfig, PowStrategy>,
+    ) -> WhirResult<Self> {
+        let num_variables = mv_parameters
+            .num_variables
+            .checked_add(usize::from(whir_parameters.zk))
+            .ok_or(WhirError::TooManyVariables(mv_parameters.num_variables))?;
+        let num_coeffs =
+            checked_pow2(num_variables).ok_or(WhirError::TooManyVariables(num_variables))?;
+        if whir_parameters.folding_factor == 0 {
+            return Err(WhirError::InvalidParameters(
+                "folding factor should be non zero",
+            ));
+        }
+        if num_variables < whir_parameters.folding_factor {
+            return Err(WhirError::InvalidParameters(
+                "fewer variables than the folding factor",
+            ));
+        }
+        if whir_parameters.pow_bits > whir_parameters.security_level {
+            return Err(WhirError::InvalidParameters(
+                "more PoW bits than the security level",
+            ));
+        }
+        if Domain::<F>::new(num_coeffs, whir_parameters.starting_log_inv_rate).is_none() {
+            return Err(WhirError::InvalidParameters(
+                "no domain of the size of the codeword, check the 2-adicity of the field",
+            ));
+        }
+        let config = Self::from_checked_parameters(mv_parameters, whir_parameters);
+        if config.zk && !config.check_zk() {
+            return Err(WhirError::InvalidParameters(
+                "the proofs would not hide the polynomial, see `check_zk`",
+            ));
+        }
+        Ok(config)
+    }
+
+    /// The configuration of the parameters, ignoring `WhirParameters::max_proof_size`.
+    ///
+    /// # Panics
+    ///
+    /// On parameters for which there is no configuration, see `try_new`.
+    #[deprecated(note = "use `try_new`, which fails instead of panicking")]
     pub fn new(
         mv_parameters: MultivariateParameters<F>,
         whir_parameters: WhirParameters<MerkleConfig, PowStrategy>,
     ) -> Self {
-        // We need to fold at least some time
-        assert!(
-            whir_parameters.folding_factor > 0,
-            "folding factor should be non zero"
-        );
-        // If less, just send the damn polynomials
-        assert!(mv_parameters.num_variables >= whir_parameters.folding_factor);
+        Self::try_new_exact(mv_parameters, whir_parameters).unwrap_or_else(|err| panic!("{err}"))
+    }
 
+    // The configuration of parameters checked by `try_new_exact`.
+    fn from_checked_parameters(
+        mv_parameters: MultivariateParameters<F>,
+        whir_parameters: WhirParameters<MerkleConfig, PowStrategy>,
+    ) -> Self {
+        // In ZK mode we commit to f(X) + X_0 * r(X) for a random mask r, which has one more variable
+        let mv_parameters = if whir_parameters.zk {
+            MultivariateParameters::new(mv_parameters.num_variables + 1)
+        } else {
+            mv_parameters
+        };
         let protocol_security_level =
             0.max(whir_parameters.security_level - whir_parameters.pow_bits);
 
-        let starting_domain = Domain::new(
-            1 << mv_parameters.num_variables,
-            whir_parameters.starting_log_inv_rate,
-        )
-        .expect("Should have found an appropriate domain - check Field 2 adicity?");
+        let num_coeffs = checked_pow2(mv_parameters.num_variables)
+            .expect("The number of coefficients should fit in a usize");
+        let starting_domain = Domain::new(num_coeffs, whir_parameters.starting_log_inv_rate)
+            .expect("Should have found an appropriate domain - check Field 2 adicity?");
 
         let final_sumcheck_rounds = mv_parameters.num_variables % whir_parameters.folding_factor;
         let num_rounds = ((mv_parameters.num_variables - final_sumcheck_rounds)
@@ -97,6 +274,12 @@ where
             ),
             field_size_bits,
         );
+        // The OOD sample puts weight on the mask, so that the sumcheck only sees blinded sums
+        let committment_ood_samples = if whir_parameters.zk {
+            committment_ood_samples.max(1)
+        } else {
+            committment_ood_samples
+        };
 
         let starting_folding_pow_bits = Self::folding_pow_bits(
             whir_parameters.security_level,
@@ -187,23 +370,299 @@ where
             security_level: whir_parameters.security_level,
             max_pow_bits: whir_parameters.pow_bits,
             committment_ood_samples,
+            statement_ood_samples: 0,
             mv_parameters,
             starting_domain,
             soundness_type: whir_parameters.soundness_type,
             starting_log_inv_rate: whir_parameters.starting_log_inv_rate,
             starting_folding_pow_bits,
             folding_factor: whir_parameters.folding_factor,
-            round_parameters,
+            log_cosets_per_leaf: 0,
+            prover_mode: ProverMode::Balanced,
+            openings_in_transcript: false,
+            round_parameters: round_parameters.into(),
             final_queries,
             final_pow_bits,
             final_sumcheck_rounds,
             final_folding_pow_bits,
+            final_form: whir_parameters.final_form,
             pow_strategy: PhantomData::default(),
             fold_optimisation: whir_parameters.fold_optimisation,
+            zk: whir_parameters.zk,
+            application_label: None,
             final_log_inv_rate: log_inv_rate,
-            leaf_hash_params: whir_parameters.leaf_hash_params,
-            two_to_one_params: whir_parameters.two_to_one_params,
+            query_sampler: Arc::new(ChaChaSampler),
+            pow_service: None,
+            leaf_hash_params: Arc::new(whir_parameters.leaf_hash_params),
+            two_to_one_params: Arc::new(whir_parameters.two_to_one_params),
+            round_merkle_params: whir_parameters.round_merkle_params.into(),
+            #[cfg(feature = "std")]
+            base_fft: Arc::new(NttBackend),
+            #[cfg(feature = "std")]
+            fft: Arc::new(NttBackend),
+        }
+    }
+
+    /// Encodes with `backend` instead of the NTT of this crate, for both the committed
+    /// polynomial (over the base field) and the folded ones (over `F`). The encodings must be
+    /// the same, see `FftBackend`.
+    #[cfg(feature = "std")]
+    pub fn with_fft_backend<B>(mut self, backend: B) -> Self
+    where
+        B: FftBackend<F> + FftBackend<F::BasePrimeField> + 'static,
+    {
+        let backend = Arc::new(backend);
+        self.base_fft = backend.clone();
+        self.fft = backend;
+        self
+    }
+
+    /// Derives the query indexes with `sampler` instead of `whir::queries::query_indexes`, e.g.
+    /// to match a verifier with other randomness plumbing. The prover and the verifier must use
+    /// the same sampler, whose name is bound into the IOPattern.
+    pub fn with_query_sampler(mut self, sampler: impl QuerySampler + 'static) -> Self {
+        self.query_sampler = Arc::new(sampler);
+        self
+    }
+
+    /// Grinds the proofs of work of the rounds with `service`, while the prover opens the
+    /// queried leaves, see `whir::pow`. The proofs do not change.
+    pub fn with_pow_service(mut self, service: impl PowService + 'static) -> Self {
+        self.pow_service = Some(Arc::new(service));
+        self
+    }
+
+    // The hash parameters of tree `tree`: 0 for the commitment, `r + 1` for the tree committed in
+    // round `r` and opened by the next one. See `WhirParameters::round_merkle_params`.
+    pub(crate) fn merkle_params(
+        &self,
+        tree: usize,
+    ) -> (&LeafParam<MerkleConfig>, &TwoToOneParam<MerkleConfig>) {
+        match tree.checked_sub(1) {
+            Some(round) if !self.round_merkle_params.is_empty() => {
+                let (leaf, two_to_one) =
+                    &self.round_merkle_params[round.min(self.round_merkle_params.len() - 1)];
+                (leaf, two_to_one)
+            }
+            _ => (&self.leaf_hash_params, &self.two_to_one_params),
+        }
+    }
+
+    // The query indexes of a round, sorted and deduplicated. A sampler drawing other than
+    // `num_queries` indexes, or indexes out of `0..domain_size`, fails the proof rather than
+    // leaving the round with fewer queries than its soundness requires.
+    pub(crate) fn query_indexes(
+        &self,
+        seed: &[u8; 32],
+        num_queries: usize,
+        domain_size: usize,
+    ) -> ProofResult<Vec<usize>> {
+        let indexes = self.query_sampler.sample(seed, num_queries, domain_size);
+        if indexes.len() != num_queries || indexes.iter().any(|&index| index >= domain_size) {
+            return Err(ProofError::InvalidProof);
         }
+        Ok(utils::dedup(indexes))
+    }
+
+    /// Binds `label` into the IOPattern of the proofs (see `WhirIOPattern`), so that proofs for
+    /// one application do not verify in another using the same parameters.
+    pub fn with_application_label(mut self, label: &str) -> WhirResult<Self> {
+        if label.is_empty() || label.contains('\0') {
+            return Err(WhirError::InvalidParameters(
+                "application label must be non-empty and without NUL bytes",
+            ));
+        }
+        self.application_label = Some(label.into());
+        Ok(self)
+    }
+
+    /// Evaluates the codewords over the coset `offset * <w>` of the subgroup instead of the
+    /// subgroup itself, e.g. to keep the codeword disjoint from the trace domain of an
+    /// integration. The offset is bound into the IOPattern (see `WhirIOPattern`).
+    pub fn with_coset_offset(mut self, offset: F::BasePrimeField) -> WhirResult<Self> {
+        self.starting_domain =
+            self.starting_domain
+                .with_offset(offset)
+                .ok_or(WhirError::InvalidParameters(
+                    "coset offset must be non-zero",
+                ))?;
+        Ok(self)
+    }
+
+    /// Precomputes the powers of the generator of the starting domain once, for the committer
+    /// and all the rounds of the prover, shared by the clones of the configuration. This costs
+    /// one field element per position of the codeword, so it is only worth it for the prover of
+    /// many proofs.
+    pub fn with_precomputed_domain(mut self) -> Self {
+        self.starting_domain = self.starting_domain.with_precomputed_powers();
+        self
+    }
+
+    /// Selects how the prover trades memory for speed, `ProverMode::Balanced` by default.
+    pub fn with_prover_mode(mut self, mode: ProverMode) -> Self {
+        self.prover_mode = mode;
+        self
+    }
+
+    /// Absorbs the openings of each round into the transcript, right after its query seed,
+    /// instead of returning them in the `WhirProof`, which is then empty: the transcript alone is
+    /// the proof. Each opening is padded to the largest one the round accepts, so that `whir_proof_size`
+    /// is exactly `proof_size_bound`. This changes the IOPattern (see `WhirIOPattern`).
+    pub fn with_openings_in_transcript(mut self) -> Self {
+        self.openings_in_transcript = true;
+        self
+    }
+
+    /// Counts `num_samples` evaluation claims of the statement as OOD samples of the commitment:
+    /// the commitment samples as many fewer of its own, and squeezes `num_samples` random points
+    /// of the hypercube's extension instead, whose evaluations the prover sends. The prover and
+    /// the verifier get these claims from `Witness::statement_ood` and `Verifier::statement_ood`,
+    /// and statements not starting with them are rejected; the outer protocol extends them with
+    /// its own claims. A random point of the hypercube's extension is at least as good an OOD
+    /// sample as the expansion of a univariate one. This changes the IOPattern (see
+    /// `WhirIOPattern`). Not available in ZK mode, where the OOD sample of the commitment also
+    /// blinds the mask.
+    pub fn with_statement_ood_samples(mut self, num_samples: usize) -> WhirResult<Self> {
+        if self.zk {
+            return Err(WhirError::InvalidParameters(
+                "the OOD samples cannot come from the statement in ZK mode",
+            ));
+        }
+        let required = self.committment_ood_samples + self.statement_ood_samples;
+        self.committment_ood_samples = required.saturating_sub(num_samples);
+        self.statement_ood_samples = num_samples;
+        Ok(self)
+    }
+
+    /// Packs `2^log_cosets_per_leaf` consecutive cosets into each Merkle leaf instead of one, so
+    /// that the trees are shallower. A query opens the whole leaf holding its coset: fewer hashes
+    /// are sent and computed, for more field elements. Fails if the codeword of the last round
+    /// has fewer cosets than a leaf. The packing is bound into the IOPattern (see
+    /// `WhirIOPattern`).
+    pub fn with_log_cosets_per_leaf(mut self, log_cosets_per_leaf: usize) -> WhirResult<Self> {
+        let last_domain_size = self.starting_domain.size() >> self.n_rounds();
+        if self.folding_factor + log_cosets_per_leaf > last_domain_size.ilog2() as usize {
+            return Err(WhirError::InvalidParameters(
+                "the last codeword must have at least as many cosets as a leaf",
+            ));
+        }
+        self.log_cosets_per_leaf = log_cosets_per_leaf;
+        Ok(self)
+    }
+
+    /// The number of field elements of a Merkle leaf.
+    pub fn leaf_size(&self) -> usize {
+        1 << (self.folding_factor + self.log_cosets_per_leaf)
+    }
+
+    // The leaves holding the cosets `indexes`, sorted and deduplicated.
+    pub(crate) fn leaf_indexes(&self, indexes: &[usize]) -> Vec<usize> {
+        utils::dedup(
+            indexes
+                .iter()
+                .map(|index| index >> self.log_cosets_per_leaf),
+        )
+    }
+
+    // The evaluations over the cosets `indexes`, cut out of the leaves `leaf_indexes` opened for
+    // them. `None` if a coset is not in the opened leaves, or a leaf is too short.
+    pub(crate) fn query_cosets<'a, A: AsRef<[F]>>(
+        &self,
+        indexes: &[usize],
+        leaf_indexes: &[usize],
+        leaves: &'a [A],
+    ) -> Option<Vec<&'a [F]>> {
+        let coset_size = 1 << self.folding_factor;
+        indexes
+            .iter()
+            .map(|index| {
+                let leaf = leaf_indexes
+                    .binary_search(&(index >> self.log_cosets_per_leaf))
+                    .ok()?;
+                let start = (index & ((1 << self.log_cosets_per_leaf) - 1)) * coset_size;
+                leaves.get(leaf)?.as_ref().get(start..start + coset_size)
+            })
+            .collect()
+    }
+
+    /// The proof-of-work difficulties, as allocated by `new` to reach the security level in each
+    /// round, or as set with `with_pow_bits`.
+    pub fn pow_bits(&self) -> PowBits {
+        PowBits {
+            starting_folding: self.starting_folding_pow_bits,
+            rounds: self
+                .round_parameters
+                .iter()
+                .map(|r| RoundPowBits {
+                    queries: r.pow_bits,
+                    folding: r.folding_pow_bits,
+                })
+                .chain([RoundPowBits {
+                    queries: self.final_pow_bits,
+                    folding: self.final_folding_pow_bits,
+                }])
+                .collect(),
+        }
+    }
+
+    /// Replaces the proof-of-work difficulties, e.g. to move them out of the rounds that are
+    /// expensive to verify in a circuit. A round with less proof of work than allocated by `new`
+    /// no longer reaches the security level on its own, see the round-by-round errors in the
+    /// `Display` of the configuration; the caller is responsible for the overall soundness.
+    /// Difficulties above `WhirParameters::pow_bits` are allowed, but fail `check_pow_bits`.
+    pub fn with_pow_bits(mut self, pow_bits: &PowBits) -> WhirResult<Self> {
+        if pow_bits.rounds.len() != self.n_rounds() + 1 {
+            return Err(WhirError::InvalidParameters(
+                "pow bits must be given for each round and the final round",
+            ));
+        }
+        let valid = |bits: f64| bits.is_finite() && bits >= 0.;
+        if !valid(pow_bits.starting_folding)
+            || !pow_bits
+                .rounds
+                .iter()
+                .all(|r| valid(r.queries) && valid(r.folding))
+        {
+            return Err(WhirError::InvalidParameters(
+                "pow bits must be finite and non-negative",
+            ));
+        }
+
+        let (final_round, rounds) = pow_bits.rounds.split_last().unwrap();
+        self.starting_folding_pow_bits = pow_bits.starting_folding;
+        self.round_parameters = self
+            .round_parameters
+            .iter()
+            .zip(rounds)
+            .map(|(r, bits)| RoundConfig {
+                pow_bits: bits.queries,
+                folding_pow_bits: bits.folding,
+                ..r.clone()
+            })
+            .collect();
+        self.final_pow_bits = final_round.queries;
+        self.final_folding_pow_bits = final_round.folding;
+        Ok(self)
+    }
+
+    /// The offset of the starting domain, one unless set with `with_coset_offset`.
+    pub fn coset_offset(&self) -> F {
+        self.starting_domain.offset()
+    }
+
+    pub fn application_label(&self) -> Option<&str> {
+        self.application_label.as_deref()
+    }
+
+    /// The domain of the committed codeword.
+    pub fn starting_domain(&self) -> &Domain<F> {
+        &self.starting_domain
+    }
+
+    /// The domain of the codeword committed in round `round` (the starting domain for round 0),
+    /// or `None` past the last round. Each round halves the domain.
+    pub fn round_domain(&self, round: usize) -> Option<Domain<F>> {
+        (round <= self.n_rounds()).then(|| self.starting_domain.scale(1 << round))
     }
 
     pub fn n_rounds(&self) -> usize {
@@ -224,13 +683,204 @@ where
             })
     }
 
-    pub fn log_eta(soundness_type: SoundnessType, log_inv_rate: usize) -> f64 {
-        // Ask me how I did this? At the time, only God and I knew. Now only God knows
-        match soundness_type {
-            SoundnessType::ProvableList => -(0.5 * log_inv_rate as f64 + LOG2_10 + 1.),
-            SoundnessType::UniqueDecoding => 0.,
-            SoundnessType::ConjectureList => -(log_inv_rate as f64 + 1.),
+    /// The number of variables of the polynomials being proven, without the mask.
+    pub fn num_variables(&self) -> usize {
+        self.mv_parameters.num_variables - self.zk as usize
+    }
+
+    /// An upper bound on the number of linear combinations of the committed polynomial revealed
+    /// by a proof: OOD answers, sumcheck evaluations, opened leaves and the final coefficients.
+    pub fn zk_revealed_evaluations(&self) -> usize {
+        let fold_size = 1 << self.folding_factor;
+        let ood_samples = self.committment_ood_samples
+            + self
+                .round_parameters
+                .iter()
+                .map(|r| r.ood_samples)
+                .sum::<usize>();
+        let queries = self
+            .round_parameters
+            .iter()
+            .map(|r| r.num_queries)
+            .sum::<usize>()
+            + self.final_queries;
+        ood_samples
+            + 3 * self.mv_parameters.num_variables
+            + queries * fold_size
+            + (1 << self.final_sumcheck_rounds)
+    }
+
+    /// Checks that the proofs hide the committed polynomial, that is that the mask is larger
+    /// than what is revealed of it. The final coefficients are sent in the clear, so they must not
+    /// depend on the unmasked part: `num_variables + 1` must be a multiple of the folding factor.
+    /// `try_new` rejects the `zk` configurations that fail it.
+    pub fn check_zk(&self) -> bool {
+        self.zk
+            && self.final_sumcheck_rounds == 0
+            && self.zk_revealed_evaluations() <= 1 << self.num_variables()
+    }
+
+    /// A projection of the memory used by the prover, so that a job can be scheduled on a
+    /// machine with enough of it. Field elements and digests are counted at their in-memory size;
+    /// allocator overhead and the heap behind boxed digests are not counted.
+    pub fn prover_memory_estimate(&self) -> MemoryEstimate {
+        let field = size_of::<F>();
+        let tree = |num_leaves: usize| {
+            num_leaves.saturating_mul(size_of::<MerkleConfig::LeafDigest>())
+                + num_leaves.saturating_mul(size_of::<MerkleConfig::InnerDigest>())
+        };
+        let leaf_size = self.leaf_size();
+        let num_variables = self.mv_parameters.num_variables;
+        let domain_size = self.starting_domain.size();
+
+        // The coefficients, the leaves and their tree; the encoding goes through one more buffer.
+        let encoding = |num_variables: usize, domain_size: usize| {
+            (1usize << num_variables)
+                .saturating_add(2 * domain_size)
+                .saturating_mul(field)
+                .saturating_add(tree(domain_size / leaf_size))
+        };
+        // With the precomputed powers of the generator, shared by all the rounds
+        let powers = if self.starting_domain.has_precomputed_powers() {
+            domain_size.saturating_mul(field)
+        } else {
+            0
+        };
+        let witness = powers.saturating_add(encoding(num_variables, domain_size));
+
+        // In each round, the sumcheck tables and the previous codeword are live while the folded
+        // polynomial is encoded. `ProverMode::LowMemory` recomputes the codeword from a copy of
+        // the coefficients instead.
+        let codeword = |num_variables: usize, domain_size: usize| match self.prover_mode {
+            ProverMode::LowMemory => 1 << num_variables,
+            ProverMode::Balanced => domain_size,
+        };
+        let rounds: Vec<usize> = (0..=self.n_rounds())
+            .map(|round| {
+                let num_variables = num_variables - round * self.folding_factor;
+                let domain_size = domain_size >> round;
+                let current = (1usize << num_variables)
+                    .saturating_mul(2)
+                    .saturating_add(codeword(num_variables, domain_size))
+                    .saturating_mul(field)
+                    .saturating_add(tree(domain_size / leaf_size));
+                if round == self.n_rounds() {
+                    current
+                } else {
+                    current.saturating_add(encoding(
+                        num_variables - self.folding_factor,
+                        domain_size / 2,
+                    ))
+                }
+            })
+            .collect();
+
+        // The opened leaves and their paths, kept until the proof is returned.
+        let proof = self
+            .round_parameters
+            .iter()
+            .map(|r| r.num_queries)
+            .chain([self.final_queries])
+            .enumerate()
+            .map(|(round, num_queries)| {
+                let depth = ((domain_size >> round) / leaf_size).trailing_zeros() as usize;
+                num_queries.saturating_mul(
+                    leaf_size * field
+                        + depth * size_of::<MerkleConfig::InnerDigest>()
+                        + size_of::<MerkleConfig::LeafDigest>(),
+                )
+            })
+            .fold(0usize, usize::saturating_add);
+
+        let peak = rounds
+            .iter()
+            .map(|round| round.saturating_add(proof))
+            .fold(witness, usize::max);
+        MemoryEstimate {
+            witness,
+            rounds,
+            proof,
+            peak,
+        }
+    }
+
+    /// An upper bound on `whir_proof_size`, that is on the bytes of the transcript and of the
+    /// compressed proof, before proving. The transcript size is exact; the openings are counted
+    /// as if no two queries of a round collided and the authentication paths shared no prefix.
+    pub fn proof_size_bound(&self) -> usize {
+        let field = F::ZERO.compressed_size();
+        let pow = |bits: f64| if bits > 0. { 8 } else { 0 };
+        let sumcheck = |rounds: usize, pow_bits: f64| rounds * (3 * field + pow(pow_bits));
+
+        // The transcript, as laid out by `WhirIOPattern`
+        let mut transcript = 32
+            + (self.committment_ood_samples + self.statement_ood_samples) * field
+            + sumcheck(self.folding_factor, self.starting_folding_pow_bits);
+        for r in self.round_parameters.iter() {
+            transcript += 32
+                + r.ood_samples * field
+                + pow(r.pow_bits)
+                + sumcheck(self.folding_factor, r.folding_pow_bits);
         }
+        transcript += (1 << self.final_sumcheck_rounds) * field
+            + pow(self.final_pow_bits)
+            + sumcheck(self.final_sumcheck_rounds, self.final_folding_pow_bits);
+
+        // The openings of each round, as laid out by `WhirProof::serialize_with_mode`
+        let openings = super::opening_bounds(self)
+            .iter()
+            .map(|bound| bound.max_size::<F, MerkleConfig>())
+            .sum::<usize>();
+
+        transcript + 4 + 8 + openings
+    }
+
+    /// In ZK mode, maps claims about the polynomial to claims about the masked polynomial, by
+    /// setting `X_0 = 0`. Rotations would wrap around into the mask, so only evaluation claims
+    /// are supported.
+    pub(crate) fn mask_statement(&self, statement: &Statement<F>) -> WhirResult<Statement<F>> {
+        if !self.zk {
+            return Ok(statement.clone());
+        }
+        if statement
+            .claim_types
+            .iter()
+            .any(|&claim_type| claim_type != ClaimType::Evaluation)
+        {
+            return Err(WhirError::UnsupportedClaim);
+        }
+        Ok(Statement {
+            points: statement
+                .points
+                .iter()
+                .map(|point| {
+                    MultilinearPoint(
+                        ark_std::iter::once(F::ZERO)
+                            .chain(point.0.clone())
+                            .collect(),
+                    )
+                })
+                .collect(),
+            evaluations: statement.evaluations.clone(),
+            claim_types: statement.claim_types.clone(),
+            // The weights are zero on the mask
+            linear_claims: statement
+                .linear_claims
+                .iter()
+                .map(|claim| {
+                    let mut weights = claim.weights.evals().to_vec();
+                    weights.resize(2 * weights.len(), F::ZERO);
+                    LinearClaim {
+                        weights: EvaluationsList::new(weights),
+                        sum: claim.sum,
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    pub fn log_eta(soundness_type: SoundnessType, log_inv_rate: usize) -> f64 {
+        soundness::log_eta(soundness_type, log_inv_rate)
     }
 
     pub fn list_size_bits(
@@ -239,18 +889,7 @@ where
         log_inv_rate: usize,
         log_eta: f64,
     ) -> f64 {
-        match soundness_type {
-            SoundnessType::ConjectureList => {
-                let result = (num_variables + log_inv_rate) as f64 - log_eta;
-                result
-            }
-            SoundnessType::ProvableList => {
-                let log_inv_sqrt_rate: f64 = log_inv_rate as f64 / 2.;
-                let result = log_inv_sqrt_rate - (1. + log_eta);
-                result
-            }
-            SoundnessType::UniqueDecoding => 0.0,
-        }
+        soundness::list_size_bits(soundness_type, num_variables, log_inv_rate, log_eta)
     }
 
     pub fn rbr_ood_sample(
@@ -261,43 +900,34 @@ where
         field_size_bits: usize,
         ood_samples: usize,
     ) -> f64 {
-        let list_size_bits =
-            Self::list_size_bits(soundness_type, num_variables, log_inv_rate, log_eta);
-
-        let error = 2. * list_size_bits + (num_variables * ood_samples) as f64;
-        (ood_samples * field_size_bits) as f64 + 1. - error
+        soundness::rbr_ood_sample(
+            soundness_type,
+            num_variables,
+            log_inv_rate,
+            log_eta,
+            field_size_bits,
+            ood_samples,
+        )
     }
 
     pub fn ood_samples(
-        security_level: usize, // We don't do PoW for OOD
+        security_level: usize,
         soundness_type: SoundnessType,
         num_variables: usize,
         log_inv_rate: usize,
         log_eta: f64,
         field_size_bits: usize,
     ) -> usize {
-        if matches!(soundness_type, SoundnessType::UniqueDecoding) {
-            0
-        } else {
-            for ood_samples in 1..64 {
-                if Self::rbr_ood_sample(
-                    soundness_type,
-                    num_variables,
-                    log_inv_rate,
-                    log_eta,
-                    field_size_bits,
-                    ood_samples,
-                ) >= security_level as f64
-                {
-                    return ood_samples;
-                }
-            }
-
-            panic!("Could not find an appropriate number of OOD samples");
-        }
+        soundness::ood_samples(
+            security_level,
+            soundness_type,
+            num_variables,
+            log_inv_rate,
+            log_eta,
+            field_size_bits,
+        )
     }
 
-    // Compute the proximity gaps term of the fold
     pub fn rbr_soundness_fold_prox_gaps(
         soundness_type: SoundnessType,
         field_size_bits: usize,
@@ -305,16 +935,13 @@ where
         log_inv_rate: usize,
         log_eta: f64,
     ) -> f64 {
-        // Recall, at each round we are only folding by two at a time
-        let error = match soundness_type {
-            SoundnessType::ConjectureList => (num_variables + log_inv_rate) as f64 - log_eta,
-            SoundnessType::ProvableList => {
-                LOG2_10 + 3.5 * log_inv_rate as f64 + 2. * num_variables as f64
-            }
-            SoundnessType::UniqueDecoding => (num_variables + log_inv_rate) as f64,
-        };
-
-        field_size_bits as f64 - error
+        soundness::rbr_soundness_fold_prox_gaps(
+            soundness_type,
+            field_size_bits,
+            num_variables,
+            log_inv_rate,
+            log_eta,
+        )
     }
 
     pub fn rbr_soundness_fold_sumcheck(
@@ -324,9 +951,13 @@ where
         log_inv_rate: usize,
         log_eta: f64,
     ) -> f64 {
-        let list_size = Self::list_size_bits(soundness_type, num_variables, log_inv_rate, log_eta);
-
-        field_size_bits as f64 - (list_size + 1.)
+        soundness::rbr_soundness_fold_sumcheck(
+            soundness_type,
+            field_size_bits,
+            num_variables,
+            log_inv_rate,
+            log_eta,
+        )
     }
 
     pub fn folding_pow_bits(
@@ -337,66 +968,30 @@ where
         log_inv_rate: usize,
         log_eta: f64,
     ) -> f64 {
-        let prox_gaps_error = Self::rbr_soundness_fold_prox_gaps(
+        soundness::folding_pow_bits(
+            security_level,
             soundness_type,
             field_size_bits,
             num_variables,
             log_inv_rate,
             log_eta,
-        );
-        let sumcheck_error = Self::rbr_soundness_fold_sumcheck(
-            soundness_type,
-            field_size_bits,
-            num_variables,
-            log_inv_rate,
-            log_eta,
-        );
-
-        let error = prox_gaps_error.min(sumcheck_error);
-
-        0_f64.max(security_level as f64 - error)
+        )
     }
 
-    // Used to select the number of queries
     pub fn queries(
         soundness_type: SoundnessType,
         protocol_security_level: usize,
         log_inv_rate: usize,
     ) -> usize {
-        let num_queries_f = match soundness_type {
-            SoundnessType::UniqueDecoding => {
-                let rate = 1. / ((1 << log_inv_rate) as f64);
-                let denom = (0.5 * (1. + rate)).log2();
-
-                -(protocol_security_level as f64) / denom
-            }
-            SoundnessType::ProvableList => {
-                (2 * protocol_security_level) as f64 / log_inv_rate as f64
-            }
-            SoundnessType::ConjectureList => protocol_security_level as f64 / log_inv_rate as f64,
-        };
-        num_queries_f.ceil() as usize
+        soundness::queries(soundness_type, protocol_security_level, log_inv_rate)
     }
 
-    // This is the bits of security of the query step
     pub fn rbr_queries(
         soundness_type: SoundnessType,
         log_inv_rate: usize,
         num_queries: usize,
     ) -> f64 {
-        let num_queries = num_queries as f64;
-        let bits_of_sec_queries = match soundness_type {
-            SoundnessType::UniqueDecoding => {
-                let rate = 1. / ((1 << log_inv_rate) as f64);
-                let denom = -(0.5 * (1. + rate)).log2();
-
-                num_queries * denom
-            }
-            SoundnessType::ProvableList => num_queries * 0.5 * log_inv_rate as f64,
-            SoundnessType::ConjectureList => num_queries * log_inv_rate as f64,
-        };
-
-        bits_of_sec_queries
+        soundness::rbr_queries(soundness_type, log_inv_rate, num_queries)
     }
 
     pub fn rbr_soundness_queries_combination(
@@ -408,20 +1003,37 @@ where
         ood_samples: usize,
         num_queries: usize,
     ) -> f64 {
-        let list_size = Self::list_size_bits(soundness_type, num_variables, log_inv_rate, log_eta);
-
-        let log_combination = ((ood_samples + num_queries) as f64).log2();
-
-        field_size_bits as f64 - (log_combination + list_size + 1.)
+        soundness::rbr_soundness_queries_combination(
+            soundness_type,
+            field_size_bits,
+            num_variables,
+            log_inv_rate,
+            log_eta,
+            ood_samples,
+            num_queries,
+        )
     }
 }
 
+/// The memory used by the prover, in bytes, as projected by `WhirConfig::prover_memory_estimate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// The witness: the polynomial, its encoding and the Merkle tree.
+    pub witness: usize,
+    /// The tables live during each round, the final one last.
+    pub rounds: Vec<usize>,
+    /// The openings accumulated in the proof.
+    pub proof: usize,
+    /// The peak over the commitment and all the rounds.
+    pub peak: usize,
+}
+
 impl<F, MerkleConfig, PowStrategy> Display for WhirConfig<F, MerkleConfig, PowStrategy>
 where
     F: FftField,
     MerkleConfig: Config,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
         self.mv_parameters.fmt(f)?;
         writeln!(f, ", folding factor: {}", self.folding_factor)?;
         writeln!(
@@ -435,7 +1047,7 @@ where
             "initial_folding_pow_bits: {}",
             self.starting_folding_pow_bits
         )?;
-        for r in &self.round_parameters {
+        for r in self.round_parameters.iter() {
             r.fmt(f)?;
         }
 
@@ -447,6 +1059,40 @@ where
             self.final_pow_bits,
             self.final_folding_pow_bits,
         )?;
+        if self.final_form != FinalForm::Coefficients {
+            writeln!(f, "final polynomial sent as: {}", self.final_form)?;
+        }
+        if self.log_cosets_per_leaf > 0 {
+            writeln!(f, "cosets per leaf: 2^{}", self.log_cosets_per_leaf)?;
+        }
+        if self.prover_mode != ProverMode::Balanced {
+            writeln!(f, "prover mode: {:?}", self.prover_mode)?;
+        }
+        if self.openings_in_transcript {
+            writeln!(f, "openings in the transcript")?;
+        }
+        if self.statement_ood_samples > 0 {
+            writeln!(
+                f,
+                "{} OOD samples of the commitment from the statement",
+                self.statement_ood_samples
+            )?;
+        }
+        if !self.round_merkle_params.is_empty() {
+            writeln!(
+                f,
+                "Merkle parameters of their own for the trees of the rounds"
+            )?;
+        }
+        if self.zk {
+            writeln!(
+                f,
+                "zk: {} revealed evaluations, mask of size 2^{}, hiding: {}",
+                self.zk_revealed_evaluations(),
+                self.num_variables(),
+                self.check_zk(),
+            )?;
+        }
 
         writeln!(f, "------------------------------------")?;
         writeln!(f, "Round by round soundness analysis:")?;
@@ -456,7 +1102,8 @@ where
         let log_eta = Self::log_eta(self.soundness_type, self.starting_log_inv_rate);
         let mut num_variables = self.mv_parameters.num_variables;
 
-        if self.committment_ood_samples > 0 {
+        let committment_ood_samples = self.committment_ood_samples + self.statement_ood_samples;
+        if committment_ood_samples > 0 {
             writeln!(
                 f,
                 "{:.1} bits -- OOD commitment",
@@ -466,7 +1113,7 @@ where
                     self.starting_log_inv_rate,
                     log_eta,
                     field_size_bits,
-                    self.committment_ood_samples
+                    committment_ood_samples
                 )
             )?;
         }
@@ -497,7 +1144,7 @@ where
 
         num_variables -= self.folding_factor;
 
-        for r in &self.round_parameters {
+        for r in self.round_parameters.iter() {
             let next_rate = r.log_inv_rate + (self.folding_factor - 1);
             let log_eta = Self::log_eta(self.soundness_type, next_rate);
 
@@ -593,7 +1240,7 @@ where
 }
 
 impl Display for RoundConfig {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
         writeln!(
             f,
             "Num_queries: {}, rate: 2^-{}, pow_bits: {}, ood_samples: {}, folding_pow: {}",
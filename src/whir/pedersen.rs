@@ -0,0 +1,307 @@
+//! Linking a WHIR commitment to a Pedersen commitment, for hybrid systems mixing hash-based and
+//! group-based commitments.
+//!
+//! The Pedersen commitment `C = sum_b v_b * G_b + r * H` is to the evaluations `v_b` over the
+//! hypercube of the multilinear committed under WHIR, in a group whose scalar field is the field
+//! of WHIR. The verifier draws a random point `z`, the prover claims `y = f(z)`, proves it on the
+//! WHIR commitment, and proves with a Sigma protocol that the vector opening `C` satisfies the
+//! same linear relation `sum_b eq(z, b) * v_b = y`. If the two committed vectors differed, they
+//! would agree at `z` with probability at most `num_variables / |F|`.
+//!
+//! The Sigma protocol is not compressed, its response has the size of the hypercube.
+
+use ark_crypto_primitives::merkle_tree::Config;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{FftField, Field};
+use ark_std::UniformRand;
+use nimue::{
+    plugins::ark::{
+        FieldChallenges, FieldIOPattern, FieldReader, FieldWriter, GroupIOPattern, GroupReader,
+        GroupWriter,
+    },
+    Arthur, ByteWriter, IOPattern, Merlin, ProofError, ProofResult,
+};
+use rand::{CryptoRng, RngCore};
+
+use super::{
+    committer::Witness,
+    iopattern::WhirIOPattern,
+    parameters::WhirConfig,
+    prover::Prover,
+    verifier::{ParsedCommitment, Verifier},
+    Statement, WhirProof,
+};
+use crate::{
+    errors::{WhirError, WhirResult},
+    poly_utils::MultilinearPoint,
+    sumcheck::prover_single::SumcheckSingle,
+};
+
+pub trait PedersenIOPattern<G: CurveGroup> {
+    /// The Pedersen commitment, the linkage at a random point and the WHIR proof of the
+    /// evaluation claim. Follows `commit_statement`.
+    fn add_pedersen_linkage<MerkleConfig: Config, PowStrategy>(
+        self,
+        params: &WhirConfig<G::ScalarField, MerkleConfig, PowStrategy>,
+    ) -> Self
+    where
+        G::ScalarField: FftField;
+}
+
+impl<G> PedersenIOPattern<G> for IOPattern
+where
+    G: CurveGroup,
+    IOPattern: GroupIOPattern<G> + FieldIOPattern<G::ScalarField>,
+{
+    fn add_pedersen_linkage<MerkleConfig: Config, PowStrategy>(
+        self,
+        params: &WhirConfig<G::ScalarField, MerkleConfig, PowStrategy>,
+    ) -> Self
+    where
+        G::ScalarField: FftField,
+    {
+        let num_variables = params.num_variables();
+        self.add_points(1, "pedersen_commitment")
+            .challenge_scalars(num_variables, "linkage_point")
+            .add_scalars(1, "linkage_evaluation")
+            .add_points(1, "linkage_nonce_commitment")
+            .add_scalars(1, "linkage_nonce_evaluation")
+            .challenge_scalars(1, "linkage_challenge")
+            .add_scalars(1 << num_variables, "linkage_response")
+            .add_scalars(1, "linkage_blinding_response")
+            .add_whir_proof(params)
+    }
+}
+
+/// The generators of the Pedersen commitments to vectors of `2^num_variables` elements.
+///
+/// They are sampled as random group elements, so the discrete logarithm relations between them
+/// are only unknown if the sampler is trusted.
+#[derive(Clone, Debug)]
+pub struct PedersenParameters<G: CurveGroup> {
+    generators: Vec<G::Affine>,
+    blinding_generator: G::Affine,
+}
+
+impl<G: CurveGroup> PedersenParameters<G> {
+    pub fn new(rng: &mut impl RngCore, num_variables: usize) -> Self {
+        let generators: Vec<G> = (0..1 << num_variables).map(|_| G::rand(rng)).collect();
+        PedersenParameters {
+            generators: G::normalize_batch(&generators),
+            blinding_generator: G::rand(rng).into_affine(),
+        }
+    }
+
+    pub fn num_variables(&self) -> usize {
+        self.generators.len().ilog2() as usize
+    }
+
+    /// The commitment to `values` with randomness `blinding`.
+    pub fn commit(&self, values: &[G::ScalarField], blinding: G::ScalarField) -> G {
+        assert_eq!(values.len(), self.generators.len());
+        G::msm_unchecked(&self.generators, values) + self.blinding_generator * blinding
+    }
+}
+
+// The weights `eq(point, b)` of the evaluation at `point`, over the hypercube
+fn evaluation_weights<F: Field>(point: &MultilinearPoint<F>) -> Vec<F> {
+    let mut weights = vec![F::ZERO; 1 << point.n_variables()];
+    SumcheckSingle::eval_eq(&point.0, &mut weights, F::ONE);
+    weights
+}
+
+fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b).map(|(a, b)| *a * b).sum()
+}
+
+pub struct PedersenLinkage<G, MerkleConfig, PowStrategy>
+where
+    G: CurveGroup,
+    G::ScalarField: FftField,
+    MerkleConfig: Config,
+{
+    config: WhirConfig<G::ScalarField, MerkleConfig, PowStrategy>,
+    pedersen: PedersenParameters<G>,
+}
+
+impl<G, MerkleConfig, PowStrategy> PedersenLinkage<G, MerkleConfig, PowStrategy>
+where
+    G: CurveGroup,
+    G::ScalarField: FftField,
+    MerkleConfig: Config<Leaf = [G::ScalarField]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    pub fn new(
+        config: WhirConfig<G::ScalarField, MerkleConfig, PowStrategy>,
+        pedersen: PedersenParameters<G>,
+    ) -> Self {
+        assert_eq!(pedersen.num_variables(), config.num_variables());
+        PedersenLinkage { config, pedersen }
+    }
+
+    /// Proves that `commitment`, the Pedersen commitment to `values` with randomness `blinding`,
+    /// is to the evaluations over the hypercube of the polynomial of `witness`.
+    pub fn prove(
+        &self,
+        merlin: &mut Merlin,
+        commitment: G,
+        values: &[G::ScalarField],
+        blinding: G::ScalarField,
+        witness: Witness<G::ScalarField, MerkleConfig>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> WhirResult<WhirProof<MerkleConfig, G::ScalarField>>
+    where
+        Merlin: GroupWriter<G> + FieldChallenges<G::ScalarField> + ByteWriter,
+    {
+        if !values.len().is_power_of_two() {
+            return Err(WhirError::NotPowerOfTwo(values.len()));
+        }
+        if values.len().ilog2() as usize != self.config.num_variables() {
+            return Err(WhirError::NumVariables {
+                expected: self.config.num_variables(),
+                found: values.len().ilog2() as usize,
+            });
+        }
+        merlin.add_points(&[commitment])?;
+        let mut point = vec![G::ScalarField::ZERO; self.config.num_variables()];
+        merlin.fill_challenge_scalars(&mut point)?;
+        let point = MultilinearPoint(point);
+        let weights = evaluation_weights(&point);
+        let evaluation = inner_product(&weights, values);
+        merlin.add_scalars(&[evaluation])?;
+
+        let nonces: Vec<_> = (0..values.len())
+            .map(|_| G::ScalarField::rand(rng))
+            .collect();
+        let nonce_blinding = G::ScalarField::rand(rng);
+        merlin.add_points(&[self.pedersen.commit(&nonces, nonce_blinding)])?;
+        merlin.add_scalars(&[inner_product(&weights, &nonces)])?;
+
+        let [challenge] = merlin.challenge_scalars()?;
+        let response: Vec<_> = nonces
+            .iter()
+            .zip(values)
+            .map(|(nonce, value)| *nonce + challenge * value)
+            .collect();
+        merlin.add_scalars(&response)?;
+        merlin.add_scalars(&[nonce_blinding + challenge * blinding])?;
+
+        let statement = Statement::new(vec![point], vec![evaluation]);
+        Prover(self.config.clone()).prove(merlin, statement, witness)
+    }
+
+    /// Verifier side of `prove`, against a WHIR commitment read with `parse_commitment`.
+    /// Returns the Pedersen commitment.
+    pub fn verify(
+        &self,
+        arthur: &mut Arthur,
+        parsed_commitment: &ParsedCommitment<G::ScalarField, MerkleConfig::InnerDigest>,
+        whir_proof: &WhirProof<MerkleConfig, G::ScalarField>,
+    ) -> ProofResult<G>
+    where
+        for<'a> Arthur<'a>: GroupReader<G>,
+    {
+        let [commitment] = arthur.next_points()?;
+        let mut point = vec![G::ScalarField::ZERO; self.config.num_variables()];
+        arthur.fill_challenge_scalars(&mut point)?;
+        let point = MultilinearPoint(point);
+        let [evaluation] = arthur.next_scalars()?;
+        let [nonce_commitment] = arthur.next_points()?;
+        let [nonce_evaluation] = arthur.next_scalars()?;
+        let [challenge] = arthur.challenge_scalars()?;
+        let mut response = vec![G::ScalarField::ZERO; 1 << self.config.num_variables()];
+        arthur.fill_next_scalars(&mut response)?;
+        let [blinding_response] = arthur.next_scalars()?;
+
+        if self.pedersen.commit(&response, blinding_response)
+            != nonce_commitment + commitment * challenge
+            || inner_product(&evaluation_weights(&point), &response)
+                != nonce_evaluation + challenge * evaluation
+        {
+            return Err(ProofError::InvalidProof);
+        }
+
+        let statement = Statement::new(vec![point], vec![evaluation]);
+        Verifier::new(self.config.clone()).verify_with_commitment(
+            arthur,
+            parsed_commitment,
+            &statement,
+            whir_proof,
+        )?;
+        Ok(commitment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_test_curves::bls12_381::{Fr, G1Projective};
+    use nimue::DefaultHash;
+    use nimue_pow::blake3::Blake3PoW;
+
+    use super::*;
+    use crate::{
+        crypto::merkle_tree::blake3 as merkle_tree,
+        parameters::MultivariateParameters,
+        poly_utils::{coeffs::CoefficientList, evals::EvaluationsList},
+        whir::{committer::Committer, fixtures::whir_parameters},
+    };
+
+    type F = Fr;
+    type G = G1Projective;
+    type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+
+    #[test]
+    fn test_pedersen_linkage() {
+        let num_variables = 5;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params =
+            whir_parameters::<MerkleConfig, Blake3PoW>((leaf_hash_params, two_to_one_params));
+        let params = WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+        let io = PedersenIOPattern::<G>::add_pedersen_linkage(
+            IOPattern::<DefaultHash>::new("🌪️").commit_statement(&params),
+            &params,
+        );
+        let pedersen = PedersenParameters::<G>::new(&mut rng, num_variables);
+        let linkage = PedersenLinkage::new(params.clone(), pedersen.clone());
+
+        let values: Vec<F> = (0..1 << num_variables).map(|_| F::rand(&mut rng)).collect();
+        let polynomial = CoefficientList::from(EvaluationsList::new(values.clone()));
+        let blinding = F::rand(&mut rng);
+
+        for tampered in [false, true] {
+            let mut committed_values = values.clone();
+            if tampered {
+                committed_values[3] += F::ONE;
+            }
+            let commitment = pedersen.commit(&committed_values, blinding);
+
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit(&mut merlin, polynomial.clone())
+                .unwrap();
+            let proof = linkage
+                .prove(
+                    &mut merlin,
+                    commitment,
+                    &committed_values,
+                    blinding,
+                    witness,
+                    &mut rng,
+                )
+                .unwrap();
+
+            let mut arthur = io.to_arthur(merlin.transcript());
+            let parsed_commitment = Verifier::new(params.clone())
+                .parse_commitment(&mut arthur)
+                .unwrap();
+            let result = linkage.verify(&mut arthur, &parsed_commitment, &proof);
+            assert_eq!(result.ok(), (!tampered).then_some(commitment));
+        }
+    }
+}
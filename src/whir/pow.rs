@@ -0,0 +1,170 @@
+//! Outsourcing the proofs of work of the rounds, see `WhirConfig::with_pow_service`.
+//!
+//! The PoW of a round follows its query seed in the transcript, and nothing depends on its nonce
+//! until the combination randomness is squeezed. With a service, the prover squeezes the PoW
+//! challenge right after the query seed, submits it, and opens and folds the queried leaves
+//! while the service grinds; it then waits for the nonce, checks it and absorbs it as
+//! `challenge_pow` would. The proofs are the same as with local grinding, so the verifier needs
+//! no service.
+//!
+//! With the openings in the transcript (`WhirConfig::with_openings_in_transcript`) the PoW
+//! challenge depends on the opening, and the prover waits for the nonce as soon as it submits
+//! it. The PoWs of the sumcheck rounds are ground locally.
+
+use ark_std::boxed::Box;
+
+/// The proof of work of a round.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowRequest {
+    /// The round of the PoW, `n_rounds` for the final round.
+    pub round: usize,
+    /// The challenge squeezed from the transcript.
+    pub challenge: [u8; 32],
+    pub bits: f64,
+}
+
+/// Grinds the proofs of work of the prover, e.g. on a GPU or a dedicated service. It must solve
+/// them for the `PowStrategy` of the configuration.
+pub trait PowService: Send + Sync {
+    /// Starts grinding `request`, and returns without waiting for the nonce.
+    fn submit(&self, request: PowRequest) -> Box<dyn PendingNonce>;
+}
+
+/// A nonce being ground by a `PowService`.
+pub trait PendingNonce: Send {
+    /// Waits for the nonce, `None` if the service failed.
+    fn wait(self: Box<Self>) -> Option<u64>;
+}
+
+/// A service grinding each PoW on its own thread, with `PowStrategy`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadPowService<PowStrategy>(ark_std::marker::PhantomData<PowStrategy>);
+
+#[cfg(feature = "std")]
+impl<PowStrategy> ThreadPowService<PowStrategy> {
+    pub fn new() -> Self {
+        ThreadPowService(ark_std::marker::PhantomData)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<PowStrategy> PowService for ThreadPowService<PowStrategy>
+where
+    PowStrategy: nimue_pow::PowStrategy + Send + Sync + 'static,
+{
+    fn submit(&self, request: PowRequest) -> Box<dyn PendingNonce> {
+        Box::new(std::thread::spawn(move || {
+            PowStrategy::new(request.challenge, request.bits).solve()
+        }))
+    }
+}
+
+#[cfg(feature = "std")]
+impl PendingNonce for std::thread::JoinHandle<Option<u64>> {
+    fn wait(self: Box<Self>) -> Option<u64> {
+        self.join().ok().flatten()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use nimue_pow::PowStrategy as _;
+
+    use super::*;
+    use crate::{
+        errors::WhirError,
+        poly_utils::{coeffs::CoefficientList, MultilinearPoint},
+        whir::{
+            committer::Committer,
+            fixtures::{config, io_pattern, MerkleConfig, PowStrategy, F},
+            parameters::WhirConfig,
+            prover::Prover,
+            verifier::Verifier,
+            Statement,
+        },
+    };
+
+    // Grinds on threads, and records the requests.
+    #[derive(Default)]
+    struct RecordingService(Arc<Mutex<Vec<PowRequest>>>);
+
+    impl PowService for RecordingService {
+        fn submit(&self, request: PowRequest) -> Box<dyn PendingNonce> {
+            self.0.lock().unwrap().push(request);
+            ThreadPowService::<PowStrategy>::new().submit(request)
+        }
+    }
+
+    // Answers with no nonce, or with one that does not solve the PoW.
+    struct FaultyService {
+        send_nonce: bool,
+    }
+
+    struct Ready(Option<u64>);
+
+    impl PendingNonce for Ready {
+        fn wait(self: Box<Self>) -> Option<u64> {
+            self.0
+        }
+    }
+
+    impl PowService for FaultyService {
+        fn submit(&self, request: PowRequest) -> Box<dyn PendingNonce> {
+            let mut strategy = PowStrategy::new(request.challenge, request.bits);
+            let wrong = (0..).find(|&nonce| !strategy.check(nonce));
+            Box::new(Ready(wrong.filter(|_| self.send_nonce)))
+        }
+    }
+
+    #[test]
+    fn test_pow_service() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables);
+        // A PoW after the queries of every round
+        let mut pow_bits = params.pow_bits();
+        for round in &mut pow_bits.rounds {
+            round.queries = 5.;
+        }
+        let params = params.with_pow_bits(&pow_bits).unwrap();
+        let io = io_pattern(&params);
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+        let prove = |params: &WhirConfig<F, MerkleConfig, PowStrategy>| {
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit(&mut merlin, polynomial.clone())
+                .unwrap();
+            Prover(params.clone())
+                .prove(&mut merlin, statement.clone(), witness)
+                .map(|proof| (merlin, proof))
+        };
+
+        let service = RecordingService::default();
+        let requests = service.0.clone();
+        let with_service = params.clone().with_pow_service(service);
+        let (merlin, proof) = prove(&with_service).unwrap();
+        // The verifier needs no service
+        let mut arthur = io.to_arthur(merlin.transcript());
+        assert!(Verifier::new(params.clone())
+            .verify(&mut arthur, &statement, &proof)
+            .is_ok());
+        let rounds: Vec<_> = requests.lock().unwrap().iter().map(|r| r.round).collect();
+        assert_eq!(rounds, (0..=params.n_rounds()).collect::<Vec<_>>());
+
+        // Nonces that are missing or do not solve the PoW fail the proof
+        for send_nonce in [false, true] {
+            let failing = params
+                .clone()
+                .with_pow_service(FaultyService { send_nonce });
+            assert!(matches!(
+                prove(&failing),
+                Err(WhirError::PowFailed { round: 0 })
+            ));
+        }
+    }
+}
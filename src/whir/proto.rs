@@ -0,0 +1,377 @@
+//! Protobuf messages for proofs and statements, following `proto/whir.proto`.
+//!
+//! The messages are plain `prost` structs, to be encoded with `prost::Message::encode_to_vec`
+//! and decoded with `prost::Message::decode`. Field elements and digests are kept as their
+//! compressed arkworks serialization, and are only checked when converting back.
+
+use ark_crypto_primitives::merkle_tree::{Config, MultiPath};
+use ark_ff::Field;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+
+use crate::{
+    poly_utils::{evals::EvaluationsList, MultilinearPoint},
+    whir,
+};
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MerklePath {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub leaf_sibling_hashes: Vec<Vec<u8>>,
+    #[prost(uint64, repeated, tag = "2")]
+    pub auth_path_prefix_lengths: Vec<u64>,
+    #[prost(message, repeated, tag = "3")]
+    pub auth_path_suffixes: Vec<AuthPathSuffix>,
+    #[prost(uint64, repeated, tag = "4")]
+    pub leaf_indexes: Vec<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AuthPathSuffix {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub digests: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Leaf {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub values: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RoundProof {
+    #[prost(message, optional, tag = "1")]
+    pub merkle_path: Option<MerklePath>,
+    #[prost(message, repeated, tag = "2")]
+    pub leaves: Vec<Leaf>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct WhirProof {
+    #[prost(message, repeated, tag = "1")]
+    pub rounds: Vec<RoundProof>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Proof {
+    #[prost(bytes = "vec", tag = "1")]
+    pub transcript: Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub whir_proof: Option<WhirProof>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Point {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub coordinates: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, prost::Enumeration)]
+#[repr(i32)]
+pub enum ClaimType {
+    Evaluation = 0,
+    Rotated = 1,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LinearClaim {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub weights: Vec<Vec<u8>>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub sum: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Statement {
+    #[prost(message, repeated, tag = "1")]
+    pub points: Vec<Point>,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub evaluations: Vec<Vec<u8>>,
+    #[prost(enumeration = "ClaimType", repeated, tag = "3")]
+    pub claim_types: Vec<i32>,
+    #[prost(message, repeated, tag = "4")]
+    pub linear_claims: Vec<LinearClaim>,
+}
+
+fn to_bytes<T: CanonicalSerialize>(value: &T) -> Result<Vec<u8>, SerializationError> {
+    let mut bytes = Vec::with_capacity(value.compressed_size());
+    value.serialize_compressed(&mut bytes)?;
+    Ok(bytes)
+}
+
+// Rejects trailing bytes, so that every value has a single encoding.
+fn from_bytes<T: CanonicalDeserialize>(mut bytes: &[u8]) -> Result<T, SerializationError> {
+    let value = T::deserialize_compressed(&mut bytes)?;
+    if !bytes.is_empty() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(value)
+}
+
+fn to_usize(value: u64) -> Result<usize, SerializationError> {
+    usize::try_from(value).map_err(|_| SerializationError::InvalidData)
+}
+
+fn all_to_bytes<T: CanonicalSerialize>(values: &[T]) -> Result<Vec<Vec<u8>>, SerializationError> {
+    values.iter().map(to_bytes).collect()
+}
+
+fn all_from_bytes<T: CanonicalDeserialize>(
+    values: &[Vec<u8>],
+) -> Result<Vec<T>, SerializationError> {
+    values.iter().map(|bytes| from_bytes(bytes)).collect()
+}
+
+/// Converts the transcript and the proof into a protobuf message, the analogue of
+/// `proof_to_bytes`.
+pub fn proof_to_proto<MerkleConfig, F>(
+    transcript: &[u8],
+    whir_proof: &whir::WhirProof<MerkleConfig, F>,
+) -> Result<Proof, SerializationError>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    let rounds = whir_proof
+        .0
+        .iter()
+        .map(|(merkle_proof, answers)| {
+            let merkle_path = MerklePath {
+                leaf_sibling_hashes: all_to_bytes(&merkle_proof.leaf_siblings_hashes)?,
+                auth_path_prefix_lengths: merkle_proof
+                    .auth_paths_prefix_lenghts
+                    .iter()
+                    .map(|&len| len as u64)
+                    .collect(),
+                auth_path_suffixes: merkle_proof
+                    .auth_paths_suffixes
+                    .iter()
+                    .map(|suffix| {
+                        Ok(AuthPathSuffix {
+                            digests: all_to_bytes(suffix)?,
+                        })
+                    })
+                    .collect::<Result<_, SerializationError>>()?,
+                leaf_indexes: merkle_proof
+                    .leaf_indexes
+                    .iter()
+                    .map(|&index| index as u64)
+                    .collect(),
+            };
+            let leaves = answers
+                .iter()
+                .map(|answer| {
+                    Ok(Leaf {
+                        values: all_to_bytes(answer)?,
+                    })
+                })
+                .collect::<Result<_, SerializationError>>()?;
+            Ok(RoundProof {
+                merkle_path: Some(merkle_path),
+                leaves,
+            })
+        })
+        .collect::<Result<_, SerializationError>>()?;
+
+    Ok(Proof {
+        transcript: transcript.to_vec(),
+        whir_proof: Some(WhirProof { rounds }),
+    })
+}
+
+/// Inverse of `proof_to_proto`, returns the transcript and the proof.
+pub fn proof_from_proto<MerkleConfig, F>(
+    proof: &Proof,
+) -> Result<(Vec<u8>, whir::WhirProof<MerkleConfig, F>), SerializationError>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    let whir_proof = proof
+        .whir_proof
+        .as_ref()
+        .ok_or(SerializationError::InvalidData)?;
+    let rounds = whir_proof
+        .rounds
+        .iter()
+        .map(|round| {
+            let merkle_path = round
+                .merkle_path
+                .as_ref()
+                .ok_or(SerializationError::InvalidData)?;
+            let merkle_proof = MultiPath::<MerkleConfig> {
+                leaf_siblings_hashes: all_from_bytes(&merkle_path.leaf_sibling_hashes)?,
+                auth_paths_prefix_lenghts: merkle_path
+                    .auth_path_prefix_lengths
+                    .iter()
+                    .map(|&len| to_usize(len))
+                    .collect::<Result<_, _>>()?,
+                auth_paths_suffixes: merkle_path
+                    .auth_path_suffixes
+                    .iter()
+                    .map(|suffix| all_from_bytes(&suffix.digests))
+                    .collect::<Result<_, _>>()?,
+                leaf_indexes: merkle_path
+                    .leaf_indexes
+                    .iter()
+                    .map(|&index| to_usize(index))
+                    .collect::<Result<_, _>>()?,
+            };
+            let answers = round
+                .leaves
+                .iter()
+                .map(|leaf| all_from_bytes(&leaf.values))
+                .collect::<Result<_, _>>()?;
+            Ok((merkle_proof, answers))
+        })
+        .collect::<Result<_, SerializationError>>()?;
+
+    Ok((
+        proof.transcript.clone(),
+        whir::WhirProof(rounds, Default::default()),
+    ))
+}
+
+pub fn statement_to_proto<F: Field>(
+    statement: &whir::Statement<F>,
+) -> Result<Statement, SerializationError> {
+    Ok(Statement {
+        points: statement
+            .points
+            .iter()
+            .map(|point| {
+                Ok(Point {
+                    coordinates: all_to_bytes(&point.0)?,
+                })
+            })
+            .collect::<Result<_, SerializationError>>()?,
+        evaluations: all_to_bytes(&statement.evaluations)?,
+        claim_types: statement
+            .claim_types
+            .iter()
+            .map(|claim_type| match claim_type {
+                whir::ClaimType::Evaluation => ClaimType::Evaluation,
+                whir::ClaimType::Rotated => ClaimType::Rotated,
+            } as i32)
+            .collect(),
+        linear_claims: statement
+            .linear_claims
+            .iter()
+            .map(|claim| {
+                Ok(LinearClaim {
+                    weights: all_to_bytes(claim.weights.evals())?,
+                    sum: to_bytes(&claim.sum)?,
+                })
+            })
+            .collect::<Result<_, SerializationError>>()?,
+    })
+}
+
+/// Inverse of `statement_to_proto`. Also checks that there are as many evaluations and claim
+/// types as points.
+pub fn statement_from_proto<F: Field>(
+    statement: &Statement,
+) -> Result<whir::Statement<F>, SerializationError> {
+    if statement.points.len() != statement.evaluations.len()
+        || statement.points.len() != statement.claim_types.len()
+    {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(whir::Statement {
+        points: statement
+            .points
+            .iter()
+            .map(|point| all_from_bytes(&point.coordinates).map(MultilinearPoint))
+            .collect::<Result<_, _>>()?,
+        evaluations: all_from_bytes(&statement.evaluations)?,
+        claim_types: statement
+            .claim_types
+            .iter()
+            .map(|&claim_type| match ClaimType::try_from(claim_type) {
+                Ok(ClaimType::Evaluation) => Ok(whir::ClaimType::Evaluation),
+                Ok(ClaimType::Rotated) => Ok(whir::ClaimType::Rotated),
+                Err(_) => Err(SerializationError::InvalidData),
+            })
+            .collect::<Result<_, _>>()?,
+        linear_claims: statement
+            .linear_claims
+            .iter()
+            .map(|claim| {
+                let weights: Vec<F> = all_from_bytes(&claim.weights)?;
+                if !weights.len().is_power_of_two() {
+                    return Err(SerializationError::InvalidData);
+                }
+                Ok(whir::LinearClaim {
+                    weights: EvaluationsList::new(weights),
+                    sum: from_bytes(&claim.sum)?,
+                })
+            })
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use nimue_pow::blake3::Blake3PoW;
+    use prost::Message;
+
+    use super::*;
+    use crate::crypto::merkle_tree::blake3 as merkle_tree;
+    use crate::parameters::MultivariateParameters;
+    use crate::poly_utils::coeffs::CoefficientList;
+    use crate::whir::{
+        committer::Committer,
+        fixtures::{io_pattern, whir_parameters, MerkleConfig, F},
+        parameters::WhirConfig,
+        prover::Prover,
+        verifier::Verifier,
+    };
+
+    #[test]
+    fn test_proto_roundtrip() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params =
+            whir_parameters::<MerkleConfig, Blake3PoW>((leaf_hash_params, two_to_one_params));
+        let params = WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+
+        let polynomial = CoefficientList::new(vec![F::from(1); 1 << num_variables]);
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let evaluation = polynomial.evaluate(&point);
+        let statement = whir::Statement::new(vec![point], vec![evaluation]);
+
+        let io = io_pattern(&params);
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial)
+            .unwrap();
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+
+        let encoded = proof_to_proto(merlin.transcript(), &proof)
+            .unwrap()
+            .encode_to_vec();
+        let (transcript, decoded) =
+            proof_from_proto::<MerkleConfig, F>(&Proof::decode(&encoded[..]).unwrap()).unwrap();
+        let encoded_statement = statement_to_proto(&statement).unwrap().encode_to_vec();
+        let decoded_statement: whir::Statement<F> =
+            statement_from_proto(&Statement::decode(&encoded_statement[..]).unwrap()).unwrap();
+        assert_eq!(decoded_statement.evaluations, statement.evaluations);
+        assert_eq!(decoded_statement.claim_types, statement.claim_types);
+
+        let verifier = Verifier::new(params);
+        let mut arthur = io.to_arthur(&transcript);
+        assert!(verifier
+            .verify(&mut arthur, &decoded_statement, &decoded)
+            .is_ok());
+
+        let mut proof = Proof::decode(&encoded[..]).unwrap();
+        proof.whir_proof.as_mut().unwrap().rounds[0].leaves[0].values[0].push(0);
+        assert!(proof_from_proto::<MerkleConfig, F>(&proof).is_err());
+    }
+}
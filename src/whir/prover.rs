@@ -1,11 +1,22 @@
-use super::{committer::Witness, parameters::WhirConfig, Statement, WhirProof};
+use super::{
+    cancel::CancellationToken,
+    committer::Witness,
+    fold::{compute_folds, FoldDomain},
+    hints::ProofHints,
+    opening_to_transcript,
+    parameters::{ProverMode, WhirConfig},
+    pow::{PendingNonce, PowRequest},
+    transcript_opening_sizes, write_opening, ClaimType, Statement, WhirProof, PROOF_FORMAT_VERSION,
+};
 use crate::{
+    crypto::merkle_tree::generate_multi_proof,
     domain::Domain,
-    ntt::expand_from_coeff,
-    parameters::FoldType,
+    errors::{WhirError, WhirResult},
+    parameters::{FinalForm, FoldType},
     poly_utils::{
         coeffs::CoefficientList,
-        fold::{compute_fold, restructure_evaluations},
+        evals::EvaluationsList,
+        fold::{encode_folded, encode_leaves},
         MultilinearPoint,
     },
     sumcheck::prover_not_skipping::SumcheckProverNotSkipping,
@@ -14,15 +25,98 @@ use crate::{
 use ark_crypto_primitives::merkle_tree::{Config, MerkleTree, MultiPath};
 use ark_ff::FftField;
 use ark_poly::EvaluationDomain;
+use ark_serialize::{CanonicalSerialize, Compress, Write};
+use ark_std::{borrow::Cow, iter, slice};
 use nimue::{
     plugins::ark::{FieldChallenges, FieldWriter},
-    ByteChallenges, ByteWriter, Merlin, ProofResult,
+    ByteChallenges, ByteWriter, Merlin, ProofError, ProofResult,
 };
 use nimue_pow::{self, PoWChallenge};
-use rand::{Rng, SeedableRng};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+type Opening<MerkleConfig, F> = (MultiPath<MerkleConfig>, Vec<Vec<F>>);
+
+// Where the prover puts the openings of the proof, in order, as soon as each round has computed
+// them.
+trait OpeningSink<MerkleConfig: Config<Leaf = [F]>, F> {
+    fn push_opening(
+        &mut self,
+        merlin: &mut Merlin,
+        opening: Opening<MerkleConfig, F>,
+    ) -> ProofResult<()>;
+}
+
+impl<MerkleConfig: Config<Leaf = [F]>, F> OpeningSink<MerkleConfig, F>
+    for Vec<Opening<MerkleConfig, F>>
+{
+    fn push_opening(
+        &mut self,
+        _: &mut Merlin,
+        opening: Opening<MerkleConfig, F>,
+    ) -> ProofResult<()> {
+        self.push(opening);
+        Ok(())
+    }
+}
+
+// Absorbs each opening into the transcript, padded to the size of its round, see
+// `WhirConfig::with_openings_in_transcript`.
+struct TranscriptSink {
+    sizes: ark_std::vec::IntoIter<usize>,
+}
+
+impl<MerkleConfig, F> OpeningSink<MerkleConfig, F> for TranscriptSink
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + ark_serialize::CanonicalDeserialize,
+{
+    fn push_opening(
+        &mut self,
+        merlin: &mut Merlin,
+        opening: Opening<MerkleConfig, F>,
+    ) -> ProofResult<()> {
+        let size = self.sizes.next().ok_or(ProofError::InvalidIO)?;
+        merlin.add_bytes(&opening_to_transcript(&opening, size)?)
+    }
+}
+
+// Writes the bytes the transcript gained since the previous opening, then the opening, see
+// `proof_from_stream_for_config`.
+struct StreamSink<W> {
+    writer: W,
+    written: usize,
+}
+
+impl<W: Write> StreamSink<W> {
+    fn write_transcript(&mut self, transcript: &[u8]) -> ProofResult<()> {
+        transcript[self.written..]
+            .serialize_compressed(&mut self.writer)
+            .map_err(|_| ProofError::SerializationError)?;
+        self.written = transcript.len();
+        Ok(())
+    }
+}
+
+impl<W, MerkleConfig, F> OpeningSink<MerkleConfig, F> for StreamSink<W>
+where
+    W: Write,
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + ark_serialize::CanonicalDeserialize,
+{
+    fn push_opening(
+        &mut self,
+        merlin: &mut Merlin,
+        opening: Opening<MerkleConfig, F>,
+    ) -> ProofResult<()> {
+        self.write_transcript(merlin.transcript())?;
+        write_opening(&mut self.writer, &opening, Compress::Yes)
+            .map_err(|_| ProofError::SerializationError)
+    }
+}
 
 pub struct Prover<F, MerkleConfig, PowStrategy>(pub WhirConfig<F, MerkleConfig, PowStrategy>)
 where
@@ -36,60 +130,178 @@ where
     MerkleConfig::InnerDigest: AsRef<[u8]>,
     PowStrategy: nimue_pow::PowStrategy,
 {
-    fn validate_parameters(&self) -> bool {
-        self.0.mv_parameters.num_variables
-            == (self.0.n_rounds() + 1) * self.0.folding_factor + self.0.final_sumcheck_rounds
+    pub(crate) fn validate_parameters(&self) -> WhirResult<()> {
+        if self.0.mv_parameters.num_variables
+            != (self.0.n_rounds() + 1) * self.0.folding_factor + self.0.final_sumcheck_rounds
+        {
+            return Err(WhirError::InvalidParameters(
+                "the rounds do not fold all the variables",
+            ));
+        }
+        Ok(())
     }
 
-    fn validate_statement(&self, statement: &Statement<F>) -> bool {
-        statement
-            .points
-            .iter()
-            .all(|point| point.0.len() == self.0.mv_parameters.num_variables)
+    fn validate_witness(&self, witness: &Witness<F, MerkleConfig>) -> WhirResult<()> {
+        if witness.polynomial.num_variables() != self.0.mv_parameters.num_variables {
+            return Err(WhirError::NumVariables {
+                expected: self.0.mv_parameters.num_variables,
+                found: witness.polynomial.num_variables(),
+            });
+        }
+        Ok(())
     }
 
-    fn validate_witness(&self, witness: &Witness<F, MerkleConfig>) -> bool {
-        witness.polynomial.num_variables() == self.0.mv_parameters.num_variables
+    pub fn prove(
+        &self,
+        merlin: &mut Merlin,
+        statement: Statement<F>,
+        witness: Witness<F, MerkleConfig>,
+    ) -> WhirResult<WhirProof<MerkleConfig, F>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        statement.validate(&self.0)?;
+        let statement = self.0.mask_statement(&statement)?;
+        self.prove_masked(merlin, statement, witness)
     }
 
-    pub fn prove(
+    /// Same as `prove`, but writes the proof to `writer` as the rounds complete instead of
+    /// returning it, so that the openings of a large proof are never all in memory. The
+    /// transcript is written along, for `proof_from_stream_for_config` to read both back (the
+    /// `Merlin` still keeps its own copy).
+    pub fn prove_to_writer<W: Write>(
         &self,
         merlin: &mut Merlin,
         statement: Statement<F>,
         witness: Witness<F, MerkleConfig>,
-    ) -> ProofResult<WhirProof<MerkleConfig, F>>
+        writer: W,
+    ) -> WhirResult<()>
     where
         Merlin: FieldChallenges<F> + ByteWriter,
     {
-        assert!(self.validate_parameters());
-        assert!(self.validate_statement(&statement));
-        assert!(self.validate_witness(&witness));
+        if self.0.openings_in_transcript {
+            return Err(WhirError::InvalidParameters(
+                "the openings are in the transcript, which is the whole proof",
+            ));
+        }
+        statement.validate(&self.0)?;
+        let statement = self.0.mask_statement(&statement)?;
+        let mut sink = StreamSink { writer, written: 0 };
+        PROOF_FORMAT_VERSION
+            .serialize_compressed(&mut sink.writer)
+            .map_err(|_| ProofError::SerializationError)?;
+        let cancel = CancellationToken::new();
+        self.prove_into(merlin, statement, witness, &mut sink, &cancel)?;
+        sink.write_transcript(merlin.transcript())?;
+        sink.writer
+            .flush()
+            .map_err(|_| ProofError::SerializationError)?;
+        Ok(())
+    }
 
-        let [combination_randomness_gen] = merlin.challenge_scalars()?;
-        let initial_claims: Vec<_> = witness
-            .ood_points
-            .into_iter()
-            .map(|ood_point| {
-                MultilinearPoint::expand_from_univariate(
-                    ood_point,
-                    self.0.mv_parameters.num_variables,
-                )
-            })
-            .chain(statement.points)
-            .collect();
-        let combination_randomness =
-            expand_randomness(combination_randomness_gen, initial_claims.len());
-        let initial_answers: Vec<_> = witness
-            .ood_answers
-            .into_iter()
-            .chain(statement.evaluations)
-            .collect();
+    /// Same as `prove`, for a statement on the committed polynomial, which in ZK mode is the
+    /// masked one.
+    pub(crate) fn prove_masked(
+        &self,
+        merlin: &mut Merlin,
+        statement: Statement<F>,
+        witness: Witness<F, MerkleConfig>,
+    ) -> WhirResult<WhirProof<MerkleConfig, F>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        self.prove_masked_cancellable(merlin, statement, witness, &CancellationToken::new())
+    }
 
-        let mut sumcheck_prover = SumcheckProverNotSkipping::new(
-            witness.polynomial.clone(),
-            &initial_claims,
-            &combination_randomness,
-            &initial_answers,
+    /// Same as `prove`, but gives up with `WhirError::ProvingAborted` once `cancel` is cancelled,
+    /// e.g. past the deadline of the job, see `whir::cancel`.
+    pub fn prove_cancellable(
+        &self,
+        merlin: &mut Merlin,
+        statement: Statement<F>,
+        witness: Witness<F, MerkleConfig>,
+        cancel: &CancellationToken,
+    ) -> WhirResult<WhirProof<MerkleConfig, F>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        statement.validate(&self.0)?;
+        let statement = self.0.mask_statement(&statement)?;
+        self.prove_masked_cancellable(merlin, statement, witness, cancel)
+    }
+
+    fn prove_masked_cancellable(
+        &self,
+        merlin: &mut Merlin,
+        statement: Statement<F>,
+        witness: Witness<F, MerkleConfig>,
+        cancel: &CancellationToken,
+    ) -> WhirResult<WhirProof<MerkleConfig, F>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        if self.0.openings_in_transcript {
+            let mut sink = TranscriptSink {
+                sizes: transcript_opening_sizes(&self.0).into_iter(),
+            };
+            self.prove_into(merlin, statement, witness, &mut sink, cancel)?;
+            return Ok(WhirProof(vec![], ProofHints::default()));
+        }
+        let mut openings = vec![];
+        self.prove_into(merlin, statement, witness, &mut openings, cancel)?;
+        Ok(WhirProof(openings, ProofHints::default()))
+    }
+
+    fn prove_into(
+        &self,
+        merlin: &mut Merlin,
+        statement: Statement<F>,
+        witness: Witness<F, MerkleConfig>,
+        sink: &mut impl OpeningSink<MerkleConfig, F>,
+        cancel: &CancellationToken,
+    ) -> WhirResult<()>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        self.validate_parameters()?;
+        statement.validate_num_variables(self.0.mv_parameters.num_variables)?;
+        self.validate_witness(&witness)?;
+        // The verifier checks the claims of the OOD samples left to the statement as well
+        let expected = witness.statement_ood.points.len();
+        let found = statement.common_prefix(&witness.statement_ood);
+        if found != expected {
+            return Err(WhirError::StatementOodSamples { expected, found });
+        }
+
+        let Witness {
+            polynomial,
+            merkle_tree,
+            merkle_leaves,
+            ood_points,
+            ood_answers,
+            statement_ood: _,
+        } = witness;
+        // The sumcheck takes the polynomial, and folds it for the rounds. Only the leaves
+        // recomputed in `ProverMode::LowMemory` need the coefficients of the polynomial itself,
+        // copied once the codeword is dropped so that both are never live together.
+        let (coefficients, merkle_leaves) = match self.0.prover_mode {
+            ProverMode::LowMemory => {
+                wipe(merkle_leaves);
+                (Some(polynomial.clone()), Vec::new())
+            }
+            ProverMode::Balanced => (None, merkle_leaves),
+        };
+
+        let [combination_randomness_gen] = merlin.challenge_scalars()?;
+        let mut sumcheck_prover = phase!(
+            constraints,
+            self.initial_sumcheck_prover(
+                &statement,
+                polynomial,
+                &ood_points,
+                &ood_answers,
+                combination_randomness_gen
+            )
         );
 
         let folding_randomness = sumcheck_prover.compute_sumcheck_polynomials::<PowStrategy>(
@@ -103,57 +315,330 @@ where
             round: 0,
             sumcheck_prover,
             folding_randomness,
-            coefficients: witness.polynomial,
-            prev_merkle: witness.merkle_tree,
-            prev_merkle_answers: witness.merkle_leaves,
-            merkle_proofs: vec![],
+            coefficients,
+            prev_merkle: merkle_tree,
+            prev_merkle_answers: merkle_leaves,
+        };
+
+        self.round(merlin, round_state, sink, cancel)
+    }
+
+    // The sumcheck prover of `polynomial` for the OOD claims of its commitment and the claims of
+    // the statement, combined with the powers of `combination_randomness_gen`.
+    pub(crate) fn initial_sumcheck_prover(
+        &self,
+        statement: &Statement<F>,
+        polynomial: CoefficientList<F>,
+        ood_points: &[F],
+        ood_answers: &[F],
+        combination_randomness_gen: F,
+    ) -> SumcheckProverNotSkipping<F> {
+        let initial_claims: Vec<_> = ood_points
+            .iter()
+            .map(|ood_point| {
+                MultilinearPoint::expand_from_univariate(
+                    *ood_point,
+                    self.0.mv_parameters.num_variables,
+                )
+            })
+            .chain(statement.points.iter().cloned())
+            .collect();
+        let combination_randomness = expand_randomness(
+            combination_randomness_gen,
+            initial_claims.len() + statement.linear_claims.len(),
+        );
+        let initial_answers: Vec<_> = ood_answers
+            .iter()
+            .chain(&statement.evaluations)
+            .copied()
+            .collect();
+        let claim_types = iter::repeat(ClaimType::Evaluation)
+            .take(self.0.committment_ood_samples)
+            .chain(statement.claim_types.iter().copied());
+
+        let mut sumcheck_prover = SumcheckProverNotSkipping::new(polynomial, &[], &[], &[]);
+        for (((point, randomness), answer), claim_type) in initial_claims
+            .iter()
+            .zip(&combination_randomness)
+            .zip(&initial_answers)
+            .zip(claim_types)
+        {
+            let (point, randomness, answer) = (slice::from_ref(point), &[*randomness], &[*answer]);
+            match claim_type {
+                ClaimType::Evaluation => {
+                    sumcheck_prover.add_new_equality(point, randomness, answer)
+                }
+                ClaimType::Rotated => {
+                    sumcheck_prover.add_new_rotated_equality(point, randomness, answer)
+                }
+            }
+        }
+        for (claim, randomness) in statement
+            .linear_claims
+            .iter()
+            .zip(&combination_randomness[initial_claims.len()..])
+        {
+            sumcheck_prover.add_new_linear_claim(&claim.weights, *randomness, claim.sum);
+        }
+        sumcheck_prover
+    }
+
+    // Encodes the folded polynomial over `domain` and commits to it in tree `tree` (see
+    // `WhirConfig::merkle_params`), with the evaluations over a coset in each leaf. Returns the
+    // tree and the leaves.
+    pub(crate) fn commit_folded(
+        &self,
+        tree: usize,
+        coefficients: &CoefficientList<F>,
+        domain: &Domain<F>,
+    ) -> (MerkleTree<MerkleConfig>, Vec<F>) {
+        let expansion = domain.size() / coefficients.num_coeffs();
+        let folded_evals = phase!(
+            encode,
+            match self.0.fold_optimisation {
+                FoldType::Naive => {
+                    let coeffs = utils::coset_shift(coefficients.coeffs(), domain.offset());
+                    let evals = self.0.fft.expand_from_coeff(&coeffs, expansion);
+                    utils::stack_evaluations(evals, self.0.folding_factor)
+                }
+                FoldType::ProverHelps => encode_folded(
+                    &*self.0.fft,
+                    coefficients.coeffs(),
+                    expansion,
+                    domain.offset(),
+                    self.0.folding_factor,
+                ),
+            }
+        );
+
+        #[cfg(not(feature = "parallel"))]
+        let leafs_iter = folded_evals.chunks_exact(self.0.leaf_size());
+        #[cfg(feature = "parallel")]
+        let leafs_iter = folded_evals.par_chunks_exact(self.0.leaf_size());
+        let (leaf_hash_params, two_to_one_params) = self.0.merkle_params(tree);
+        let merkle_tree = phase!(
+            merkle_tree,
+            MerkleTree::<MerkleConfig>::new(leaf_hash_params, two_to_one_params, leafs_iter)
+                .unwrap()
+        );
+        (merkle_tree, folded_evals)
+    }
+
+    // Opens the leaves holding the cosets `indexes`, which are sorted and deduplicated. The leaves
+    // are borrowed from the codeword: they are only copied into the proof once folded, as the
+    // codeword is wiped when the next round replaces it.
+    pub(crate) fn open<'a>(
+        &self,
+        merkle_tree: &MerkleTree<MerkleConfig>,
+        leaves: &'a [F],
+        indexes: &[usize],
+    ) -> (MultiPath<MerkleConfig>, Vec<&'a [F]>) {
+        let leaf_indexes = self.0.leaf_indexes(indexes);
+        let merkle_proof = phase!(
+            open,
+            generate_multi_proof(merkle_tree, leaf_indexes).unwrap()
+        );
+        let leaf_size = self.0.leaf_size();
+        let answers = merkle_proof
+            .leaf_indexes
+            .iter()
+            .map(|i| &leaves[i * leaf_size..(i + 1) * leaf_size])
+            .collect();
+        (merkle_proof, answers)
+    }
+
+    // Opens the cosets `indexes` of the codeword of the round state, whose leaves are recomputed
+    // from its coefficients in `ProverMode::LowMemory`, see `open`.
+    fn open_round<'a>(
+        &self,
+        round_state: &'a RoundState<F, MerkleConfig>,
+        indexes: &[usize],
+    ) -> (MultiPath<MerkleConfig>, Vec<Cow<'a, [F]>>) {
+        if self.0.prover_mode != ProverMode::LowMemory {
+            let (merkle_proof, answers) = self.open(
+                &round_state.prev_merkle,
+                &round_state.prev_merkle_answers,
+                indexes,
+            );
+            return (
+                merkle_proof,
+                answers.into_iter().map(Cow::Borrowed).collect(),
+            );
+        }
+        let leaf_indexes = self.0.leaf_indexes(indexes);
+        let merkle_proof = phase!(
+            open,
+            generate_multi_proof(&round_state.prev_merkle, leaf_indexes).unwrap()
+        );
+        let cosets_per_leaf = 1 << self.0.log_cosets_per_leaf;
+        let answers: Vec<_> = phase!(
+            recompute_leaves,
+            merkle_proof
+                .leaf_indexes
+                .iter()
+                .map(|leaf| {
+                    Cow::Owned(encode_leaves(
+                        round_state.coefficients.as_ref().unwrap().coeffs(),
+                        &round_state.domain,
+                        self.0.folding_factor,
+                        self.0.fold_optimisation,
+                        leaf * cosets_per_leaf..(leaf + 1) * cosets_per_leaf,
+                    ))
+                })
+                .collect()
+        );
+        (merkle_proof, answers)
+    }
+
+    // Evaluates the opened leaves of the cosets `indexes` of `domain` in the folding randomness.
+    pub(crate) fn fold_answers<A: AsRef<[F]>>(
+        &self,
+        domain: &Domain<F>,
+        folding_randomness: &MultilinearPoint<F>,
+        indexes: &[usize],
+        answers: &[A],
+    ) -> Vec<F> {
+        let domain = FoldDomain::new(domain, self.0.folding_factor, self.0.fold_optimisation);
+        compute_folds(&domain, folding_randomness, indexes, answers)
+    }
+
+    // Submits the PoW of `round` to the PoW service as soon as its challenge follows the query
+    // seed, that is unless the opening of the round is absorbed in between, see `whir::pow`.
+    fn submit_pow_early(
+        &self,
+        merlin: &mut Merlin,
+        round: usize,
+        bits: f64,
+    ) -> ProofResult<Option<PendingPow>> {
+        if self.0.openings_in_transcript {
+            return Ok(None);
+        }
+        self.submit_pow(merlin, round, bits)
+    }
+
+    fn submit_pow(
+        &self,
+        merlin: &mut Merlin,
+        round: usize,
+        bits: f64,
+    ) -> ProofResult<Option<PendingPow>> {
+        let Some(service) = &self.0.pow_service else {
+            return Ok(None);
+        };
+        if bits <= 0. {
+            return Ok(None);
+        }
+        let mut challenge = [0u8; 32];
+        merlin.fill_challenge_bytes(&mut challenge)?;
+        let request = PowRequest {
+            round,
+            challenge,
+            bits,
         };
+        Ok(Some(PendingPow {
+            request,
+            nonce: service.submit(request),
+        }))
+    }
 
-        self.round(merlin, round_state)
+    // The PoW of `round`, with the nonce of `pending` if it was submitted early, otherwise
+    // ground by the PoW service or here. The nonce is absorbed as `challenge_pow` does.
+    fn pow(
+        &self,
+        merlin: &mut Merlin,
+        round: usize,
+        bits: f64,
+        pending: Option<PendingPow>,
+    ) -> WhirResult<()> {
+        let pending = match pending {
+            Some(pending) => pending,
+            None => match self.submit_pow(merlin, round, bits)? {
+                Some(pending) => pending,
+                None => return Ok(merlin.challenge_pow::<PowStrategy>(bits)?),
+            },
+        };
+        let PowRequest {
+            challenge, bits, ..
+        } = pending.request;
+        let nonce = pending
+            .nonce
+            .wait()
+            .filter(|&nonce| PowStrategy::new(challenge, bits).check(nonce))
+            .ok_or(WhirError::PowFailed { round })?;
+        merlin.add_bytes(&nonce.to_be_bytes())?;
+        Ok(())
     }
 
     fn round(
         &self,
         merlin: &mut Merlin,
         mut round_state: RoundState<F, MerkleConfig>,
-    ) -> ProofResult<WhirProof<MerkleConfig, F>> {
-        // Fold the coefficients
-        let folded_coefficients = round_state
-            .coefficients
-            .fold(&round_state.folding_randomness);
+        sink: &mut impl OpeningSink<MerkleConfig, F>,
+        cancel: &CancellationToken,
+    ) -> WhirResult<()> {
+        let round = round_state.round;
+        let check_cancelled = || {
+            if cancel.is_cancelled() {
+                event!(round, "prover aborted");
+                return Err(WhirError::ProvingAborted { round });
+            }
+            Ok(())
+        };
+        check_cancelled()?;
+
+        // The coefficients, folded by the sumcheck
+        let folded_coefficients = phase!(fold, round_state.sumcheck_prover.coefficients());
 
         let num_variables =
             self.0.mv_parameters.num_variables - (round_state.round + 1) * self.0.folding_factor;
+        event!(
+            round = round_state.round,
+            num_variables,
+            domain_size = round_state.domain.size(),
+            "prover round"
+        );
 
         // Base case
         if round_state.round == self.0.n_rounds() {
-            // Coefficients of the polynomial
-            merlin.add_scalars(folded_coefficients.coeffs())?;
+            // The polynomial, in the form of the configuration
+            match self.0.final_form {
+                FinalForm::Coefficients => merlin.add_scalars(folded_coefficients.coeffs())?,
+                FinalForm::Evaluations => merlin
+                    .add_scalars(EvaluationsList::from(folded_coefficients.clone()).evals())?,
+            }
 
             // Final verifier queries and answers
             let mut queries_seed = [0u8; 32];
             merlin.fill_challenge_bytes(&mut queries_seed)?;
-            let mut final_gen = rand_chacha::ChaCha20Rng::from_seed(queries_seed);
-            let final_challenge_indexes = utils::dedup((0..self.0.final_queries).map(|_| {
-                final_gen.gen_range(0..round_state.domain.folded_size(self.0.folding_factor))
-            }));
+            let final_challenge_indexes = self.0.query_indexes(
+                &queries_seed,
+                self.0.final_queries,
+                round_state.domain.folded_size(self.0.folding_factor),
+            )?;
+            event!(indexes = ?final_challenge_indexes, "prover final queries");
+            let pending_pow = self.submit_pow_early(merlin, round, self.0.final_pow_bits)?;
 
-            let merkle_proof = round_state
-                .prev_merkle
-                .generate_multi_proof(final_challenge_indexes.clone())
-                .unwrap();
-            let fold_size = 1 << self.0.folding_factor;
-            let answers = final_challenge_indexes
-                .into_iter()
-                .map(|i| {
-                    round_state.prev_merkle_answers[i * fold_size..(i + 1) * fold_size].to_vec()
-                })
-                .collect();
-            round_state.merkle_proofs.push((merkle_proof, answers));
+            let (merkle_proof, answers) = self.open_round(&round_state, &final_challenge_indexes);
+            sink.push_opening(
+                merlin,
+                (
+                    merkle_proof,
+                    answers.iter().map(|leaf| leaf.to_vec()).collect(),
+                ),
+            )?;
 
             // PoW
             if self.0.final_pow_bits > 0. {
-                merlin.challenge_pow::<PowStrategy>(self.0.final_pow_bits)?;
+                check_cancelled()?;
+                phase!(
+                    pow,
+                    self.pow(merlin, round, self.0.final_pow_bits, pending_pow)
+                )?;
+                event!(
+                    bits = self.0.final_pow_bits,
+                    nonce = %utils::pow_nonce(merlin.transcript()),
+                    "prover final proof of work"
+                );
             }
 
             // Final sumcheck
@@ -165,39 +650,27 @@ where
                     self.0.final_folding_pow_bits,
                 )?;
 
-            return Ok(WhirProof(round_state.merkle_proofs));
+            return Ok(());
         }
 
         let round_params = &self.0.round_parameters[round_state.round];
 
         // Fold the coefficients, and compute fft of polynomial (and commit)
+        // Nothing of the next round can run while the tree is hashed: the root is absorbed before
+        // the OOD and STIR challenges, and the sumcheck over them yields the next folding
+        // randomness.
         let new_domain = round_state.domain.scale(2);
-        let expansion = new_domain.size() / folded_coefficients.num_coeffs();
-        let evals = expand_from_coeff(folded_coefficients.coeffs(), expansion);
-        // TODO: `stack_evaluations` and `restructure_evaluations` are really in-place algorithms.
-        // They also partially overlap and undo one another. We should merge them.
-        let folded_evals = utils::stack_evaluations(evals, self.0.folding_factor);
-        let folded_evals = restructure_evaluations(
-            folded_evals,
-            self.0.fold_optimisation,
-            new_domain.backing_domain.group_gen(),
-            new_domain.backing_domain.group_gen_inv(),
-            self.0.folding_factor,
-        );
-
-        #[cfg(not(feature = "parallel"))]
-        let leafs_iter = folded_evals.chunks_exact(1 << self.0.folding_factor);
-        #[cfg(feature = "parallel")]
-        let leafs_iter = folded_evals.par_chunks_exact(1 << self.0.folding_factor);
-        let merkle_tree = MerkleTree::<MerkleConfig>::new(
-            &self.0.leaf_hash_params,
-            &self.0.two_to_one_params,
-            leafs_iter,
-        )
-        .unwrap();
+        let (merkle_tree, folded_evals) =
+            self.commit_folded(round + 1, &folded_coefficients, &new_domain);
+        check_cancelled()?;
 
         let root = merkle_tree.root();
         merlin.add_bytes(root.as_ref())?;
+        event!(
+            round = round_state.round,
+            root = %utils::Hex(root.as_ref()),
+            "prover round root"
+        );
 
         // OOD Samples
         let mut ood_points = vec![F::ZERO; round_params.ood_samples];
@@ -216,71 +689,70 @@ where
         // STIR queries
         let mut stir_queries_seed = [0u8; 32];
         merlin.fill_challenge_bytes(&mut stir_queries_seed)?;
-        let mut stir_gen = rand_chacha::ChaCha20Rng::from_seed(stir_queries_seed);
-        let stir_challenges_indexes =
-            utils::dedup((0..round_params.num_queries).map(|_| {
-                stir_gen.gen_range(0..round_state.domain.folded_size(self.0.folding_factor))
-            }));
-        let domain_scaled_gen = round_state
-            .domain
-            .backing_domain
-            .element(1 << self.0.folding_factor);
+        let stir_challenges_indexes = self.0.query_indexes(
+            &stir_queries_seed,
+            round_params.num_queries,
+            round_state.domain.folded_size(self.0.folding_factor),
+        )?;
+        event!(
+            round = round_state.round,
+            indexes = ?stir_challenges_indexes,
+            "prover queries"
+        );
+        let pending_pow = self.submit_pow_early(merlin, round, round_params.pow_bits)?;
+        // The points of the folded domain, the images of the queried cosets.
+        let folded_domain = round_state.domain.scale(1 << self.0.folding_factor);
         let stir_challenges: Vec<_> = ood_points
             .into_iter()
             .chain(
                 stir_challenges_indexes
                     .iter()
-                    .map(|i| domain_scaled_gen.pow([*i as u64])),
+                    .map(|i| folded_domain.element(*i)),
             )
             .map(|univariate| MultilinearPoint::expand_from_univariate(univariate, num_variables))
             .collect();
 
-        let merkle_proof = round_state
-            .prev_merkle
-            .generate_multi_proof(stir_challenges_indexes.clone())
+        let (merkle_proof, answers) = self.open_round(&round_state, &stir_challenges_indexes);
+        let cosets = self
+            .0
+            .query_cosets(
+                &stir_challenges_indexes,
+                &merkle_proof.leaf_indexes,
+                &answers,
+            )
             .unwrap();
-        let fold_size = 1 << self.0.folding_factor;
-        let answers: Vec<_> = stir_challenges_indexes
-            .iter()
-            .map(|i| round_state.prev_merkle_answers[i * fold_size..(i + 1) * fold_size].to_vec())
-            .collect();
         // Evaluate answers in the folding randomness.
         let mut stir_evaluations = ood_answers.clone();
-        match self.0.fold_optimisation {
-            FoldType::Naive => {
-                // See `Verifier::compute_folds_full`
-                let domain_size = round_state.domain.backing_domain.size();
-                let domain_gen = round_state.domain.backing_domain.element(1);
-                let domain_gen_inv = domain_gen.inverse().unwrap();
-                let coset_domain_size = 1 << self.0.folding_factor;
-                let coset_generator_inv =
-                    domain_gen_inv.pow([(domain_size / coset_domain_size) as u64]);
-                stir_evaluations.extend(stir_challenges_indexes.iter().zip(&answers).map(
-                    |(index, answers)| {
-                        // The coset is w^index * <w_coset_generator>
-                        //let _coset_offset = domain_gen.pow(&[*index as u64]);
-                        let coset_offset_inv = domain_gen_inv.pow([*index as u64]);
-
-                        compute_fold(
-                            answers,
-                            &round_state.folding_randomness.0,
-                            coset_offset_inv,
-                            coset_generator_inv,
-                            F::from(2).inverse().unwrap(),
-                            self.0.folding_factor,
-                        )
-                    },
-                ))
-            }
-            FoldType::ProverHelps => stir_evaluations.extend(answers.iter().map(|answers| {
-                CoefficientList::new(answers.to_vec()).evaluate(&round_state.folding_randomness)
-            })),
-        }
-        round_state.merkle_proofs.push((merkle_proof, answers));
+        stir_evaluations.extend(phase!(
+            fold_openings,
+            self.fold_answers(
+                &round_state.domain,
+                &round_state.folding_randomness,
+                &stir_challenges_indexes,
+                &cosets,
+            )
+        ));
+        sink.push_opening(
+            merlin,
+            (
+                merkle_proof,
+                answers.iter().map(|leaf| leaf.to_vec()).collect(),
+            ),
+        )?;
 
         // PoW
         if round_params.pow_bits > 0. {
-            merlin.challenge_pow::<PowStrategy>(round_params.pow_bits)?;
+            check_cancelled()?;
+            phase!(
+                pow,
+                self.pow(merlin, round, round_params.pow_bits, pending_pow)
+            )?;
+            event!(
+                round = round_state.round,
+                bits = round_params.pow_bits,
+                nonce = %utils::pow_nonce(merlin.transcript()),
+                "prover proof of work"
+            );
         }
 
         // Randomness for combination
@@ -288,10 +760,13 @@ where
         let combination_randomness =
             expand_randomness(combination_randomness_gen, stir_challenges.len());
 
-        round_state.sumcheck_prover.add_new_equality(
-            &stir_challenges,
-            &combination_randomness,
-            &stir_evaluations,
+        phase!(
+            constraints,
+            round_state.sumcheck_prover.add_new_equality(
+                &stir_challenges,
+                &combination_randomness,
+                &stir_evaluations,
+            )
         );
 
         let folding_randomness = round_state
@@ -302,21 +777,35 @@ where
                 round_params.folding_pow_bits,
             )?;
 
-        let round_state = RoundState {
-            round: round_state.round + 1,
-            domain: new_domain,
-            sumcheck_prover: round_state.sumcheck_prover,
-            folding_randomness,
-            coefficients: folded_coefficients, // TODO: Is this redundant with `sumcheck_prover.coeff` ?
-            prev_merkle: merkle_tree,
-            prev_merkle_answers: folded_evals,
-            merkle_proofs: round_state.merkle_proofs,
+        round_state.round += 1;
+        round_state.domain = new_domain;
+        round_state.folding_randomness = folding_randomness;
+        let (folded_coefficients, folded_evals) = match self.0.prover_mode {
+            ProverMode::LowMemory => {
+                wipe(folded_evals);
+                (Some(folded_coefficients), Vec::new())
+            }
+            ProverMode::Balanced => {
+                #[cfg(feature = "zeroize")]
+                {
+                    let mut folded_coefficients = folded_coefficients;
+                    folded_coefficients.zeroize();
+                }
+                (None, folded_evals)
+            }
         };
+        round_state.replace_polynomial(folded_coefficients, merkle_tree, folded_evals);
 
-        self.round(merlin, round_state)
+        self.round(merlin, round_state, sink, cancel)
     }
 }
 
+// A PoW submitted to the PoW service, whose nonce is not absorbed yet.
+struct PendingPow {
+    request: PowRequest,
+    nonce: Box<dyn PendingNonce>,
+}
+
 struct RoundState<F, MerkleConfig>
 where
     F: FftField,
@@ -326,8 +815,54 @@ where
     domain: Domain<F>,
     sumcheck_prover: SumcheckProverNotSkipping<F>,
     folding_randomness: MultilinearPoint<F>,
-    coefficients: CoefficientList<F>,
+    // The coefficients of the polynomial of the round in `ProverMode::LowMemory`, to recompute
+    // the opened leaves. The other modes keep the leaves, and the sumcheck folds the polynomial.
+    coefficients: Option<CoefficientList<F>>,
     prev_merkle: MerkleTree<MerkleConfig>,
     prev_merkle_answers: Vec<F>,
-    merkle_proofs: Vec<(MultiPath<MerkleConfig>, Vec<Vec<F>>)>,
+}
+
+impl<F, MerkleConfig> RoundState<F, MerkleConfig>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    // Moves on to the folded polynomial, wiping the tables of the current one.
+    fn replace_polynomial(
+        &mut self,
+        coefficients: Option<CoefficientList<F>>,
+        merkle_tree: MerkleTree<MerkleConfig>,
+        merkle_answers: Vec<F>,
+    ) {
+        #[cfg(feature = "zeroize")]
+        {
+            self.coefficients.zeroize();
+            self.prev_merkle_answers.zeroize();
+        }
+        self.coefficients = coefficients;
+        self.prev_merkle = merkle_tree;
+        self.prev_merkle_answers = merkle_answers;
+    }
+}
+
+// Drops a codeword that is no longer needed, wiping it first.
+fn wipe<F: FftField>(#[allow(unused_mut)] mut codeword: Vec<F>) {
+    #[cfg(feature = "zeroize")]
+    codeword.zeroize();
+    drop(codeword);
+}
+
+// The round state owns the witness once proving starts, so wiping it here also covers the
+// early returns on transcript errors.
+#[cfg(feature = "zeroize")]
+impl<F, MerkleConfig> Drop for RoundState<F, MerkleConfig>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    fn drop(&mut self) {
+        self.coefficients.zeroize();
+        self.sumcheck_prover.zeroize();
+        self.prev_merkle_answers.zeroize();
+    }
 }
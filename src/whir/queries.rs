@@ -0,0 +1,101 @@
+//! The derivation of the query indexes of a round from the 32-byte seed squeezed from the
+//! transcript (`stir_queries_seed` and `final_queries_seed` in `WhirIOPattern`).
+//!
+//! The default derivation, `query_indexes`, is the following. The seed keys ChaCha20 (20 rounds,
+//! nonce and block counter starting at zero, as `rand_chacha::ChaCha20Rng::from_seed`), whose
+//! keystream is read as little-endian 32-bit words. Each draw reads the next two words `w0, w1`
+//! as the 64-bit `x = w0 + 2^32 * w1`, and maps it to `0..domain_size` with Lemire's rejection
+//! sampling (that of `rand::Rng::gen_range` on a `u64`):
+//! - with `n = domain_size`, `zone = (n << n.leading_zeros()) - 1`;
+//! - `x * n`, as a 128-bit product, splits into its high and low 64-bit halves `hi` and `lo`;
+//! - if `lo <= zone`, the index is `hi`, otherwise `x` is rejected and another one drawn.
+//!
+//! `num_queries` indexes are drawn, rejected draws not counted, then sorted and deduplicated, so
+//! a round may have fewer distinct queries than `num_queries`.
+//!
+//! A verifier with other randomness plumbing, e.g. on chain, can reproduce this derivation, or
+//! plug its own into the configuration with `WhirConfig::with_query_sampler`. The name of a
+//! sampler other than the default one is bound into the IOPattern, and a sampler drawing the
+//! wrong number of indexes, or indexes out of the domain, fails the proof.
+
+use ark_std::vec::Vec;
+use rand::SeedableRng;
+
+use crate::utils;
+
+/// Derives query indexes from a seed, for all the rounds of a configuration. The prover and the
+/// verifier must use the same sampler.
+pub trait QuerySampler: Send + Sync {
+    /// Identifies the sampler in the IOPattern, so that proofs sampled otherwise do not verify.
+    fn name(&self) -> &str;
+    /// Draws exactly `num_queries` indexes in `0..domain_size` from `seed`. The indexes are
+    /// sorted and deduplicated by the caller.
+    fn sample(&self, seed: &[u8; 32], num_queries: usize, domain_size: usize) -> Vec<usize>;
+}
+
+/// The default sampler, see `query_indexes`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChaChaSampler;
+
+impl ChaChaSampler {
+    pub(crate) const NAME: &'static str = "chacha20";
+}
+
+impl QuerySampler for ChaChaSampler {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn sample(&self, seed: &[u8; 32], num_queries: usize, domain_size: usize) -> Vec<usize> {
+        let mut gen = rand_chacha::ChaCha20Rng::from_seed(*seed);
+        (0..num_queries)
+            .map(|_| utils::sample_index(&mut gen, domain_size))
+            .collect()
+    }
+}
+
+/// The default derivation of `num_queries` indexes in `0..domain_size` from `seed`, sorted and
+/// deduplicated, as described in the module documentation.
+pub fn query_indexes(seed: &[u8; 32], num_queries: usize, domain_size: usize) -> Vec<usize> {
+    utils::dedup(ChaChaSampler.sample(seed, num_queries, domain_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+
+    // The derivation as documented, from the words of the keystream.
+    fn documented_indexes(seed: &[u8; 32], num_queries: usize, domain_size: usize) -> Vec<usize> {
+        let mut keystream = rand_chacha::ChaCha20Rng::from_seed(*seed);
+        let n = domain_size as u64;
+        let zone = (n << n.leading_zeros()).wrapping_sub(1);
+        let indexes = (0..num_queries).map(|_| loop {
+            let (w0, w1) = (keystream.next_u32(), keystream.next_u32());
+            let x = w0 as u64 + ((w1 as u64) << 32);
+            let product = x as u128 * n as u128;
+            let (hi, lo) = ((product >> 64) as u64, product as u64);
+            if lo <= zone {
+                break hi as usize;
+            }
+        });
+        utils::dedup(indexes)
+    }
+
+    #[test]
+    fn test_query_indexes() {
+        let seed = [7u8; 32];
+        // Powers of two as in WHIR, and other sizes, which reject more often
+        for domain_size in [1, 2, 1 << 10, 1 << 20, 3, 1000, (1 << 40) + 1] {
+            let indexes = query_indexes(&seed, 100, domain_size);
+            assert_eq!(indexes, documented_indexes(&seed, 100, domain_size));
+            assert!(indexes.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(indexes.iter().all(|&index| index < domain_size));
+        }
+        assert_eq!(query_indexes(&seed, 100, 1), [0]);
+        let sampled = ChaChaSampler.sample(&seed, 10, 64);
+        assert_eq!(sampled.len(), 10);
+        assert_eq!(utils::dedup(sampled), query_indexes(&seed, 10, 64));
+    }
+}
@@ -0,0 +1,249 @@
+//! Openings of the polynomial at a range of consecutive points of the hypercube, e.g. a slice of
+//! a column, with a few evaluation claims instead of one per point.
+//!
+//! The range `start..start + len` of the table (in the order of `EvaluationsList`) splits into at
+//! most `2 * log(len)` aligned blocks `j * 2^m..(j + 1) * 2^m`, over which the first `n - m`
+//! variables are the bits of `j` and the last `m` are free. The prover sends the values of the
+//! range; the verifier then samples `r` and each block becomes the claim that the polynomial
+//! evaluates at `(bits(j), r_0, ..., r_{m - 1})` to the multilinear extension of the values of
+//! the block at `r`. A block with a wrong value passes with probability at most `m / |F|`.
+//!
+//! A block of a single point has no free variable, and is a claim at a point of the hypercube:
+//! the statement it is added to must not already have one there (see `Statement::validate`).
+
+use ark_ff::Field;
+use ark_std::vec::Vec;
+use nimue::{
+    plugins::ark::{FieldChallenges, FieldIOPattern, FieldReader, FieldWriter},
+    Arthur, IOPattern, Merlin, ProofError, ProofResult,
+};
+
+use super::Statement;
+use crate::{
+    errors::{WhirError, WhirResult},
+    poly_utils::{evals::EvaluationsList, hypercube::BinaryHypercubePoint, MultilinearPoint},
+};
+
+pub trait RangeIOPattern<F> {
+    /// The values of the range `start..start + len` and the randomness of its claims, between
+    /// `commit_statement` and `add_whir_proof`.
+    fn add_range(self, start: usize, len: usize) -> Self;
+}
+
+impl<F> RangeIOPattern<F> for IOPattern
+where
+    F: Field,
+    IOPattern: FieldIOPattern<F>,
+{
+    fn add_range(self, start: usize, len: usize) -> Self {
+        let num_randomness = num_randomness(start, len);
+        let io = FieldIOPattern::<F>::add_scalars(self, len, "range_values");
+        if num_randomness == 0 {
+            return io;
+        }
+        FieldIOPattern::<F>::challenge_scalars(io, num_randomness, "range_randomness")
+    }
+}
+
+// The aligned blocks of `start..start + len`, as their first index and the log of their size,
+// largest first within each side of the range.
+fn blocks(start: usize, len: usize) -> Vec<(usize, usize)> {
+    let (mut position, end) = (start, start + len);
+    let mut blocks = Vec::new();
+    while position < end {
+        let mut log_size = position.trailing_zeros().min(usize::BITS - 1) as usize;
+        while position + (1 << log_size) > end {
+            log_size -= 1;
+        }
+        blocks.push((position, log_size));
+        position += 1 << log_size;
+    }
+    blocks
+}
+
+// The number of random coordinates of the claims, that of the largest block.
+fn num_randomness(start: usize, len: usize) -> usize {
+    blocks(start, len)
+        .into_iter()
+        .map(|(_, log_size)| log_size)
+        .max()
+        .unwrap_or(0)
+}
+
+/// The claim that the polynomial evaluates to `values` at the points `start..start +
+/// values.len()` of the hypercube.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeClaim<F> {
+    pub start: usize,
+    pub values: Vec<F>,
+}
+
+impl<F: Field> RangeClaim<F> {
+    pub fn new(start: usize, values: Vec<F>) -> Self {
+        RangeClaim { start, values }
+    }
+
+    /// The claim of the evaluations of `evaluations` over `start..start + len`.
+    pub fn from_evaluations(evaluations: &EvaluationsList<F>, start: usize, len: usize) -> Self {
+        RangeClaim::new(start, evaluations.evals()[start..start + len].to_vec())
+    }
+
+    /// The number of challenges `statement` takes.
+    pub fn num_randomness(&self) -> usize {
+        num_randomness(self.start, self.values.len())
+    }
+
+    /// The evaluation claims of the blocks of the range at `randomness`, see the module
+    /// documentation. Fails if the range is not within the hypercube of `num_variables`
+    /// variables, or `randomness` has fewer than `num_randomness` challenges.
+    pub fn statement(&self, num_variables: usize, randomness: &[F]) -> WhirResult<Statement<F>> {
+        let num_points = crate::utils::checked_pow2(num_variables)
+            .ok_or(WhirError::TooManyVariables(num_variables))?;
+        let end = self.start.checked_add(self.values.len());
+        if end.map_or(true, |end| end > num_points) {
+            return Err(WhirError::TooLong {
+                max: num_points,
+                found: end.unwrap_or(usize::MAX),
+            });
+        }
+        if randomness.len() < self.num_randomness() {
+            return Err(WhirError::InvalidParameters(
+                "not enough randomness for the claims of the range",
+            ));
+        }
+
+        let (points, evaluations) = blocks(self.start, self.values.len())
+            .into_iter()
+            .map(|(position, log_size)| {
+                let free = MultilinearPoint(randomness[..log_size].to_vec());
+                let offset = position - self.start;
+                let values = &self.values[offset..offset + (1 << log_size)];
+                let evaluation = EvaluationsList::new(values.to_vec()).evaluate(&free);
+                let mut point = MultilinearPoint::<F>::from_binary_hypercube_point(
+                    BinaryHypercubePoint(position >> log_size),
+                    num_variables - log_size,
+                );
+                point.0.extend(free.0);
+                (point, evaluation)
+            })
+            .unzip();
+        Ok(Statement::new(points, evaluations))
+    }
+
+    /// Sends the values of the range as laid out by `RangeIOPattern::add_range`, and returns its
+    /// claims, to prove along with the rest of the statement.
+    pub fn prove(&self, merlin: &mut Merlin, num_variables: usize) -> WhirResult<Statement<F>>
+    where
+        Merlin: FieldWriter<F> + FieldChallenges<F>,
+    {
+        merlin.add_scalars(&self.values)?;
+        let mut randomness = vec![F::ZERO; self.num_randomness()];
+        if !randomness.is_empty() {
+            merlin.fill_challenge_scalars(&mut randomness)?;
+        }
+        self.statement(num_variables, &randomness)
+    }
+}
+
+/// Reads the values of the range `start..start + len` from the transcript, and returns them
+/// with the claims the WHIR proof must then be verified against, see `RangeClaim::prove`.
+pub fn read_range<F: Field>(
+    arthur: &mut Arthur,
+    start: usize,
+    len: usize,
+    num_variables: usize,
+) -> ProofResult<(RangeClaim<F>, Statement<F>)>
+where
+    for<'a> Arthur<'a>: FieldReader<F> + FieldChallenges<F>,
+{
+    let mut values = vec![F::ZERO; len];
+    arthur.fill_next_scalars(&mut values)?;
+    let claim = RangeClaim::new(start, values);
+    let mut randomness = vec![F::ZERO; claim.num_randomness()];
+    if !randomness.is_empty() {
+        arthur.fill_challenge_scalars(&mut randomness)?;
+    }
+    let statement = claim
+        .statement(num_variables, &randomness)
+        .map_err(|_| ProofError::InvalidIO)?;
+    Ok((claim, statement))
+}
+
+#[cfg(test)]
+mod tests {
+    use nimue::DefaultHash;
+
+    use super::*;
+    use crate::{
+        poly_utils::coeffs::CoefficientList,
+        whir::{
+            committer::Committer,
+            fixtures::{config, F},
+            iopattern::WhirIOPattern,
+            prover::Prover,
+            verifier::Verifier,
+        },
+    };
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(blocks(0, 8), [(0, 3)]);
+        assert_eq!(blocks(3, 10), [(3, 0), (4, 2), (8, 2), (12, 0)]);
+        assert_eq!(blocks(5, 0), []);
+        assert_eq!(num_randomness(3, 10), 2);
+        for (start, len) in [(0, 1), (1, 30), (7, 9), (16, 16)] {
+            let covered: Vec<_> = blocks(start, len)
+                .into_iter()
+                .flat_map(|(position, log_size)| position..position + (1 << log_size))
+                .collect();
+            assert_eq!(covered, Vec::from_iter(start..start + len));
+        }
+    }
+
+    #[test]
+    fn test_range_claim() {
+        let num_variables = 6;
+        let params = config(num_variables);
+        let (start, len) = (5, 40);
+        let io = IOPattern::<DefaultHash>::new("🌪️").commit_statement(&params);
+        let io = RangeIOPattern::<F>::add_range(io, start, len).add_whir_proof(&params);
+
+        let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+        let evaluations = EvaluationsList::from(polynomial.clone());
+        let claim = RangeClaim::from_evaluations(&evaluations, start, len);
+
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial)
+            .unwrap();
+        let statement = claim.prove(&mut merlin, num_variables).unwrap();
+        assert!(statement.points.len() <= 2 * len.ilog2() as usize);
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement, witness)
+            .unwrap();
+
+        let verifier = Verifier::new(params.clone());
+        let mut arthur = io.to_arthur(merlin.transcript());
+        let commitment = verifier.parse_commitment(&mut arthur).unwrap();
+        let (read, statement) = read_range(&mut arthur, start, len, num_variables).unwrap();
+        assert_eq!(read, claim);
+        assert!(verifier
+            .verify_with_commitment(&mut arthur, &commitment, &statement, &proof)
+            .is_ok());
+
+        // A wrong value makes the claim of its block false
+        let mut wrong = claim.clone();
+        wrong.values[20] += F::from(1);
+        let randomness: Vec<F> = (0..claim.num_randomness()).map(F::from).collect();
+        let (right, wrong) = (
+            claim.statement(num_variables, &randomness).unwrap(),
+            wrong.statement(num_variables, &randomness).unwrap(),
+        );
+        let differs = |i: usize| right.evaluations[i] != wrong.evaluations[i];
+        assert_eq!((0..right.points.len()).filter(|&i| differs(i)).count(), 1);
+        for (point, &evaluation) in right.points.iter().zip(&right.evaluations) {
+            assert_eq!(evaluations.evaluate(point), evaluation);
+        }
+        assert!(claim.statement(5, &randomness).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! Estimates of the size of a circuit verifying WHIR proofs, to choose the parameters of a
+//! recursive composition before writing the circuit.
+//!
+//! The circuit is described by the costs of its gadgets, in whatever unit the arithmetization
+//! counts (R1CS constraints, Plonkish rows): the configuration fixes how many times the verifier
+//! hashes, multiplies and decomposes into bits, and the costs turn this into the size of each
+//! component. The hash is assumed to be algebraic (e.g. Poseidon2), with digests of one field
+//! element, for the Merkle trees and for the Fiat-Shamir sponge alike.
+
+use ark_crypto_primitives::merkle_tree::Config;
+use ark_ff::FftField;
+use ark_std::vec;
+
+use super::parameters::WhirConfig;
+use crate::utils::ceil_f64;
+
+/// The costs of the gadgets of the circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GadgetCosts {
+    /// A permutation of the hash, which compresses two digests or absorbs `rate` elements.
+    pub permutation: usize,
+    /// The field elements absorbed or squeezed per permutation.
+    pub rate: usize,
+    /// A multiplication in the field of the proof; additions are taken to be free.
+    pub mul: usize,
+    /// Constraining a bit of the decomposition of a field element.
+    pub bit: usize,
+}
+
+/// The cost of the circuit verifying a proof, per component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VerifierCost {
+    /// Hashing the opened leaves and their authentication paths.
+    pub merkle: usize,
+    /// The sponge absorbing the prover messages and squeezing the challenges.
+    pub transcript: usize,
+    /// Decomposing the query indexes to select the paths and the points of the queries.
+    pub queries: usize,
+    /// Folding the opened leaves at the folding randomness.
+    pub folding: usize,
+    /// The checks of the sumcheck rounds.
+    pub sumcheck: usize,
+    /// Evaluating the combined constraints and the final polynomial.
+    pub constraints: usize,
+    /// Checking the proofs of work.
+    pub pow: usize,
+}
+
+impl VerifierCost {
+    pub fn total(&self) -> usize {
+        self.merkle
+            + self.transcript
+            + self.queries
+            + self.folding
+            + self.sumcheck
+            + self.constraints
+            + self.pow
+    }
+}
+
+/// The cost of the circuit verifying the proofs of `num_evaluations` evaluation claims for
+/// `params`. This is an estimate: it counts the operations of the native verifier, not the
+/// optimizations a circuit may make.
+pub fn verifier_cost<F, MerkleConfig, PowStrategy>(
+    params: &WhirConfig<F, MerkleConfig, PowStrategy>,
+    costs: &GadgetCosts,
+    num_evaluations: usize,
+) -> VerifierCost
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    let k = params.folding_factor;
+    let fold_size = 1 << k;
+    let leaf_permutations = params.leaf_size().div_ceil(costs.rate.max(1));
+    let mut cost = VerifierCost::default();
+    let (mut absorbed, mut squeezed) = (0usize, 0usize);
+    let mut pows = vec![params.starting_folding_pow_bits; k];
+
+    // The commitment, and the sumcheck rounds of each fold: a degree-2 polynomial sent, checked
+    // against the claim and evaluated at the challenge.
+    absorbed += 1 + params.committment_ood_samples + params.statement_ood_samples;
+    squeezed += params.committment_ood_samples
+        + params.statement_ood_samples * params.mv_parameters.num_variables
+        + 1;
+    let sumcheck = |rounds: usize, absorbed: &mut usize, squeezed: &mut usize| {
+        *absorbed += 3 * rounds;
+        *squeezed += rounds;
+        4 * rounds * costs.mul
+    };
+    cost.sumcheck += sumcheck(k, &mut absorbed, &mut squeezed);
+
+    let mut num_variables = params.mv_parameters.num_variables;
+    // Each statement point is evaluated in `eq` over all the variables.
+    cost.constraints += num_evaluations * (2 * num_variables + 1) * costs.mul;
+
+    let num_queries = params
+        .round_parameters
+        .iter()
+        .map(|r| r.num_queries)
+        .chain([params.final_queries]);
+    for (round, num_queries) in num_queries.enumerate() {
+        let depth = ((params.starting_domain.size() >> round) / params.leaf_size()).trailing_zeros()
+            as usize;
+        num_variables -= k.min(num_variables);
+
+        cost.merkle += num_queries * (leaf_permutations + depth) * costs.permutation;
+        cost.queries += num_queries * depth * (costs.bit + costs.mul);
+        cost.folding += num_queries * fold_size * costs.mul;
+        squeezed += num_queries;
+
+        if let Some(r) = params.round_parameters.get(round) {
+            // The new commitment, and its constraints evaluated in `eq` over the point expanded
+            // from a univariate one.
+            absorbed += 1 + r.ood_samples;
+            squeezed += r.ood_samples + 1;
+            cost.constraints += (r.ood_samples + num_queries) * (3 * num_variables + 1) * costs.mul;
+            pows.push(r.pow_bits);
+            pows.resize(pows.len() + k, r.folding_pow_bits);
+            cost.sumcheck += sumcheck(k, &mut absorbed, &mut squeezed);
+        } else {
+            // The final polynomial, evaluated at each of the final queries.
+            let final_coeffs = 1 << params.final_sumcheck_rounds;
+            absorbed += final_coeffs;
+            cost.constraints += num_queries * final_coeffs * costs.mul;
+            pows.push(params.final_pow_bits);
+            pows.resize(
+                pows.len() + params.final_sumcheck_rounds,
+                params.final_folding_pow_bits,
+            );
+            cost.sumcheck += sumcheck(params.final_sumcheck_rounds, &mut absorbed, &mut squeezed);
+        }
+    }
+
+    for bits in pows.into_iter().filter(|&bits| bits > 0.) {
+        squeezed += 1;
+        cost.pow += costs.permutation + (ceil_f64(bits) as usize) * costs.bit;
+    }
+    let rate = costs.rate.max(1);
+    cost.transcript = (absorbed.div_ceil(rate) + squeezed.div_ceil(rate)) * costs.permutation;
+    cost
+}
+
+#[cfg(test)]
+mod tests {
+    use nimue_pow::blake3::Blake3PoW;
+
+    use super::*;
+    use crate::{
+        crypto::{fields::Field64_2, merkle_tree::blake3 as merkle_tree},
+        parameters::{MultivariateParameters, WhirParameters},
+        whir::fixtures::whir_parameters,
+    };
+
+    type F = Field64_2;
+    type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+
+    fn config(starting_log_inv_rate: usize) -> WhirConfig<F, MerkleConfig, Blake3PoW> {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params = WhirParameters::<MerkleConfig, Blake3PoW> {
+            security_level: 100,
+            pow_bits: 20,
+            folding_factor: 4,
+            starting_log_inv_rate,
+            ..whir_parameters((leaf_hash_params, two_to_one_params))
+        };
+        WhirConfig::try_new(MultivariateParameters::new(16), whir_params).unwrap()
+    }
+
+    #[test]
+    fn test_verifier_cost() {
+        let params = config(1);
+        // Only the permutations count, with leaves absorbed in one
+        let hashes_only = GadgetCosts {
+            permutation: 1,
+            rate: 1 << 4,
+            mul: 0,
+            bit: 0,
+        };
+        let cost = verifier_cost(&params, &hashes_only, 1);
+        let paths: usize = params
+            .round_parameters
+            .iter()
+            .map(|r| r.num_queries)
+            .chain([params.final_queries])
+            .enumerate()
+            .map(|(round, num_queries)| {
+                let num_leaves = params.starting_domain.size() >> round >> 4;
+                num_queries * (1 + num_leaves.trailing_zeros() as usize)
+            })
+            .sum();
+        assert_eq!(cost.merkle, paths);
+        assert_eq!(cost.folding + cost.sumcheck + cost.constraints, 0);
+        assert_eq!(cost.total(), cost.merkle + cost.transcript + cost.pow);
+
+        // A higher rate means fewer queries, so a smaller circuit
+        let costs = GadgetCosts {
+            permutation: 300,
+            rate: 8,
+            mul: 1,
+            bit: 1,
+        };
+        assert!(
+            verifier_cost(&config(2), &costs, 1).total()
+                < verifier_cost(&params, &costs, 1).total()
+        );
+    }
+}
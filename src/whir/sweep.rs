@@ -0,0 +1,224 @@
+//! Sweeps of the parameters of WHIR, to compare the costs of the configurations reaching a
+//! security level. The costs are counts from the configuration, not measurements: the proof size
+//! is `WhirConfig::proof_size_bound`, and the prover and verifier costs count field
+//! multiplications and hash evaluations, the first of which dominate the proving time.
+
+use ark_crypto_primitives::merkle_tree::Config;
+use ark_ff::FftField;
+use ark_std::fmt::Write;
+
+use super::parameters::WhirConfig;
+use crate::{
+    crypto::fields::FieldWithSize,
+    parameters::{MultivariateParameters, WhirParameters},
+    utils::exp2_f64,
+};
+
+/// The costs of a configuration of the sweep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepRow {
+    pub folding_factor: usize,
+    pub starting_log_inv_rate: usize,
+    pub pow_bits: usize,
+    /// Whether the configuration reaches the security level within `pow_bits` of PoW.
+    pub secure: bool,
+    pub num_rounds: usize,
+    pub proof_size: usize,
+    /// Field multiplications of the prover: encodings, sumchecks and evaluations of the
+    /// constraints.
+    pub prover_field_ops: usize,
+    /// Hash evaluations of the prover: Merkle trees and, expected, PoWs.
+    pub prover_hashes: usize,
+    /// Hash evaluations of the verifier: Merkle paths and PoWs.
+    pub verifier_hashes: usize,
+}
+
+fn log2(n: usize) -> usize {
+    n.trailing_zeros() as usize
+}
+
+// The expected number of hashes to find a PoW of `bits` bits.
+fn pow_hashes(bits: f64) -> usize {
+    if bits > 0. {
+        exp2_f64(bits) as usize
+    } else {
+        0
+    }
+}
+
+impl SweepRow {
+    fn new<F, MerkleConfig, PowStrategy>(
+        params: &WhirConfig<F, MerkleConfig, PowStrategy>,
+        whir_parameters: &WhirParameters<MerkleConfig, PowStrategy>,
+    ) -> Self
+    where
+        F: FftField,
+        MerkleConfig: Config,
+    {
+        let fold_size = 1 << params.folding_factor;
+        let mut num_variables = params.mv_parameters.num_variables;
+        let mut domain_size = params.starting_domain.size();
+        let (mut field_ops, mut hashes, mut verifier_hashes) = (0usize, 0usize, 0usize);
+
+        // Committing: the encoding and the tree.
+        field_ops += domain_size * log2(domain_size);
+        hashes += 2 * (domain_size / fold_size);
+        // The initial sumcheck: the tables, folded once per round.
+        field_ops += 3 * (1 << num_variables);
+        let mut pows = vec![params.starting_folding_pow_bits; params.folding_factor];
+
+        for r in params.round_parameters.iter() {
+            let depth = log2(domain_size / fold_size);
+            num_variables -= params.folding_factor;
+            domain_size /= 2;
+            let num_constraints = r.ood_samples + r.num_queries;
+            // The new encoding and its tree, the constraints and the sumcheck.
+            field_ops += domain_size * log2(domain_size);
+            hashes += 2 * (domain_size / fold_size);
+            field_ops += (num_constraints + 3) * (1 << num_variables);
+            verifier_hashes += r.num_queries * (depth + 1);
+            pows.push(r.pow_bits);
+            pows.resize(pows.len() + params.folding_factor, r.folding_pow_bits);
+        }
+
+        let depth = log2(domain_size / fold_size);
+        verifier_hashes += params.final_queries * (depth + 1);
+        pows.push(params.final_pow_bits);
+        pows.resize(
+            pows.len() + params.final_sumcheck_rounds,
+            params.final_folding_pow_bits,
+        );
+        for bits in pows {
+            hashes += pow_hashes(bits);
+            verifier_hashes += usize::from(bits > 0.);
+        }
+
+        SweepRow {
+            folding_factor: params.folding_factor,
+            starting_log_inv_rate: params.starting_log_inv_rate,
+            pow_bits: whir_parameters.pow_bits,
+            secure: params.check_pow_bits(),
+            num_rounds: params.n_rounds(),
+            proof_size: params.proof_size_bound(),
+            prover_field_ops: field_ops,
+            prover_hashes: hashes,
+            verifier_hashes,
+        }
+    }
+}
+
+/// The costs of the configurations for `num_variables` variables with each of the folding
+/// factors, starting rates and PoW budgets, the other parameters taken from `whir_parameters`.
+/// Combinations for which there is no configuration are skipped.
+pub fn sweep<F, MerkleConfig, PowStrategy>(
+    num_variables: usize,
+    whir_parameters: &WhirParameters<MerkleConfig, PowStrategy>,
+    folding_factors: &[usize],
+    log_inv_rates: &[usize],
+    pow_bits: &[usize],
+) -> Vec<SweepRow>
+where
+    F: FftField + FieldWithSize,
+    MerkleConfig: Config + Clone,
+    PowStrategy: Clone,
+{
+    let mut rows = Vec::new();
+    let Ok(mv_parameters) = MultivariateParameters::<F>::try_new(num_variables) else {
+        return rows;
+    };
+    for &folding_factor in folding_factors {
+        for &starting_log_inv_rate in log_inv_rates {
+            for &pow_bits in pow_bits {
+                let whir_parameters = WhirParameters {
+                    folding_factor,
+                    starting_log_inv_rate,
+                    pow_bits,
+                    // Each row is the configuration of its parameters
+                    max_proof_size: None,
+                    ..whir_parameters.clone()
+                };
+                if let Ok(params) = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                    mv_parameters,
+                    whir_parameters.clone(),
+                ) {
+                    rows.push(SweepRow::new(&params, &whir_parameters));
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// The rows as CSV, with a header.
+pub fn to_csv(rows: &[SweepRow]) -> String {
+    let mut csv = String::from(
+        "folding_factor,starting_log_inv_rate,pow_bits,secure,num_rounds,proof_size,\
+         prover_field_ops,prover_hashes,verifier_hashes\n",
+    );
+    for row in rows {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{}",
+            row.folding_factor,
+            row.starting_log_inv_rate,
+            row.pow_bits,
+            row.secure,
+            row.num_rounds,
+            row.proof_size,
+            row.prover_field_ops,
+            row.prover_hashes,
+            row.verifier_hashes,
+        )
+        .unwrap();
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use nimue_pow::blake3::Blake3PoW;
+
+    use super::{sweep, to_csv};
+    use crate::{
+        crypto::{fields::Field64_2, merkle_tree::blake3 as merkle_tree},
+        parameters::WhirParameters,
+        whir::fixtures::whir_parameters,
+    };
+
+    type F = Field64_2;
+    type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+
+    #[test]
+    fn test_sweep() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params = WhirParameters::<MerkleConfig, Blake3PoW> {
+            security_level: 100,
+            folding_factor: 0,
+            starting_log_inv_rate: 0,
+            ..whir_parameters((leaf_hash_params, two_to_one_params))
+        };
+        // Folding by 0 or by more than the number of variables has no configuration
+        let rows = sweep::<F, _, _>(12, &whir_params, &[0, 2, 4, 13], &[1, 2], &[0, 20]);
+        assert_eq!(rows.len(), 2 * 2 * 2);
+        for row in &rows {
+            assert!([2, 4].contains(&row.folding_factor));
+        }
+        // A higher rate means fewer queries, so smaller proofs
+        let size = |folding_factor, rate| {
+            rows.iter()
+                .find(|row| {
+                    row.folding_factor == folding_factor
+                        && row.starting_log_inv_rate == rate
+                        && row.pow_bits == 20
+                })
+                .unwrap()
+                .proof_size
+        };
+        assert!(size(4, 2) < size(4, 1));
+
+        let csv = to_csv(&rows);
+        assert_eq!(csv.lines().count(), rows.len() + 1);
+        assert!(csv.starts_with("folding_factor,"));
+    }
+}
@@ -1,24 +1,83 @@
-use std::iter;
+use ark_std::{iter, vec::Vec};
 
-use ark_crypto_primitives::merkle_tree::Config;
-use ark_ff::FftField;
+use ark_crypto_primitives::{
+    crh::CRHScheme,
+    merkle_tree::{Config, MultiPath},
+};
+use ark_ff::{batch_inversion, FftField, Field};
 use ark_poly::EvaluationDomain;
 use nimue::{
     plugins::ark::{FieldChallenges, FieldReader},
-    Arthur, ByteChallenges, ByteReader, ProofError, ProofResult,
+    Arthur, ByteChallenges, ByteReader, IOPattern, ProofError, ProofResult,
 };
 use nimue_pow::{self, PoWChallenge};
-use rand::{Rng, SeedableRng};
 
 use crate::{
-    parameters::FoldType,
-    poly_utils::{coeffs::CoefficientList, eq_poly_outside, fold::compute_fold, MultilinearPoint},
+    parameters::{FinalForm, FoldType},
+    poly_utils::{
+        coeffs::CoefficientList, eq_poly_outside, eq_poly_rotated, eq_poly_univariate,
+        evals::EvaluationsList, MultilinearPoint,
+    },
     sumcheck::proof::SumcheckPolynomial,
-    utils::{self, expand_randomness},
+    utils::expand_randomness,
+};
+
+use super::{
+    fold::{compute_folds, compute_folds_with_inverses, FoldDomain},
+    hints::ProofHints,
+    opening_from_transcript,
+    parameters::WhirConfig,
+    proof_from_bytes_for_config, transcript_opening_sizes, ClaimType, Statement, WhirProof,
 };
 
-use super::{parameters::WhirConfig, Statement, WhirProof};
+/// The weight of the combination of the initial claims at `point`, which the verifier checks
+/// the last sumcheck claim against.
+///
+/// The claims are, in this order, the out-of-domain samples `ood_points` of the commitment
+/// (univariate points, expanded as in `MultilinearPoint::expand_from_univariate`), the claims of
+/// the statement by point, then its linear claims, and `combination_randomness` has one
+/// coefficient per claim. The weight is the sum of the coefficients times the weights of the
+/// claims at `point`:
+/// - `eq(expand(z), point)` for an out-of-domain sample `z`, see `eq_poly_univariate`;
+/// - `eq(z, point)` for an evaluation claim at `z`, see `eq_poly_outside`;
+/// - `eq_rot(z, point)` for a rotated claim at `z`, see `eq_poly_rotated`;
+/// - the multilinear extension of the weights at `point` for a linear claim.
+///
+/// so that `sum_b weight(b) * p(b)` is the combination of the claimed values, for `b` over the
+/// hypercube.
+pub fn compute_statement_weight<F: Field>(
+    ood_points: &[F],
+    statement: &Statement<F>,
+    combination_randomness: &[F],
+    point: &MultilinearPoint<F>,
+) -> F {
+    let claims = ood_points
+        .iter()
+        .map(|&ood_point| eq_poly_univariate(point, ood_point))
+        .chain(statement.points.iter().zip(&statement.claim_types).map(
+            |(claim_point, claim_type)| match claim_type {
+                ClaimType::Evaluation => eq_poly_outside(claim_point, point),
+                ClaimType::Rotated => eq_poly_rotated(claim_point, point),
+            },
+        ))
+        .chain(
+            statement
+                .linear_claims
+                .iter()
+                .map(|claim| claim.weights.evaluate(point)),
+        );
+    claims
+        .zip(combination_randomness)
+        .map(|(weight, randomness)| *randomness * weight)
+        .sum()
+}
 
+/// Verifies WHIR proofs for one configuration.
+///
+/// The domain of each round is derived from the configuration once, in `new`, so that verifying
+/// many proofs with one `Verifier` does not derive it again for each. The verifier holds no
+/// state of its own between proofs: it is `Send + Sync` whenever the Merkle parameters are, and
+/// can be shared between threads behind an `Arc`.
 pub struct Verifier<F, MerkleConfig, PowStrategy>
 where
     F: FftField,
@@ -26,41 +85,154 @@ where
 {
     params: WhirConfig<F, MerkleConfig, PowStrategy>,
     two_inv: F,
+    // One per round, then the final round.
+    domains: Vec<RoundDomain<F>>,
 }
 
+// The domain of a round, as the verifier uses it: the queries are at
+// `exp_offset * exp_gen^index` for an index below `folded_size`, and their answers are folded
+// with the inverses.
+#[derive(Debug, Clone)]
+struct RoundDomain<F> {
+    folded_size: usize,
+    exp_gen: F,
+    exp_offset: F,
+    gen: F,
+    offset: F,
+    gen_inv: F,
+    offset_inv: F,
+    coset_generator_inv: F,
+}
+
+/// The commitment read from the transcript, see `Verifier::parse_commitment`.
 #[derive(Clone)]
-struct ParsedCommitment<F, D> {
-    root: D,
-    ood_points: Vec<F>,
-    ood_answers: Vec<F>,
+pub struct ParsedCommitment<F, D> {
+    pub(crate) root: D,
+    pub(crate) ood_points: Vec<F>,
+    pub(crate) ood_answers: Vec<F>,
+    // The claims of the OOD samples left to the statement, see
+    // `WhirConfig::with_statement_ood_samples`.
+    pub(crate) statement_ood: Statement<F>,
+}
+
+/// The Merkle verification of an opening, left to the caller by
+/// `Verifier::verify_delegating_merkle`: the leaves `leaf_indexes` of the tree `tree`, with the
+/// hashes `leaf_hashes`, must open to `root` with the authentication paths of `path`.
+pub struct MerkleCheck<MerkleConfig: Config> {
+    /// The tree, whose hash parameters are `WhirConfig::merkle_params(tree)`: the commitment,
+    /// then the tree committed in each round.
+    pub tree: usize,
+    pub root: MerkleConfig::InnerDigest,
+    pub leaf_indexes: Vec<usize>,
+    pub leaf_hashes: Vec<MerkleConfig::LeafDigest>,
+    pub path: MultiPath<MerkleConfig>,
+}
+
+impl<MerkleConfig: Config> MerkleCheck<MerkleConfig> {
+    /// The (root, index, leaf hash) triples of the opening.
+    pub fn triples(
+        &self,
+    ) -> impl Iterator<Item = (&MerkleConfig::InnerDigest, usize, &MerkleConfig::LeafDigest)> {
+        self.leaf_indexes
+            .iter()
+            .zip(&self.leaf_hashes)
+            .map(|(&index, hash)| (&self.root, index, hash))
+    }
 }
 
 #[derive(Clone)]
-struct ParsedProof<F> {
-    initial_combination_randomness: Vec<F>,
-    initial_sumcheck_rounds: Vec<(SumcheckPolynomial<F>, F)>,
-    rounds: Vec<ParsedRound<F>>,
-    final_domain_gen_inv: F,
-    final_randomness_indexes: Vec<usize>,
-    final_randomness_points: Vec<F>,
-    final_randomness_answers: Vec<Vec<F>>,
-    final_folding_randomness: MultilinearPoint<F>,
-    final_sumcheck_rounds: Vec<(SumcheckPolynomial<F>, F)>,
-    final_sumcheck_randomness: MultilinearPoint<F>,
-    final_coefficients: CoefficientList<F>,
+pub(crate) struct ParsedProof<F> {
+    pub(crate) initial_combination_randomness: Vec<F>,
+    pub(crate) initial_sumcheck_rounds: Vec<(SumcheckPolynomial<F>, F)>,
+    pub(crate) rounds: Vec<ParsedRound<F>>,
+    pub(crate) final_domain_gen_inv: F,
+    pub(crate) final_domain_offset_inv: F,
+    pub(crate) final_randomness_indexes: Vec<usize>,
+    pub(crate) final_randomness_points: Vec<F>,
+    pub(crate) final_randomness_answers: Vec<Vec<F>>,
+    pub(crate) final_folding_randomness: MultilinearPoint<F>,
+    pub(crate) final_sumcheck_rounds: Vec<(SumcheckPolynomial<F>, F)>,
+    pub(crate) final_sumcheck_randomness: MultilinearPoint<F>,
+    pub(crate) final_polynomial: FinalPolynomial<F>,
+    // The inverses of the offsets of the queried cosets from the hints, one per opening, or none.
+    pub(crate) coset_offsets_inv: Vec<Vec<F>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ParsedRound<F> {
+    pub(crate) folding_randomness: MultilinearPoint<F>,
+    pub(crate) ood_points: Vec<F>,
+    pub(crate) ood_answers: Vec<F>,
+    pub(crate) stir_challenges_indexes: Vec<usize>,
+    pub(crate) stir_challenges_points: Vec<F>,
+    pub(crate) stir_challenges_answers: Vec<Vec<F>>,
+    pub(crate) combination_randomness: Vec<F>,
+    pub(crate) sumcheck_rounds: Vec<(SumcheckPolynomial<F>, F)>,
+    pub(crate) domain_gen_inv: F,
+    pub(crate) domain_offset_inv: F,
 }
 
+/// The polynomial of the last round, in the form it was sent in, see `FinalForm`.
 #[derive(Debug, Clone)]
-struct ParsedRound<F> {
-    folding_randomness: MultilinearPoint<F>,
-    ood_points: Vec<F>,
-    ood_answers: Vec<F>,
-    stir_challenges_indexes: Vec<usize>,
-    stir_challenges_points: Vec<F>,
-    stir_challenges_answers: Vec<Vec<F>>,
-    combination_randomness: Vec<F>,
-    sumcheck_rounds: Vec<(SumcheckPolynomial<F>, F)>,
-    domain_gen_inv: F,
+pub(crate) enum FinalPolynomial<F> {
+    Coefficients(CoefficientList<F>),
+    Evaluations(EvaluationsList<F>),
+}
+
+impl<F: Field> FinalPolynomial<F> {
+    pub(crate) fn new(form: FinalForm, values: Vec<F>) -> Self {
+        match form {
+            FinalForm::Coefficients => FinalPolynomial::Coefficients(CoefficientList::new(values)),
+            FinalForm::Evaluations => FinalPolynomial::Evaluations(EvaluationsList::new(values)),
+        }
+    }
+
+    pub(crate) fn evaluate(&self, point: &MultilinearPoint<F>) -> F {
+        match self {
+            FinalPolynomial::Coefficients(coefficients) => coefficients.evaluate(point),
+            FinalPolynomial::Evaluations(evaluations) => evaluations.evaluate(point),
+        }
+    }
+
+    // The evaluations at the points expanded from `points`, see `evaluate_at_univariate`.
+    pub(crate) fn evaluate_at_univariate(&self, points: &[F]) -> Vec<F> {
+        match self {
+            FinalPolynomial::Coefficients(coefficients) => {
+                coefficients.evaluate_at_univariate(points)
+            }
+            FinalPolynomial::Evaluations(evaluations) => points
+                .iter()
+                .map(|&point| {
+                    evaluations.evaluate(&MultilinearPoint::expand_from_univariate(
+                        point,
+                        evaluations.num_variables(),
+                    ))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<F, D> ParsedCommitment<F, D> {
+    pub fn root(&self) -> &D {
+        &self.root
+    }
+
+    /// The number of out-of-domain samples of the commitment.
+    pub fn num_ood_samples(&self) -> usize {
+        self.ood_points.len()
+    }
+
+    /// The out-of-domain points sampled after the root, as univariate points.
+    pub fn ood_points(&self) -> &[F] {
+        &self.ood_points
+    }
+
+    /// The answers of the prover at `ood_points`, which can only be trusted once a proof
+    /// against the commitment verifies, see `Verifier::ood_statement`.
+    pub fn ood_answers(&self) -> &[F] {
+        &self.ood_answers
+    }
 }
 
 impl<F, MerkleConfig, PowStrategy> Verifier<F, MerkleConfig, PowStrategy>
@@ -71,17 +243,78 @@ where
     PowStrategy: nimue_pow::PowStrategy,
 {
     pub fn new(params: WhirConfig<F, MerkleConfig, PowStrategy>) -> Self {
+        let domains = Self::round_domains(&params);
         Verifier {
             params,
             two_inv: F::from(2).inverse().unwrap(), // The only inverse in the entire code :)
+            domains,
+        }
+    }
+
+    // The domain of each round and of the final round. Each is the square of the previous one,
+    // of half its size.
+    fn round_domains(params: &WhirConfig<F, MerkleConfig, PowStrategy>) -> Vec<RoundDomain<F>> {
+        let backing_domain = &params.starting_domain.backing_domain;
+        let folding_factor = 1 << params.folding_factor;
+        let mut domain = RoundDomain {
+            folded_size: params.starting_domain.size() / folding_factor,
+            exp_gen: backing_domain.group_gen().pow([folding_factor as u64]),
+            // The domain is the coset offset * <domain_gen>
+            exp_offset: params.starting_domain.offset().pow([folding_factor as u64]),
+            gen: backing_domain.group_gen(),
+            offset: backing_domain.coset_offset(),
+            gen_inv: backing_domain.group_gen_inv(),
+            offset_inv: backing_domain.coset_offset_inv(),
+            coset_generator_inv: F::ONE,
+        };
+        let mut domains = Vec::with_capacity(params.n_rounds() + 1);
+        for _ in 0..=params.n_rounds() {
+            // This is such that coset_generator^folding_factor = F::ONE
+            domain.coset_generator_inv = domain.gen_inv.pow([domain.folded_size as u64]);
+            domains.push(domain.clone());
+            domain = RoundDomain {
+                folded_size: domain.folded_size / 2,
+                exp_gen: domain.exp_gen.square(),
+                exp_offset: domain.exp_offset.square(),
+                gen: domain.gen.square(),
+                offset: domain.offset.square(),
+                gen_inv: domain.gen_inv.square(),
+                offset_inv: domain.offset_inv.square(),
+                coset_generator_inv: F::ONE,
+            };
         }
+        domains
+    }
+
+    /// The answers of `commitment` as evaluation claims on the committed polynomial, at the
+    /// expansions of the out-of-domain points (see `MultilinearPoint::expand_from_univariate`).
+    /// The proof binds the polynomial to them, so once it verifies against the commitment, an
+    /// outer protocol can use them as evaluations without asking for more openings. `None` in ZK
+    /// mode, where the answers are those of the masked polynomial.
+    pub fn ood_statement(
+        &self,
+        commitment: &ParsedCommitment<F, MerkleConfig::InnerDigest>,
+    ) -> Option<Statement<F>> {
+        if self.params.zk {
+            return None;
+        }
+        let num_variables = self.params.mv_parameters.num_variables;
+        let points = commitment
+            .ood_points
+            .iter()
+            .map(|&point| MultilinearPoint::expand_from_univariate(point, num_variables))
+            .collect();
+        Some(Statement::new(points, commitment.ood_answers.clone()))
     }
 
-    fn parse_commitment(
+    /// Reads the commitment from the transcript. Only needed when the protocol using WHIR
+    /// has messages between the commitment and the proof, otherwise see `verify`.
+    pub fn parse_commitment(
         &self,
         arthur: &mut Arthur,
     ) -> ProofResult<ParsedCommitment<F, MerkleConfig::InnerDigest>> {
         let root: [u8; 32] = arthur.next_bytes()?;
+        event!(root = %crate::utils::Hex(&root), "verifier commitment");
 
         let mut ood_points = vec![F::ZERO; self.params.committment_ood_samples];
         let mut ood_answers = vec![F::ZERO; self.params.committment_ood_samples];
@@ -90,25 +323,88 @@ where
             arthur.fill_next_scalars(&mut ood_answers)?;
         }
 
+        let num_variables = self.params.mv_parameters.num_variables;
+        let mut statement_ood = Statement::new(vec![], vec![]);
+        if self.params.statement_ood_samples > 0 {
+            let mut coords = vec![F::ZERO; self.params.statement_ood_samples * num_variables];
+            let mut answers = vec![F::ZERO; self.params.statement_ood_samples];
+            arthur.fill_challenge_scalars(&mut coords)?;
+            arthur.fill_next_scalars(&mut answers)?;
+            let points = coords
+                .chunks_exact(num_variables)
+                .map(|coords| MultilinearPoint(coords.to_vec()))
+                .collect();
+            statement_ood = Statement::new(points, answers);
+        }
+
         Ok(ParsedCommitment {
             root: root.into(),
             ood_points,
             ood_answers,
+            statement_ood,
         })
     }
 
+    /// The evaluation claims at the points that the verifier squeezed after `commitment` for the
+    /// OOD samples left to the statement (see `WhirConfig::with_statement_ood_samples`), with the
+    /// answers of the prover. The statement of the proof must start with them; the outer
+    /// protocol extends them with its own claims. Empty for other configurations.
+    pub fn statement_ood(
+        &self,
+        commitment: &ParsedCommitment<F, MerkleConfig::InnerDigest>,
+    ) -> Statement<F> {
+        commitment.statement_ood.clone()
+    }
+
+    // The opening of `round` absorbed into the transcript, see
+    // `WhirConfig::with_openings_in_transcript`.
+    fn read_transcript_opening(
+        &self,
+        arthur: &mut Arthur,
+        round: usize,
+    ) -> ProofResult<(MultiPath<MerkleConfig>, Vec<Vec<F>>)> {
+        let mut bytes = vec![0; transcript_opening_sizes(&self.params)[round]];
+        arthur.fill_next_bytes(&mut bytes)?;
+        opening_from_transcript(&self.params, round, &bytes)
+    }
+
     fn parse_proof(
         &self,
         arthur: &mut Arthur,
         parsed_commitment: &ParsedCommitment<F, MerkleConfig::InnerDigest>,
         statement: &Statement<F>, // Will be needed later
         whir_proof: &WhirProof<MerkleConfig, F>,
+        mut delegated: Option<&mut Vec<MerkleCheck<MerkleConfig>>>,
     ) -> ProofResult<ParsedProof<F>> {
+        let shape = if self.params.openings_in_transcript {
+            whir_proof.0.is_empty()
+        } else {
+            whir_proof.check_shape(&self.params)
+        };
+        // The claims of the OOD samples left to the statement are those squeezed by the verifier
+        let expected = parsed_commitment.statement_ood.points.len();
+        if statement.common_prefix(&parsed_commitment.statement_ood) != expected {
+            event!("verifier rejected the OOD claims of the statement");
+            return Err(ProofError::InvalidProof);
+        }
+
+        let hints = &whir_proof.1.coset_offsets_inv;
+        let shape = shape
+            && (hints.is_empty()
+                || (matches!(self.params.fold_optimisation, FoldType::Naive)
+                    && hints.len() == self.params.n_rounds() + 1));
+        if !shape {
+            event!("verifier rejected the shape of the proof");
+            return Err(ProofError::InvalidProof);
+        }
+
         // Derive combination randomness and first sumcheck polynomial
         let [combination_randomness_gen]: [F; 1] = arthur.challenge_scalars()?;
         let initial_combination_randomness = expand_randomness(
             combination_randomness_gen,
-            parsed_commitment.ood_points.len() + statement.points.len(),
+            parsed_commitment.ood_points.len()
+                + statement.points.len()
+                + statement.linear_claims.len(),
         );
 
         // Initial sumcheck
@@ -128,17 +424,18 @@ where
             MultilinearPoint(sumcheck_rounds.iter().map(|&(_, r)| r).rev().collect());
 
         let mut prev_root = parsed_commitment.root.clone();
-        let domain_gen = self.params.starting_domain.backing_domain.group_gen();
-        let mut exp_domain_gen = domain_gen.pow([1 << self.params.folding_factor]);
-        let mut domain_gen_inv = self.params.starting_domain.backing_domain.group_gen_inv();
-        let mut domain_size = self.params.starting_domain.size();
         let mut rounds = vec![];
 
         for r in 0..self.params.n_rounds() {
-            let (merkle_proof, answers) = &whir_proof.0[r];
             let round_params = &self.params.round_parameters[r];
+            let domain = &self.domains[r];
 
             let new_root: [u8; 32] = arthur.next_bytes()?;
+            event!(
+                round = r,
+                root = %crate::utils::Hex(&new_root),
+                "verifier round root"
+            );
 
             let mut ood_points = vec![F::ZERO; round_params.ood_samples];
             let mut ood_answers = vec![F::ZERO; round_params.ood_samples];
@@ -149,31 +446,41 @@ where
 
             let mut stir_queries_seed = [0u8; 32];
             arthur.fill_challenge_bytes(&mut stir_queries_seed)?;
-            let mut stir_gen = rand_chacha::ChaCha20Rng::from_seed(stir_queries_seed);
-            let folded_domain_size = domain_size / (1 << self.params.folding_factor);
-            let stir_challenges_indexes = utils::dedup(
-                (0..round_params.num_queries).map(|_| stir_gen.gen_range(0..folded_domain_size)),
-            );
-            let stir_challenges_points = stir_challenges_indexes
-                .iter()
-                .map(|index| exp_domain_gen.pow([*index as u64]))
-                .collect();
-
-            if !merkle_proof
-                .verify(
-                    &self.params.leaf_hash_params,
-                    &self.params.two_to_one_params,
-                    &prev_root,
-                    answers.iter().map(|a| a.as_ref()),
-                )
-                .unwrap()
-                || merkle_proof.leaf_indexes != stir_challenges_indexes
+            let stir_challenges_indexes = self.params.query_indexes(
+                &stir_queries_seed,
+                round_params.num_queries,
+                domain.folded_size,
+            )?;
+            event!(round = r, indexes = ?stir_challenges_indexes, "verifier queries");
+            let transcript_opening;
+            let (merkle_proof, answers) = if self.params.openings_in_transcript {
+                transcript_opening = self.read_transcript_opening(arthur, r)?;
+                &transcript_opening
+            } else {
+                whir_proof.0.get(r).ok_or(ProofError::InvalidProof)?
+            };
+            let stir_challenges_points =
+                self.query_points(domain, &stir_challenges_indexes, hints.get(r))?;
+
+            if !self.check_merkle(
+                r,
+                &prev_root,
+                merkle_proof,
+                answers,
+                delegated.as_deref_mut(),
+            )? || merkle_proof.leaf_indexes != self.params.leaf_indexes(&stir_challenges_indexes)
             {
+                event!(round = r, "verifier rejected the Merkle openings");
                 return Err(ProofError::InvalidProof);
             }
 
             if round_params.pow_bits > 0. {
                 arthur.challenge_pow::<PowStrategy>(round_params.pow_bits)?;
+                event!(
+                    round = r,
+                    bits = round_params.pow_bits,
+                    "verifier proof of work"
+                );
             }
 
             let [combination_randomness_gen] = arthur.challenge_scalars()?;
@@ -197,59 +504,75 @@ where
             let new_folding_randomness =
                 MultilinearPoint(sumcheck_rounds.iter().map(|&(_, r)| r).rev().collect());
 
+            let stir_challenges_answers = self.query_cosets(
+                &stir_challenges_indexes,
+                &merkle_proof.leaf_indexes,
+                answers,
+            )?;
             rounds.push(ParsedRound {
                 folding_randomness,
                 ood_points,
                 ood_answers,
                 stir_challenges_indexes,
                 stir_challenges_points,
-                stir_challenges_answers: answers.to_vec(),
+                stir_challenges_answers,
                 combination_randomness,
                 sumcheck_rounds,
-                domain_gen_inv,
+                domain_gen_inv: domain.gen_inv,
+                domain_offset_inv: domain.offset_inv,
             });
 
             folding_randomness = new_folding_randomness;
 
             prev_root = new_root.into();
-            exp_domain_gen = exp_domain_gen * exp_domain_gen;
-            domain_gen_inv = domain_gen_inv * domain_gen_inv;
-            domain_size /= 2;
         }
+        let domain = &self.domains[self.params.n_rounds()];
 
-        let mut final_coefficients = vec![F::ZERO; 1 << self.params.final_sumcheck_rounds];
-        arthur.fill_next_scalars(&mut final_coefficients)?;
-        let final_coefficients = CoefficientList::new(final_coefficients);
+        let mut final_polynomial = vec![F::ZERO; 1 << self.params.final_sumcheck_rounds];
+        arthur.fill_next_scalars(&mut final_polynomial)?;
+        let final_polynomial = FinalPolynomial::new(self.params.final_form, final_polynomial);
 
         // Final queries verify
         let mut queries_seed = [0u8; 32];
         arthur.fill_challenge_bytes(&mut queries_seed)?;
-        let mut final_gen = rand_chacha::ChaCha20Rng::from_seed(queries_seed);
-        let folded_domain_size = domain_size / (1 << self.params.folding_factor);
-        let final_randomness_indexes = utils::dedup(
-            (0..self.params.final_queries).map(|_| final_gen.gen_range(0..folded_domain_size)),
-        );
-        let final_randomness_points = final_randomness_indexes
-            .iter()
-            .map(|index| exp_domain_gen.pow([*index as u64]))
-            .collect();
-
-        let (final_merkle_proof, final_randomness_answers) = &whir_proof.0[whir_proof.0.len() - 1];
-        if !final_merkle_proof
-            .verify(
-                &self.params.leaf_hash_params,
-                &self.params.two_to_one_params,
-                &prev_root,
-                final_randomness_answers.iter().map(|a| a.as_ref()),
-            )
-            .unwrap()
-            || final_merkle_proof.leaf_indexes != final_randomness_indexes
+        let final_randomness_indexes = self.params.query_indexes(
+            &queries_seed,
+            self.params.final_queries,
+            domain.folded_size,
+        )?;
+        event!(indexes = ?final_randomness_indexes, "verifier final queries");
+        let final_randomness_points = self.query_points(
+            domain,
+            &final_randomness_indexes,
+            hints.get(self.params.n_rounds()),
+        )?;
+
+        let transcript_opening;
+        let (final_merkle_proof, final_randomness_answers) = if self.params.openings_in_transcript {
+            transcript_opening = self.read_transcript_opening(arthur, self.params.n_rounds())?;
+            &transcript_opening
+        } else {
+            whir_proof.0.last().ok_or(ProofError::InvalidProof)?
+        };
+        if !self.check_merkle(
+            self.params.n_rounds(),
+            &prev_root,
+            final_merkle_proof,
+            final_randomness_answers,
+            delegated,
+        )? || final_merkle_proof.leaf_indexes
+            != self.params.leaf_indexes(&final_randomness_indexes)
         {
+            event!("verifier rejected the final Merkle openings");
             return Err(ProofError::InvalidProof);
         }
 
         if self.params.final_pow_bits > 0. {
             arthur.challenge_pow::<PowStrategy>(self.params.final_pow_bits)?;
+            event!(
+                bits = self.params.final_pow_bits,
+                "verifier final proof of work"
+            );
         }
 
         let mut final_sumcheck_rounds = Vec::with_capacity(self.params.final_sumcheck_rounds);
@@ -271,21 +594,123 @@ where
                 .collect(),
         );
 
+        let final_randomness_answers = self.query_cosets(
+            &final_randomness_indexes,
+            &final_merkle_proof.leaf_indexes,
+            final_randomness_answers,
+        )?;
         Ok(ParsedProof {
             initial_combination_randomness,
             initial_sumcheck_rounds: sumcheck_rounds,
             rounds,
-            final_domain_gen_inv: domain_gen_inv,
+            final_domain_gen_inv: domain.gen_inv,
+            final_domain_offset_inv: domain.offset_inv,
             final_folding_randomness: folding_randomness,
             final_randomness_indexes,
             final_randomness_points,
-            final_randomness_answers: final_randomness_answers.to_vec(),
+            final_randomness_answers,
             final_sumcheck_rounds,
             final_sumcheck_randomness,
-            final_coefficients,
+            final_polynomial,
+            coset_offsets_inv: hints.clone(),
         })
     }
 
+    // The query points `exp_offset * exp_gen^index` of `indexes`. With the inverses of the
+    // offsets of their cosets from the hints, each is the square of the offset, and one
+    // multiplication checks its inverse. The indexes are sorted, so the offsets are those of the
+    // previous index times a power of the generator by the gap between them.
+    fn query_points(
+        &self,
+        domain: &RoundDomain<F>,
+        indexes: &[usize],
+        coset_offsets_inv: Option<&Vec<F>>,
+    ) -> ProofResult<Vec<F>> {
+        let Some(coset_offsets_inv) = coset_offsets_inv else {
+            return Ok(indexes
+                .iter()
+                .map(|index| domain.exp_offset * domain.exp_gen.pow([*index as u64]))
+                .collect());
+        };
+        if coset_offsets_inv.len() != indexes.len() {
+            return Err(ProofError::InvalidProof);
+        }
+        let (mut previous, mut power) = (0, F::ONE);
+        indexes
+            .iter()
+            .zip(coset_offsets_inv)
+            .map(|(&index, &inverse)| {
+                power *= domain.gen.pow([(index - previous) as u64]);
+                previous = index;
+                let mut point = domain.offset * power;
+                if point * inverse != F::ONE {
+                    event!(index, "verifier rejected a hint");
+                    return Err(ProofError::InvalidProof);
+                }
+                for _ in 0..self.params.folding_factor {
+                    point.square_in_place();
+                }
+                Ok(point)
+            })
+            .collect()
+    }
+
+    // Checks the opening of the leaves `answers` of the tree `tree` against `root`, or only
+    // hashes them and leaves the check to the caller in `delegated`.
+    fn check_merkle(
+        &self,
+        tree: usize,
+        root: &MerkleConfig::InnerDigest,
+        merkle_proof: &MultiPath<MerkleConfig>,
+        answers: &[Vec<F>],
+        delegated: Option<&mut Vec<MerkleCheck<MerkleConfig>>>,
+    ) -> ProofResult<bool> {
+        let (leaf_hash_params, two_to_one_params) = self.params.merkle_params(tree);
+        let Some(delegated) = delegated else {
+            return Ok(phase!(
+                merkle_verify,
+                merkle_proof
+                    .verify(
+                        leaf_hash_params,
+                        two_to_one_params,
+                        root,
+                        answers.iter().map(|a| a.as_ref()),
+                    )
+                    .unwrap_or(false)
+            ));
+        };
+        if answers.len() != merkle_proof.leaf_indexes.len() {
+            return Ok(false);
+        }
+        let leaf_hashes = answers
+            .iter()
+            .map(|leaf| MerkleConfig::LeafHash::evaluate(leaf_hash_params, leaf.as_slice()))
+            .collect::<Result<_, _>>()
+            .map_err(|_| ProofError::InvalidProof)?;
+        delegated.push(MerkleCheck {
+            tree,
+            root: root.clone(),
+            leaf_indexes: merkle_proof.leaf_indexes.clone(),
+            leaf_hashes,
+            path: merkle_proof.clone(),
+        });
+        Ok(true)
+    }
+
+    // The queried cosets `indexes`, cut out of the leaves opened for them.
+    fn query_cosets(
+        &self,
+        indexes: &[usize],
+        leaf_indexes: &[usize],
+        leaves: &[Vec<F>],
+    ) -> ProofResult<Vec<Vec<F>>> {
+        let cosets = self
+            .params
+            .query_cosets(indexes, leaf_indexes, leaves)
+            .ok_or(ProofError::InvalidProof)?;
+        Ok(cosets.into_iter().map(<[F]>::to_vec).collect())
+    }
+
     fn compute_v_poly(
         &self,
         parsed_commitment: &ParsedCommitment<F, MerkleConfig::InnerDigest>,
@@ -303,35 +728,22 @@ where
                 .collect(),
         );
 
-        let mut value = parsed_commitment
-            .ood_points
-            .iter()
-            .map(|ood_point| MultilinearPoint::expand_from_univariate(*ood_point, num_variables))
-            .chain(statement.points.clone())
-            .zip(&proof.initial_combination_randomness)
-            .map(|(point, randomness)| *randomness * eq_poly_outside(&point, &folding_randomness))
-            .sum();
+        let mut value = compute_statement_weight(
+            &parsed_commitment.ood_points,
+            statement,
+            &proof.initial_combination_randomness,
+            &folding_randomness,
+        );
 
         for round_proof in &proof.rounds {
             num_variables -= self.params.folding_factor;
             folding_randomness = MultilinearPoint(folding_randomness.0[..num_variables].to_vec());
 
-            let ood_points = &round_proof.ood_points;
-            let stir_challenges_points = &round_proof.stir_challenges_points;
-            let stir_challenges: Vec<_> = ood_points
+            let sum_of_claims: F = round_proof
+                .ood_points
                 .iter()
-                .chain(stir_challenges_points)
-                .cloned()
-                .map(|univariate| {
-                    MultilinearPoint::expand_from_univariate(univariate, num_variables)
-                    // TODO:
-                    // Maybe refactor outside
-                })
-                .collect();
-
-            let sum_of_claims: F = stir_challenges
-                .into_iter()
-                .map(|point| eq_poly_outside(&point, &folding_randomness))
+                .chain(&round_proof.stir_challenges_points)
+                .map(|univariate| eq_poly_univariate(&folding_randomness, *univariate))
                 .zip(&round_proof.combination_randomness)
                 .map(|(point, rand)| point * rand)
                 .sum();
@@ -343,135 +755,145 @@ where
     }
 
     fn compute_folds(&self, parsed: &ParsedProof<F>) -> Vec<Vec<F>> {
-        match self.params.fold_optimisation {
-            FoldType::Naive => self.compute_folds_full(parsed),
-            FoldType::ProverHelps => self.compute_folds_helped(parsed),
-        }
+        let rounds = parsed.rounds.iter().map(|round| {
+            (
+                &round.folding_randomness,
+                &round.stir_challenges_indexes,
+                &round.stir_challenges_answers,
+            )
+        });
+        let final_round = iter::once((
+            &parsed.final_folding_randomness,
+            &parsed.final_randomness_indexes,
+            &parsed.final_randomness_answers,
+        ));
+        rounds
+            .chain(final_round)
+            .zip(&self.domains)
+            .enumerate()
+            .map(
+                |(opening, ((folding_randomness, indexes, answers), domain))| {
+                    let domain = FoldDomain {
+                        offset_inv: domain.offset_inv,
+                        gen_inv: domain.gen_inv,
+                        coset_generator_inv: domain.coset_generator_inv,
+                        two_inv: self.two_inv,
+                        folding_factor: self.params.folding_factor,
+                        fold_type: self.params.fold_optimisation,
+                    };
+                    match parsed.coset_offsets_inv.get(opening) {
+                        Some(inverses) => compute_folds_with_inverses(
+                            &domain,
+                            folding_randomness,
+                            inverses,
+                            answers,
+                        ),
+                        None => compute_folds(&domain, folding_randomness, indexes, answers),
+                    }
+                },
+            )
+            .collect()
     }
 
-    fn compute_folds_full(&self, parsed: &ParsedProof<F>) -> Vec<Vec<F>> {
-        let mut domain_size = self.params.starting_domain.backing_domain.size();
-        let coset_domain_size = 1 << self.params.folding_factor;
-
-        let mut result = Vec::new();
-
-        for round in &parsed.rounds {
-            // This is such that coset_generator^coset_domain_size = F::ONE
-            //let _coset_generator = domain_gen.pow(&[(domain_size / coset_domain_size) as u64]);
-            let coset_generator_inv = round
-                .domain_gen_inv
-                .pow([(domain_size / coset_domain_size) as u64]);
-
-            let evaluations: Vec<_> = round
-                .stir_challenges_indexes
-                .iter()
-                .zip(&round.stir_challenges_answers)
-                .map(|(index, answers)| {
-                    // The coset is w^index * <w_coset_generator>
-                    //let _coset_offset = domain_gen.pow(&[*index as u64]);
-                    let coset_offset_inv = round.domain_gen_inv.pow([*index as u64]);
-
-                    compute_fold(
-                        answers,
-                        &round.folding_randomness.0,
-                        coset_offset_inv,
-                        coset_generator_inv,
-                        self.two_inv,
-                        self.params.folding_factor,
-                    )
-                })
-                .collect();
-            result.push(evaluations);
-            domain_size /= 2;
-        }
-
-        let domain_gen_inv = parsed.final_domain_gen_inv;
-
-        // Final round
-        let coset_generator_inv = domain_gen_inv.pow([(domain_size / coset_domain_size) as u64]);
-        let evaluations: Vec<_> = parsed
-            .final_randomness_indexes
-            .iter()
-            .zip(&parsed.final_randomness_answers)
-            .map(|(index, answers)| {
-                // The coset is w^index * <w_coset_generator>
-                //let _coset_offset = domain_gen.pow(&[*index as u64]);
-                let coset_offset_inv = domain_gen_inv.pow([*index as u64]);
-
-                compute_fold(
-                    answers,
-                    &parsed.final_folding_randomness.0,
-                    coset_offset_inv,
-                    coset_generator_inv,
-                    self.two_inv,
-                    self.params.folding_factor,
-                )
-            })
-            .collect();
-        result.push(evaluations);
-
-        result
+    pub fn verify(
+        &self,
+        arthur: &mut Arthur,
+        statement: &Statement<F>,
+        whir_proof: &WhirProof<MerkleConfig, F>,
+    ) -> ProofResult<()> {
+        let parsed_commitment = self.parse_commitment(arthur)?;
+        self.verify_with_commitment(arthur, &parsed_commitment, statement, whir_proof)
     }
 
-    fn compute_folds_helped(&self, parsed: &ParsedProof<F>) -> Vec<Vec<F>> {
-        let mut result = Vec::new();
-
-        for round in &parsed.rounds {
-            let evaluations: Vec<_> = round
-                .stir_challenges_answers
-                .iter()
-                .map(|answers| {
-                    CoefficientList::new(answers.to_vec()).evaluate(&round.folding_randomness)
-                })
-                .collect();
-            result.push(evaluations);
-        }
-
-        // Final round
-        let evaluations: Vec<_> = parsed
-            .final_randomness_answers
-            .iter()
-            .map(|answers| {
-                CoefficientList::new(answers.to_vec()).evaluate(&parsed.final_folding_randomness)
-            })
-            .collect();
-        result.push(evaluations);
-
-        result
+    /// Verifies the proof against a commitment previously read with `parse_commitment`.
+    pub fn verify_with_commitment(
+        &self,
+        arthur: &mut Arthur,
+        parsed_commitment: &ParsedCommitment<F, MerkleConfig::InnerDigest>,
+        statement: &Statement<F>,
+        whir_proof: &WhirProof<MerkleConfig, F>,
+    ) -> ProofResult<()> {
+        statement.validate(&self.params)?;
+        let statement = self.params.mask_statement(statement)?;
+        self.verify_masked(arthur, parsed_commitment, &statement, whir_proof)
     }
 
-    pub fn verify(
+    /// Same as `verify_with_commitment`, for a statement on the committed polynomial, which in
+    /// ZK mode is the masked one.
+    pub(crate) fn verify_masked(
         &self,
         arthur: &mut Arthur,
+        parsed_commitment: &ParsedCommitment<F, MerkleConfig::InnerDigest>,
         statement: &Statement<F>,
         whir_proof: &WhirProof<MerkleConfig, F>,
     ) -> ProofResult<()> {
         // We first do a pass in which we rederive all the FS challenges
         // Then we will check the algebraic part (so to optimise inversions)
+        let parsed = self.parse_proof(arthur, parsed_commitment, statement, whir_proof, None)?;
+        self.check_proof(parsed_commitment, statement, &parsed)
+    }
+
+    /// Same as `verify`, but without the Merkle verification of the openings, e.g. for an
+    /// accelerator or an on-chain precompile to perform it: the proof only verifies if it
+    /// passes, and every returned `MerkleCheck` holds. The indexes of the opened leaves are still
+    /// checked against the queries.
+    pub fn verify_delegating_merkle(
+        &self,
+        arthur: &mut Arthur,
+        statement: &Statement<F>,
+        whir_proof: &WhirProof<MerkleConfig, F>,
+    ) -> ProofResult<Vec<MerkleCheck<MerkleConfig>>> {
         let parsed_commitment = self.parse_commitment(arthur)?;
-        let parsed = self.parse_proof(arthur, &parsed_commitment, statement, whir_proof)?;
+        statement.validate(&self.params)?;
+        let statement = self.params.mask_statement(statement)?;
+        let mut checks = Vec::with_capacity(self.params.n_rounds() + 1);
+        let parsed = self.parse_proof(
+            arthur,
+            &parsed_commitment,
+            &statement,
+            whir_proof,
+            Some(&mut checks),
+        )?;
+        self.check_proof(&parsed_commitment, &statement, &parsed)?;
+        Ok(checks)
+    }
 
-        let computed_folds = self.compute_folds(&parsed);
+    // The algebraic checks of a proof whose challenges are already derived.
+    pub(crate) fn check_proof(
+        &self,
+        parsed_commitment: &ParsedCommitment<F, MerkleConfig::InnerDigest>,
+        statement: &Statement<F>,
+        parsed: &ParsedProof<F>,
+    ) -> ProofResult<()> {
+        let computed_folds = phase!(compute_folds, self.compute_folds(parsed));
 
         // Check the first polynomial
-        let (mut prev_poly, mut randomness) = parsed.initial_sumcheck_rounds[0].clone();
+        let ((first_poly, first_randomness), initial_sumcheck_rounds) = parsed
+            .initial_sumcheck_rounds
+            .split_first()
+            .ok_or(ProofError::InvalidProof)?;
+        let mut prev_poly = first_poly.clone();
+        let mut randomness = *first_randomness;
         if prev_poly.sum_over_hypercube()
             != parsed_commitment
                 .ood_answers
                 .iter()
                 .copied()
                 .chain(statement.evaluations.clone())
+                .chain(statement.linear_claims.iter().map(|claim| claim.sum))
                 .zip(&parsed.initial_combination_randomness)
                 .map(|(ans, rand)| ans * rand)
                 .sum()
         {
+            event!("verifier rejected the initial sumcheck");
             return Err(ProofError::InvalidProof);
         }
 
         // Check the rest of the rounds
-        for (sumcheck_poly, new_randomness) in &parsed.initial_sumcheck_rounds[1..] {
+        for (sumcheck_poly, new_randomness) in initial_sumcheck_rounds {
             if sumcheck_poly.sum_over_hypercube() != prev_poly.evaluate_at_point(&randomness.into())
             {
+                event!("verifier rejected the initial sumcheck");
                 return Err(ProofError::InvalidProof);
             }
             prev_poly = sumcheck_poly.clone();
@@ -479,7 +901,10 @@ where
         }
 
         for (round, folds) in parsed.rounds.iter().zip(&computed_folds) {
-            let (sumcheck_poly, new_randomness) = &round.sumcheck_rounds[0].clone();
+            let ((sumcheck_poly, new_randomness), sumcheck_rounds) = round
+                .sumcheck_rounds
+                .split_first()
+                .ok_or(ProofError::InvalidProof)?;
 
             let values = round.ood_answers.iter().copied().chain(folds.clone());
 
@@ -490,6 +915,7 @@ where
                     .sum::<F>();
 
             if sumcheck_poly.sum_over_hypercube() != claimed_sum {
+                event!("verifier rejected the sumcheck of a round");
                 return Err(ProofError::InvalidProof);
             }
 
@@ -497,10 +923,11 @@ where
             randomness = *new_randomness;
 
             // Check the rest of the round
-            for (sumcheck_poly, new_randomness) in &round.sumcheck_rounds[1..] {
+            for (sumcheck_poly, new_randomness) in sumcheck_rounds {
                 if sumcheck_poly.sum_over_hypercube()
                     != prev_poly.evaluate_at_point(&randomness.into())
                 {
+                    event!("verifier rejected the sumcheck of a round");
                     return Err(ProofError::InvalidProof);
                 }
                 prev_poly = sumcheck_poly.clone();
@@ -509,24 +936,27 @@ where
         }
 
         // Check the foldings computed from the proof match the evaluations of the polynomial
-        let final_folds = &computed_folds[computed_folds.len() - 1];
+        let final_folds = computed_folds.last().ok_or(ProofError::InvalidProof)?;
         let final_evaluations = parsed
-            .final_coefficients
+            .final_polynomial
             .evaluate_at_univariate(&parsed.final_randomness_points);
         if !final_folds
             .iter()
             .zip(final_evaluations)
             .all(|(&fold, eval)| fold == eval)
         {
+            event!("verifier rejected the final folds");
             return Err(ProofError::InvalidProof);
         }
 
         // Check the final sumchecks
-        if self.params.final_sumcheck_rounds > 0 {
-            let (sumcheck_poly, new_randomness) = &parsed.final_sumcheck_rounds[0].clone();
+        if let Some(((sumcheck_poly, new_randomness), final_sumcheck_rounds)) =
+            parsed.final_sumcheck_rounds.split_first()
+        {
             let claimed_sum = prev_poly.evaluate_at_point(&randomness.into());
 
             if sumcheck_poly.sum_over_hypercube() != claimed_sum {
+                event!("verifier rejected the final sumcheck");
                 return Err(ProofError::InvalidProof);
             }
 
@@ -534,10 +964,11 @@ where
             randomness = *new_randomness;
 
             // Check the rest of the round
-            for (sumcheck_poly, new_randomness) in &parsed.final_sumcheck_rounds[1..] {
+            for (sumcheck_poly, new_randomness) in final_sumcheck_rounds {
                 if sumcheck_poly.sum_over_hypercube()
                     != prev_poly.evaluate_at_point(&randomness.into())
                 {
+                    event!("verifier rejected the final sumcheck");
                     return Err(ProofError::InvalidProof);
                 }
                 prev_poly = sumcheck_poly.clone();
@@ -546,17 +977,469 @@ where
         }
 
         // Check the final sumcheck evaluation
-        let evaluation_of_v_poly = self.compute_v_poly(&parsed_commitment, statement, &parsed);
+        let evaluation_of_v_poly = phase!(
+            compute_v_poly,
+            self.compute_v_poly(parsed_commitment, statement, parsed)
+        );
 
         if prev_poly.evaluate_at_point(&randomness.into())
             != evaluation_of_v_poly
                 * parsed
-                    .final_coefficients
+                    .final_polynomial
                     .evaluate(&parsed.final_sumcheck_randomness)
         {
+            event!("verifier rejected the final evaluation");
             return Err(ProofError::InvalidProof);
         }
 
         Ok(())
     }
+
+    /// Verifies the proof, then returns it with the hints which make its verification cheaper,
+    /// see `whir::hints`. There are none with `FoldType::ProverHelps`.
+    pub fn hint_proof(
+        &self,
+        arthur: &mut Arthur,
+        statement: &Statement<F>,
+        whir_proof: &WhirProof<MerkleConfig, F>,
+    ) -> ProofResult<WhirProof<MerkleConfig, F>> {
+        let parsed_commitment = self.parse_commitment(arthur)?;
+        statement.validate(&self.params)?;
+        let statement = self.params.mask_statement(statement)?;
+        let parsed = self.parse_proof(arthur, &parsed_commitment, &statement, whir_proof, None)?;
+        self.check_proof(&parsed_commitment, &statement, &parsed)?;
+
+        let mut hinted = whir_proof.clone();
+        hinted.1 = ProofHints::default();
+        if matches!(self.params.fold_optimisation, FoldType::Naive) {
+            let indexes = parsed
+                .rounds
+                .iter()
+                .map(|round| &round.stir_challenges_indexes)
+                .chain(iter::once(&parsed.final_randomness_indexes));
+            hinted.1.coset_offsets_inv = indexes
+                .zip(&self.domains)
+                .map(|(indexes, domain)| {
+                    let mut inverses: Vec<F> = indexes
+                        .iter()
+                        .map(|&index| domain.offset * domain.gen.pow([index as u64]))
+                        .collect();
+                    batch_inversion(&mut inverses);
+                    inverses
+                })
+                .collect();
+        }
+        Ok(hinted)
+    }
+
+    /// Verifies a proof packed by `proof_to_bytes`, replaying its transcript against `io`.
+    pub fn verify_bytes(
+        &self,
+        io: &IOPattern,
+        statement: &Statement<F>,
+        proof_bytes: &[u8],
+    ) -> ProofResult<()> {
+        let (transcript, whir_proof) = proof_from_bytes_for_config(proof_bytes, &self.params)
+            .map_err(|_| ProofError::SerializationError)?;
+        let mut arthur = io.to_arthur(&transcript);
+        self.verify(&mut arthur, statement, &whir_proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use ark_crypto_primitives::{crh::CRHScheme, merkle_tree::MultiPath};
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::UniformRand;
+    use nimue::ProofResult;
+    use rand::Rng;
+
+    use crate::{
+        crypto::merkle_tree::blake3 as merkle_tree,
+        parameters::{FoldType, MultivariateParameters, WhirParameters},
+        poly_utils::{
+            coeffs::CoefficientList, evals::EvaluationsList, hypercube::BinaryHypercubePoint,
+            MultilinearPoint,
+        },
+        whir::{
+            committer::Committer,
+            fixtures::{config, io_pattern, whir_parameters, MerkleConfig, PowStrategy, F},
+            hints::HINTED_PROOF_FORMAT_VERSION,
+            parameters::WhirConfig,
+            proof_to_bytes,
+            prover::Prover,
+            verifier::{compute_statement_weight, Verifier},
+            ClaimType, LinearClaim, Statement, WhirProof,
+        },
+    };
+
+    type Openings = Vec<(MultiPath<MerkleConfig>, Vec<Vec<F>>)>;
+
+    // The verifier runs on untrusted inputs: whatever they are, it must return.
+    fn assert_returns(verify: impl FnOnce() -> ProofResult<()>) -> bool {
+        catch_unwind(AssertUnwindSafe(verify))
+            .expect("the verifier panicked")
+            .is_ok()
+    }
+
+    #[test]
+    fn test_ood_statement() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        for zk in [false, true] {
+            let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+                zk,
+                ..whir_parameters((leaf_hash_params.clone(), two_to_one_params.clone()))
+            };
+            let params = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params,
+            )
+            .unwrap();
+            let io = io_pattern(&params);
+            let polynomial = CoefficientList::new((0..1 << num_variables).map(F::from).collect());
+            let point = MultilinearPoint::rand(&mut rng, num_variables);
+            let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit(&mut merlin, polynomial.clone())
+                .unwrap();
+            let proof = Prover(params.clone())
+                .prove(&mut merlin, statement.clone(), witness)
+                .unwrap();
+
+            let verifier = Verifier::new(params.clone());
+            let mut arthur = io.to_arthur(merlin.transcript());
+            let commitment = verifier.parse_commitment(&mut arthur).unwrap();
+            assert_eq!(commitment.num_ood_samples(), params.committment_ood_samples);
+            assert_eq!(commitment.ood_answers().len(), commitment.num_ood_samples());
+            assert!(verifier
+                .verify_with_commitment(&mut arthur, &commitment, &statement, &proof)
+                .is_ok());
+
+            let Some(ood_statement) = verifier.ood_statement(&commitment) else {
+                assert!(zk);
+                continue;
+            };
+            assert!(!zk);
+            for (point, &answer) in ood_statement.points.iter().zip(&ood_statement.evaluations) {
+                assert_eq!(polynomial.evaluate(point), answer);
+            }
+            // A statement of the configuration, e.g. to merge with the claims of an outer proof
+            assert!(ood_statement.validate(&params).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_verifier_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Verifier<F, MerkleConfig, PowStrategy>>();
+    }
+
+    #[test]
+    fn test_statement_weight() {
+        let num_variables = 4;
+        let mut rng = ark_std::test_rng();
+        let polynomial =
+            CoefficientList::new((0..1 << num_variables).map(|_| F::rand(&mut rng)).collect());
+        let evals = EvaluationsList::from(polynomial.clone());
+        let mut next_rows = evals.evals().to_vec();
+        next_rows.rotate_left(1);
+        let next_rows = EvaluationsList::new(next_rows);
+
+        let ood_points = [F::rand(&mut rng), F::rand(&mut rng)];
+        let (point, rotated_point) = (
+            MultilinearPoint::rand(&mut rng, num_variables),
+            MultilinearPoint::rand(&mut rng, num_variables),
+        );
+        let weights =
+            EvaluationsList::new((0..1 << num_variables).map(|_| F::rand(&mut rng)).collect());
+        let sum = weights
+            .evals()
+            .iter()
+            .zip(evals.evals())
+            .map(|(w, e)| *w * e)
+            .sum();
+        let statement = Statement {
+            evaluations: vec![
+                polynomial.evaluate(&point),
+                next_rows.evaluate(&rotated_point),
+            ],
+            points: vec![point, rotated_point],
+            claim_types: vec![ClaimType::Evaluation, ClaimType::Rotated],
+            linear_claims: vec![LinearClaim { weights, sum }],
+        };
+        let claimed = ood_points
+            .iter()
+            .map(|&z| {
+                polynomial.evaluate(&MultilinearPoint::expand_from_univariate(z, num_variables))
+            })
+            .chain(statement.evaluations.iter().copied())
+            .chain([sum]);
+        let randomness: Vec<F> = (0..5).map(|_| F::rand(&mut rng)).collect();
+
+        // The weight is that of the combined claim: summed against the polynomial over the
+        // hypercube, it gives the combination of the claimed values
+        let weighted_sum: F = (0..1 << num_variables)
+            .map(|b| {
+                let b = MultilinearPoint::from_binary_hypercube_point(
+                    BinaryHypercubePoint(b),
+                    num_variables,
+                );
+                compute_statement_weight(&ood_points, &statement, &randomness, &b)
+                    * evals.evaluate(&b)
+            })
+            .sum();
+        let combination: F = claimed.zip(&randomness).map(|(v, r)| v * r).sum();
+        assert_eq!(weighted_sum, combination);
+    }
+
+    #[test]
+    fn test_verifier_never_panics() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        for fold_optimisation in [FoldType::Naive, FoldType::ProverHelps] {
+            let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+                fold_optimisation,
+                ..whir_parameters((leaf_hash_params.clone(), two_to_one_params.clone()))
+            };
+            let params = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params,
+            )
+            .unwrap();
+            let io = io_pattern(&params);
+
+            let polynomial =
+                CoefficientList::new((0..1 << num_variables).map(|_| F::rand(&mut rng)).collect());
+            let point = MultilinearPoint::rand(&mut rng, num_variables);
+            let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit(&mut merlin, polynomial)
+                .unwrap();
+            let proof = Prover(params.clone())
+                .prove(&mut merlin, statement.clone(), witness)
+                .unwrap();
+            let transcript = merlin.transcript().to_vec();
+            let verifier = Verifier::new(params);
+            let verify = |transcript: &[u8], statement: &Statement<F>, proof| {
+                assert_returns(|| verifier.verify(&mut io.to_arthur(transcript), statement, proof))
+            };
+            assert!(verify(&transcript, &statement, &proof));
+
+            // Transcripts
+            for len in 0..transcript.len() {
+                assert!(!verify(&transcript[..len], &statement, &proof));
+            }
+            for _ in 0..64 {
+                let mut mutated = transcript.clone();
+                let i = rng.gen_range(0..mutated.len());
+                mutated[i] ^= 1 << rng.gen_range(0..8);
+                assert!(!verify(&mutated, &statement, &proof));
+            }
+
+            // Proofs
+            let mutations: [fn(&mut Openings, usize); 9] = [
+                |openings, r| {
+                    openings.remove(r);
+                },
+                |openings, r| {
+                    let opening = openings[r].clone();
+                    openings.insert(r, opening);
+                },
+                |openings, r| {
+                    openings[r].1.pop();
+                },
+                |openings, r| {
+                    let answer = openings[r].1[0].clone();
+                    openings[r].1.push(answer);
+                },
+                |openings, r| {
+                    openings[r].1[0].pop();
+                },
+                |openings, r| openings[r].0.leaf_indexes[0] += 1,
+                |openings, r| {
+                    openings[r].0.leaf_siblings_hashes.pop();
+                },
+                |openings, r| {
+                    let last = openings[r].0.auth_paths_prefix_lenghts.len() - 1;
+                    openings[r].0.auth_paths_prefix_lenghts[last] = usize::MAX;
+                },
+                |openings, r| {
+                    openings[r].0.auth_paths_suffixes[0].clear();
+                },
+            ];
+            for r in 0..proof.0.len() {
+                for mutation in mutations {
+                    let mut mutated = proof.clone();
+                    mutation(&mut mutated.0, r);
+                    assert!(!verify(&transcript, &statement, &mutated));
+                }
+            }
+
+            // Statements
+            let mut wrong_statement = statement.clone();
+            wrong_statement.evaluations.push(F::from(1));
+            assert!(!verify(&transcript, &wrong_statement, &proof));
+            let wrong_statement = Statement::new(
+                vec![MultilinearPoint::rand(&mut rng, num_variables + 1)],
+                statement.evaluations.clone(),
+            );
+            assert!(!verify(&transcript, &wrong_statement, &proof));
+
+            // Packed proofs
+            let proof_bytes = proof_to_bytes(&transcript, &proof).unwrap();
+            for len in 0..proof_bytes.len() {
+                assert!(!assert_returns(|| verifier.verify_bytes(
+                    &io,
+                    &statement,
+                    &proof_bytes[..len]
+                )));
+            }
+            let garbage: Vec<u8> = (0..proof_bytes.len()).map(|_| rng.gen()).collect();
+            assert!(!assert_returns(
+                || verifier.verify_bytes(&io, &statement, &garbage)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_delegated_merkle() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        let params = config(num_variables);
+        let io = io_pattern(&params);
+        let polynomial =
+            CoefficientList::new((0..1 << num_variables).map(|_| F::rand(&mut rng)).collect());
+        let point = MultilinearPoint::rand(&mut rng, num_variables);
+        let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial)
+            .unwrap();
+        let proof = Prover(params.clone())
+            .prove(&mut merlin, statement.clone(), witness)
+            .unwrap();
+        let transcript = merlin.transcript();
+        let verifier = Verifier::new(params.clone());
+
+        let checks = verifier
+            .verify_delegating_merkle(&mut io.to_arthur(transcript), &statement, &proof)
+            .unwrap();
+        assert_eq!(checks.len(), params.n_rounds() + 1);
+        assert_eq!(checks[0].root.as_ref(), &transcript[..32]);
+        for (tree, (check, (path, leaves))) in checks.iter().zip(&proof.0).enumerate() {
+            assert_eq!(check.tree, tree);
+            let (leaf_hash_params, two_to_one_params) = params.merkle_params(tree);
+            assert!(check
+                .path
+                .verify(
+                    leaf_hash_params,
+                    two_to_one_params,
+                    &check.root,
+                    leaves.iter().map(|leaf| leaf.as_slice())
+                )
+                .unwrap());
+            let triples: Vec<_> = check.triples().collect();
+            assert_eq!(triples.len(), path.leaf_indexes.len());
+            for ((root, index, hash), leaf) in triples.into_iter().zip(leaves) {
+                assert_eq!(root, &check.root);
+                assert!(path.leaf_indexes.contains(&index));
+                assert_eq!(
+                    *hash,
+                    merkle_tree::LeafH::<F>::evaluate(leaf_hash_params, leaf.as_slice()).unwrap()
+                );
+            }
+        }
+
+        // A broken authentication path is left to the caller
+        let mut broken = proof.clone();
+        broken.0[0].0.leaf_siblings_hashes[0] = [0; 32].into();
+        assert!(verifier
+            .verify(&mut io.to_arthur(transcript), &statement, &broken)
+            .is_err());
+        let delegated = verifier
+            .verify_delegating_merkle(&mut io.to_arthur(transcript), &statement, &broken)
+            .unwrap();
+        assert_eq!(delegated[0].leaf_hashes, checks[0].leaf_hashes);
+
+        // The field arithmetic is still checked
+        let mut wrong_statement = statement.clone();
+        wrong_statement.evaluations[0] += F::from(1);
+        assert!(verifier
+            .verify_delegating_merkle(&mut io.to_arthur(transcript), &wrong_statement, &proof)
+            .is_err());
+    }
+
+    #[test]
+    fn test_proof_hints() {
+        let num_variables = 6;
+        let mut rng = ark_std::test_rng();
+        for fold_optimisation in [FoldType::Naive, FoldType::ProverHelps] {
+            let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+            let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+                fold_optimisation,
+                ..whir_parameters((leaf_hash_params, two_to_one_params))
+            };
+            let params = WhirConfig::<F, MerkleConfig, PowStrategy>::try_new(
+                MultivariateParameters::new(num_variables),
+                whir_params,
+            )
+            .unwrap();
+            let io = io_pattern(&params);
+            let polynomial =
+                CoefficientList::new((0..1 << num_variables).map(|_| F::rand(&mut rng)).collect());
+            let point = MultilinearPoint::rand(&mut rng, num_variables);
+            let statement = Statement::new(vec![point.clone()], vec![polynomial.evaluate(&point)]);
+            let mut merlin = io.to_merlin();
+            let witness = Committer::new(params.clone())
+                .commit(&mut merlin, polynomial)
+                .unwrap();
+            let proof = Prover(params.clone())
+                .prove(&mut merlin, statement.clone(), witness)
+                .unwrap();
+            let transcript = merlin.transcript();
+            let verifier = Verifier::new(params.clone());
+
+            let hinted = verifier
+                .hint_proof(&mut io.to_arthur(transcript), &statement, &proof)
+                .unwrap();
+            if matches!(fold_optimisation, FoldType::ProverHelps) {
+                assert!(hinted.1.is_empty());
+                continue;
+            }
+            assert_eq!(hinted.1.coset_offsets_inv.len(), params.n_rounds() + 1);
+            assert!(verifier
+                .verify(&mut io.to_arthur(transcript), &statement, &hinted)
+                .is_ok());
+
+            // The hints are serialized after the openings, with their own version
+            let mut bytes = vec![];
+            hinted.serialize_compressed(&mut bytes).unwrap();
+            assert_eq!(bytes[..4], HINTED_PROOF_FORMAT_VERSION.to_le_bytes());
+            let read = WhirProof::deserialize_for_config(&bytes[..], &params).unwrap();
+            assert_eq!(read.1, hinted.1);
+            assert!(verifier
+                .verify(&mut io.to_arthur(transcript), &statement, &read)
+                .is_ok());
+
+            // A wrong inverse, or hints for only some of the openings, are rejected
+            let mut wrong = hinted.clone();
+            wrong.1.coset_offsets_inv[1][0] += F::from(1);
+            assert!(verifier
+                .verify(&mut io.to_arthur(transcript), &statement, &wrong)
+                .is_err());
+            let mut partial = hinted.clone();
+            partial.1.coset_offsets_inv.pop();
+            assert!(verifier
+                .verify(&mut io.to_arthur(transcript), &statement, &partial)
+                .is_err());
+        }
+    }
 }
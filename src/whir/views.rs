@@ -0,0 +1,351 @@
+//! Consistency of a WHIR commitment with a plain Merkle commitment to the evaluations of the
+//! same polynomial over the hypercube, for subsystems that read the evaluation table directly.
+//!
+//! The table is committed in a Merkle tree whose leaves hold the evaluations at `leaf_size`
+//! consecutive points of the hypercube, in the order of `EvaluationsList`. Once both roots are in
+//! the transcript, the verifier samples `num_checks` leaves, the prover opens them, and a WHIR
+//! proof shows that the committed polynomial evaluates to the opened values at their points. A
+//! table that differs from the polynomial on a fraction `delta` of its leaves passes with
+//! probability at most `(1 - delta)^num_checks`: this bounds how far the two views can diverge,
+//! it does not make them equal.
+
+use ark_crypto_primitives::merkle_tree::{Config, MerkleTree, MultiPath};
+use ark_ff::FftField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use nimue::{
+    plugins::ark::{FieldChallenges, FieldReader},
+    Arthur, ByteChallenges, ByteIOPattern, ByteReader, ByteWriter, IOPattern, Merlin, ProofError,
+    ProofResult,
+};
+
+use super::{
+    committer::Witness, parameters::WhirConfig, prover::Prover, queries::query_indexes,
+    verifier::Verifier, Statement, WhirProof,
+};
+use crate::{
+    errors::{WhirError, WhirResult},
+    poly_utils::{evals::EvaluationsList, hypercube::BinaryHypercubePoint, MultilinearPoint},
+};
+
+pub trait ViewsIOPattern {
+    /// The root of the evaluation table and the seed of the checked leaves, between
+    /// `commit_statement` and `add_whir_proof`.
+    fn add_views_consistency(self) -> Self;
+}
+
+impl ViewsIOPattern for IOPattern
+where
+    IOPattern: ByteIOPattern,
+{
+    fn add_views_consistency(self) -> Self {
+        self.add_bytes(32, "evaluations_root")
+            .challenge_bytes(32, "consistency_seed")
+    }
+}
+
+/// The Merkle commitment to the evaluation table.
+pub struct EvaluationsTree<F, MerkleConfig>
+where
+    MerkleConfig: Config,
+{
+    pub merkle_tree: MerkleTree<MerkleConfig>,
+    pub evaluations: Vec<F>,
+    pub leaf_size: usize,
+}
+
+impl<F, MerkleConfig> EvaluationsTree<F, MerkleConfig>
+where
+    MerkleConfig: Config,
+{
+    pub fn root(&self) -> MerkleConfig::InnerDigest {
+        self.merkle_tree.root()
+    }
+
+    pub fn num_leaves(&self) -> usize {
+        self.evaluations.len() / self.leaf_size
+    }
+}
+
+/// The openings of the checked leaves of the table, and the proof of the evaluations of the
+/// committed polynomial at their points.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ConsistencyProof<MerkleConfig, F>
+where
+    MerkleConfig: Config<Leaf = [F]>,
+    F: Sized + Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    pub merkle_proof: MultiPath<MerkleConfig>,
+    pub leaves: Vec<Vec<F>>,
+    pub whir_proof: WhirProof<MerkleConfig, F>,
+}
+
+// The statement that the committed polynomial evaluates to `leaves` at the points of the
+// leaves `indexes` of a table in `num_variables` variables.
+fn leaf_statement<F: FftField>(
+    num_variables: usize,
+    leaf_size: usize,
+    indexes: &[usize],
+    leaves: &[Vec<F>],
+) -> Statement<F> {
+    let points = indexes
+        .iter()
+        .flat_map(|index| (index * leaf_size..(index + 1) * leaf_size))
+        .map(|b| {
+            MultilinearPoint::from_binary_hypercube_point(BinaryHypercubePoint(b), num_variables)
+        })
+        .collect();
+    let evaluations = leaves.iter().flatten().copied().collect();
+    Statement::new(points, evaluations)
+}
+
+fn sample_leaves(seed: [u8; 32], num_leaves: usize, num_checks: usize) -> Vec<usize> {
+    query_indexes(&seed, num_checks, num_leaves)
+}
+
+pub struct ViewsProver<F, MerkleConfig, PowStrategy>(WhirConfig<F, MerkleConfig, PowStrategy>)
+where
+    F: FftField,
+    MerkleConfig: Config;
+
+impl<F, MerkleConfig, PowStrategy> ViewsProver<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    pub fn new(config: WhirConfig<F, MerkleConfig, PowStrategy>) -> Self {
+        Self(config)
+    }
+
+    /// Commits to the evaluation table, with `leaf_size` evaluations per leaf, using the Merkle
+    /// parameters of the configuration.
+    pub fn commit_evaluations(
+        &self,
+        evaluations: &EvaluationsList<F::BasePrimeField>,
+        leaf_size: usize,
+    ) -> WhirResult<EvaluationsTree<F, MerkleConfig>> {
+        let num_variables = self.0.mv_parameters.num_variables;
+        if evaluations.num_variables() != num_variables {
+            return Err(WhirError::NumVariables {
+                expected: num_variables,
+                found: evaluations.num_variables(),
+            });
+        }
+        if !leaf_size.is_power_of_two() || leaf_size > evaluations.evals().len() {
+            return Err(WhirError::NotPowerOfTwo(leaf_size));
+        }
+        let evaluations: Vec<F> = evaluations
+            .evals()
+            .iter()
+            .map(|&e| F::from_base_prime_field(e))
+            .collect();
+        let merkle_tree = MerkleTree::new(
+            &self.0.leaf_hash_params,
+            &self.0.two_to_one_params,
+            evaluations.chunks_exact(leaf_size),
+        )
+        .map_err(|_| WhirError::InvalidParameters("cannot build the evaluation tree"))?;
+        Ok(EvaluationsTree {
+            merkle_tree,
+            evaluations,
+            leaf_size,
+        })
+    }
+
+    /// Proves that `table` commits to the evaluations of the polynomial of `witness`, at
+    /// `num_checks` random leaves. The transcript must be right after the commitment.
+    pub fn prove(
+        &self,
+        merlin: &mut Merlin,
+        witness: Witness<F, MerkleConfig>,
+        table: &EvaluationsTree<F, MerkleConfig>,
+        num_checks: usize,
+    ) -> WhirResult<ConsistencyProof<MerkleConfig, F>>
+    where
+        Merlin: FieldChallenges<F> + ByteWriter,
+    {
+        merlin.add_bytes(table.root().as_ref())?;
+        let mut seed = [0u8; 32];
+        merlin.fill_challenge_bytes(&mut seed)?;
+        let indexes = sample_leaves(seed, table.num_leaves(), num_checks);
+        event!(indexes = ?indexes, "consistency checks");
+
+        let merkle_proof = table
+            .merkle_tree
+            .generate_multi_proof(indexes.clone())
+            .map_err(|_| WhirError::InvalidParameters("cannot open the evaluation tree"))?;
+        let leaves: Vec<Vec<F>> = indexes
+            .iter()
+            .map(|i| table.evaluations[i * table.leaf_size..(i + 1) * table.leaf_size].to_vec())
+            .collect();
+        let statement = leaf_statement(
+            self.0.mv_parameters.num_variables,
+            table.leaf_size,
+            &indexes,
+            &leaves,
+        );
+        let whir_proof = Prover(self.0.clone()).prove(merlin, statement, witness)?;
+        Ok(ConsistencyProof {
+            merkle_proof,
+            leaves,
+            whir_proof,
+        })
+    }
+}
+
+pub struct ViewsVerifier<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config,
+{
+    params: WhirConfig<F, MerkleConfig, PowStrategy>,
+    verifier: Verifier<F, MerkleConfig, PowStrategy>,
+}
+
+impl<F, MerkleConfig, PowStrategy> ViewsVerifier<F, MerkleConfig, PowStrategy>
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = [F]>,
+    MerkleConfig::InnerDigest: AsRef<[u8]> + From<[u8; 32]>,
+    PowStrategy: nimue_pow::PowStrategy,
+{
+    pub fn new(params: WhirConfig<F, MerkleConfig, PowStrategy>) -> Self {
+        ViewsVerifier {
+            verifier: Verifier::new(params.clone()),
+            params,
+        }
+    }
+
+    /// Checks that the table committed to by `table_root`, with `leaf_size` evaluations per
+    /// leaf, agrees with the polynomial committed to in the transcript at `num_checks` random
+    /// leaves.
+    pub fn verify(
+        &self,
+        arthur: &mut Arthur,
+        table_root: &MerkleConfig::InnerDigest,
+        leaf_size: usize,
+        num_checks: usize,
+        proof: &ConsistencyProof<MerkleConfig, F>,
+    ) -> ProofResult<()>
+    where
+        Arthur: FieldReader<F> + FieldChallenges<F> + ByteReader + ByteChallenges,
+    {
+        let num_variables = self.params.mv_parameters.num_variables;
+        if !leaf_size.is_power_of_two() || leaf_size > 1 << num_variables {
+            return Err(ProofError::InvalidProof);
+        }
+        let parsed_commitment = self.verifier.parse_commitment(arthur)?;
+        let root: [u8; 32] = arthur.next_bytes()?;
+        if &root[..] != table_root.as_ref() {
+            event!("verifier rejected the root of the evaluation table");
+            return Err(ProofError::InvalidProof);
+        }
+        let mut seed = [0u8; 32];
+        arthur.fill_challenge_bytes(&mut seed)?;
+        let indexes = sample_leaves(seed, (1 << num_variables) / leaf_size, num_checks);
+
+        if proof.merkle_proof.leaf_indexes != indexes
+            || proof.leaves.len() != indexes.len()
+            || proof.leaves.iter().any(|leaf| leaf.len() != leaf_size)
+            || !proof
+                .merkle_proof
+                .verify(
+                    &self.params.leaf_hash_params,
+                    &self.params.two_to_one_params,
+                    &MerkleConfig::InnerDigest::from(root),
+                    proof.leaves.iter().map(|leaf| leaf.as_ref()),
+                )
+                .unwrap_or(false)
+        {
+            event!("verifier rejected the openings of the evaluation table");
+            return Err(ProofError::InvalidProof);
+        }
+
+        let statement = leaf_statement(num_variables, leaf_size, &indexes, &proof.leaves);
+        self.verifier.verify_with_commitment(
+            arthur,
+            &parsed_commitment,
+            &statement,
+            &proof.whir_proof,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::{AdditiveGroup, Field};
+    use nimue::DefaultHash;
+    use nimue_pow::blake3::Blake3PoW;
+
+    use super::*;
+    use crate::{
+        crypto::{
+            fields::{Field64, Field64_2},
+            merkle_tree::blake3 as merkle_tree,
+        },
+        parameters::MultivariateParameters,
+        poly_utils::coeffs::CoefficientList,
+        whir::{committer::Committer, fixtures::whir_parameters, iopattern::WhirIOPattern},
+    };
+
+    type F = Field64_2;
+    type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+
+    #[test]
+    fn test_views_consistency() {
+        let num_variables = 6;
+        let (leaf_size, num_checks) = (4, 5);
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = merkle_tree::default_config::<F>(&mut rng);
+        let whir_params =
+            whir_parameters::<MerkleConfig, Blake3PoW>((leaf_hash_params, two_to_one_params));
+        let params = WhirConfig::<F, MerkleConfig, Blake3PoW>::try_new(
+            MultivariateParameters::new(num_variables),
+            whir_params,
+        )
+        .unwrap();
+        let io = IOPattern::<DefaultHash>::new("🌪️")
+            .commit_statement(&params)
+            .add_views_consistency()
+            .add_whir_proof(&params);
+
+        let polynomial = CoefficientList::new(
+            (0..1 << num_variables)
+                .map(|i| Field64::from(i as u64))
+                .collect(),
+        );
+        let prover = ViewsProver::new(params.clone());
+        let verifier = ViewsVerifier::new(params.clone());
+        let evaluations = EvaluationsList::from(polynomial.clone());
+        let table = prover.commit_evaluations(&evaluations, leaf_size).unwrap();
+        let root = table.root();
+        let mut merlin = io.to_merlin();
+        let witness = Committer::new(params.clone())
+            .commit(&mut merlin, polynomial)
+            .unwrap();
+        let proof = prover
+            .prove(&mut merlin, witness, &table, num_checks)
+            .unwrap();
+        let transcript = merlin.transcript().to_vec();
+        let verify = |root, proof: &ConsistencyProof<MerkleConfig, F>, leaf_size| {
+            let mut arthur = io.to_arthur(&transcript);
+            verifier.verify(&mut arthur, root, leaf_size, num_checks, proof)
+        };
+        assert!(verify(&root, &proof, leaf_size).is_ok());
+        assert!(verify(&root, &proof, 2 * leaf_size).is_err());
+
+        // The root of a table diverging from the polynomial
+        let zeros = EvaluationsList::new(vec![Field64::ZERO; 1 << num_variables]);
+        let other = prover.commit_evaluations(&zeros, leaf_size).unwrap();
+        assert!(verify(&other.root(), &proof, leaf_size).is_err());
+        let mut tampered = proof.clone();
+        tampered.leaves[0][0] += F::ONE;
+        assert!(verify(&root, &tampered, leaf_size).is_err());
+
+        assert!(prover.commit_evaluations(&evaluations, 3).is_err());
+        assert!(prover
+            .commit_evaluations(&EvaluationsList::new(vec![Field64::ZERO; 4]), leaf_size)
+            .is_err());
+    }
+}
@@ -53,7 +53,7 @@ where
         let folded_evals = restructure_evaluations(
             folded_evals,
             self.0.fold_optimisation,
-            base_domain.group_gen(),
+            base_domain.coset_offset_inv(),
             base_domain.group_gen_inv(),
             self.0.folding_factor,
         );
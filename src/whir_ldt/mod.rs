@@ -32,10 +32,13 @@ mod tests {
 
     use crate::crypto::fields::Field64;
     use crate::crypto::merkle_tree::blake3 as merkle_tree;
-    use crate::parameters::{FoldType, MultivariateParameters, SoundnessType, WhirParameters};
+    use crate::parameters::{FoldType, MultivariateParameters, SoundnessType};
     use crate::poly_utils::coeffs::CoefficientList;
     use crate::whir_ldt::{
-        committer::Committer, iopattern::WhirIOPattern, parameters::WhirConfig, prover::Prover,
+        committer::Committer,
+        iopattern::WhirIOPattern,
+        parameters::{LdtParameters, WhirConfig},
+        prover::Prover,
         verifier::Verifier,
     };
 
@@ -57,7 +60,7 @@ mod tests {
 
         let mv_params = MultivariateParameters::<F>::new(num_variables);
 
-        let whir_params = WhirParameters::<MerkleConfig, PowStrategy> {
+        let ldt_params = LdtParameters::<MerkleConfig, PowStrategy> {
             security_level: 32,
             pow_bits,
             folding_factor,
@@ -69,7 +72,7 @@ mod tests {
             _pow_parameters: Default::default(),
         };
 
-        let params = WhirConfig::<F, MerkleConfig, PowStrategy>::new(mv_params, whir_params);
+        let params = WhirConfig::<F, MerkleConfig, PowStrategy>::new(mv_params, ldt_params);
 
         let polynomial = CoefficientList::new(vec![F::from(1); num_coeffs]);
 
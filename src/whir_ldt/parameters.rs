@@ -8,8 +8,97 @@ use crate::{
     crypto::fields::FieldWithSize,
     domain::Domain,
     parameters::{FoldType, MultivariateParameters, SoundnessType, WhirParameters},
+    soundness,
+    utils::checked_pow2,
 };
 
+/// The parameters of WHIR as a LDT. As there is no statement, they are those of `WhirParameters`
+/// without the mask, the final form and the proof size budget.
+pub struct LdtParameters<MerkleConfig, PowStrategy>
+where
+    MerkleConfig: Config,
+{
+    pub starting_log_inv_rate: usize,
+    pub folding_factor: usize,
+    pub soundness_type: SoundnessType,
+    pub security_level: usize,
+    pub pow_bits: usize,
+
+    pub fold_optimisation: FoldType,
+
+    // PoW parameters
+    pub _pow_parameters: PhantomData<PowStrategy>,
+
+    // Merkle tree parameters
+    pub leaf_hash_params: LeafParam<MerkleConfig>,
+    pub two_to_one_params: TwoToOneParam<MerkleConfig>,
+}
+
+// Not derived, which would require the markers `MerkleConfig` and `PowStrategy` to be `Clone`.
+impl<MerkleConfig, PowStrategy> Clone for LdtParameters<MerkleConfig, PowStrategy>
+where
+    MerkleConfig: Config,
+{
+    fn clone(&self) -> Self {
+        LdtParameters {
+            starting_log_inv_rate: self.starting_log_inv_rate,
+            folding_factor: self.folding_factor,
+            soundness_type: self.soundness_type,
+            security_level: self.security_level,
+            pow_bits: self.pow_bits,
+            fold_optimisation: self.fold_optimisation,
+            _pow_parameters: PhantomData,
+            leaf_hash_params: self.leaf_hash_params.clone(),
+            two_to_one_params: self.two_to_one_params.clone(),
+        }
+    }
+}
+
+/// The parameters of the LDT with the rates, security and hashes of `whir_parameters`, e.g. to
+/// compare WHIR with the LDT. The parameters only used by `whir` are dropped.
+impl<MerkleConfig, PowStrategy> From<WhirParameters<MerkleConfig, PowStrategy>>
+    for LdtParameters<MerkleConfig, PowStrategy>
+where
+    MerkleConfig: Config,
+{
+    fn from(whir_parameters: WhirParameters<MerkleConfig, PowStrategy>) -> Self {
+        assert!(!whir_parameters.zk, "the LDT has no zero-knowledge mode");
+        assert!(
+            whir_parameters.round_merkle_params.is_empty(),
+            "the LDT hashes all its trees alike"
+        );
+        LdtParameters {
+            starting_log_inv_rate: whir_parameters.starting_log_inv_rate,
+            folding_factor: whir_parameters.folding_factor,
+            soundness_type: whir_parameters.soundness_type,
+            security_level: whir_parameters.security_level,
+            pow_bits: whir_parameters.pow_bits,
+            fold_optimisation: whir_parameters.fold_optimisation,
+            _pow_parameters: PhantomData,
+            leaf_hash_params: whir_parameters.leaf_hash_params,
+            two_to_one_params: whir_parameters.two_to_one_params,
+        }
+    }
+}
+
+impl<MerkleConfig, PowStrategy> Display for LdtParameters<MerkleConfig, PowStrategy>
+where
+    MerkleConfig: Config,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Targeting {}-bits of security with {}-bits of PoW - soundness: {:?}",
+            self.security_level, self.pow_bits, self.soundness_type
+        )?;
+        writeln!(
+            f,
+            "Starting rate: 2^-{}, folding_factor: {}, fold_opt_type: {}",
+            self.starting_log_inv_rate, self.folding_factor, self.fold_optimisation,
+        )
+    }
+}
+
 #[derive(Clone)]
 pub struct WhirConfig<F, MerkleConfig, PowStrategy>
 where
@@ -59,58 +148,57 @@ where
 {
     pub fn new(
         mv_parameters: MultivariateParameters<F>,
-        whir_parameters: WhirParameters<MerkleConfig, PowStrategy>,
+        ldt_parameters: LdtParameters<MerkleConfig, PowStrategy>,
     ) -> Self {
         // We need to fold at least some time
         assert!(
-            whir_parameters.folding_factor > 0,
+            ldt_parameters.folding_factor > 0,
             "folding factor should be non zero"
         );
         // If less, just send the damn polynomials
-        assert!(mv_parameters.num_variables >= whir_parameters.folding_factor);
+        assert!(mv_parameters.num_variables >= ldt_parameters.folding_factor);
         let protocol_security_level =
-            0.max(whir_parameters.security_level - whir_parameters.pow_bits);
+            0.max(ldt_parameters.security_level - ldt_parameters.pow_bits);
 
-        let starting_domain = Domain::new(
-            1 << mv_parameters.num_variables,
-            whir_parameters.starting_log_inv_rate,
-        )
-        .expect("Should have found an appropriate domain");
+        let num_coeffs = checked_pow2(mv_parameters.num_variables)
+            .expect("The number of coefficients should fit in a usize");
+        let starting_domain = Domain::new(num_coeffs, ldt_parameters.starting_log_inv_rate)
+            .expect("Should have found an appropriate domain");
 
-        let final_sumcheck_rounds = mv_parameters.num_variables % whir_parameters.folding_factor;
+        let final_sumcheck_rounds = mv_parameters.num_variables % ldt_parameters.folding_factor;
         let num_rounds = ((mv_parameters.num_variables - final_sumcheck_rounds)
-            / whir_parameters.folding_factor)
+            / ldt_parameters.folding_factor)
             - 1;
 
         let field_size_bits = F::field_size_in_bits();
 
         let prox_gaps_error = Self::rbr_soundness_fold_prox_gaps(
-            whir_parameters.soundness_type,
+            ldt_parameters.soundness_type,
             field_size_bits,
             mv_parameters.num_variables,
-            whir_parameters.starting_log_inv_rate,
-            Self::log_eta(whir_parameters.starting_log_inv_rate),
-        ) + (whir_parameters.folding_factor as f64).log2();
+            ldt_parameters.starting_log_inv_rate,
+            Self::log_eta(ldt_parameters.starting_log_inv_rate),
+        ) + (ldt_parameters.folding_factor as f64).log2();
         let starting_folding_pow_bits =
-            0_f64.max(whir_parameters.security_level as f64 - prox_gaps_error);
+            0_f64.max(ldt_parameters.security_level as f64 - prox_gaps_error);
 
         let mut round_parameters = Vec::with_capacity(num_rounds);
-        let mut num_variables = mv_parameters.num_variables - whir_parameters.folding_factor;
-        let mut log_inv_rate = whir_parameters.starting_log_inv_rate;
+        let mut num_variables = mv_parameters.num_variables - ldt_parameters.folding_factor;
+        let mut log_inv_rate = ldt_parameters.starting_log_inv_rate;
         for _ in 0..num_rounds {
             // Queries are set w.r.t. to old rate, while the rest to the new rate
-            let next_rate = log_inv_rate + (whir_parameters.folding_factor - 1);
+            let next_rate = log_inv_rate + (ldt_parameters.folding_factor - 1);
 
             let log_next_eta = Self::log_eta(next_rate);
             let num_queries = Self::queries(
-                whir_parameters.soundness_type,
+                ldt_parameters.soundness_type,
                 protocol_security_level,
                 log_inv_rate,
             );
 
             let ood_samples = Self::ood_samples(
-                whir_parameters.security_level,
-                whir_parameters.soundness_type,
+                ldt_parameters.security_level,
+                ldt_parameters.soundness_type,
                 num_variables,
                 next_rate,
                 log_next_eta,
@@ -118,9 +206,9 @@ where
             );
 
             let query_error =
-                Self::rbr_queries(whir_parameters.soundness_type, log_inv_rate, num_queries);
+                Self::rbr_queries(ldt_parameters.soundness_type, log_inv_rate, num_queries);
             let combination_error = Self::rbr_soundness_queries_combination(
-                whir_parameters.soundness_type,
+                ldt_parameters.soundness_type,
                 field_size_bits,
                 num_variables,
                 next_rate,
@@ -130,11 +218,11 @@ where
             );
 
             let pow_bits = 0_f64
-                .max(whir_parameters.security_level as f64 - (query_error.min(combination_error)));
+                .max(ldt_parameters.security_level as f64 - (query_error.min(combination_error)));
 
             let folding_pow_bits = Self::folding_pow_bits(
-                whir_parameters.security_level,
-                whir_parameters.soundness_type,
+                ldt_parameters.security_level,
+                ldt_parameters.soundness_type,
                 field_size_bits,
                 num_variables,
                 next_rate,
@@ -149,43 +237,43 @@ where
                 log_inv_rate,
             });
 
-            num_variables -= whir_parameters.folding_factor;
+            num_variables -= ldt_parameters.folding_factor;
             log_inv_rate = next_rate;
         }
 
         let final_queries = Self::queries(
-            whir_parameters.soundness_type,
+            ldt_parameters.soundness_type,
             protocol_security_level,
             log_inv_rate,
         );
 
         let final_pow_bits = 0_f64.max(
-            whir_parameters.security_level as f64
-                - Self::rbr_queries(whir_parameters.soundness_type, log_inv_rate, final_queries),
+            ldt_parameters.security_level as f64
+                - Self::rbr_queries(ldt_parameters.soundness_type, log_inv_rate, final_queries),
         );
 
         let final_folding_pow_bits =
-            0_f64.max(whir_parameters.security_level as f64 - (field_size_bits - 1) as f64);
+            0_f64.max(ldt_parameters.security_level as f64 - (field_size_bits - 1) as f64);
 
         WhirConfig {
-            security_level: whir_parameters.security_level,
-            max_pow_bits: whir_parameters.pow_bits,
+            security_level: ldt_parameters.security_level,
+            max_pow_bits: ldt_parameters.pow_bits,
             mv_parameters,
             starting_domain,
-            soundness_type: whir_parameters.soundness_type,
-            starting_log_inv_rate: whir_parameters.starting_log_inv_rate,
+            soundness_type: ldt_parameters.soundness_type,
+            starting_log_inv_rate: ldt_parameters.starting_log_inv_rate,
             starting_folding_pow_bits,
-            folding_factor: whir_parameters.folding_factor,
+            folding_factor: ldt_parameters.folding_factor,
             round_parameters,
             final_queries,
             final_pow_bits,
             final_sumcheck_rounds,
             final_folding_pow_bits,
             pow_strategy: PhantomData::default(),
-            fold_optimisation: whir_parameters.fold_optimisation,
+            fold_optimisation: ldt_parameters.fold_optimisation,
             final_log_inv_rate: log_inv_rate,
-            leaf_hash_params: whir_parameters.leaf_hash_params,
-            two_to_one_params: whir_parameters.two_to_one_params,
+            leaf_hash_params: ldt_parameters.leaf_hash_params,
+            two_to_one_params: ldt_parameters.two_to_one_params,
         }
     }
 
@@ -217,18 +305,7 @@ where
         log_inv_rate: usize,
         log_eta: f64,
     ) -> f64 {
-        match soundness_type {
-            SoundnessType::ConjectureList => {
-                let result = (num_variables + log_inv_rate) as f64 - log_eta;
-                result
-            }
-            SoundnessType::ProvableList => {
-                let log_inv_sqrt_rate: f64 = log_inv_rate as f64 / 2.;
-                let result = log_inv_sqrt_rate - (1. + log_eta);
-                result
-            }
-            SoundnessType::UniqueDecoding => 0.0,
-        }
+        soundness::list_size_bits(soundness_type, num_variables, log_inv_rate, log_eta)
     }
 
     pub fn rbr_ood_sample(
@@ -239,43 +316,34 @@ where
         field_size_bits: usize,
         ood_samples: usize,
     ) -> f64 {
-        let list_size_bits =
-            Self::list_size_bits(soundness_type, num_variables, log_inv_rate, log_eta);
-
-        let error = 2. * list_size_bits + (num_variables * ood_samples) as f64;
-        (ood_samples * field_size_bits) as f64 + 1. - error
+        soundness::rbr_ood_sample(
+            soundness_type,
+            num_variables,
+            log_inv_rate,
+            log_eta,
+            field_size_bits,
+            ood_samples,
+        )
     }
 
     pub fn ood_samples(
-        security_level: usize, // We don't do PoW for OOD
+        security_level: usize,
         soundness_type: SoundnessType,
         num_variables: usize,
         log_inv_rate: usize,
         log_eta: f64,
         field_size_bits: usize,
     ) -> usize {
-        if matches!(soundness_type, SoundnessType::UniqueDecoding) {
-            0
-        } else {
-            for ood_samples in 1..64 {
-                if Self::rbr_ood_sample(
-                    soundness_type,
-                    num_variables,
-                    log_inv_rate,
-                    log_eta,
-                    field_size_bits,
-                    ood_samples,
-                ) >= security_level as f64
-                {
-                    return ood_samples;
-                }
-            }
-
-            panic!("Could not find an appropriate number of OOD samples");
-        }
+        soundness::ood_samples(
+            security_level,
+            soundness_type,
+            num_variables,
+            log_inv_rate,
+            log_eta,
+            field_size_bits,
+        )
     }
 
-    // Compute the proximity gaps term of the fold
     pub fn rbr_soundness_fold_prox_gaps(
         soundness_type: SoundnessType,
         field_size_bits: usize,
@@ -283,16 +351,13 @@ where
         log_inv_rate: usize,
         log_eta: f64,
     ) -> f64 {
-        // Recall, at each round we are only folding by two at a time
-        let error = match soundness_type {
-            SoundnessType::ConjectureList => (num_variables + log_inv_rate) as f64 - log_eta,
-            SoundnessType::ProvableList => {
-                LOG2_10 + 3.5 * log_inv_rate as f64 + 2. * num_variables as f64
-            }
-            SoundnessType::UniqueDecoding => (num_variables + log_inv_rate) as f64,
-        };
-
-        field_size_bits as f64 - error
+        soundness::rbr_soundness_fold_prox_gaps(
+            soundness_type,
+            field_size_bits,
+            num_variables,
+            log_inv_rate,
+            log_eta,
+        )
     }
 
     pub fn rbr_soundness_fold_sumcheck(
@@ -302,9 +367,13 @@ where
         log_inv_rate: usize,
         log_eta: f64,
     ) -> f64 {
-        let list_size = Self::list_size_bits(soundness_type, num_variables, log_inv_rate, log_eta);
-
-        field_size_bits as f64 - (list_size + 1.)
+        soundness::rbr_soundness_fold_sumcheck(
+            soundness_type,
+            field_size_bits,
+            num_variables,
+            log_inv_rate,
+            log_eta,
+        )
     }
 
     pub fn folding_pow_bits(
@@ -315,66 +384,30 @@ where
         log_inv_rate: usize,
         log_eta: f64,
     ) -> f64 {
-        let prox_gaps_error = Self::rbr_soundness_fold_prox_gaps(
-            soundness_type,
-            field_size_bits,
-            num_variables,
-            log_inv_rate,
-            log_eta,
-        );
-        let sumcheck_error = Self::rbr_soundness_fold_sumcheck(
+        soundness::folding_pow_bits(
+            security_level,
             soundness_type,
             field_size_bits,
             num_variables,
             log_inv_rate,
             log_eta,
-        );
-
-        let error = prox_gaps_error.min(sumcheck_error);
-
-        0_f64.max(security_level as f64 - error)
+        )
     }
 
-    // Used to select the number of queries
     pub fn queries(
         soundness_type: SoundnessType,
         protocol_security_level: usize,
         log_inv_rate: usize,
     ) -> usize {
-        let num_queries_f = match soundness_type {
-            SoundnessType::UniqueDecoding => {
-                let rate = 1. / ((1 << log_inv_rate) as f64);
-                let denom = (0.5 * (1. + rate)).log2();
-
-                -(protocol_security_level as f64) / denom
-            }
-            SoundnessType::ProvableList => {
-                (2 * protocol_security_level) as f64 / log_inv_rate as f64
-            }
-            SoundnessType::ConjectureList => protocol_security_level as f64 / log_inv_rate as f64,
-        };
-        num_queries_f.ceil() as usize
+        soundness::queries(soundness_type, protocol_security_level, log_inv_rate)
     }
 
-    // This is the bits of security of the query step
     pub fn rbr_queries(
         soundness_type: SoundnessType,
         log_inv_rate: usize,
         num_queries: usize,
     ) -> f64 {
-        let num_queries = num_queries as f64;
-        let bits_of_sec_queries = match soundness_type {
-            SoundnessType::UniqueDecoding => {
-                let rate = 1. / ((1 << log_inv_rate) as f64);
-                let denom = -(0.5 * (1. + rate)).log2();
-
-                num_queries * denom
-            }
-            SoundnessType::ProvableList => num_queries * 0.5 * log_inv_rate as f64,
-            SoundnessType::ConjectureList => num_queries * log_inv_rate as f64,
-        };
-
-        bits_of_sec_queries
+        soundness::rbr_queries(soundness_type, log_inv_rate, num_queries)
     }
 
     pub fn rbr_soundness_queries_combination(
@@ -386,11 +419,15 @@ where
         ood_samples: usize,
         num_queries: usize,
     ) -> f64 {
-        let list_size = Self::list_size_bits(soundness_type, num_variables, log_inv_rate, log_eta);
-
-        let log_combination = ((ood_samples + num_queries) as f64).log2();
-
-        field_size_bits as f64 - (log_combination + list_size + 1.)
+        soundness::rbr_soundness_queries_combination(
+            soundness_type,
+            field_size_bits,
+            num_variables,
+            log_inv_rate,
+            log_eta,
+            ood_samples,
+            num_queries,
+        )
     }
 }
 
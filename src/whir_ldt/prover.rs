@@ -19,7 +19,7 @@ use nimue::{
     ByteChallenges, ByteWriter, Merlin, ProofResult,
 };
 use nimue_pow::{self, PoWChallenge};
-use rand::{Rng, SeedableRng};
+use rand::SeedableRng;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
@@ -102,7 +102,10 @@ where
             merlin.fill_challenge_bytes(&mut queries_seed)?;
             let mut final_gen = rand_chacha::ChaCha20Rng::from_seed(queries_seed);
             let final_challenge_indexes = utils::dedup((0..self.0.final_queries).map(|_| {
-                final_gen.gen_range(0..round_state.domain.folded_size(self.0.folding_factor))
+                utils::sample_index(
+                    &mut final_gen,
+                    round_state.domain.folded_size(self.0.folding_factor),
+                )
             }));
 
             let merkle_proof = round_state
@@ -152,7 +155,7 @@ where
         let folded_evals = restructure_evaluations(
             folded_evals,
             self.0.fold_optimisation,
-            new_domain.backing_domain.group_gen(),
+            new_domain.backing_domain.coset_offset_inv(),
             new_domain.backing_domain.group_gen_inv(),
             self.0.folding_factor,
         );
@@ -191,10 +194,12 @@ where
         let mut stir_queries_seed = [0u8; 32];
         merlin.fill_challenge_bytes(&mut stir_queries_seed)?;
         let mut stir_gen = rand_chacha::ChaCha20Rng::from_seed(stir_queries_seed);
-        let stir_challenges_indexes =
-            utils::dedup((0..round_params.num_queries).map(|_| {
-                stir_gen.gen_range(0..round_state.domain.folded_size(self.0.folding_factor))
-            }));
+        let stir_challenges_indexes = utils::dedup((0..round_params.num_queries).map(|_| {
+            utils::sample_index(
+                &mut stir_gen,
+                round_state.domain.folded_size(self.0.folding_factor),
+            )
+        }));
         let domain_scaled_gen = round_state
             .domain
             .backing_domain
@@ -224,15 +229,14 @@ where
             FoldType::Naive => {
                 // See `Verifier::compute_folds_full`
                 let domain_size = round_state.domain.backing_domain.size();
-                let domain_gen = round_state.domain.backing_domain.element(1);
-                let domain_gen_inv = domain_gen.inverse().unwrap();
+                let domain_gen_inv = round_state.domain.backing_domain.group_gen_inv();
                 let coset_domain_size = 1 << self.0.folding_factor;
                 let coset_generator_inv =
                     domain_gen_inv.pow([(domain_size / coset_domain_size) as u64]);
+                let two_inv = F::from(2).inverse().unwrap();
                 stir_evaluations.extend(stir_challenges_indexes.iter().zip(&answers).map(
                     |(index, answers)| {
                         // The coset is w^index * <w_coset_generator>
-                        //let _coset_offset = domain_gen.pow(&[*index as u64]);
                         let coset_offset_inv = domain_gen_inv.pow([*index as u64]);
 
                         compute_fold(
@@ -240,7 +244,7 @@ where
                             &round_state.folding_randomness.0,
                             coset_offset_inv,
                             coset_generator_inv,
-                            F::from(2).inverse().unwrap(),
+                            two_inv,
                             self.0.folding_factor,
                         )
                     },
@@ -8,7 +8,7 @@ use nimue::{
     Arthur, ByteChallenges, ByteReader, ProofError, ProofResult,
 };
 use nimue_pow::{self, PoWChallenge};
-use rand::{Rng, SeedableRng};
+use rand::SeedableRng;
 
 use crate::{
     parameters::FoldType,
@@ -123,7 +123,8 @@ where
             let mut stir_gen = rand_chacha::ChaCha20Rng::from_seed(stir_queries_seed);
             let folded_domain_size = domain_size / (1 << self.params.folding_factor);
             let stir_challenges_indexes = utils::dedup(
-                (0..round_params.num_queries).map(|_| stir_gen.gen_range(0..folded_domain_size)),
+                (0..round_params.num_queries)
+                    .map(|_| utils::sample_index(&mut stir_gen, folded_domain_size)),
             );
             let stir_challenges_points = stir_challenges_indexes
                 .iter()
@@ -198,7 +199,8 @@ where
         let mut final_gen = rand_chacha::ChaCha20Rng::from_seed(queries_seed);
         let folded_domain_size = domain_size / (1 << self.params.folding_factor);
         let final_randomness_indexes = utils::dedup(
-            (0..self.params.final_queries).map(|_| final_gen.gen_range(0..folded_domain_size)),
+            (0..self.params.final_queries)
+                .map(|_| utils::sample_index(&mut final_gen, folded_domain_size)),
         );
         let final_randomness_points = final_randomness_indexes
             .iter()